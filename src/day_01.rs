@@ -1,37 +1,51 @@
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
+use super::utils::fs;
 use std::io::{BufRead, BufReader};
 
 /// Calculates the solution to Day 01 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> i32 {
-    // Open up the file (read-only)
-    let file = super::utils::fs::open_file(filename);
-    // Created a buffered reader of the file
-    let file = BufReader::new(file);
+pub fn solution_part_1(filename: String) -> Result<i32, AocError> {
     // Calculate total fuel requirement
     let mut total_fuel_req: i32 = 0;
-    for line in file.lines() {
-        let module_mass = line.unwrap().parse::<i32>().unwrap();
+    for module_mass in get_module_masses(filename)? {
         let fuel_req = calculate_fuel_req(module_mass);
         total_fuel_req += fuel_req;
     }
     // Display challenge solution
-    return total_fuel_req;
+    return Ok(total_fuel_req);
 }
 
 /// Calculates the solution to Day 01 Part 2 challenge.
-pub fn solution_part_2(filename: String) -> i32 {
-    // Open up the file
-    let file = super::utils::fs::open_file(filename);
-    // Create a buffered reader
-    let file = BufReader::new(file);
+pub fn solution_part_2(filename: String) -> Result<i32, AocError> {
     // Calculate the total fuel requirement (recursive)
     let mut total_fuel_req: i32 = 0;
-    for line in file.lines() {
-        let module_mass: i32 = line.unwrap().parse::<i32>().unwrap();
+    for module_mass in get_module_masses(filename)? {
         let fuel_req: i32 = calculate_fuel_req_recursive(module_mass);
         total_fuel_req += fuel_req;
     }
     // Display challenge solution
-    return total_fuel_req;
+    return Ok(total_fuel_req);
+}
+
+/// Reads the module masses from the given file, with one mass given on each line.
+fn get_module_masses(filename: String) -> Result<Vec<i32>, AocError> {
+    // Open up the file (read-only) and create a buffered reader
+    let file = BufReader::new(fs::try_open_file(filename.clone())?);
+    let mut module_masses: Vec<i32> = vec![];
+    for (line_index, line) in file.lines().enumerate() {
+        let line = line.map_err(|e| AocError::Io {
+            filename: filename.clone(),
+            source: e,
+        })?;
+        match line.trim().parse::<i32>() {
+            Ok(mass) => module_masses.push(mass),
+            Err(e) => {
+                let message = format!("bad module mass \"{}\" ({})", line, e);
+                return Err(AocError::parse_error(line_index + 1, 1, message));
+            }
+        }
+    }
+    return Ok(module_masses);
 }
 
 /// Calculates the fuel requirement for a single given module mass.
@@ -62,6 +76,19 @@ fn calculate_fuel_req_recursive(module_mass: i32) -> i32 {
     return total_fuel_req;
 }
 
+/// Solution for Day 01, exposed through the common Solution interface.
+pub struct Day01;
+
+impl Solution for Day01 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,55 +96,55 @@ mod tests {
     /// Test actual solution for Part 1 - to check if this has been broken.
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_01/input.txt"));
+        let result = solution_part_1(String::from("./input/day_01/input.txt")).unwrap();
         assert_eq!(3147032, result);
     }
 
     #[test]
     fn test_p2_actual_solution() {
-        let result = solution_part_2(String::from("./input/day_01/input.txt"));
+        let result = solution_part_2(String::from("./input/day_01/input.txt")).unwrap();
         assert_eq!(4717699, result);
     }
 
     #[test]
     fn test_p1_example_input_1() {
-        let result = solution_part_1(String::from("./input/day_01/test/test_01.txt"));
+        let result = solution_part_1(String::from("./input/day_01/test/test_01.txt")).unwrap();
         assert_eq!(2, result);
     }
 
     #[test]
     fn test_p1_example_input_2() {
-        let result = solution_part_1(String::from("./input/day_01/test/test_02.txt"));
+        let result = solution_part_1(String::from("./input/day_01/test/test_02.txt")).unwrap();
         assert_eq!(2, result);
     }
 
     #[test]
     fn test_p1_example_input_3() {
-        let result = solution_part_1(String::from("./input/day_01/test/test_03.txt"));
+        let result = solution_part_1(String::from("./input/day_01/test/test_03.txt")).unwrap();
         assert_eq!(654, result);
     }
 
     #[test]
     fn test_p1_example_input_4() {
-        let result = solution_part_1(String::from("./input/day_01/test/test_04.txt"));
+        let result = solution_part_1(String::from("./input/day_01/test/test_04.txt")).unwrap();
         assert_eq!(33583, result);
     }
 
     #[test]
     fn test_p2_example_input_2() {
-        let result = solution_part_2(String::from("./input/day_01/test/test_02.txt"));
+        let result = solution_part_2(String::from("./input/day_01/test/test_02.txt")).unwrap();
         assert_eq!(2, result);
     }
 
     #[test]
     fn test_p2_example_input_3() {
-        let result = solution_part_2(String::from("./input/day_01/test/test_03.txt"));
+        let result = solution_part_2(String::from("./input/day_01/test/test_03.txt")).unwrap();
         assert_eq!(966, result);
     }
 
     #[test]
     fn test_p2_example_input_4() {
-        let result = solution_part_2(String::from("./input/day_01/test/test_04.txt"));
+        let result = solution_part_2(String::from("./input/day_01/test/test_04.txt")).unwrap();
         assert_eq!(50346, result);
     }
 }
@@ -1,43 +1,60 @@
 // Import project utility modules
-use super::utils::fs;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
 use super::utils::intcode::IntcodeMachine;
 use std::collections::VecDeque;
 
 /// Calculates and displays the solution to Day 02 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> i64 {
-    // Open file
-    let mut file = fs::open_file(filename);
+pub fn solution_part_1(filename: String) -> Result<i64, AocError> {
     // Extract intcode program arguments
-    let int_args = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+    let int_args = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     let mut machine = IntcodeMachine::new(int_args, VecDeque::new());
-    machine.execute_program();
+    machine.try_execute_program()?;
     // Process the intcode program
     let result = machine.get_location_zero();
-    return result;
+    return Ok(result);
 }
 
 /// Calculates and displays the solution to Day 02 Part 2 challenge.
-pub fn solution_part_2(filename: String) -> i64 {
+pub fn solution_part_2(filename: String) -> Result<i64, AocError> {
     // This is the value we are looking for in position zero across the runs
     const TARGET_LOC_ZERO: i64 = 19690720;
-    // Open file
-    let mut file = fs::open_file(filename);
     // Extract intcode program arguments
-    let int_args = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+    let int_args = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
+    if int_args.len() < 3 {
+        return Err(AocError::InvalidProgram(String::from(
+            "program is too short to be given a noun and verb",
+        )));
+    }
     // Let's process the intcode program with each possible value pair
     for (p1, p2) in iproduct!(0..100, 0..100) {
         let mut updated_int_args = int_args.to_vec();
         updated_int_args[1] = p1;
         updated_int_args[2] = p2;
         let mut machine = IntcodeMachine::new(updated_int_args, VecDeque::new());
-        machine.execute_program();
+        machine.try_execute_program()?;
         if machine.get_location_zero() == TARGET_LOC_ZERO {
             let output = 100 * p1 + p2;
-            return output;
+            return Ok(output);
         }
     }
-    // Shouldn't get here!
-    panic!("Day 02 Part 2: HERE BE DRAGONS!");
+    return Err(AocError::InvalidProgram(format!(
+        "no noun and verb give {} in location 0",
+        TARGET_LOC_ZERO
+    )));
+}
+
+/// Solution for Day 02, exposed through the common Solution interface.
+pub struct Day02;
+
+impl Solution for Day02 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
 }
 
 #[cfg(test)]
@@ -47,38 +64,52 @@ mod tests {
     /// Test actual solution for Part 1 - to check this hasn't been broken.
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_02/input.txt"));
+        let result = solution_part_1(String::from("./input/day_02/input.txt")).unwrap();
         assert_eq!(8017076, result);
     }
 
     /// Test actual solution for Part 2 - to check this hasn't been broken.
     #[test]
     fn test_p2_actual_solution() {
-        let result = solution_part_2(String::from("./input/day_02/input.txt"));
+        let result = solution_part_2(String::from("./input/day_02/input.txt")).unwrap();
         assert_eq!(3146, result);
     }
 
     #[test]
     fn test_p1_example_input_1() {
-        let result = solution_part_1(String::from("./input/day_02/test/test_01.txt"));
+        let result = solution_part_1(String::from("./input/day_02/test/test_01.txt")).unwrap();
         assert_eq!(2, result);
     }
 
     #[test]
     fn test_p1_example_input_2() {
-        let result = solution_part_1(String::from("./input/day_02/test/test_02.txt"));
+        let result = solution_part_1(String::from("./input/day_02/test/test_02.txt")).unwrap();
         assert_eq!(2, result);
     }
 
     #[test]
     fn test_p1_example_input_3() {
-        let result = solution_part_1(String::from("./input/day_02/test/test_03.txt"));
+        let result = solution_part_1(String::from("./input/day_02/test/test_03.txt")).unwrap();
         assert_eq!(2, result);
     }
 
     #[test]
     fn test_p1_example_input_4() {
-        let result = solution_part_1(String::from("./input/day_02/test/test_04.txt"));
+        let result = solution_part_1(String::from("./input/day_02/test/test_04.txt")).unwrap();
         assert_eq!(30, result);
     }
+
+    #[test]
+    fn test_p2_bad_program() {
+        let results = vec![
+            solution_part_2(String::from("./input/day_02/test/test_short.txt")),
+            solution_part_2(String::from("./input/day_02/test/test_no_target.txt")),
+        ];
+        for result in results {
+            match result {
+                Err(AocError::InvalidProgram(_)) => (),
+                _ => panic!("Expected invalid program error."),
+            }
+        }
+    }
 }
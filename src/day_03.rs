@@ -1,5 +1,7 @@
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
+use super::utils::io;
 use std::collections::HashMap;
-use std::io::Read;
 use std::usize::*;
 
 // Config parameters
@@ -20,19 +22,13 @@ const GRID_WIRE_1: usize = 1;
 const GRID_WIRE_2: usize = 2;
 
 /// Calculates and displays the solution for Day 03 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> usize {
-    // Open file
-    let mut file = super::utils::fs::open_file(filename);
+pub fn solution_part_1(filename: String) -> Result<usize, AocError> {
     // Read file lines
-    let mut file_str = String::new();
-    match file.read_to_string(&mut file_str) {
-        Err(e) => panic!("Failed to read file to string. ({})", e.to_string()),
-        Ok(_) => 0,
-    };
+    let file_str = io::try_read_filename_to_string(filename)?;
     // Separate out the two wires given in input file
     let mut lines = file_str.lines();
-    let wire_one: Vec<&str> = lines.next().unwrap().split(',').collect();
-    let wire_two: Vec<&str> = lines.next().unwrap().split(',').collect();
+    let wire_one: Vec<&str> = get_wire_line(lines.next(), 1)?.split(',').collect();
+    let wire_two: Vec<&str> = get_wire_line(lines.next(), 2)?.split(',').collect();
     // Initialise variables required for processing wires
     let mut intersections = Vec::<Vec<usize>>::new();
     let mut wire_move_records = HashMap::<usize, HashMap<usize, usize>>::new();
@@ -40,18 +36,20 @@ pub fn solution_part_1(filename: String) -> usize {
     // Process wire one
     let wire_one_move_record = process_wire_nogrid(
         wire_one,
+        GRID_WIRE_1,
         &wire_move_records,
         &mut intersections,
         &start_loc,
-    );
+    )?;
     wire_move_records.insert(GRID_WIRE_1, wire_one_move_record);
     // Process wire two
     let wire_two_move_record = process_wire_nogrid(
         wire_two,
+        GRID_WIRE_2,
         &wire_move_records,
         &mut intersections,
         &start_loc,
-    );
+    )?;
     wire_move_records.insert(GRID_WIRE_2, wire_two_move_record);
     // Calculate Manhattan distances for all intersection points
     let mut distances = Vec::<usize>::new();
@@ -63,24 +61,20 @@ pub fn solution_part_1(filename: String) -> usize {
         distances.push(manhattan_dist);
     }
     // Return result
-    let min_manhattan_dist = distances.iter().min().unwrap();
-    return *min_manhattan_dist;
+    match distances.iter().min() {
+        Some(min_manhattan_dist) => return Ok(*min_manhattan_dist),
+        None => return Err(no_intersection_error()),
+    }
 }
 
 /// Calculates and displays the solution for Day 03 Part 2.
-pub fn solution_part_2(filename: String) -> usize {
-    // Open file
-    let mut file = super::utils::fs::open_file(filename);
+pub fn solution_part_2(filename: String) -> Result<usize, AocError> {
     // Read file lines
-    let mut file_str = String::new();
-    match file.read_to_string(&mut file_str) {
-        Err(e) => panic!("Failed to read file to string. ({})", e.to_string()),
-        Ok(_) => 0,
-    };
+    let file_str = io::try_read_filename_to_string(filename)?;
     // Separate out the two wires given in input file
     let mut lines = file_str.lines();
-    let wire_one: Vec<&str> = lines.next().unwrap().split(',').collect();
-    let wire_two: Vec<&str> = lines.next().unwrap().split(',').collect();
+    let wire_one: Vec<&str> = get_wire_line(lines.next(), 1)?.split(',').collect();
+    let wire_two: Vec<&str> = get_wire_line(lines.next(), 2)?.split(',').collect();
     // Initialise variables required for processing wires
     let mut intersections = Vec::<Vec<usize>>::new();
     let mut wire_move_records = HashMap::<usize, HashMap<usize, usize>>::new();
@@ -88,18 +82,20 @@ pub fn solution_part_2(filename: String) -> usize {
     // Process wire one
     let wire_one_move_record = process_wire_nogrid(
         wire_one,
+        GRID_WIRE_1,
         &wire_move_records,
         &mut intersections,
         &start_loc,
-    );
+    )?;
     wire_move_records.insert(GRID_WIRE_1, wire_one_move_record);
     // Process wire two
     let wire_two_move_record = process_wire_nogrid(
         wire_two,
+        GRID_WIRE_2,
         &wire_move_records,
         &mut intersections,
         &start_loc,
-    );
+    )?;
     wire_move_records.insert(GRID_WIRE_2, wire_two_move_record);
     // Calculated the combined moves to each intersection to find minimum val
     let mut min_combined_moves: usize = MAX;
@@ -118,8 +114,24 @@ pub fn solution_part_2(filename: String) -> usize {
             min_combined_moves = combined_moves;
         }
     }
+    if min_combined_moves == usize::MAX {
+        return Err(no_intersection_error());
+    }
     // Display result
-    return min_combined_moves;
+    return Ok(min_combined_moves);
+}
+
+/// Creates the error returned when the wires never cross away from the starting location.
+fn no_intersection_error() -> AocError {
+    return AocError::parse_error(3, 1, String::from("wires don't cross"));
+}
+
+/// Checks that the input line for the given wire number (1 or 2) is present.
+fn get_wire_line(line: Option<&str>, wire_num: usize) -> Result<&str, AocError> {
+    match line {
+        Some(l) => Ok(l),
+        None => Err(AocError::parse_error(wire_num, 1, format!("missing wire {}", wire_num))),
+    }
 }
 
 /// Calculates the Manhattan distance between the two given points. Function
@@ -143,7 +155,8 @@ fn calc_manhattan_dist(start_loc: &Vec<usize>, intersection: &Vec<usize>) -> usi
 
 /// Processes the current wire move command, recording any intersections as they
 /// occur. The current location and moves made so far are updated so the new values
-/// can be used in processing the subsequent wire move commands.
+/// can be used in processing the subsequent wire move commands. Returns an error if the wire
+/// leaves the grid.
 fn wire_make_moves_nogrid(
     dir: char,
     num_moves: i32,
@@ -152,7 +165,7 @@ fn wire_make_moves_nogrid(
     intersections: &mut Vec<Vec<usize>>,
     curr_loc: &mut Vec<usize>,
     move_record: &mut HashMap<usize, usize>,
-) {
+) -> Result<(), String> {
     for _ in 0..num_moves {
         // Generate key for storing wire moves
         let key = curr_loc[X_VAR] * GRID_ORD_MAG + curr_loc[Y_VAR];
@@ -173,46 +186,55 @@ fn wire_make_moves_nogrid(
         // Check the current move direction and update location as required
         if dir == DIR_RIGHT {
             if curr_loc[X_VAR] == (GRID_X - 1) {
-                panic!("Grid X-var upper limit reached - make the grid BIGGER!");
+                return Err(String::from("Grid X-var upper limit reached - make the grid BIGGER!"));
             }
             curr_loc[X_VAR] += 1;
         } else if dir == DIR_LEFT {
             if curr_loc[X_VAR] == 0 {
-                panic!("Grid X-var lower limit reached - make the grid BIGGER!");
+                return Err(String::from("Grid X-var lower limit reached - make the grid BIGGER!"));
             }
             curr_loc[X_VAR] -= 1;
         } else if dir == DIR_UP {
             if curr_loc[Y_VAR] == 0 {
-                panic!("Grid Y-var lower limit reached - make the grid BIGGER!");
+                return Err(String::from("Grid Y-var lower limit reached - make the grid BIGGER!"));
             }
             curr_loc[Y_VAR] -= 1;
         } else if dir == DIR_DOWN {
             if curr_loc[Y_VAR] == (GRID_Y - 1) {
-                panic!("Grid Y-var upper limit reacher - make the grid BIGGER!");
+                return Err(String::from("Grid Y-var upper limit reacher - make the grid BIGGER!"));
             }
             curr_loc[Y_VAR] += 1;
         } else {
             // Shouldn't get here!
-            panic!("Day 03 - HERE BE DRAGONS!");
+            return Err(format!("bad wire direction: {}", dir));
         }
     }
+    return Ok(());
 }
 
 /// Processes the given wire, using the record of previous wire moves to determine
-/// if any intersections occur.
+/// if any intersections occur. Returns a parse error if a move isn't valid.
 fn process_wire_nogrid(
     wire: Vec<&str>,
+    wire_num: usize,
     previous_wire_move_record: &HashMap::<usize, HashMap<usize, usize>>,
     intersections: &mut Vec<Vec<usize>>,
     start_loc: &Vec<usize>,
-) -> HashMap<usize, usize> {
+) -> Result<HashMap<usize, usize>, AocError> {
     let mut current_location = start_loc.to_vec();
     let mut moves_so_far = 0;
     let mut move_record = HashMap::<usize, usize>::new();
-    // Process each move in the given wire
+    // Process each move in the given wire, keeping track of the column where it starts
+    let mut column = 1;
     for code in wire {
-        let dir = code.chars().next().unwrap();
-        let num_moves = code[1..].parse::<i32>().unwrap();
+        let bad_move = |message: String| AocError::parse_error(wire_num, column, message);
+        let dir = match code.chars().next() {
+            Some(c) if [DIR_RIGHT, DIR_LEFT, DIR_UP, DIR_DOWN].contains(&c) => c,
+            _ => return Err(bad_move(format!("bad wire move: {}", code))),
+        };
+        let num_moves = code[1..]
+            .parse::<i32>()
+            .map_err(|_| bad_move(format!("bad wire move: {}", code)))?;
         wire_make_moves_nogrid(
             dir,
             num_moves,
@@ -221,9 +243,24 @@ fn process_wire_nogrid(
             intersections,
             &mut current_location,
             &mut move_record,
-        );
+        )
+        .map_err(bad_move)?;
+        column += code.len() + 1;
+    }
+    return Ok(move_record);
+}
+
+/// Solution for Day 03, exposed through the common Solution interface.
+pub struct Day03;
+
+impl Solution for Day03 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
     }
-    return move_record;
 }
 
 #[cfg(test)]
@@ -233,38 +270,61 @@ mod tests {
     /// Test actual solution for Part 1 - to check if this has been broken.
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_03/input.txt"));
+        let result = solution_part_1(String::from("./input/day_03/input.txt")).unwrap();
         assert_eq!(1211, result);
     }
 
     /// Test actual solution for Part 2 - to check if this has been broken.
     #[test]
     fn test_p2_actual_solution() {
-        let result = solution_part_2(String::from("./input/day_03/input.txt"));
+        let result = solution_part_2(String::from("./input/day_03/input.txt")).unwrap();
         assert_eq!(101386, result);
     }
 
     #[test]
     fn test_p1_example_input_1() {
-        let result = solution_part_1(String::from("./input/day_03/test/test_01.txt"));
+        let result = solution_part_1(String::from("./input/day_03/test/test_01.txt")).unwrap();
         assert_eq!(159, result);
     }
 
     #[test]
     fn test_p1_example_input_2() {
-        let result = solution_part_1(String::from("./input/day_03/test/test_02.txt"));
+        let result = solution_part_1(String::from("./input/day_03/test/test_02.txt")).unwrap();
         assert_eq!(135, result);
     }
 
     #[test]
     fn test_p2_example_input_1() {
-        let result = solution_part_2(String::from("./input/day_03/test/test_01.txt"));
+        let result = solution_part_2(String::from("./input/day_03/test/test_01.txt")).unwrap();
         assert_eq!(610, result);
     }
 
     #[test]
     fn test_p2_example_input_2() {
-        let result = solution_part_2(String::from("./input/day_03/test/test_02.txt"));
+        let result = solution_part_2(String::from("./input/day_03/test/test_02.txt")).unwrap();
         assert_eq!(410, result);
     }
+
+    #[test]
+    fn test_bad_wires() {
+        let expected = vec![
+            ("./input/day_06/input.txt", (1, 1)),
+            ("./input/day_03/test/test_bad_move.txt", (2, 4)),
+            ("./input/day_03/test/test_no_crossing.txt", (3, 1)),
+        ];
+        for (filename, location) in expected {
+            let results = vec![
+                solution_part_1(String::from(filename)),
+                solution_part_2(String::from(filename)),
+            ];
+            for result in results {
+                match result {
+                    Err(AocError::Parse { line, column, .. }) => {
+                        assert_eq!(location, (line, column))
+                    }
+                    _ => panic!("Expected parse error."),
+                }
+            }
+        }
+    }
 }
@@ -1,23 +1,10 @@
-use std::fs::File;
-use std::io::Read;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
+use super::utils::io;
 
 /// Calculates solution for Day 04 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> i32 {
-    // Open file
-    let mut file: File = super::utils::fs::open_file(filename);
-    // Read file line
-    let mut input = String::new();
-    match file.read_to_string(&mut input) {
-        Err(e) => panic!("Error: {}", e.to_string()),
-        Ok(_) => 0
-    };
-    // Extract arguments
-    let str_args: Vec<&str> = input.split("-").collect();
-    if str_args.len() != 2 {
-        panic!("Bad input!");
-    }
-    let lower_bound: i32 = str_args[0].parse::<i32>().unwrap();
-    let upper_bound: i32 = str_args[1].parse::<i32>().unwrap();
+pub fn solution_part_1(filename: String) -> Result<i32, AocError> {
+    let (lower_bound, upper_bound) = get_password_range(filename)?;
     // Check password range
     let mut num_valid_passwords = 0;
     for pass_attempt in lower_bound..upper_bound+1 {
@@ -26,26 +13,12 @@ pub fn solution_part_1(filename: String) -> i32 {
             num_valid_passwords += 1;
         }
     }
-    return num_valid_passwords;
+    return Ok(num_valid_passwords);
 }
 
 /// Calculates solution for Day 04 Part 2 challenge.
-pub fn solution_part_2(filename: String) -> i32 {
-    // Open file
-    let mut file: File = super::utils::fs::open_file(filename);
-    // Read file line
-    let mut input = String::new();
-    match file.read_to_string(&mut input) {
-        Err(e) => panic!("Error: {}", e.to_string()),
-        Ok(_) => 0,
-    };
-    // Extract arguments
-    let str_args: Vec<&str> = input.split("-").collect();
-    if str_args.len() != 2 {
-        panic!("Bad input!");
-    }
-    let lower_bound: i32 = str_args[0].parse::<i32>().unwrap();
-    let upper_bound: i32 = str_args[1].parse::<i32>().unwrap();
+pub fn solution_part_2(filename: String) -> Result<i32, AocError> {
+    let (lower_bound, upper_bound) = get_password_range(filename)?;
     // Check password range
     let mut num_valid_passwords = 0;
     for pass_attempt in lower_bound..upper_bound+1 {
@@ -54,7 +27,32 @@ pub fn solution_part_2(filename: String) -> i32 {
             num_valid_passwords += 1;
         }
     }
-    return num_valid_passwords;
+    return Ok(num_valid_passwords);
+}
+
+/// Reads the password range from the given file. Range is given in the form "<lower>-<upper>".
+fn get_password_range(filename: String) -> Result<(i32, i32), AocError> {
+    // Read file line
+    let input = io::try_read_filename_to_string(filename)?;
+    // Extract arguments
+    let str_args: Vec<&str> = input.trim().split('-').collect();
+    if str_args.len() != 2 {
+        let message = String::from("bad input - expected <lower>-<upper>");
+        return Err(AocError::parse_error(1, 1, message));
+    }
+    let mut bounds: Vec<i32> = vec![];
+    let mut column = 1;
+    for str_arg in str_args {
+        match str_arg.parse::<i32>() {
+            Ok(v) => bounds.push(v),
+            Err(e) => {
+                let message = format!("bad range bound \"{}\" ({})", str_arg, e);
+                return Err(AocError::parse_error(1, column, message));
+            }
+        }
+        column += str_arg.len() + 1;
+    }
+    return Ok((bounds[0], bounds[1]));
 }
 
 /// Checks the validity of the given password attempt using the conditions
@@ -127,6 +125,19 @@ fn check_password_validity_part2(pass_attempt: i32) -> bool {
     return false;
 }
 
+/// Solution for Day 04, exposed through the common Solution interface.
+pub struct Day04;
+
+impl Solution for Day04 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,14 +145,14 @@ mod tests {
     /// Test actual solution for Part 1 - to check if this has been broken.
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_04/input.txt"));
+        let result = solution_part_1(String::from("./input/day_04/input.txt")).unwrap();
         assert_eq!(1675, result);
     }
 
     /// Test actual solution for Part 2 - to check if this has been broken.
     #[test]
     fn test_p2_actual_solution() {
-        let result = solution_part_2(String::from("./input/day_04/input.txt"));
+        let result = solution_part_2(String::from("./input/day_04/input.txt")).unwrap();
         assert_eq!(1142, result);
     }
 
@@ -1,26 +1,38 @@
 // Import project utility modules
-use super::utils::fs;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
 use super::utils::intcode::IntcodeMachine;
 use std::collections::VecDeque;
 
 /// Solution for Day 05 Part 1.
-pub fn solution_part_1(filename: String) -> i64 {
-    let mut file = fs::open_file(filename);
-    let int_args = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+pub fn solution_part_1(filename: String) -> Result<i64, AocError> {
+    let int_args = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     let mut machine = IntcodeMachine::new(int_args, VecDeque::from(vec![1]));
-    machine.execute_program();
-    let output = machine.get_output_vec().pop_back().unwrap();
-    return output;
+    machine.try_execute_program()?;
+    let output = machine.try_get_last_output()?;
+    return Ok(output);
 }
 
 /// Solution for Day 05 Part 2.
-pub fn solution_part_2(filename: String) -> i64 {
-    let mut file = fs::open_file(filename);
-    let int_args = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+pub fn solution_part_2(filename: String) -> Result<i64, AocError> {
+    let int_args = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     let mut machine = IntcodeMachine::new(int_args, VecDeque::from(vec![5]));
-    machine.execute_program();
-    let output = machine.get_output();
-    return output;
+    machine.try_execute_program()?;
+    let output = machine.try_get_output()?;
+    return Ok(output);
+}
+
+/// Solution for Day 05, exposed through the common Solution interface.
+pub struct Day05;
+
+impl Solution for Day05 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
 }
 
 #[cfg(test)]
@@ -30,15 +42,29 @@ mod tests {
     /// Test actual solution for Part 1 - to check if this has been broken.
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_05/input.txt"));
+        let result = solution_part_1(String::from("./input/day_05/input.txt")).unwrap();
         assert_eq!(12428642, result);
     }
 
     /// Test actual solution for Part 2 - to check if this has been broken.
     #[test]
     fn test_p2_actual_solution() {
-        let result = solution_part_2(String::from("./input/day_05/input.txt"));
+        let result = solution_part_2(String::from("./input/day_05/input.txt")).unwrap();
         assert_eq!(918655, result);
     }
-}
 
+    /// Tests that a program which never outputs gives an error rather than a panic.
+    #[test]
+    fn test_program_without_output() {
+        let results = vec![
+            solution_part_1(String::from("./input/day_02/input.txt")),
+            solution_part_2(String::from("./input/day_02/input.txt")),
+        ];
+        for result in results {
+            match result {
+                Err(AocError::InvalidProgram(_)) => (),
+                _ => panic!("Expected invalid program error."),
+            }
+        }
+    }
+}
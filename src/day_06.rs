@@ -1,30 +1,31 @@
-use super::utils::fs;
-use std::io::BufReader;
-use std::io::BufRead;
-use std::io::Lines;
-use std::fs::File;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
+use super::utils::io;
 use std::collections::HashMap;
 
 use super::utils::orbit::OrbitNode;
 
 /// Calculates the solution for Day 06 Part 1.
-pub fn solution_part_1(filename: String) -> i32 {
+pub fn solution_part_1(filename: String) -> Result<i32, AocError> {
     return get_number_of_orbits(filename);
 }
 
 /// Calculates the solution for Day 06 Part 2.
-pub fn solution_part_2(filename: String) -> i32 {
+pub fn solution_part_2(filename: String) -> Result<i32, AocError> {
     return get_number_of_orbit_transfers(filename, String::from("YOU"), String::from("SAN"));
 }
 
 /// Calculates the number of orbit transfers required to get from the start node
 /// to the end node, using the map file included in the given map file.
-fn get_number_of_orbit_transfers(filename: String, start_id: String, end_id: String) -> i32 {
-    let file = fs::open_file(filename);
-    let lines = BufReader::new(file).lines();
-    let nodes = process_orbit_map(lines);
-    let mut start_id_parent_id = nodes.get(&start_id).unwrap().get_parent_id();
-    let mut end_id_parent_id = nodes.get(&end_id).unwrap().get_parent_id();
+fn get_number_of_orbit_transfers(
+    filename: String,
+    start_id: String,
+    end_id: String,
+) -> Result<i32, AocError> {
+    let contents = io::try_read_filename_to_string(filename)?;
+    let nodes = process_orbit_map(&contents)?;
+    let mut start_id_parent_id = get_node(&start_id, &nodes, &contents)?.get_parent_id();
+    let mut end_id_parent_id = get_node(&end_id, &nodes, &contents)?.get_parent_id();
     let mut start_id_parent_chain = Vec::<String>::new();
     let mut end_id_parent_chain = Vec::<String>::new();
     // Generate the chain of parents from the start node
@@ -63,40 +64,58 @@ fn get_number_of_orbit_transfers(filename: String, start_id: String, end_id: Str
             break;
         }
     }
+    if last_common_parent.is_empty() {
+        let line = contents.lines().count() + 1;
+        let message = format!("{} and {} don't orbit a common object", start_id, end_id);
+        return Err(AocError::parse_error(line, 1, message));
+    }
     // Calculate number of parents
     let num_parents_start_id = get_number_of_parents(&start_id, &nodes);
     let num_parents_end_id = get_number_of_parents(&end_id, &nodes);
     let num_parents_common_parent = get_number_of_parents(&last_common_parent, &nodes);
     // Calculate result - subtract two to extract first jump from start and end ids
     let result = num_parents_start_id + num_parents_end_id - 2*num_parents_common_parent - 2;
-    return result;
+    return Ok(result);
 }
 
-/// Gets the total number of direct and indirect orbits in the given map file. Panics
-/// if a bad map file is given.
-fn get_number_of_orbits(filename: String) -> i32 {
-    let file = fs::open_file(filename);
-    let lines: Lines<BufReader<File>> = BufReader::new(file).lines();
+/// Gets the total number of direct and indirect orbits in the given map file.
+fn get_number_of_orbits(filename: String) -> Result<i32, AocError> {
+    let contents = io::try_read_filename_to_string(filename)?;
     // Keep track of each node in the orbit map
-    let nodes = process_orbit_map(lines);
+    let nodes = process_orbit_map(&contents)?;
     // Now we need to check how many parents each node has and add these up
     let mut num_orbits = 0;
     for node_name in nodes.keys() {
         num_orbits += get_number_of_parents(node_name, &nodes);
     }
-    return num_orbits;
+    return Ok(num_orbits);
 }
 
-/// Takes the lines from an orbit map and constructs a HashMap consisting of each
+/// Takes the contents of an orbit map and constructs a HashMap consisting of each
 /// node specified somewhere in the file connected to each of its parent and children
-/// nodes.
-fn process_orbit_map(raw_input: Lines<BufReader<File>>) -> HashMap<String, OrbitNode> {
+/// nodes. Returns a parse error if a line isn't an orbit, an object orbits more than
+/// one other object, or the orbits form a cycle.
+fn process_orbit_map(contents: &str) -> Result<HashMap<String, OrbitNode>, AocError> {
     let mut nodes = HashMap::<String, OrbitNode>::new();
-    for raw_line in raw_input {
-        let line = raw_line.unwrap();
+    for (line_index, line) in contents.lines().enumerate() {
         let args: Vec<&str> = line.split(")").collect();
+        if args.len() != 2 {
+            let message = String::from("bad orbit - expected <centre>)<orbiter>");
+            return Err(AocError::parse_error(line_index + 1, 1, message));
+        }
         let centre_of_mass = String::from(args[0]);
         let orbiter = String::from(args[1]);
+        if let Some(orbit_node) = nodes.get(&orbiter) {
+            if orbit_node.has_parent() {
+                let column = args[0].len() + 2;
+                let message = format!("{} already orbits another object", orbiter);
+                return Err(AocError::parse_error(line_index + 1, column, message));
+            }
+        }
+        if is_orbiting(&centre_of_mass, &orbiter, &nodes) {
+            let message = format!("{} orbiting {} forms a cycle", orbiter, centre_of_mass);
+            return Err(AocError::parse_error(line_index + 1, 1, message));
+        }
         // Add the new orbit relationships to the node map
         if !nodes.contains_key(&centre_of_mass) && !nodes.contains_key(&orbiter) {
             // Create new node for COM and orbiter
@@ -132,7 +151,37 @@ fn process_orbit_map(raw_input: Lines<BufReader<File>>) -> HashMap<String, Orbit
             }
         }
     }
-    return nodes;
+    return Ok(nodes);
+}
+
+/// Checks if the given node is, or directly or indirectly orbits, the other node.
+fn is_orbiting(node_name: &str, other_name: &str, nodes: &HashMap<String, OrbitNode>) -> bool {
+    let mut node_name = String::from(node_name);
+    loop {
+        if node_name == other_name {
+            return true;
+        }
+        match nodes.get(&node_name) {
+            Some(node) if node.has_parent() => node_name = node.get_parent_id(),
+            _ => return false,
+        }
+    }
+}
+
+/// Gets the node with the given name, returning a parse error if it isn't in the orbit map.
+fn get_node<'a>(
+    node_name: &str,
+    nodes: &'a HashMap<String, OrbitNode>,
+    contents: &str,
+) -> Result<&'a OrbitNode, AocError> {
+    match nodes.get(node_name) {
+        Some(node) => return Ok(node),
+        None => {
+            let line = contents.lines().count() + 1;
+            let message = format!("no object named {}", node_name);
+            return Err(AocError::parse_error(line, 1, message));
+        }
+    }
 }
 
 /// Gets the number of parents the given OrbitNode has. 
@@ -147,6 +196,19 @@ fn get_number_of_parents(node_name: &String, nodes: &HashMap<String, OrbitNode>)
 }
 
 
+/// Solution for Day 06, exposed through the common Solution interface.
+pub struct Day06;
+
+impl Solution for Day06 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,26 +216,44 @@ mod tests {
     /// Test actual solution for Part 1 - to check if this has been broken.
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_06/input.txt"));
+        let result = solution_part_1(String::from("./input/day_06/input.txt")).unwrap();
         assert_eq!(162816, result);
     }
 
     /// Test actual solution for Part 2 - to check if this has been broken.
     #[test]
     fn test_p2_actual_solution() {
-        let result = solution_part_2(String::from("./input/day_06/input.txt"));
+        let result = solution_part_2(String::from("./input/day_06/input.txt")).unwrap();
         assert_eq!(304, result);
     }
 
     #[test]
     fn test_p1_example_input() {
-        let result = get_number_of_orbits(String::from("./input/day_06/test/test_01.txt"));
+        let result = get_number_of_orbits(String::from("./input/day_06/test/test_01.txt")).unwrap();
         assert_eq!(42, result);
     }
 
     #[test]
     fn test_p2_example_input() {
-        let result = get_number_of_orbit_transfers(String::from("./input/day_06/test/test_02.txt"), String::from("YOU"), String::from("SAN"));
+        let result = get_number_of_orbit_transfers(String::from("./input/day_06/test/test_02.txt"), String::from("YOU"), String::from("SAN")).unwrap();
         assert_eq!(4, result);
     }
+
+    #[test]
+    fn test_bad_orbit_maps() {
+        let expected = vec![
+            ("./input/day_02/input.txt", (1, 1)),
+            ("./input/day_06/test/test_two_parents.txt", (3, 3)),
+            ("./input/day_06/test/test_cycle.txt", (3, 1)),
+            // Example map for Part 1 has no YOU or SAN objects
+            ("./input/day_06/test/test_01.txt", (12, 1)),
+        ];
+        for (filename, location) in expected {
+            match solution_part_2(String::from(filename)) {
+                Err(AocError::Parse { line, column, .. }) => assert_eq!(location, (line, column)),
+                _ => panic!("Expected parse error."),
+            }
+        }
+        assert!(solution_part_1(String::from("./input/day_06/test/test_cycle.txt")).is_err());
+    }
 }
\ No newline at end of file
@@ -1,33 +1,22 @@
-use super::utils::fs;
-use super::utils::intcode::IntcodeMachine;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
+use super::utils::intcode::{IntcodeMachine, IntcodeNetwork, NetworkState, Topology};
 use itertools::Itertools;
 use std::collections::VecDeque;
 
 /// Calculates the solution for Day 07 Part 1. Returned value is tuple containing maximum output
 /// value (index 0) and associated phase combinations (5 values) for amplifiers (index 1).
-pub fn solution_part_1(filename: String) -> (i64, Vec<i64>) {
-    // Load initial memory for amplifiers from input file
-    let mut file = fs::open_file(filename);
-    let initial_memory = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+pub fn solution_part_1(filename: String) -> Result<(i64, Vec<i64>), AocError> {
+    // Load initial memory for amplifiers from input file - each amplifier is forked from this one
+    let initial_memory = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
+    let amp_template = IntcodeMachine::new(initial_memory, VecDeque::new());
     // Generate all possible permutations of phase settings (0-4)
     let phase_permutations = (0..5).permutations(5);
     let mut max_output_value = 0;
     let mut max_outputs_phases = vec![-1, -1, -1, -1, -1];
     for permu in phase_permutations {
-        // Run amplifier A
-        let amp_a_output = run_intcode_machine_as_amp(initial_memory.clone(), permu[0], 0);
-        // Run amplifier B
-        let amp_b_output =
-            run_intcode_machine_as_amp(initial_memory.clone(), permu[1], amp_a_output);
-        // Run amplifier C
-        let amp_c_output =
-            run_intcode_machine_as_amp(initial_memory.clone(), permu[2], amp_b_output);
-        // Run amplifier D
-        let amp_d_output =
-            run_intcode_machine_as_amp(initial_memory.clone(), permu[3], amp_c_output);
-        // Run amplifier E
-        let amp_e_output_to_thruster =
-            run_intcode_machine_as_amp(initial_memory.clone(), permu[4], amp_d_output);
+        // Run amplifiers A to E in a chain, with amplifier A given the first signal
+        let amp_e_output_to_thruster = run_amp_network(&amp_template, &permu, Topology::Chain)?;
         // Check if output is greatest seen so far
         if amp_e_output_to_thruster > max_output_value {
             println!(
@@ -39,100 +28,99 @@ pub fn solution_part_1(filename: String) -> (i64, Vec<i64>) {
         }
     }
     // Return output value and associated phase setting combination
-    return (max_output_value, max_outputs_phases);
+    return Ok((max_output_value, max_outputs_phases));
 }
 
 /// Calculates the solution for Day 07 Part 2. Returned value is tuple containing maximum output
 /// value (index 0) and associated phase combinations (5 values) for amplifiers (index 1).
-pub fn solution_part_2(filename: String) -> (i64, Vec<i64>) {
-    // Load initial memory for amplifiers from input file
-    let mut file = fs::open_file(filename);
-    let initial_memory = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+pub fn solution_part_2(filename: String) -> Result<(i64, Vec<i64>), AocError> {
+    // Load initial memory for amplifiers from input file - each amplifier is forked from this one
+    let initial_memory = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
+    let amp_template = IntcodeMachine::new(initial_memory, VecDeque::new());
     // Generate all possible permutations of phase settings (5-9)
     let phase_permutations = (5..10).permutations(5);
     let mut max_output_value = 0;
     let mut max_output_phases = vec![-1, -1, -1, -1, -1];
     for permu in phase_permutations {
-        // Initial configuration for machines
-        let mut amp_a = IntcodeMachine::new(initial_memory.clone(), VecDeque::from(vec![permu[0], 0]));
-        let mut amp_b = IntcodeMachine::new(initial_memory.clone(), VecDeque::from(vec![permu[1]]));
-        let mut amp_c = IntcodeMachine::new(initial_memory.clone(), VecDeque::from(vec![permu[2]]));
-        let mut amp_d = IntcodeMachine::new(initial_memory.clone(), VecDeque::from(vec![permu[3]]));
-        let mut amp_e = IntcodeMachine::new(initial_memory.clone(), VecDeque::from(vec![permu[4]]));
-        // Maintain the output value of Amp E in case it ends up being the output to thrusters
-        let mut amp_e_output = -1;
-        // Continue executing until amplifier E has halted
-        loop {
-            // Amp A
-            amp_a.execute_program_break_on_output(true);
-            if !amp_a.has_halted() {
-                let amp_a_output = amp_a.get_output(); // Assume output len 1 is program works correctly
-                amp_a.clear_output();
-                amp_b.add_input(amp_a_output);
-            }
-            // Amp B
-            amp_b.execute_program_break_on_output(true);
-            if !amp_b.has_halted() {
-                let amp_b_output = amp_b.get_output();
-                amp_b.clear_output();
-                amp_c.add_input(amp_b_output);
-            }
-            // Amp C
-            amp_c.execute_program_break_on_output(true);
-            if !amp_c.has_halted() {
-                let amp_c_output = amp_c.get_output();
-                amp_c.clear_output();
-                amp_d.add_input(amp_c_output);
-            }
-            // Amp D
-            amp_d.execute_program_break_on_output(true);
-            if !amp_d.has_halted() {
-                let amp_d_output = amp_d.get_output();
-                amp_d.clear_output();
-                amp_e.add_input(amp_d_output);
-            }
-            // Amp E
-            amp_e.execute_program_break_on_output(true);
-            if !amp_e.has_halted() {
-                amp_e_output = amp_e.get_output();
-                amp_e.clear_output();
-                amp_a.add_input(amp_e_output);
-            } else { // Amp E halted - take its output as the output to thrusters
-                let output_to_thrusters = amp_e_output;
-                if output_to_thrusters > max_output_value {
-                    println!(
-                        "Found new max: {} > {} ({:?})",
-                        output_to_thrusters, max_output_value, permu
-                    );
-                    max_output_value = output_to_thrusters;
-                    max_output_phases = permu.to_vec();
-                }
-                break;
-            }
+        let output_to_thrusters = run_amp_network(&amp_template, &permu, Topology::Ring)?;
+        if output_to_thrusters > max_output_value {
+            println!(
+                "Found new max: {} > {} ({:?})",
+                output_to_thrusters, max_output_value, permu
+            );
+            max_output_value = output_to_thrusters;
+            max_output_phases = permu.to_vec();
         }
     }
 
-    return (max_output_value, max_output_phases);
+    return Ok((max_output_value, max_output_phases));
+}
+
+/// Runs amplifiers forked from the template machine with the given phases, connected in the given
+/// topology (a chain or a feedback loop), until they have all halted. Returns the last output of the
+/// final amplifier, which is the output to the thrusters.
+fn run_amp_network(
+    amp_template: &IntcodeMachine,
+    phases: &[i64],
+    topology: Topology,
+) -> Result<i64, AocError> {
+    // Connect the amplifiers, with amplifier A given the first signal
+    let amps = phases.iter().map(|&phase| fork_amp(amp_template, phase)).collect();
+    let mut amp_network = IntcodeNetwork::new(amps, topology);
+    amp_network.add_input(0, 0);
+    // Continue executing until all amplifiers have halted
+    match amp_network.run() {
+        NetworkState::Halted => (),
+        NetworkState::Faulted(index) => {
+            let fault = amp_network.get_machine(index).get_fault().unwrap().clone();
+            return Err(AocError::MachineFault(fault));
+        }
+        state => {
+            return Err(AocError::InvalidProgram(format!(
+                "amplifier network stopped before halting ({:?})",
+                state
+            )))
+        }
+    }
+    // Last output of the final amplifier is the output to thrusters
+    return match amp_network.get_last_output() {
+        Some(output) => Ok(output),
+        None => Err(AocError::InvalidProgram(String::from(
+            "final amplifier produced no output",
+        ))),
+    };
 }
 
-/// Runs the given program in an IntcodeMachine instance, using the given phase and input as the two
-/// input values to the machine (prior to program execution).
-fn run_intcode_machine_as_amp(initial_memory: Vec<i64>, phase: i64, input_value: i64) -> i64 {
-    let amp_input = VecDeque::from(vec![phase, input_value]);
-    let mut amp_machine = IntcodeMachine::new(initial_memory, amp_input);
-    amp_machine.execute_program();
-    let amp_output = amp_machine.get_output();
-    return amp_output;
+/// Forks a new amplifier from the template machine, with the given phase as its first input value.
+fn fork_amp(amp_template: &IntcodeMachine, phase: i64) -> IntcodeMachine {
+    let mut amp_machine = amp_template.fork();
+    amp_machine.add_input(phase);
+    return amp_machine;
+}
+
+/// Solution for Day 07, exposed through the common Solution interface.
+pub struct Day07;
+
+impl Solution for Day07 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        // Answer is the maximum thruster signal - the phase settings are not needed
+        return Ok(Answer::from(solution_part_1(filename)?.0));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?.0));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::intcode::ThreadedNetwork;
 
     /// Test actual solution for Part 1 - to check if this has been broken.
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_07/input.txt"));
+        let result = solution_part_1(String::from("./input/day_07/input.txt")).unwrap();
         assert_eq!(880726, result.0);
         assert_eq!(vec![2,0,1,4,3], result.1);
     }
@@ -140,42 +128,75 @@ mod tests {
     /// Test actual solution for Part 2 - to check if this has been broken.
     #[test]
     fn test_p2_actual_solution() {
-        let result = solution_part_2(String::from("./input/day_07/input.txt"));
+        let result = solution_part_2(String::from("./input/day_07/input.txt")).unwrap();
         assert_eq!(4931744, result.0);
         assert_eq!(vec![7,8,5,6,9], result.1);
     }
 
     #[test]
     fn test_p1_ex_input_01() {
-        let result = solution_part_1(String::from("./input/day_07/test/test_01.txt"));
+        let result = solution_part_1(String::from("./input/day_07/test/test_01.txt")).unwrap();
         assert_eq!(43210, result.0);
         assert_eq!(vec![4, 3, 2, 1, 0], result.1);
     }
 
     #[test]
     fn test_p1_ex_input_02() {
-        let result = solution_part_1(String::from("./input/day_07/test/test_02.txt"));
+        let result = solution_part_1(String::from("./input/day_07/test/test_02.txt")).unwrap();
         assert_eq!(54321, result.0);
         assert_eq!(vec![0, 1, 2, 3, 4], result.1);
     }
 
     #[test]
     fn test_p1_ex_input_03() {
-        let result = solution_part_1(String::from("./input/day_07/test/test_03.txt"));
+        let result = solution_part_1(String::from("./input/day_07/test/test_03.txt")).unwrap();
         assert_eq!(65210, result.0);
         assert_eq!(vec![1, 0, 4, 3, 2], result.1);
     }
 
+    /// Tests that running the amplifiers on their own threads gives the same outputs to thrusters
+    /// as the scheduled network, for every phase setting.
+    #[test]
+    fn test_p2_threaded_matches_network() {
+        let initial_memory = IntcodeMachine::try_extract_intcode_memory_from_filename(String::from(
+            "./input/day_07/input.txt",
+        ))
+        .unwrap();
+        let amp_template = IntcodeMachine::new(initial_memory, VecDeque::new());
+        for permu in (5..10).permutations(5) {
+            let amps = permu.iter().map(|&phase| fork_amp(&amp_template, phase)).collect();
+            let amp_network = ThreadedNetwork::ring(amps);
+            amp_network.send_input(0);
+            let (amps, output) = amp_network.join();
+            assert!(amps.iter().all(|amp| amp.has_halted()));
+            let expected = run_amp_network(&amp_template, &permu, Topology::Ring).unwrap();
+            assert_eq!(Some(&expected), output.last());
+        }
+    }
+
+    /// Tests that amplifier programs which never output give an error rather than a panic.
+    #[test]
+    fn test_program_without_output() {
+        match solution_part_1(String::from("./input/day_02/input.txt")) {
+            Err(AocError::InvalidProgram(_)) => (),
+            _ => panic!("Expected invalid program error."),
+        }
+        match solution_part_2(String::from("./input/day_02/input.txt")) {
+            Err(AocError::InvalidProgram(_)) => (),
+            _ => panic!("Expected invalid program error."),
+        }
+    }
+
     #[test]
     fn test_p2_ex_input_04() {
-        let result = solution_part_2(String::from("./input/day_07/test/test_04.txt"));
+        let result = solution_part_2(String::from("./input/day_07/test/test_04.txt")).unwrap();
         assert_eq!(139629729, result.0);
         assert_eq!(vec![9,8,7,6,5], result.1);
     }
 
     #[test]
     fn test_p2_ex_input_05() {
-        let result = solution_part_2(String::from("./input/day_07/test/test_05.txt"));
+        let result = solution_part_2(String::from("./input/day_07/test/test_05.txt")).unwrap();
         assert_eq!(18216, result.0);
         assert_eq!(vec![9,7,8,5,6], result.1);
     }
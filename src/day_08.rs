@@ -1,20 +1,16 @@
-use std::io::Read;
-use super::utils::fs;
+use super::solution::{Answer, Image, Solution};
+use super::utils::error::AocError;
+use super::utils::io;
 use super::utils::sif::SifImage;
 use std::u32::*;
 
 /// Calculates solution for Day 08 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> u32 {
-    let mut file = fs::open_file(filename);
-    let mut read_buf = String::from("");
-    match file.read_to_string(&mut read_buf) {
-        Err(e) => panic!("Error reading file. ({})", e.to_string()),
-        Ok(_) => 0,
-    };
+pub fn solution_part_1(filename: String) -> Result<u32, AocError> {
+    let mut read_buf = io::try_read_filename_to_string(filename)?;
     read_buf = String::from(read_buf.trim());
     // Load image
     let mut sif_image = SifImage::new(25, 6);
-    sif_image.load_image_data(read_buf);
+    sif_image.try_load_image_data(read_buf)?;
     // Check layers for one with fewest 0's
     let num_layers = sif_image.get_num_layers();
     let mut min_zero_count: u32 = MAX;
@@ -30,24 +26,31 @@ pub fn solution_part_1(filename: String) -> u32 {
     let num_1 = sif_image.get_layer_digit_count(min_zero_layer_index, 1).unwrap();
     let num_2 = sif_image.get_layer_digit_count(min_zero_layer_index, 2).unwrap();
     let result = num_1 * num_2;
-    return result;
+    return Ok(result);
 }
 
-/// Displays solution for Day 08 Part 2 challenge. Output resembles string "GKCKH".
-pub fn solution_part_2(filename: String) {
-    let mut file = fs::open_file(filename);
-    let mut read_buf = String::from("");
-    match file.read_to_string(&mut read_buf) {
-        Err(e) => panic!("Error reading file. ({})", e.to_string()),
-        Ok(_) => 0,
-    };
+/// Calculates solution for Day 08 Part 2 challenge. Rendered image resembles string "GKCKH".
+pub fn solution_part_2(filename: String) -> Result<Image, AocError> {
+    let mut read_buf = io::try_read_filename_to_string(filename)?;
     read_buf = String::from(read_buf.trim());
     // Load image
     let mut sif_image = SifImage::new(25, 6);
-    sif_image.load_image_data(read_buf);
+    sif_image.try_load_image_data(read_buf)?;
     sif_image.process_image();
-    // Render image
-    sif_image.render_image();
+    return Ok(sif_image.to_image());
+}
+
+/// Solution for Day 08, exposed through the common Solution interface.
+pub struct Day08;
+
+impl Solution for Day08 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
 }
 
 #[cfg(test)]
@@ -56,7 +59,36 @@ mod tests {
 
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_08/input.txt"));
+        let result = solution_part_1(String::from("./input/day_08/input.txt")).unwrap();
         assert_eq!(1463, result);
     }
+
+    #[test]
+    fn test_p2_actual_solution() {
+        let result = solution_part_2(String::from("./input/day_08/input.txt")).unwrap();
+        let expected = concat!(
+            " ##  #  #  ##  #  # #  # \n",
+            "#  # # #  #  # # #  #  # \n",
+            "#    ##   #    ##   #### \n",
+            "# ## # #  #    # #  #  # \n",
+            "#  # # #  #  # # #  #  # \n",
+            " ### #  #  ##  #  # #  # \n",
+        );
+        assert_eq!(expected, result.render());
+    }
+
+    #[test]
+    fn test_bad_image_data() {
+        let expected = vec![
+            ("./input/day_02/input.txt", (1, 2)),
+            ("./input/day_08/test/test_partial_layer.txt", (1, 154)),
+        ];
+        for (filename, location) in expected {
+            match solution_part_1(String::from(filename)) {
+                Err(AocError::Parse { line, column, .. }) => assert_eq!(location, (line, column)),
+                _ => panic!("Expected parse error."),
+            }
+            assert!(solution_part_2(String::from(filename)).is_err());
+        }
+    }
 }
\ No newline at end of file
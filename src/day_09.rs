@@ -1,25 +1,37 @@
 use std::collections::VecDeque;
-use super::utils::fs;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
 use super::utils::intcode::IntcodeMachine;
 
 /// Calculates solution for Day 09 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> i64 {
-    let mut file = fs::open_file(filename);
-    let int_args = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+pub fn solution_part_1(filename: String) -> Result<i64, AocError> {
+    let int_args = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     let mut machine = IntcodeMachine::new(int_args, VecDeque::from(vec![1]));
-    machine.execute_program();
-    let output = machine.get_output();
-    return output;
+    machine.try_execute_program()?;
+    let output = machine.try_get_output()?;
+    return Ok(output);
 }
 
 /// Calculates solution for Day 09 Part 2 challenge.
-pub fn solution_part_2(filename: String) -> i64 {
-    let mut file = fs::open_file(filename);
-    let int_args = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+pub fn solution_part_2(filename: String) -> Result<i64, AocError> {
+    let int_args = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     let mut machine = IntcodeMachine::new(int_args, VecDeque::from(vec![2]));
-    machine.execute_program();
-    let output = machine.get_output();
-    return output;
+    machine.try_execute_program()?;
+    let output = machine.try_get_output()?;
+    return Ok(output);
+}
+
+/// Solution for Day 09, exposed through the common Solution interface.
+pub struct Day09;
+
+impl Solution for Day09 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
 }
 
 #[cfg(test)]
@@ -29,14 +41,29 @@ mod tests {
     ///  Tests actual solution for Day 09 Part 1 challenge.
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_09/input.txt"));
+        let result = solution_part_1(String::from("./input/day_09/input.txt")).unwrap();
         assert_eq!(result, 2745604242);
     }
 
     ///  Tests actual solution for Day 09 Part 2 challenge.
     #[test]
     fn test_p2_actual_solution() {
-        let result = solution_part_2(String::from("./input/day_09/input.txt"));
+        let result = solution_part_2(String::from("./input/day_09/input.txt")).unwrap();
         assert_eq!(result, 51135);
     }
+
+    /// Tests that a program which never outputs gives an error rather than a panic.
+    #[test]
+    fn test_program_without_output() {
+        let results = vec![
+            solution_part_1(String::from("./input/day_02/input.txt")),
+            solution_part_2(String::from("./input/day_02/input.txt")),
+        ];
+        for result in results {
+            match result {
+                Err(AocError::InvalidProgram(_)) => (),
+                _ => panic!("Expected invalid program error."),
+            }
+        }
+    }
 }
@@ -1,12 +1,10 @@
-use super::utils::fs;
+use super::solution::{Answer, Image, Solution};
+use super::utils::error::AocError;
 use super::utils::intcode::IntcodeMachine;
 use super::utils::maps::CardinalDirection;
 use euclid::*;
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::fs::File;
-use std::io::BufWriter;
-use std::path::Path;
 
 /// This struct is used to represent the current state of the hull-paining robot introduced in Day
 /// 11.
@@ -26,6 +24,14 @@ struct GridDimensions {
     y_max: i32,
 }
 
+/// Result of painting the hull: the number of squares painted at least once, the dimensions of the
+/// painted area and the paint state of the hull.
+type PaintedHull = (
+    i32,
+    GridDimensions,
+    HashMap<Point2D<i32, UnknownUnit>, GridPaintState>,
+);
+
 /// Enum used to represent the state of the hull squares. All locations are assumed to be
 /// BlackUnpainted initially.
 #[derive(PartialEq, Clone, Copy)]
@@ -49,40 +55,35 @@ impl GridPaintState {
         }
     }
 
-    /// Converts the paint colour into the corresponding RGBA values.
-    pub fn get_rgba_from_paint_colour(&self) -> Vec<u8> {
-        match self {
-            GridPaintState::BlackUnpainted => vec![0, 0, 0, 255],
-            GridPaintState::BlackPainted => vec![0, 0, 0, 255],
-            GridPaintState::WhitePainted => vec![255, 255, 255, 255],
-        }
+    /// Checks if the paint colour is white, i.e. would be shown as a lit pixel.
+    pub fn is_white(&self) -> bool {
+        return *self == GridPaintState::WhitePainted;
     }
 }
 
 /// Calculates the solution for Day 11 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> i32 {
+pub fn solution_part_1(filename: String) -> Result<i32, AocError> {
     // Load up robot initial memory
-    let mut file = fs::open_file(filename);
-    let initial_memory = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+    let initial_memory = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     // Let's paint the hull
-    let result = paint_hull(initial_memory, HashMap::new());
-    return result.0;
+    let result = paint_hull(initial_memory, HashMap::new())?;
+    return Ok(result.0);
 }
 
-/// Calculates the solution for Day 11 Part 2 challenge.
-pub fn solution_part_2(filename: String) {
+/// Calculates the solution for Day 11 Part 2 challenge. Returned image shows the registration
+/// identifier painted by the robot.
+pub fn solution_part_2(filename: String) -> Result<Image, AocError> {
     // Load up robot initial memory
-    let mut file = fs::open_file(filename);
-    let initial_memory = IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+    let initial_memory = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     // Let's paint the hull
     let mut initial_grid_state: HashMap<Point2D<i32, UnknownUnit>, GridPaintState> = HashMap::new();
     initial_grid_state.insert(Point2D::new(0, 0), GridPaintState::WhitePainted);
-    let result = paint_hull(initial_memory, initial_grid_state);
+    let result = paint_hull(initial_memory, initial_grid_state)?;
     // Extract data from the result tuple
     let grid_dimensions: GridDimensions = result.1;
     let grid_state: HashMap<Point2D<i32, UnknownUnit>, GridPaintState> = result.2;
     // Reconstruct the painted state
-    let mut image_data: Vec<u8> = vec![];
+    let mut image = Image::new(grid_dimensions.width as usize, grid_dimensions.height as usize);
     for y_loc in 0..grid_dimensions.height {
         for x_loc in 0..grid_dimensions.width {
             // Calculate the adjusted location as it would have been seen by robot
@@ -90,22 +91,27 @@ pub fn solution_part_2(filename: String) {
             let y_adj = y_loc as i32 + grid_dimensions.y_min;
             let adjusted_location: Point2D<i32, UnknownUnit> = Point2D::new(x_adj, y_adj);
             // Get the square colour to reconstruct the end state
-            let mut rgba_values = match grid_state.get(&adjusted_location) {
-                None => GridPaintState::BlackUnpainted.get_rgba_from_paint_colour(),
-                Some(colour) => colour.get_rgba_from_paint_colour(),
+            let is_white = match grid_state.get(&adjusted_location) {
+                None => GridPaintState::BlackUnpainted.is_white(),
+                Some(colour) => colour.is_white(),
             };
-            image_data.append(&mut rgba_values);
+            image.set_pixel(x_loc as usize, y_loc as usize, is_white);
         }
     }
-    // Write the data to a PNG image
-    let path = Path::new(r"./aoc-2019-day-11-p2.png");
-    let file = File::create(path).unwrap();
-    let w = BufWriter::new(file);
-    let mut encoder = png::Encoder::new(w, grid_dimensions.width, grid_dimensions.height);
-    encoder.set_color(png::ColorType::RGBA);
-    encoder.set_depth(png::BitDepth::Eight);
-    let mut writer = encoder.write_header().unwrap();
-    writer.write_image_data(&image_data).unwrap();
+    return Ok(image);
+}
+
+/// Solution for Day 11, exposed through the common Solution interface.
+pub struct Day11;
+
+impl Solution for Day11 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
 }
 
 /// Calculates the width and height of a grid from the minimum and maximum observed x- and y-
@@ -126,15 +132,12 @@ fn calculate_grid_dimensions(min_x: i32, max_x: i32, min_y: i32, max_y: i32) ->
 ///
 /// The return data is a tuple consisting of: (index 0) number of squares painted at least once,
 /// (index 1) width and height dimenions of the resulting painted area, and (index 2) the paint
-/// state of the hull.
+/// state of the hull. An error is returned if the robot program faults or doesn't give a valid
+/// colour and rotation each time it is given the current colour.
 fn paint_hull(
     initial_memory: Vec<i64>,
     initial_grid_state: HashMap<Point2D<i32, UnknownUnit>, GridPaintState>,
-) -> (
-    i32,
-    GridDimensions,
-    HashMap<Point2D<i32, UnknownUnit>, GridPaintState>,
-) {
+) -> Result<PaintedHull, AocError> {
     // Initialise robot
     let mut robot = HullPaintingRobot {
         computer: IntcodeMachine::new(initial_memory.clone(), VecDeque::from(vec![])),
@@ -166,21 +169,19 @@ fn paint_hull(
         };
         // Provide appropriate input to robot and execute program
         robot.computer.add_input(robot_input);
-        robot.computer.execute_program();
+        robot.computer.try_execute_program()?;
         // Read the two output values from robot
-        let paint_colour_arg = robot.computer.get_output_and_remove() as i32;
-        let rotate_arg = robot.computer.get_output_and_remove() as i32;
+        let paint_colour_arg = robot.computer.try_get_output_and_remove()? as i32;
+        let rotate_arg = robot.computer.try_get_output_and_remove()?;
         if !robot.computer.is_output_empty() {
-            panic!("Output should be empty but isn't!");
+            return Err(AocError::InvalidProgram(String::from(
+                "robot gave more than two outputs",
+            )));
         }
         // Paint current grid square
-        let new_colour = GridPaintState::get_state_from_integer(paint_colour_arg);
-        match new_colour {
-            Err(e) => panic!(e),
-            Ok(val) => {
-                grid_state.insert(robot.location, val);
-            }
-        }
+        let new_colour = GridPaintState::get_state_from_integer(paint_colour_arg)
+            .map_err(AocError::InvalidProgram)?;
+        grid_state.insert(robot.location, new_colour);
         // Increment result if square was not already painted
         if prev_colour == GridPaintState::BlackUnpainted {
             coloured_at_least_once += 1;
@@ -188,13 +189,18 @@ fn paint_hull(
         // if robot has halted, result result
         if robot.computer.has_halted() {
             let grid_dimensions = calculate_grid_dimensions(min_x, max_x, min_y, max_y);
-            return (coloured_at_least_once, grid_dimensions, grid_state);
+            return Ok((coloured_at_least_once, grid_dimensions, grid_state));
         }
         // Rotate robot by 90 degrees and move by one square
         let rotate_direction = match rotate_arg {
             0 => false,
             1 => true,
-            _ => panic!("Unknown rotation direction value observed."),
+            _ => {
+                return Err(AocError::InvalidProgram(format!(
+                    "unknown rotation direction: {}",
+                    rotate_arg
+                )))
+            }
         };
         robot.direction = robot
             .direction
@@ -235,7 +241,22 @@ mod tests {
 
     #[test]
     fn test_p1_actual_solution() {
-        let result = solution_part_1(String::from("./input/day_11/input.txt"));
+        let result = solution_part_1(String::from("./input/day_11/input.txt")).unwrap();
         assert_eq!(1686, result);
     }
+
+    #[test]
+    fn test_bad_robot_program() {
+        let results = vec![
+            solution_part_1(String::from("./input/day_02/input.txt")),
+            solution_part_1(String::from("./input/day_11/test/test_bad_colour.txt")),
+        ];
+        for result in results {
+            match result {
+                Err(AocError::InvalidProgram(_)) => (),
+                _ => panic!("Expected invalid program error."),
+            }
+        }
+        assert!(solution_part_2(String::from("./input/day_02/input.txt")).is_err());
+    }
 }
@@ -1,4 +1,5 @@
-use super::utils::fs;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
 use super::utils::io;
 use super::utils::math;
 use itertools::Itertools;
@@ -9,6 +10,9 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::Hash;
 use std::hash::Hasher;
 
+/// Number of moons given in the input, all of which the simulation expects to be present.
+const NUM_MOONS: usize = 4;
+
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
 struct SpaceObject {
     pos_x: i64,
@@ -112,9 +116,9 @@ impl VelocityDelta {
 }
 
 /// Calculates solution to Day 12 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> i64 {
-    let mut moons = get_moon_data(filename);
-    return calculate_total_energy(&mut moons, 1000);
+pub fn solution_part_1(filename: String) -> Result<i64, AocError> {
+    let mut moons = get_moon_data(filename)?;
+    return Ok(calculate_total_energy(&mut moons, 1000));
 }
 
 /// Calculates the hashes for the state of the x-, y- and z-axes for all the moons taken together.
@@ -136,8 +140,8 @@ fn get_moon_xyz_hashes(moons: &Vec<SpaceObject>) -> (u64, u64, u64) {
 }
 
 /// Calculates solution for Day 12 Part 2 challenge.
-pub fn solution_part_2(filename: String) -> u128 {
-    let mut moons = get_moon_data(filename);
+pub fn solution_part_2(filename: String) -> Result<u128, AocError> {
+    let mut moons = get_moon_data(filename)?;
     let mut steps: u64 = 0;
     // We are looking at each axis individually, so need to keep hashes for each axis
     let mut x_hashes = HashSet::<u64>::new();
@@ -183,25 +187,42 @@ pub fn solution_part_2(filename: String) -> u128 {
     }
     let repeat_steps = vec![x_repeat_steps, y_repeat_steps, z_repeat_steps];
     let lcm = math::calculate_lcm(repeat_steps);
-    return lcm;
+    return Ok(lcm);
 }
 
-/// Parses the given file and returns a vector containing the moons specified in file.
-fn get_moon_data(filename: String) -> Vec<SpaceObject> {
+/// Parses the given file and returns a vector containing the moons specified in file. Returns a
+/// parse error if a line doesn't specify a moon, or there aren't exactly four moons.
+fn get_moon_data(filename: String) -> Result<Vec<SpaceObject>, AocError> {
     // Open file and initialise space objects
-    let mut file = fs::open_file(filename);
-    let raw_input = io::read_file_to_string(&mut file);
+    let raw_input = io::try_read_filename_to_string(filename)?;
     // Read the input data using a regex and insert objects into array
-    let moon_regex = Regex::new(r"<x=(-?\d+), y=(-?\d+), z=(-?\d+)>").unwrap();
+    let moon_regex = Regex::new(r"^<x=(-?\d+), y=(-?\d+), z=(-?\d+)>$").unwrap();
     let mut moons: Vec<SpaceObject> = vec![];
-    for capture in moon_regex.captures_iter(&raw_input) {
-        let x_pos = capture[1].parse().unwrap();
-        let y_pos = capture[2].parse().unwrap();
-        let z_pos = capture[3].parse().unwrap();
-        let moon = SpaceObject::new(x_pos, y_pos, z_pos);
+    for (line_index, line) in raw_input.lines().enumerate() {
+        let capture = match moon_regex.captures(line) {
+            Some(capture) => capture,
+            None => {
+                let message = String::from("bad moon - expected <x=X, y=Y, z=Z>");
+                return Err(AocError::parse_error(line_index + 1, 1, message));
+            }
+        };
+        let mut position = [0; 3];
+        for (axis, axis_position) in position.iter_mut().enumerate() {
+            let value = capture.get(axis + 1).unwrap();
+            *axis_position = value.as_str().parse().map_err(|_| {
+                let message = format!("bad moon position: {}", value.as_str());
+                return AocError::parse_error(line_index + 1, value.start() + 1, message);
+            })?;
+        }
+        let moon = SpaceObject::new(position[0], position[1], position[2]);
         moons.push(moon);
     }
-    return moons;
+    if moons.len() != NUM_MOONS {
+        let line = raw_input.lines().count() + 1;
+        let message = format!("expected {} moons, found {}", NUM_MOONS, moons.len());
+        return Err(AocError::parse_error(line, 1, message));
+    }
+    return Ok(moons);
 }
 
 fn do_moon_step(moons: &mut Vec<SpaceObject>) {
@@ -278,45 +299,74 @@ fn calculate_velocity_deltas(
     return (moon_a_vel_delta, moon_b_vel_delta);
 }
 
+/// Solution for Day 12, exposed through the common Solution interface.
+pub struct Day12;
+
+impl Solution for Day12 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_p1_ex_input_01() {
-        let mut moons = get_moon_data(String::from("./input/day_12/test/test_01.txt"));
+        let mut moons = get_moon_data(String::from("./input/day_12/test/test_01.txt")).unwrap();
         let total_energy = calculate_total_energy(&mut moons, 10);
         assert_eq!(179, total_energy);
     }
 
     #[test]
     fn test_p1_ex_input_02() {
-        let mut moons = get_moon_data(String::from("./input/day_12/test/test_02.txt"));
+        let mut moons = get_moon_data(String::from("./input/day_12/test/test_02.txt")).unwrap();
         let total_energy = calculate_total_energy(&mut moons, 100);
         assert_eq!(1940, total_energy);
     }
 
     #[test]
     fn test_p1_solution() {
-        let result = solution_part_1(String::from("./input/day_12/input.txt"));
+        let result = solution_part_1(String::from("./input/day_12/input.txt")).unwrap();
         assert_eq!(7179, result);
     }
 
     #[test]
     fn test_p2_ex_input_01() {
-        let result = solution_part_2(String::from("./input/day_12/test/test_01.txt"));
+        let result = solution_part_2(String::from("./input/day_12/test/test_01.txt")).unwrap();
         assert_eq!(2772, result);
     }
 
     #[test]
     fn test_p2_ex_input_02() {
-        let result = solution_part_2(String::from("./input/day_12/test/test_02.txt"));
+        let result = solution_part_2(String::from("./input/day_12/test/test_02.txt")).unwrap();
         assert_eq!(4686774924, result);
     }
 
     #[test]
     fn test_p2_solution() {
-        let result = solution_part_2(String::from("./input/day_12/input.txt"));
+        let result = solution_part_2(String::from("./input/day_12/input.txt")).unwrap();
         assert_eq!(428576638953552, result);
     }
+
+    #[test]
+    fn test_bad_moon_data() {
+        let expected = vec![
+            ("./input/day_02/input.txt", (1, 1)),
+            ("./input/day_12/test/test_three_moons.txt", (4, 1)),
+            ("./input/day_12/test/test_big_position.txt", (2, 9)),
+        ];
+        for (filename, location) in expected {
+            match solution_part_1(String::from(filename)) {
+                Err(AocError::Parse { line, column, .. }) => assert_eq!(location, (line, column)),
+                _ => panic!("Expected parse error."),
+            }
+            assert!(solution_part_2(String::from(filename)).is_err());
+        }
+    }
 }
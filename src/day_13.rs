@@ -1,4 +1,6 @@
-use super::utils::intcode::IntcodeMachine;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
+use super::utils::intcode::{IntcodeMachine, Profile, RunState};
 use std::collections::VecDeque;
 use std::collections::HashMap;
 
@@ -21,34 +23,44 @@ struct Point {
 }
 
 /// Calculates the solution for Day 13 Part 1.
-pub fn solution_part_1(filename: String) -> u64 {
-    let initial_memory = IntcodeMachine::extract_intcode_memory_from_filename(filename);
+pub fn solution_part_1(filename: String) -> Result<u64, AocError> {
+    let initial_memory = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     let mut arcade_machine = IntcodeMachine::new(initial_memory.clone(), VecDeque::from(vec![]));
     let mut screen = HashMap::<Point, i64>::new();
+    let mut outputs = Vec::<i64>::new();
     loop {
-        // Should be able to run machine and break on output at least three times validly
-        for _ in 0..3 {
-            arcade_machine.execute_program_break_on_output(true);
-        }
-        // Check if the arcade machine has halted
-        if arcade_machine.has_halted() {
-            if arcade_machine.get_output_vec().len() != 0 {
-                panic!("Arcade machine output is not empty after halting!");
+        // Collect output values until we have a full tile triple
+        match arcade_machine.run()? {
+            RunState::Halted => {
+                if !outputs.is_empty() {
+                    return Err(arcade_error("halted part way through a tile"));
+                }
+                break;
+            }
+            RunState::NeedsInput => {
+                return Err(arcade_error("requested input without a game running"))
             }
-            break;
+            RunState::Output(value) => outputs.push(value),
+            state => {
+                return Err(arcade_error(&format!("stopped unexpectedly: {:?}", state)));
+            }
+        }
+        if outputs.len() < 3 {
+            continue;
         }
-        let x_pos = arcade_machine.get_output_and_remove();
-        let y_pos = arcade_machine.get_output_and_remove();
-        let tile_id = arcade_machine.get_output_and_remove();
+        let x_pos = outputs[0];
+        let y_pos = outputs[1];
+        let tile_id = outputs[2];
+        outputs.clear();
         // Check validity of output values
         if x_pos < 0 {
-            panic!(format!("Bad x-pos: {}", x_pos));
+            return Err(arcade_error(&format!("gave bad x-pos: {}", x_pos)));
         }
         if y_pos < 0 {
-            panic!(format!("Bad y-pos: {}", y_pos));
+            return Err(arcade_error(&format!("gave bad y-pos: {}", y_pos)));
         }
         if tile_id < 0 || tile_id > 4 {
-            panic!(format!("Bad tile id: {}", tile_id));
+            return Err(arcade_error(&format!("gave bad tile id: {}", tile_id)));
         }
         let position = Point{x: x_pos, y: y_pos};
         screen.insert(position, tile_id);
@@ -60,56 +72,89 @@ pub fn solution_part_1(filename: String) -> u64 {
             block_count += 1;
         }
     }
-    return block_count;
+    return Ok(block_count);
 }
 
-pub fn solution_part_2(filename: String) -> i64 {
-    let mut initial_memory = IntcodeMachine::extract_intcode_memory_from_filename(filename);
+/// Calculates the solution for Day 13 Part 2.
+pub fn solution_part_2(filename: String) -> Result<i64, AocError> {
+    let mut arcade_machine = create_game_machine(filename)?;
+    return play_game(&mut arcade_machine);
+}
+
+/// Plays the game as in Part 2 with profiling enabled on the arcade machine, returning the final
+/// score and the execution profile of the game.
+pub fn profile_part_2(filename: String) -> Result<(i64, Profile), AocError> {
+    let mut arcade_machine = create_game_machine(filename)?;
+    arcade_machine.enable_profiling();
+    let score = play_game(&mut arcade_machine)?;
+    return Ok((score, arcade_machine.take_profile().unwrap()));
+}
+
+/// Creates the arcade machine for Part 2, with the game program loaded from the given file.
+fn create_game_machine(filename: String) -> Result<IntcodeMachine, AocError> {
+    let mut initial_memory = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     // Insert 2 quarters to play for free
     initial_memory[0] = 2;
-    let mut arcade_machine = IntcodeMachine::new(initial_memory.clone(), VecDeque::from(vec![]));
+    return Ok(IntcodeMachine::new(initial_memory, VecDeque::from(vec![])));
+}
+
+/// Creates the error returned when the arcade machine doesn't behave like an arcade game.
+fn arcade_error(message: &str) -> AocError {
+    return AocError::InvalidProgram(format!("arcade machine {}", message));
+}
+
+/// Plays the game running on the arcade machine until it halts, moving the joystick to follow the
+/// ball with the paddle. Returns the final score.
+fn play_game(arcade_machine: &mut IntcodeMachine) -> Result<i64, AocError> {
     let mut score = 0;
     let mut ball_positions = Vec::<Point>::from(vec![]);
     let mut paddle_positions: Vec<Point> = vec![];
     let mut block_positions = Vec::<Point>::from(vec![]);
     let mut screen = HashMap::<Point, i64>::new();
+    let mut outputs = Vec::<i64>::new();
     loop {
-        // Run machine to get output triple
-        for _ in 0..3 {
-            arcade_machine.execute_program_break_on_output(true);
-        }
-        // Check if the arcade machine has halted
-        if arcade_machine.has_halted() {
-            if arcade_machine.get_output_vec().len() != 0 {
-                panic!("Arcade machine output is not empty after halting!");
-            }
-            break;
-        }
-        // Check if machine is awaiting input
-        if arcade_machine.is_awaiting_input() {
-            // Check what direction to move the joystick
-            if ball_positions.is_empty() {
-                panic!("Don't know how to move paddle - no ball positions observed.");
+        // Run machine to get output triple, moving the joystick whenever the machine needs input
+        match arcade_machine.run()? {
+            RunState::Halted => {
+                if !outputs.is_empty() {
+                    return Err(arcade_error("halted part way through a tile"));
+                }
+                break;
             }
-            // If we haven't seen the paddle yet, don't try to move it
-            if paddle_positions.is_empty() {
-                arcade_machine.add_input(JOYSTICK_NEUTRAL);
+            RunState::NeedsInput => {
+                // Check what direction to move the joystick
+                if ball_positions.is_empty() {
+                    return Err(arcade_error("no ball positions observed to move paddle"));
+                }
+                // If we haven't seen the paddle yet, don't try to move it
+                if paddle_positions.is_empty() {
+                    arcade_machine.add_input(JOYSTICK_NEUTRAL);
+                    continue;
+                }
+                // Ball is to left of paddle
+                if ball_positions.last().unwrap().x < paddle_positions.first().unwrap().x {
+                    arcade_machine.add_input(JOYSTICK_LEFT);
+                // Ball is to right of paddle
+                } else if ball_positions.last().unwrap().x > paddle_positions.last().unwrap().x {
+                    arcade_machine.add_input(JOYSTICK_RIGHT);
+                // Ball is above one of the paddle elements
+                } else {
+                    arcade_machine.add_input(JOYSTICK_NEUTRAL);
+                }
+                continue;
             }
-            // Ball is to left of paddle
-            if ball_positions.last().unwrap().x < paddle_positions.first().unwrap().x {
-                arcade_machine.add_input(JOYSTICK_LEFT);
-            // Ball is to right of paddle
-            } else if ball_positions.last().unwrap().x > paddle_positions.last().unwrap().x {
-                arcade_machine.add_input(JOYSTICK_RIGHT);
-            // Ball is above one of the paddle elements
-            } else {
-                arcade_machine.add_input(JOYSTICK_NEUTRAL);
+            RunState::Output(value) => outputs.push(value),
+            state => {
+                return Err(arcade_error(&format!("stopped unexpectedly: {:?}", state)));
             }
+        }
+        if outputs.len() < 3 {
             continue;
         }
-        let output_1 = arcade_machine.get_output_and_remove();
-        let output_2 = arcade_machine.get_output_and_remove();
-        let output_3 = arcade_machine.get_output_and_remove();
+        let output_1 = outputs[0];
+        let output_2 = outputs[1];
+        let output_3 = outputs[2];
+        outputs.clear();
         // Check if score has been updated
         if output_1 == -1 && output_2 == 0 {
             score = output_3;
@@ -150,5 +195,64 @@ pub fn solution_part_2(filename: String) -> i64 {
             continue;
         }
     }
-    return score;
+    return Ok(score);
+}
+
+/// Solution for Day 13, exposed through the common Solution interface.
+pub struct Day13;
+
+impl Solution for Day13 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///  Tests actual solution for Day 13 Part 1 challenge.
+    #[test]
+    fn test_p1_actual_solution() {
+        let result = solution_part_1(String::from("./input/day_13/input.txt")).unwrap();
+        assert_eq!(result, 291);
+    }
+
+    ///  Tests actual solution for Day 13 Part 2 challenge.
+    #[test]
+    fn test_p2_actual_solution() {
+        let result = solution_part_2(String::from("./input/day_13/input.txt")).unwrap();
+        assert_eq!(result, 14204);
+    }
+
+    ///  Tests that profiling the Day 13 Part 2 game gives the same score and counts its I/O.
+    #[test]
+    fn test_p2_profile() {
+        let (score, profile) = profile_part_2(String::from("./input/day_13/input.txt")).unwrap();
+        assert_eq!(score, 14204);
+        assert_eq!(profile.get_outputs_produced() % 3, 0);
+        assert!(profile.get_inputs_consumed() > 0);
+        assert!(!profile.get_hot_loops(1).is_empty());
+    }
+
+    ///  Tests that programs which aren't arcade games give errors rather than panics.
+    #[test]
+    fn test_bad_arcade_program() {
+        let results = vec![
+            solution_part_1(String::from("./input/day_05/input.txt")).map(|_| ()),
+            solution_part_1(String::from("./input/day_17/input.txt")).map(|_| ()),
+            solution_part_2(String::from("./input/day_09/input.txt")).map(|_| ()),
+            solution_part_2(String::from("./input/day_17/input.txt")).map(|_| ()),
+        ];
+        for result in results {
+            match result {
+                Err(AocError::InvalidProgram(_)) => (),
+                _ => panic!("Expected invalid program error."),
+            }
+        }
+    }
 }
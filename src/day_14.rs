@@ -1,4 +1,5 @@
-use super::utils::fs;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
 use super::utils::io;
 use std::collections::HashMap;
 
@@ -6,28 +7,46 @@ use std::collections::HashMap;
 struct Material {
     name: String,
     quantity: u64,
+    /// Column of the material in the input file, used to report errors.
+    column: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct ChemicalReaction {
     input: Vec<Material>,
     output: Material,
+    /// Line of the reaction in the input file, used to report errors.
+    line: usize,
 }
 
 /// Calculates solution for Day 14 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> u64 {
+pub fn solution_part_1(filename: String) -> Result<u64, AocError> {
     return get_min_ore_needed_one_fuel(filename);
 }
 
 /// Calculates the minimum ORE needed to produce one unit of FUEL from reactions specified in given
 /// filename.
-fn get_min_ore_needed_one_fuel(filename: String) -> u64 {
-    let reactions = get_reactions_from_filename(filename);
+fn get_min_ore_needed_one_fuel(filename: String) -> Result<u64, AocError> {
+    let reactions = get_reactions_from_filename(filename)?;
     // Get fuel reaction and do initial checks
-    let fuel_reaction = reactions.get("FUEL").unwrap().clone();
+    let fuel_reaction = get_fuel_reaction(&reactions)?.clone();
     let (min_ore_needed, _) =
-        get_ore_needed_for_reaction(&reactions.clone(), &fuel_reaction, &mut HashMap::new());
-    return min_ore_needed;
+        get_ore_needed_for_reaction(&reactions.clone(), &fuel_reaction, &mut HashMap::new())?;
+    return Ok(min_ore_needed);
+}
+
+/// Returns the reaction that produces FUEL, or a parse error (located just past the last line of
+/// the input) if there isn't one.
+fn get_fuel_reaction(
+    reactions: &HashMap<String, ChemicalReaction>,
+) -> Result<&ChemicalReaction, AocError> {
+    match reactions.get("FUEL") {
+        Some(reaction) => return Ok(reaction),
+        None => {
+            let message = String::from("no reaction produces FUEL");
+            return Err(AocError::parse_error(reactions.len() + 1, 1, message));
+        }
+    }
 }
 
 /// Multiply material amounts on both sides of the given ChemicalReaction.
@@ -43,89 +62,106 @@ fn multiply_reaction(reaction: &ChemicalReaction, multiplier: u64) -> ChemicalRe
 /// Calculates solution for Day 14 Part 2 challenge - using a binary search style algorithm to find
 /// the fuel that requires the amount of ORE closest to the target of ONE TRILLION without going
 /// over.
-pub fn solution_part_2(filename: String) -> u64 {
+pub fn solution_part_2(filename: String) -> Result<u64, AocError> {
     let ore_target = 1e12 as u64;
-    let reactions = get_reactions_from_filename(filename.clone());
-    let fuel_reaction = reactions.get("FUEL").unwrap();
+    let reactions = get_reactions_from_filename(filename.clone())?;
+    let fuel_reaction = get_fuel_reaction(&reactions)?;
     let mut low: u64 = 1;
     let mut high: u64 = ore_target;
     loop {
         if high - low <= 1 {
-            return low;
+            return Ok(low);
         }
         let mid = (high - low) / 2 + low;
         println!("Trying {} FUEL...", mid);
         let fuel_reaction_mult = multiply_reaction(&fuel_reaction, mid);
         let (ore_needed, _) =
-            get_ore_needed_for_reaction(&reactions, &fuel_reaction_mult, &HashMap::new());
+            get_ore_needed_for_reaction(&reactions, &fuel_reaction_mult, &HashMap::new())?;
         // Adjust upper and lower limits of search based on ore_needed
         if ore_needed < ore_target {
             low = mid;
         } else if ore_needed > ore_target {
             high = mid;
         } else {
-            return mid;
+            return Ok(mid);
         }
     }
 }
 
-fn get_reactions_from_filename(filename: String) -> HashMap<String, ChemicalReaction> {
-    let mut file = fs::open_file(filename);
-    let raw_input = io::read_file_to_string(&mut file);
+/// Reads the chemical reactions from the given filename, returning an error if the file can't be
+/// read or a reaction is poorly formed.
+fn get_reactions_from_filename(
+    filename: String,
+) -> Result<HashMap<String, ChemicalReaction>, AocError> {
+    let raw_input = io::try_read_filename_to_string(filename)?;
     let mut reactions = HashMap::<String, ChemicalReaction>::new();
     // Read lines into chemical reactions
-    for line in raw_input.lines() {
-        let line = line.trim();
-        let side_split: Vec<String> = line.split("=>").map(|x| String::from(x)).collect();
+    for (line_index, line) in raw_input.lines().enumerate() {
+        let line_num = line_index + 1;
+        let side_split: Vec<&str> = line.split("=>").collect();
         if side_split.len() != 2 {
-            panic!("Bad number of sides for reaction: {}", side_split.len());
+            return Err(AocError::parse_error(
+                line_num,
+                1,
+                format!("bad number of sides for reaction: {}", side_split.len()),
+            ));
         }
         // Split up the materials on LHS and collect into vector
-        let lhs_split: Vec<String> = side_split[0].split(",").map(|x| String::from(x)).collect();
         let mut lhs_materials: Vec<Material> = vec![];
-        for item in lhs_split {
-            let item = item.trim();
-            let material_split: Vec<String> = item.split(' ').map(|x| String::from(x)).collect();
-            if material_split.len() != 2 {
-                panic!("Bad material format on LHS");
-            }
-            let quantity = material_split[0].parse::<u64>().unwrap();
-            let material = Material {
-                name: material_split[1].clone(),
-                quantity,
-            };
-            lhs_materials.push(material);
+        let mut column = 1;
+        for item in side_split[0].split(',') {
+            lhs_materials.push(parse_material(item, line_num, column)?);
+            column += item.len() + 1;
         }
         // Get the material type and quantity from RHS
-        let rhs_split: Vec<String> = side_split[1]
-            .trim()
-            .split(" ")
-            .map(|x| String::from(x))
-            .collect();
-        if rhs_split.len() != 2 {
-            panic!("Bad material format on RHS.");
-        }
-        let quantity = rhs_split[0].parse::<u64>().unwrap();
-        let output_material = Material {
-            name: rhs_split[1].clone(),
-            quantity,
-        };
+        let rhs_column = side_split[0].len() + "=>".len() + 1;
+        let output_material = parse_material(side_split[1], line_num, rhs_column)?;
         // Create the chemical reaction
         let reaction = ChemicalReaction {
             input: lhs_materials.clone(),
             output: output_material.clone(),
+            line: line_num,
         };
         // Check if there is more than one reaction that can produce the same output material
-        let result = reactions.insert(rhs_split[1].clone(), reaction);
-        match result {
-            None => (),
-            _ => panic!(
-                "More than one formula to produce chemical: {}",
-                output_material.name
-            ),
+        let result = reactions.insert(output_material.name.clone(), reaction);
+        if result.is_some() {
+            return Err(AocError::parse_error(
+                line_num,
+                rhs_column,
+                format!(
+                    "more than one formula to produce chemical: {}",
+                    output_material.name
+                ),
+            ));
         }
     }
-    return reactions;
+    return Ok(reactions);
+}
+
+/// Parses a single material in the form "<quantity> <name>". The given line and column locate the
+/// start of the material text, and are used to report parse errors.
+fn parse_material(text: &str, line: usize, column: usize) -> Result<Material, AocError> {
+    let column = column + text.len() - text.trim_start().len();
+    let material_split: Vec<&str> = text.trim().split(' ').collect();
+    if material_split.len() != 2 {
+        return Err(AocError::parse_error(
+            line,
+            column,
+            format!("bad material format: {}", text.trim()),
+        ));
+    }
+    let quantity = match material_split[0].parse::<u64>() {
+        Ok(v) => v,
+        Err(e) => {
+            let message = format!("bad material quantity: {} ({})", material_split[0], e);
+            return Err(AocError::parse_error(line, column, message));
+        }
+    };
+    return Ok(Material {
+        name: material_split[1].to_string(),
+        quantity,
+        column,
+    });
 }
 
 /// Calculates how much ORE is needed to produce the output of the given target reaction.
@@ -133,19 +169,31 @@ fn get_reactions_from_filename(filename: String) -> HashMap<String, ChemicalReac
 /// Extra amounts of materials remaining after each reaction is run are tracked between runs. This
 /// is done so that this extra amount can be used if enough is held, rather than making more of the
 /// material from raw ORE.
+///
+/// Returns a parse error (located at the input material) if an input material other than ORE isn't
+/// produced by any reaction.
 fn get_ore_needed_for_reaction(
     reactions_record: &HashMap<String, ChemicalReaction>,
     target_reaction: &ChemicalReaction,
     remainders: &HashMap<String, u64>,
-) -> (u64, HashMap<String, u64>) {
+) -> Result<(u64, HashMap<String, u64>), AocError> {
     if target_reaction.input.len() == 1 && target_reaction.input[0].name == "ORE" {
-        return (target_reaction.input[0].quantity, remainders.clone());
+        return Ok((target_reaction.input[0].quantity, remainders.clone()));
     }
     let mut total_ore_needed = 0;
     let mut remainders = remainders.clone();
 
     for input_material in target_reaction.input.clone() {
-        let input_reaction = reactions_record.get(&input_material.name).unwrap();
+        let input_reaction = match reactions_record.get(&input_material.name) {
+            Some(reaction) => reaction,
+            None => {
+                return Err(AocError::parse_error(
+                    target_reaction.line,
+                    input_material.column,
+                    format!("no reaction produces chemical: {}", input_material.name),
+                ));
+            }
+        };
         if !remainders.contains_key(&input_material.name) {
             remainders.insert(input_material.name.clone(), 0);
         }
@@ -165,69 +213,113 @@ fn get_ore_needed_for_reaction(
             *remainders.get_mut(&input_material.name).unwrap() = amount_remaining;
             // Get ore needed to produce output of multiplied reaction
             let (ore_needed, new_remainders) =
-                get_ore_needed_for_reaction(&reactions_record, &input_reaction_mult, &remainders);
+                get_ore_needed_for_reaction(&reactions_record, &input_reaction_mult, &remainders)?;
             total_ore_needed += ore_needed;
             remainders = new_remainders.clone();
         }
     }
-    return (total_ore_needed, remainders.clone());
+    return Ok((total_ore_needed, remainders.clone()));
+}
+
+/// Solution for Day 14, exposed through the common Solution interface.
+pub struct Day14;
+
+impl Solution for Day14 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_material_errors() {
+        let material = parse_material(" 7 A", 1, 10).unwrap();
+        assert_eq!(String::from("A"), material.name);
+        assert_eq!(7, material.quantity);
+        match parse_material(" x A", 3, 10) {
+            Err(AocError::Parse { line, column, .. }) => {
+                assert_eq!(3, line);
+                assert_eq!(11, column);
+            }
+            _ => panic!("Expected parse error."),
+        }
+        assert!(parse_material("7A", 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_missing_reaction_errors() {
+        let result = solution_part_1(String::from("./input/day_14/test/test_no_fuel.txt"));
+        match result {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((3, 1), (line, column)),
+            _ => panic!("Expected parse error."),
+        }
+        assert!(solution_part_2(String::from("./input/day_14/test/test_no_fuel.txt")).is_err());
+        let result = solution_part_1(String::from("./input/day_14/test/test_no_reaction.txt"));
+        match result {
+            Err(AocError::Parse { line, column, .. }) => assert_eq!((2, 6), (line, column)),
+            _ => panic!("Expected parse error."),
+        }
+        assert!(solution_part_2(String::from("./input/day_14/test/test_no_reaction.txt")).is_err());
+    }
+
     #[test]
     pub fn test_p1_example_01() {
-        let result = solution_part_1(String::from("./input/day_14/test/test_01.txt"));
+        let result = solution_part_1(String::from("./input/day_14/test/test_01.txt")).unwrap();
         assert_eq!(31, result);
     }
 
     #[test]
     pub fn test_p1_example_02() {
-        let result = solution_part_1(String::from("./input/day_14/test/test_02.txt"));
+        let result = solution_part_1(String::from("./input/day_14/test/test_02.txt")).unwrap();
         assert_eq!(165, result);
     }
 
     #[test]
     pub fn test_p1_example_03() {
-        let result = solution_part_1(String::from("./input/day_14/test/test_03.txt"));
+        let result = solution_part_1(String::from("./input/day_14/test/test_03.txt")).unwrap();
         assert_eq!(13312, result);
     }
 
     #[test]
     pub fn test_p1_example_04() {
-        let result = solution_part_1(String::from("./input/day_14/test/test_04.txt"));
+        let result = solution_part_1(String::from("./input/day_14/test/test_04.txt")).unwrap();
         assert_eq!(180697, result);
     }
 
     #[test]
     pub fn test_p1_example_05() {
-        let result = solution_part_1(String::from("./input/day_14/test/test_05.txt"));
+        let result = solution_part_1(String::from("./input/day_14/test/test_05.txt")).unwrap();
         assert_eq!(2210736, result);
     }
 
     #[test]
     pub fn test_p1_solution() {
-        let result = solution_part_1(String::from("./input/day_14/input.txt"));
+        let result = solution_part_1(String::from("./input/day_14/input.txt")).unwrap();
         assert_eq!(278404, result);
     }
 
     #[test]
     pub fn test_p2_example_03() {
-        let result = solution_part_2(String::from("./input/day_14/test/test_03.txt"));
+        let result = solution_part_2(String::from("./input/day_14/test/test_03.txt")).unwrap();
         assert_eq!(82892753, result);
     }
 
     #[test]
     pub fn test_p2_example_04() {
-        let result = solution_part_2(String::from("./input/day_14/test/test_04.txt"));
+        let result = solution_part_2(String::from("./input/day_14/test/test_04.txt")).unwrap();
         assert_eq!(5586022, result);
     }
 
     #[test]
     pub fn test_p2_example_05() {
-        let result = solution_part_2(String::from("./input/day_14/test/test_05.txt"));
+        let result = solution_part_2(String::from("./input/day_14/test/test_05.txt")).unwrap();
         assert_eq!(460664, result);
     }
 }
@@ -1,3 +1,5 @@
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
 use super::utils::intcode::IntcodeMachine;
 use std::collections::HashMap;
 use std::collections::VecDeque;
@@ -102,17 +104,23 @@ impl RepairDroid {
     }
 
     /// Reverses the last successful move by changing the manual tracking location and processing
-    /// the reverse move through the internal Intcode computer.
-    pub fn rewind_move(&mut self) {
-        if self.breadcrumbs.is_empty() {
-            panic!("No breadcrumbs - cannot rewind moves.");
-        }
-        let back_direction = self.breadcrumbs.pop().unwrap().get_opposite_direction();
+    /// the reverse move through the internal Intcode computer. Returns an error if there are no
+    /// moves to rewind, i.e. the whole map has been explored.
+    pub fn rewind_move(&mut self) -> Result<(), AocError> {
+        let back_direction = match self.breadcrumbs.pop() {
+            Some(direction) => direction.get_opposite_direction(),
+            None => {
+                return Err(AocError::InvalidProgram(String::from(
+                    "droid explored the whole map without finding the oxygen system",
+                )))
+            }
+        };
         // Update manual tracking of location
         self.current_location = self.current_location.get_updated_location(back_direction);
         // Update location in repair droid intcode computer
         self.current_direction = back_direction;
-        self.try_move();
+        self.try_move()?;
+        return Ok(());
     }
 
     /// Gets the next location in the current direction.
@@ -136,11 +144,11 @@ impl RepairDroid {
     }
 
     /// Trys to process a move through the internal Intcode computer and returns the status code
-    /// indicating success or failure.
-    pub fn try_move(&mut self) -> i64 {
+    /// indicating success or failure. Returns an error if the computer faults or gives no status.
+    pub fn try_move(&mut self) -> Result<i64, AocError> {
         self.computer.add_input(self.current_direction.get_code());
-        self.computer.execute_program_break_on_output(true);
-        return self.computer.get_output_and_remove();
+        self.computer.try_execute_program_break_on_output(true)?;
+        return self.computer.try_get_output_and_remove();
     }
 
     /// Adds the target location to the known tiles with the given tile code.
@@ -211,12 +219,12 @@ impl RepairDroid {
     }
 
     /// Crawls the repair droid through the map until it finds the oxygen tank.
-    pub fn crawl_map_to_oxygen(&mut self) -> u64 {
+    pub fn crawl_map_to_oxygen(&mut self) -> Result<u64, AocError> {
         let mut moves_attempted_from_current = 0;
         loop {
             if moves_attempted_from_current == 4 {
                 moves_attempted_from_current = 0;
-                self.rewind_move();
+                self.rewind_move()?;
                 continue;
             }
             // Check if already explored or blocked
@@ -226,7 +234,7 @@ impl RepairDroid {
                 continue;
             }
             // Get outcome status from move
-            let status = self.try_move();
+            let status = self.try_move()?;
             match status {
                 STATUS_HIT_WALL => {
                     moves_attempted_from_current += 1;
@@ -238,9 +246,9 @@ impl RepairDroid {
                 }
                 STATUS_GOOD_MOVE_OXYGEN => {
                     self.handle_successful_move(true);
-                    return self.get_num_moves_from_origin();
+                    return Ok(self.get_num_moves_from_origin());
                 }
-                _ => panic!("Bad move status observed: {}", status),
+                _ => return Err(bad_move_status_error(status)),
             }
         }
     }
@@ -250,17 +258,17 @@ impl RepairDroid {
     ///
     /// Search is known to be finished when repair droid is back at origin (no breadcrumbs) and all
     /// tiles around the origin have been explored.
-    pub fn find_longest_path_from_start(&mut self) -> u64 {
+    pub fn find_longest_path_from_start(&mut self) -> Result<u64, AocError> {
         self.reset_to_current_location();
         let mut longest_path_seen = 0;
         loop {
             // Check if the droid has returned to the starting location with no more tiles to explore
             if self.breadcrumbs.len() == 0 && self.check_all_around_explored() {
-                return longest_path_seen as u64;
+                return Ok(longest_path_seen as u64);
             }
             // Check if all directions have been exhausted from current location
             if self.check_all_around_explored() {
-                self.rewind_move();
+                self.rewind_move()?;
                 continue;
             }
             // Check if the target location has been explored - don't need to try move.
@@ -269,7 +277,7 @@ impl RepairDroid {
                 continue;
             }
             // Try to move the droid and check outcome of move
-            let status = self.try_move();
+            let status = self.try_move()?;
             match status {
                 STATUS_HIT_WALL => {
                     self.handle_bad_move();
@@ -280,7 +288,7 @@ impl RepairDroid {
                 STATUS_GOOD_MOVE_OXYGEN => {
                     self.handle_successful_move(true);
                 }
-                _ => panic!("Bad move status observed: {}", status),
+                _ => return Err(bad_move_status_error(status)),
             }
             // Check if we have seen a longer path than previously observed
             if self.breadcrumbs.len() > longest_path_seen {
@@ -290,23 +298,41 @@ impl RepairDroid {
     }
 }
 
+/// Creates the error returned when the droid gives a status code it shouldn't.
+fn bad_move_status_error(status: i64) -> AocError {
+    return AocError::InvalidProgram(format!("bad move status observed: {}", status));
+}
+
 /// Calculates the solution for Day 15 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> u64 {
-    let initial_memory: Vec<i64> = IntcodeMachine::extract_intcode_memory_from_filename(filename);
+pub fn solution_part_1(filename: String) -> Result<u64, AocError> {
+    let initial_memory: Vec<i64> = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     let mut repair_droid = RepairDroid::new(initial_memory.clone());
-    repair_droid.crawl_map_to_oxygen();
-    return repair_droid.get_num_moves_from_origin();
+    repair_droid.crawl_map_to_oxygen()?;
+    return Ok(repair_droid.get_num_moves_from_origin());
 }
 
 /// Calculates the solution for Day 15 Part 2 challenge.
-pub fn solution_part_2(filename: String) -> u64 {
-    let initial_memory: Vec<i64> = IntcodeMachine::extract_intcode_memory_from_filename(filename);
+pub fn solution_part_2(filename: String) -> Result<u64, AocError> {
+    let initial_memory: Vec<i64> = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
     let mut repair_droid = RepairDroid::new(initial_memory.clone());
     // Move the droid to the location of the oxygen
-    repair_droid.crawl_map_to_oxygen();
+    repair_droid.crawl_map_to_oxygen()?;
     // Find the longest path from the location of oxygen
-    let longest_path = repair_droid.find_longest_path_from_start();
-    return longest_path;
+    let longest_path = repair_droid.find_longest_path_from_start()?;
+    return Ok(longest_path);
+}
+
+/// Solution for Day 15, exposed through the common Solution interface.
+pub struct Day15;
+
+impl Solution for Day15 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
 }
 
 #[cfg(test)]
@@ -315,13 +341,28 @@ mod tests {
 
     #[test]
     fn test_d15_p1_solution() {
-        let result = solution_part_1(String::from("./input/day_15/input.txt"));
+        let result = solution_part_1(String::from("./input/day_15/input.txt")).unwrap();
         assert_eq!(208, result);
     }
 
     #[test]
     fn test_d15_p2_solution() {
-        let result = solution_part_2(String::from("./input/day_15/input.txt"));
+        let result = solution_part_2(String::from("./input/day_15/input.txt")).unwrap();
         assert_eq!(306, result);
     }
+
+    #[test]
+    fn test_bad_droid_program() {
+        let results = vec![
+            solution_part_1(String::from("./input/day_02/input.txt")),
+            solution_part_1(String::from("./input/day_15/test/test_all_walls.txt")),
+            solution_part_2(String::from("./input/day_15/test/test_bad_status.txt")),
+        ];
+        for result in results {
+            match result {
+                Err(AocError::InvalidProgram(_)) => (),
+                _ => panic!("Expected invalid program error."),
+            }
+        }
+    }
 }
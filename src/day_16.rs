@@ -1,4 +1,6 @@
-use std::fs;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
+use super::utils::io;
 
 #[derive(Copy, Clone, PartialEq)]
 struct FftRangeSum {
@@ -157,17 +159,17 @@ impl FftPattern {
 }
 
 /// Calculates the solution to Day 16 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> String {
+pub fn solution_part_1(filename: String) -> Result<String, AocError> {
     return get_fft_result_string(filename, 1, 100);
 }
 
 /// Calculates the solution to Day 16 Part 2 challenge.
-pub fn solution_part_2(filename: String) -> String {
-    let input_digits = get_input_signal_from_filename(filename);
+pub fn solution_part_2(filename: String) -> Result<String, AocError> {
+    let input_digits = get_input_signal_from_filename(filename)?;
     let message_offset = get_message_offset(&input_digits);
     let output = perform_fft(&input_digits, 10000, 100);
     let message = get_message_with_offset_from_fft(output, message_offset);
-    return message;
+    return Ok(message);
 }
 
 /// Gets the message offset from the initial signal. Message offset is encoded as an integer value
@@ -180,11 +182,15 @@ fn get_message_offset(initial_digits: &Vec<i64>) -> usize {
     return offset_string.parse::<usize>().unwrap();
 }
 
-fn get_fft_result_string(filename: String, num_repeats_init: usize, num_phases: u64) -> String {
-    let input_digits = get_input_signal_from_filename(filename);
+fn get_fft_result_string(
+    filename: String,
+    num_repeats_init: usize,
+    num_phases: u64,
+) -> Result<String, AocError> {
+    let input_digits = get_input_signal_from_filename(filename)?;
     let output = perform_fft(&input_digits, num_repeats_init, num_phases);
     // Generate string of first out digits of output
-    return get_message_with_offset_from_fft(output, 0);
+    return Ok(get_message_with_offset_from_fft(output, 0));
 }
 
 /// Extracts the eight-character message offset by the specifed value from the given FFT output.
@@ -196,13 +202,22 @@ fn get_message_with_offset_from_fft(fft_output: Vec<i64>, message_offset: usize)
     return message;
 }
 
-/// Extracts the input signal from the given filename.
-fn get_input_signal_from_filename(filename: String) -> Vec<i64> {
-    let mut raw_input = fs::read_to_string(filename).unwrap();
+/// Extracts the input signal from the given filename. Returns an error if the signal contains
+/// anything other than digits.
+fn get_input_signal_from_filename(filename: String) -> Result<Vec<i64>, AocError> {
+    let mut raw_input = io::try_read_filename_to_string(filename)?;
     raw_input = String::from(raw_input.trim());
-    let input_digits: Vec<i64> = 
-        raw_input.chars().map(|x| x.to_digit(10).unwrap() as i64).collect();
-    return input_digits;
+    let mut input_digits: Vec<i64> = vec![];
+    for (index, c) in raw_input.chars().enumerate() {
+        match c.to_digit(10) {
+            Some(digit) => input_digits.push(digit as i64),
+            None => {
+                let message = format!("bad signal digit: {}", c);
+                return Err(AocError::parse_error(1, index + 1, message));
+            }
+        }
+    }
+    return Ok(input_digits);
 }
 
 /// Executes the Day 16 FFT algorithm using the input digits repeated the specified number of times
@@ -313,65 +328,78 @@ fn perform_fft(input_digits: &Vec<i64>, num_repeats: usize, num_phases: u64) ->
     return phase_output;
 }
 
+/// Solution for Day 16, exposed through the common Solution interface.
+pub struct Day16;
+
+impl Solution for Day16 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_d16_p1_solution() {
-        let result = solution_part_1(String::from("./input/day_16/input.txt"));
+        let result = solution_part_1(String::from("./input/day_16/input.txt")).unwrap();
         assert_eq!("27229269", result);
     }
 
     #[ignore]
     #[test]
     fn test_d16_p2_solution() {
-        let result = solution_part_2(String::from("./input/day_16/input.txt"));
+        let result = solution_part_2(String::from("./input/day_16/input.txt")).unwrap();
         assert_eq!("26857164", result);
     }
 
     #[test]
     fn test_d16_p1_example_01() {
-        let result = get_fft_result_string(String::from("./input/day_16/test/test_01.txt"), 1, 100);
+        let result = get_fft_result_string(String::from("./input/day_16/test/test_01.txt"), 1, 100).unwrap();
         assert_eq!("24176176", result);
     }
 
     #[test]
     fn test_d16_p1_example_02() {
-        let result = get_fft_result_string(String::from("./input/day_16/test/test_02.txt"), 1, 100);
+        let result = get_fft_result_string(String::from("./input/day_16/test/test_02.txt"), 1, 100).unwrap();
         assert_eq!("73745418", result);
     }
 
     #[test]
     fn test_d16_p1_example_03() {
-        let result = get_fft_result_string(String::from("./input/day_16/test/test_03.txt"), 1, 100);
+        let result = get_fft_result_string(String::from("./input/day_16/test/test_03.txt"), 1, 100).unwrap();
         assert_eq!("52432133", result);
     }
 
     #[test]
     fn test_d16_p1_example_04() {
-        let result = get_fft_result_string(String::from("./input/day_16/test/test_04.txt"), 1, 4);
+        let result = get_fft_result_string(String::from("./input/day_16/test/test_04.txt"), 1, 4).unwrap();
         assert_eq!("01029498", result);
     }
 
     #[ignore]
     #[test]
     fn test_d16_p2_example_01() {
-        let result = solution_part_2(String::from("./input/day_16/test/test_05.txt"));
+        let result = solution_part_2(String::from("./input/day_16/test/test_05.txt")).unwrap();
         assert_eq!("84462026", result);
     }
 
     #[ignore]
     #[test]
     fn test_d16_p2_example_02() {
-        let result = solution_part_2(String::from("./input/day_16/test/test_06.txt"));
+        let result = solution_part_2(String::from("./input/day_16/test/test_06.txt")).unwrap();
         assert_eq!("78725270", result);
     }
 
     #[ignore]
     #[test]
     fn test_d16_p2_example_03() {
-        let result = solution_part_2(String::from("./input/day_16/test/test_07.txt"));
+        let result = solution_part_2(String::from("./input/day_16/test/test_07.txt")).unwrap();
         assert_eq!("53553731", result);
     }
 }
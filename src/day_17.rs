@@ -1,4 +1,6 @@
-use super::utils::intcode::IntcodeMachine;
+use super::solution::{Answer, Solution};
+use super::utils::error::AocError;
+use super::utils::intcode::{AsciiAdapter, IntcodeMachine};
 use super::utils::strings;
 use std::cmp::Ordering;
 use std::collections::HashMap;
@@ -90,7 +92,7 @@ impl Point {
 }
 
 struct AsciiMachine {
-    intcode_computer: IntcodeMachine,
+    intcode_computer: AsciiAdapter,
     intcode_computer_orig: IntcodeMachine,
     map: HashMap<Point, char>,
     robot_location: Point,
@@ -102,10 +104,11 @@ struct AsciiMachine {
 
 impl AsciiMachine {
     /// Creates a new ASCII machine and processes the camera view to determine the scaffold map.
-    pub fn new(ascii_program: Vec<i64>) -> Self {
-        let mut intcode_computer = IntcodeMachine::new(ascii_program.clone(), VecDeque::new());
-        let intcode_computer_orig = intcode_computer.clone();
-        intcode_computer.execute_program();
+    /// Returns an error if the camera view doesn't show exactly one vacuum robot.
+    pub fn new(ascii_program: Vec<i64>) -> Result<Self, AocError> {
+        let intcode_computer_orig = IntcodeMachine::new(ascii_program, VecDeque::new());
+        let mut intcode_computer = AsciiAdapter::new(intcode_computer_orig.fork());
+        let camera_view = intcode_computer.read_until_prompt()?;
         let mut scan_location = Point::new(0, 0);
         let mut scaffold_locations: Vec<Point> = vec![];
         let mut map: HashMap<Point, char> = HashMap::new();
@@ -113,11 +116,7 @@ impl AsciiMachine {
         let mut robot_direction = Direction::North;
         let mut map_width = 0;
         let mut map_height = 0;
-        loop {
-            if intcode_computer.is_output_empty() {
-                break;
-            }
-            let output_char = (intcode_computer.get_output_and_remove() as u8) as char;
+        for output_char in camera_view.chars() {
             if output_char == '\n' {
                 // Line feed received
                 scan_location.x = 0;
@@ -126,7 +125,9 @@ impl AsciiMachine {
             } else if "<>^v".contains(output_char) {
                 // Observed location of robot
                 if robot_location.x != -1 && robot_location.y != -1 {
-                    panic!("Already have a location for the vacuum robot.");
+                    return Err(AocError::InvalidProgram(String::from(
+                        "camera view shows more than one vacuum robot",
+                    )));
                 }
                 robot_direction = match output_char {
                     '<' => Direction::West,
@@ -148,7 +149,12 @@ impl AsciiMachine {
             map_height = scan_location.y;
             scan_location.x += 1;
         }
-        return Self {
+        if robot_location.x == -1 && robot_location.y == -1 {
+            return Err(AocError::InvalidProgram(String::from(
+                "camera view doesn't show the vacuum robot",
+            )));
+        }
+        return Ok(Self {
             intcode_computer: intcode_computer,
             intcode_computer_orig: intcode_computer_orig,
             robot_location: robot_location,
@@ -157,14 +163,15 @@ impl AsciiMachine {
             scaffold_intersections: Self::find_scaffold_intersections(scaffold_locations.clone()),
             map_width: map_width + 1, // adjust for zero-indexed map location
             map_height: map_height + 1, // adjust for zero-indexed map location
-        };
+        });
     }
 
     /// Resets the state of the internal Intcode computer and awakens the vacuum robot.
     pub fn awaken_robot(&mut self) {
         let mut initial_memory = self.intcode_computer_orig.get_memory_dump();
         initial_memory[0] = 2;
-        self.intcode_computer = IntcodeMachine::new(initial_memory, VecDeque::new());
+        self.intcode_computer =
+            AsciiAdapter::new(IntcodeMachine::new(initial_memory, VecDeque::new()));
     }
 
     /// Finds the scaffold intersections and records the locations within the ASCII computer.
@@ -271,10 +278,13 @@ impl AsciiMachine {
     /// - Subroutine B
     /// - Subroutine C
     /// - Camera enabled (y/n)
-    pub fn get_movement_commands(&self) -> Vec<String> {
+    ///
+    /// Returns an error if the path can't be split into movement functions that fit in the robot's
+    /// memory.
+    pub fn get_movement_commands(&self) -> Result<Vec<String>, AocError> {
         let mut commands: Vec<String> = vec![];
         let path = self.find_path_to_traverse_scaffold(true);
-        for a_end in 0..path.len() - 2 {
+        for a_end in 0..path.len().saturating_sub(2) {
             let b_start = a_end + 1;
             for b_end in b_start..path.len() - 1 {
                 let mut new_path = path.clone().join("");
@@ -302,34 +312,36 @@ impl AsciiMachine {
                     main_routine = main_routine.chars().join(",");
                     main_routine.push('\n');
                     if main_routine.len() > 21 {
-                        panic!("Bad main routine length: {}", main_routine.len());
+                        return Err(bad_movement_command_error("main routine", &main_routine));
                     }
                     commands.push(main_routine);
                     // Generate A sub-routine
                     a_command = Self::format_subroutine_string(a_command);
                     if a_command.len() > 21 {
-                        panic!("Bad A-subroutine length: {}", a_command.len());
+                        return Err(bad_movement_command_error("A-subroutine", &a_command));
                     }
                     commands.push(a_command);
                     // Generate B sub-routine
                     b_command = Self::format_subroutine_string(b_command);
                     if b_command.len() > 21 {
-                        panic!("Bad B-subroutine length: {}", b_command.len());
+                        return Err(bad_movement_command_error("B-subroutine", &b_command));
                     }
                     commands.push(b_command);
                     // Generate C sub-routine
                     c_command = Self::format_subroutine_string(c_command);
                     if c_command.len() > 21 {
-                        panic!("Bad C-subroutine length: {}", c_command.len());
+                        return Err(bad_movement_command_error("C-subroutine", &c_command));
                     }
                     commands.push(c_command);
                     // Camera feed state - not enabled
                     commands.push("n\n".to_owned());
-                    return commands;
+                    return Ok(commands);
                 }
             }
         }
-        return vec![];
+        return Err(AocError::InvalidProgram(String::from(
+            "scaffold path can't be split into movement functions",
+        )));
     }
 
     /// Takes the input movement subroutine string and inserts comma characters between turn and
@@ -370,51 +382,65 @@ impl AsciiMachine {
         {
             return false;
         }
+        // Lines of the camera view can be shorter than the first line, leaving squares unseen
         let target_char = match self.map.get(&target_square) {
             Some(v) => *v,
-            None => panic!(
-                "Bad target square: [{:?}]. Map width is {}. Map height is {}.",
-                target_square, self.map_width, self.map_height
-            ),
+            None => return false,
         };
-        // let target_char = *self.map.get(&target_square).unwrap();
         return target_char == '#';
     }
 }
 
+/// Creates the error returned when a movement command is too long for the robot's memory.
+fn bad_movement_command_error(name: &str, command: &str) -> AocError {
+    return AocError::InvalidProgram(format!("bad {} length: {}", name, command.len()));
+}
+
 /// Solution for Day 17 Part 1 challenge.
-pub fn solution_part_1(filename: String) -> i64 {
-    let ascii_program = IntcodeMachine::extract_intcode_memory_from_filename(filename);
-    let ascii_machine = AsciiMachine::new(ascii_program);
+pub fn solution_part_1(filename: String) -> Result<i64, AocError> {
+    let ascii_program = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
+    let ascii_machine = AsciiMachine::new(ascii_program)?;
     ascii_machine.render_map();
     let align_param_sum = ascii_machine.calculate_alignment_parameter_sum();
-    return align_param_sum;
+    return Ok(align_param_sum);
 }
 
 /// Solution for Day 17 Part 2 challenge.
-pub fn solution_part_2(filename: String) -> i64 {
+pub fn solution_part_2(filename: String) -> Result<i64, AocError> {
     // Load up the ascii program to get camera view of scaffold
-    let ascii_program = IntcodeMachine::extract_intcode_memory_from_filename(filename);
-    let mut ascii_machine = AsciiMachine::new(ascii_program);
+    let ascii_program = IntcodeMachine::try_extract_intcode_memory_from_filename(filename)?;
+    let mut ascii_machine = AsciiMachine::new(ascii_program)?;
     // Awaken robot and reset memory of intcode computer
     ascii_machine.awaken_robot();
     // Get the commands required for robot to traverse all scaffold locations at least once.
-    let commands = ascii_machine.get_movement_commands();
-    // Provide movement commands as ASCII characters to robot
+    let commands = ascii_machine.get_movement_commands()?;
+    // Provide movement commands as lines of ASCII text to robot
     for item in commands {
-        for c in item.chars() {
-            ascii_machine.intcode_computer.add_input(c as i64);
-        }
+        ascii_machine.intcode_computer.send_line(item.trim_end());
     }
-    // Set robot off on crawl to meet the other robots left outside.
-    ascii_machine.intcode_computer.execute_program();
-    // Consume all outputs within ASCII character range of values until dust collected output
-    loop {
-        let output = ascii_machine.intcode_computer.get_output_and_remove();
-        if output <= 128 { // Value is within range of ASCII character values
-            continue;
+    // Set robot off on crawl to meet the other robots left outside, skipping the prompts shown.
+    ascii_machine.intcode_computer.read_until_prompt()?;
+    // Only non-ASCII value output is the amount of dust collected by vacuum robot.
+    match ascii_machine.intcode_computer.read_value() {
+        Some(dust) => return Ok(dust),
+        None => {
+            return Err(AocError::InvalidProgram(String::from(
+                "vacuum robot didn't report the amount of dust collected",
+            )))
         }
-        return output; // Output here is amount of dust collected by vacuum robot.
+    }
+}
+
+/// Solution for Day 17, exposed through the common Solution interface.
+pub struct Day17;
+
+impl Solution for Day17 {
+    fn part_1(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_1(filename)?));
+    }
+
+    fn part_2(&self, filename: String) -> Result<Answer, AocError> {
+        return Ok(Answer::from(solution_part_2(filename)?));
     }
 }
 
@@ -424,13 +450,30 @@ mod tests {
 
     #[test]
     fn test_day_17_p1_solution() {
-        let result = solution_part_1(String::from("./input/day_17/input.txt"));
+        let result = solution_part_1(String::from("./input/day_17/input.txt")).unwrap();
         assert_eq!(3936, result);
     }
 
     #[test]
     fn test_day_17_p2_solution() {
-        let result = solution_part_2(String::from("./input/day_17/input.txt"));
+        let result = solution_part_2(String::from("./input/day_17/input.txt")).unwrap();
         assert_eq!(785733, result);
     }
+
+    #[test]
+    fn test_bad_camera_program() {
+        let results = vec![
+            solution_part_1(String::from("./input/day_02/input.txt")),
+            solution_part_2(String::from("./input/day_02/input.txt")),
+            solution_part_1(String::from("./input/day_17/test/test_two_robots.txt")),
+            // Robot can be seen, but there is no scaffold for it to move along
+            solution_part_2(String::from("./input/day_17/test/test_no_scaffold.txt")),
+        ];
+        for result in results {
+            match result {
+                Err(AocError::InvalidProgram(_)) => (),
+                _ => panic!("Expected invalid program error."),
+            }
+        }
+    }
 }
@@ -17,6 +17,9 @@ pub mod day_15;
 pub mod day_16;
 pub mod day_17;
 // Other required declarations
+pub mod runner;
+pub mod solution;
+pub mod transpiled;
 pub mod utils;
 // Declare itertools here with tag so its macros can be used in project
 #[macro_use] extern crate itertools;
@@ -27,7 +30,15 @@ extern crate regex;
 extern crate primes;
 extern crate ndarray;
 
+use std::env;
+use std::process;
+
 fn main() {
-    let solution = day_17::solution_part_2(String::from("./input/day_17/input.txt"));
-    println!("Solution: {:?}", solution);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = runner::parse_args(&args).and_then(runner::execute_command);
+    if let Err(e) = result {
+        eprintln!("ERROR - {}", e);
+        eprintln!("{}", runner::USAGE);
+        process::exit(2);
+    }
 }
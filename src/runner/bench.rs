@@ -0,0 +1,194 @@
+use super::format_duration;
+use super::registry::get_default_input;
+use crate::transpiled::day_09;
+use crate::utils::intcode::{IntcodeMachine, MachineFault, RunState};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Intcode program (taken from the default input of a day) and the input values given to it for
+/// a single benchmark run.
+struct Workload {
+    name: &'static str,
+    day: u32,
+    input: Vec<i64>,
+    /// Runs the program transpiled from the day's input once, if there is one in src/transpiled.
+    transpiled: Option<TranspiledRun>,
+}
+
+/// Runs a transpiled program from the start given the input values, returning the time taken
+/// running it (not setting it up) and its output.
+type TranspiledRun = fn(&[i64]) -> Result<(Duration, VecDeque<i64>), MachineFault>;
+
+/// Returns the workloads run by the benchmark.
+fn get_workloads() -> Vec<Workload> {
+    return vec![
+        Workload {
+            name: "Day 05 thermal radiator diagnostic",
+            day: 5,
+            input: vec![5],
+            transpiled: None,
+        },
+        Workload {
+            name: "Day 07 amplifier (phase 4)",
+            day: 7,
+            input: vec![4, 0],
+            transpiled: None,
+        },
+        Workload {
+            name: "Day 09 BOOST test mode",
+            day: 9,
+            input: vec![1],
+            transpiled: Some(run_day_09_transpiled),
+        },
+        Workload {
+            name: "Day 09 BOOST sensor boost mode",
+            day: 9,
+            input: vec![2],
+            transpiled: Some(run_day_09_transpiled),
+        },
+    ];
+}
+
+/// Runs every benchmark workload the given number of times through both the original (uncached)
+/// execution core and the decoded instruction cache, displaying the average time taken per run by
+/// each and the speedup. Workloads with a transpiled program in src/transpiled are also timed
+/// running as compiled code. Returns an error if a program can't be loaded, faults, or produces
+/// different output from the two cores (or the transpiled program).
+pub fn run_benchmark(iterations: u32) -> Result<(), String> {
+    if iterations == 0 {
+        return Err(String::from("The number of iterations must be at least 1."));
+    }
+    for workload in get_workloads() {
+        let program = IntcodeMachine::try_extract_intcode_memory_from_filename(get_default_input(
+            workload.day,
+        ))
+        .map_err(|e| format!("{} failed - {}", workload.name, e))?;
+        let template = IntcodeMachine::new(program, VecDeque::new());
+        let fault_error = |e: MachineFault| format!("{} failed - {}", workload.name, e);
+        let (uncached_time, uncached_output) =
+            time_workload(&template, &workload.input, false, iterations).map_err(fault_error)?;
+        let (cached_time, cached_output) =
+            time_workload(&template, &workload.input, true, iterations).map_err(fault_error)?;
+        if uncached_output != cached_output {
+            return Err(format!(
+                "{} output differs - uncached {:?}, cached {:?}",
+                workload.name, uncached_output, cached_output
+            ));
+        }
+        let uncached_avg = uncached_time / iterations;
+        let cached_avg = cached_time / iterations;
+        print!(
+            "{:<36} uncached: {:>12}  cached: {:>12}  speedup: {:.2}x",
+            workload.name,
+            format_duration(uncached_avg),
+            format_duration(cached_avg),
+            uncached_avg.as_secs_f64() / cached_avg.as_secs_f64().max(1e-9)
+        );
+        if let Some(result) = time_transpiled(&workload, iterations) {
+            let (transpiled_time, transpiled_output) = result.map_err(fault_error)?;
+            if transpiled_output != cached_output {
+                return Err(format!(
+                    "{} output differs - cached {:?}, transpiled {:?}",
+                    workload.name, cached_output, transpiled_output
+                ));
+            }
+            print!(
+                "  transpiled: {:>12}",
+                format_duration(transpiled_time / iterations)
+            );
+        }
+        println!();
+    }
+    return Ok(());
+}
+
+/// Runs the program held by the template machine to completion the given number of times, each
+/// time on a fresh fork given the input values. Returns the total time taken and the output of
+/// the last run.
+fn time_workload(
+    template: &IntcodeMachine,
+    input: &[i64],
+    use_decode_cache: bool,
+    iterations: u32,
+) -> Result<(Duration, VecDeque<i64>), MachineFault> {
+    let mut total_time = Duration::new(0, 0);
+    let mut output = VecDeque::new();
+    for _ in 0..iterations {
+        let mut machine = template.fork();
+        machine.set_decode_cache_enabled(use_decode_cache);
+        for &value in input {
+            machine.add_input(value);
+        }
+        // Only time program execution, not setting up the machine
+        let start = Instant::now();
+        machine.try_execute_program()?;
+        total_time += start.elapsed();
+        output = machine.get_output_vec();
+    }
+    return Ok((total_time, output));
+}
+
+/// Runs the transpiled program for the workload (if there is one) the given number of times, each
+/// time from the start given the input values. Returns the total time taken and the output of the
+/// last run, or None if the day has no transpiled program.
+fn time_transpiled(
+    workload: &Workload,
+    iterations: u32,
+) -> Option<Result<(Duration, VecDeque<i64>), MachineFault>> {
+    let run = workload.transpiled?;
+    let mut total_time = Duration::new(0, 0);
+    let mut output = VecDeque::new();
+    for _ in 0..iterations {
+        match run(&workload.input) {
+            Ok((time, run_output)) => {
+                total_time += time;
+                output = run_output;
+            }
+            Err(fault) => return Some(Err(fault)),
+        }
+    }
+    return Some(Ok((total_time, output)));
+}
+
+/// Runs the program transpiled from the day 9 input once, given the input values.
+fn run_day_09_transpiled(input: &[i64]) -> Result<(Duration, VecDeque<i64>), MachineFault> {
+    let mut program = day_09::TranspiledProgram::new();
+    for &value in input {
+        program.add_input(value);
+    }
+    let mut output = VecDeque::new();
+    let start = Instant::now();
+    while let RunState::Output(value) = program.run()? {
+        output.push_back(value);
+    }
+    return Ok((start.elapsed(), output));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_workload() {
+        // Outputs the sum of its two inputs
+        let program = vec![3, 11, 3, 12, 1, 11, 12, 11, 4, 11, 99];
+        let template = IntcodeMachine::new(program, VecDeque::new());
+        for &use_decode_cache in [false, true].iter() {
+            let (_, output) = time_workload(&template, &[3, 4], use_decode_cache, 3).unwrap();
+            assert_eq!(VecDeque::from(vec![7]), output);
+        }
+    }
+
+    #[test]
+    fn test_time_transpiled() {
+        let workloads = get_workloads();
+        assert!(time_transpiled(&workloads[0], 1).is_none());
+        let (_, output) = time_transpiled(&workloads[3], 2).unwrap().unwrap();
+        assert_eq!(VecDeque::from(vec![51135]), output);
+    }
+
+    #[test]
+    fn test_zero_iterations() {
+        assert!(run_benchmark(0).is_err());
+    }
+}
@@ -0,0 +1,715 @@
+mod bench;
+mod registry;
+
+use super::solution::Answer;
+use super::utils::intcode::{analyze, fuzz, transpile, Console, Debugger, IntcodeMachine};
+use std::collections::VecDeque;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub use self::registry::{find_day, get_default_input, get_registry, DayEntry};
+
+/// Usage text displayed when the command-line arguments can't be processed.
+pub const USAGE: &str = "Usage:
+    aoc2019 run --day <day> [--part <1|2>] [--input <path>] [--png <path>]
+    aoc2019 all [--skip <day>.<part>]...
+    aoc2019 debug (--day <day> | --input <path>)
+    aoc2019 bench [--iterations <n>]
+    aoc2019 profile (--day <day> | --input <path>) [--values <v1,v2,...>]
+    aoc2019 console (--day <day> | --input <path>) [--transcript <path>]
+    aoc2019 analyze (--day <day> | --input <path>) [--dot <path>]
+    aoc2019 transpile (--day <day> | --input <path>) [--output <path>]
+    aoc2019 fuzz [--seed <n>] [--programs <n>]
+
+Options:
+    --day <day>      Day to run (1-17)
+    --part <part>    Part to run (1 or 2). Both parts are run if omitted.
+    --input <path>   Input file. Defaults to ./input/day_NN/input.txt
+    --png <path>     Also write image answers to the given PNG file
+    --iterations <n> Number of times to run each benchmark program (default 10)
+    --values <list>  Comma-separated input values given to the profiled program
+    --transcript <path>  Log the console session to the given file
+    --dot <path>     Also write the control-flow graph to the given Graphviz DOT file
+    --output <path>  Write the transpiled Rust module to the given file instead of stdout
+    --seed <n>       Seed for the fuzzed programs. Taken from the clock if omitted.
+    --programs <n>   Number of programs to fuzz (default 10000)
+
+The `all` command runs every registered solution against its default input and reports the
+time taken by each part. Individual parts can be excluded with --skip, e.g. --skip 16.2
+
+The `debug` command loads an Intcode program (the day's default input unless --input is given)
+into an interactive debugger that reads commands from stdin. Enter `help` at its prompt to list
+the debugger commands.
+
+The `bench` command times Intcode programs from the default inputs, comparing the original
+execution core with the decoded instruction cache, and with the transpiled program where there is
+one.
+
+The `profile` command runs an Intcode program with the given input values until it halts or needs
+more input, then reports the instructions executed per opcode and address, the hottest loops, and
+the number of values input and output.
+
+The `console` command runs an Intcode program that communicates in ASCII text, sending each line
+read from stdin to the program and showing its output as it is produced. Lines starting with `/`
+are console commands, used to save and load snapshots of the machine and to log a transcript of
+the session. Enter `/help` at the console to list them.
+
+The `analyze` command builds the control-flow graph of an Intcode program without running it, then
+reports the number of basic blocks and edges, any reachable invalid instructions, instructions
+that write into code, and the input and output instructions.
+
+The `transpile` command translates an Intcode program into a Rust module defining a
+TranspiledProgram, which runs the program as compiled Rust code and falls back to the interpreter
+for self-modifying code. Modules for programs from the puzzle inputs are kept in src/transpiled.
+
+The `fuzz` command generates random Intcode programs and checks that each one finishes in the same
+state on every variant of the machine as on a minimal reference interpreter. The first program
+that differs is reported, and the same programs are generated again when given the same seed.";
+
+/// Commands that can be given to the runner binary.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run {
+        day: u32,
+        part: Option<u32>,
+        input: Option<String>,
+        png: Option<String>,
+    },
+    RunAll {
+        skip: Vec<(u32, u32)>,
+    },
+    Debug {
+        input: String,
+    },
+    Bench {
+        iterations: u32,
+    },
+    Profile {
+        input: String,
+        values: Vec<i64>,
+    },
+    Console {
+        input: String,
+        transcript: Option<String>,
+    },
+    Analyze {
+        input: String,
+        dot: Option<String>,
+    },
+    Transpile {
+        input: String,
+        output: Option<String>,
+    },
+    Fuzz {
+        seed: Option<u32>,
+        programs: u32,
+    },
+    Help,
+}
+
+/// Parses the given command-line arguments (excluding the program name) into a command.
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    if args.is_empty() {
+        return Err(String::from("No command given."));
+    }
+    match args[0].as_str() {
+        "run" => parse_run_args(&args[1..]),
+        "all" => parse_all_args(&args[1..]),
+        "debug" => parse_debug_args(&args[1..]),
+        "bench" => parse_bench_args(&args[1..]),
+        "profile" => parse_profile_args(&args[1..]),
+        "console" => parse_console_args(&args[1..]),
+        "analyze" => parse_analyze_args(&args[1..]),
+        "transpile" => parse_transpile_args(&args[1..]),
+        "fuzz" => parse_fuzz_args(&args[1..]),
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        other => Err(format!("Unknown command: {}", other)),
+    }
+}
+
+/// Parses the options given to the "run" command.
+fn parse_run_args(args: &[String]) -> Result<Command, String> {
+    let mut day: Option<u32> = None;
+    let mut part: Option<u32> = None;
+    let mut input: Option<String> = None;
+    let mut png: Option<String> = None;
+    parse_options(args, |option, value| {
+        match option {
+            "--day" | "-d" => day = Some(parse_number(option, &value)?),
+            "--part" | "-p" => part = Some(parse_number(option, &value)?),
+            "--input" | "-i" => input = Some(value),
+            "--png" => png = Some(value),
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    })?;
+    let day = match day {
+        Some(d) => d,
+        None => return Err(String::from("The --day option is required.")),
+    };
+    if let Some(p) = part {
+        if p != 1 && p != 2 {
+            return Err(format!("Bad part number: {}", p));
+        }
+    }
+    return Ok(Command::Run {
+        day,
+        part,
+        input,
+        png,
+    });
+}
+
+/// Parses the options given to the "all" command.
+fn parse_all_args(args: &[String]) -> Result<Command, String> {
+    let mut skip: Vec<(u32, u32)> = vec![];
+    parse_options(args, |option, value| {
+        match option {
+            "--skip" | "-s" => skip.push(parse_day_part(&value)?),
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    })?;
+    return Ok(Command::RunAll { skip });
+}
+
+/// Parses the options given to the "debug" command.
+fn parse_debug_args(args: &[String]) -> Result<Command, String> {
+    let input = parse_program_options(args, |_, _| Ok(false))?;
+    return Ok(Command::Debug { input });
+}
+
+/// Parses the options given to the "bench" command.
+fn parse_bench_args(args: &[String]) -> Result<Command, String> {
+    let mut iterations = 10;
+    parse_options(args, |option, value| {
+        match option {
+            "--iterations" | "-n" => iterations = parse_number(option, &value)?,
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    })?;
+    return Ok(Command::Bench { iterations });
+}
+
+/// Parses the options given to the "profile" command.
+fn parse_profile_args(args: &[String]) -> Result<Command, String> {
+    let mut values: Vec<i64> = vec![];
+    let input = parse_program_options(args, |option, value| {
+        match option {
+            "--values" | "-v" => {
+                values = IntcodeMachine::parse_intcode_memory(&value)
+                    .map_err(|e| format!("Bad value for option {}: {}", option, e))?
+            }
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    })?;
+    return Ok(Command::Profile { input, values });
+}
+
+/// Parses the options given to the "console" command.
+fn parse_console_args(args: &[String]) -> Result<Command, String> {
+    let mut transcript: Option<String> = None;
+    let input = parse_program_options(args, |option, value| {
+        match option {
+            "--transcript" | "-t" => transcript = Some(value),
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    })?;
+    return Ok(Command::Console { input, transcript });
+}
+
+/// Parses the options given to the "analyze" command.
+fn parse_analyze_args(args: &[String]) -> Result<Command, String> {
+    let mut dot: Option<String> = None;
+    let input = parse_program_options(args, |option, value| {
+        match option {
+            "--dot" => dot = Some(value),
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    })?;
+    return Ok(Command::Analyze { input, dot });
+}
+
+/// Parses the options given to the "transpile" command.
+fn parse_transpile_args(args: &[String]) -> Result<Command, String> {
+    let mut output: Option<String> = None;
+    let input = parse_program_options(args, |option, value| {
+        match option {
+            "--output" | "-o" => output = Some(value),
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    })?;
+    return Ok(Command::Transpile { input, output });
+}
+
+/// Parses the options given to the "fuzz" command.
+fn parse_fuzz_args(args: &[String]) -> Result<Command, String> {
+    let mut seed: Option<u32> = None;
+    let mut programs = 10000;
+    parse_options(args, |option, value| {
+        match option {
+            "--seed" | "-s" => seed = Some(parse_number(option, &value)?),
+            "--programs" | "-n" => programs = parse_number(option, &value)?,
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    })?;
+    return Ok(Command::Fuzz { seed, programs });
+}
+
+/// Parses command options, each of which is followed by its value. Every option and value is passed
+/// to parse_option, which returns false if it doesn't recognise the option.
+fn parse_options<F>(args: &[String], mut parse_option: F) -> Result<(), String>
+where
+    F: FnMut(&str, String) -> Result<bool, String>,
+{
+    let mut index = 0;
+    while index < args.len() {
+        let option = args[index].as_str();
+        let value = match args.get(index + 1) {
+            Some(v) => v.clone(),
+            None => return Err(format!("Missing value for option: {}", option)),
+        };
+        if !parse_option(option, value)? {
+            return Err(format!("Unknown option: {}", option));
+        }
+        index += 2;
+    }
+    return Ok(());
+}
+
+/// Parses the options given to a command that loads an Intcode program, returning the path of the
+/// program given by the --day or --input option. Any other option is passed to parse_option, as
+/// for parse_options().
+fn parse_program_options<F>(args: &[String], mut parse_option: F) -> Result<String, String>
+where
+    F: FnMut(&str, String) -> Result<bool, String>,
+{
+    let mut day: Option<u32> = None;
+    let mut input: Option<String> = None;
+    parse_options(args, |option, value| {
+        match option {
+            "--day" | "-d" => day = Some(parse_number(option, &value)?),
+            "--input" | "-i" => input = Some(value),
+            _ => return parse_option(option, value),
+        }
+        return Ok(true);
+    })?;
+    return resolve_input(day, input);
+}
+
+/// Returns the path of the program to load, which is the --input file if given, or else the default
+/// input for the --day.
+fn resolve_input(day: Option<u32>, input: Option<String>) -> Result<String, String> {
+    match (input, day) {
+        (Some(i), _) => return Ok(i),
+        (None, Some(d)) => return Ok(get_default_input(d)),
+        (None, None) => return Err(String::from("The --day or --input option is required.")),
+    }
+}
+
+/// Parses a day and part given in the form "<day>.<part>", e.g. "16.2".
+fn parse_day_part(value: &str) -> Result<(u32, u32), String> {
+    let split: Vec<&str> = value.split('.').collect();
+    if split.len() != 2 {
+        return Err(format!("Bad day and part (expected <day>.<part>): {}", value));
+    }
+    let day = parse_number("--skip", split[0])?;
+    let part = parse_number("--skip", split[1])?;
+    if part != 1 && part != 2 {
+        return Err(format!("Bad part number: {}", part));
+    }
+    return Ok((day, part));
+}
+
+/// Parses the value of a numeric command-line option.
+fn parse_number(option: &str, value: &str) -> Result<u32, String> {
+    return value
+        .parse::<u32>()
+        .map_err(|_| format!("Bad value for option {}: {}", option, value));
+}
+
+/// Executes the given command, printing answers to stdout.
+pub fn execute_command(command: Command) -> Result<(), String> {
+    match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Command::Run {
+            day,
+            part,
+            input,
+            png,
+        } => {
+            let entry = match find_day(day) {
+                Some(e) => e,
+                None => return Err(format!("No solution registered for day {}.", day)),
+            };
+            let input = input.unwrap_or_else(|| get_default_input(day));
+            let parts = match part {
+                Some(p) => vec![p],
+                None => vec![1, 2],
+            };
+            for p in parts {
+                let answer = match entry.solution.solve(p, input.clone()).unwrap() {
+                    Ok(a) => a,
+                    Err(e) => return Err(format!("Day {:02} Part {} failed - {}", day, p, e)),
+                };
+                println!("Day {:02} Part {}: {}", day, p, answer);
+                if let (Answer::Image(image), Some(path)) = (&answer, &png) {
+                    if let Err(e) = image.write_png(Path::new(path)) {
+                        return Err(format!("Couldn't write PNG to {}. ({})", path, e));
+                    }
+                }
+            }
+            return Ok(());
+        }
+        Command::RunAll { skip } => {
+            return run_all(&skip);
+        }
+        Command::Debug { input } => {
+            let memory = IntcodeMachine::try_extract_intcode_memory_from_filename(input)
+                .map_err(|e| e.to_string())?;
+            let mut debugger = Debugger::new(IntcodeMachine::new(memory, VecDeque::new()));
+            let stdin = io::stdin();
+            let mut stdout = io::stdout();
+            return debugger
+                .run_repl(stdin.lock(), &mut stdout)
+                .map_err(|e| format!("Debugger I/O failed. ({})", e));
+        }
+        Command::Bench { iterations } => {
+            return bench::run_benchmark(iterations);
+        }
+        Command::Profile { input, values } => {
+            let memory = IntcodeMachine::try_extract_intcode_memory_from_filename(input)
+                .map_err(|e| e.to_string())?;
+            let mut machine = IntcodeMachine::new(memory, VecDeque::from(values));
+            machine.enable_profiling();
+            let result = machine.try_execute_program();
+            let stop_reason = match &result {
+                Err(fault) => format!("fault ({})", fault),
+                Ok(()) if machine.has_halted() => String::from("halted"),
+                Ok(()) => String::from("needs input"),
+            };
+            println!("stopped: {}", stop_reason);
+            print!("{}", machine.take_profile().unwrap());
+            return result.map_err(|e| format!("Intcode machine fault: {}", e));
+        }
+        Command::Console { input, transcript } => {
+            let memory = IntcodeMachine::try_extract_intcode_memory_from_filename(input)
+                .map_err(|e| e.to_string())?;
+            let mut console = Console::new(IntcodeMachine::new(memory, VecDeque::new()));
+            if let Some(path) = transcript {
+                console.start_transcript(path).map_err(|e| e.to_string())?;
+            }
+            let stdin = io::stdin();
+            let mut stdout = io::stdout();
+            console
+                .run(stdin.lock(), &mut stdout)
+                .map_err(|e| format!("Console I/O failed. ({})", e))?;
+            return console.end_transcript().map_err(|e| e.to_string());
+        }
+        Command::Analyze { input, dot } => {
+            let memory = IntcodeMachine::try_extract_intcode_memory_from_filename(input)
+                .map_err(|e| e.to_string())?;
+            let graph = analyze(&memory);
+            print!("{}", graph);
+            if let Some(path) = dot {
+                if let Err(e) = std::fs::write(&path, graph.to_dot()) {
+                    return Err(format!("Couldn't write DOT file to {}. ({})", path, e));
+                }
+            }
+            return Ok(());
+        }
+        Command::Transpile { input, output } => {
+            let memory = IntcodeMachine::try_extract_intcode_memory_from_filename(input.clone())
+                .map_err(|e| e.to_string())?;
+            let code = transpile(&memory, &input);
+            match output {
+                Some(path) => {
+                    if let Err(e) = std::fs::write(&path, code) {
+                        return Err(format!("Couldn't write Rust module to {}. ({})", path, e));
+                    }
+                }
+                None => print!("{}", code),
+            }
+            return Ok(());
+        }
+        Command::Fuzz { seed, programs } => {
+            let seed = seed.unwrap_or_else(|| {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+                return now.subsec_nanos();
+            });
+            println!("seed: {}", seed);
+            match fuzz(u64::from(seed), u64::from(programs)) {
+                Ok(report) => print!("{}", report),
+                Err(mismatch) => return Err(mismatch.to_string()),
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Runs both parts of every registered day against the default inputs, displaying each answer with
+/// the wall-clock time taken. The total time across all parts is displayed at the end. Parts listed
+/// in the skip list are not run.
+///
+/// Parts that fail are reported and the remaining parts are still run. An error is returned at the
+/// end if any part failed.
+fn run_all(skip: &[(u32, u32)]) -> Result<(), String> {
+    let mut total_time = Duration::new(0, 0);
+    let mut num_failed = 0;
+    for entry in get_registry() {
+        let input = get_default_input(entry.day);
+        for part in 1..=2 {
+            if skip.contains(&(entry.day, part)) {
+                println!("Day {:02} Part {}: skipped", entry.day, part);
+                continue;
+            }
+            let start = Instant::now();
+            let result = entry.solution.solve(part, input.clone()).unwrap();
+            let elapsed = start.elapsed();
+            total_time += elapsed;
+            let answer = match result {
+                Ok(a) => a,
+                Err(e) => {
+                    println!("Day {:02} Part {}: ERROR - {}", entry.day, part, e);
+                    num_failed += 1;
+                    continue;
+                }
+            };
+            println!(
+                "Day {:02} Part {}: {:<30} [{}]",
+                entry.day,
+                part,
+                format_answer_summary(&answer),
+                format_duration(elapsed)
+            );
+            // Images don't fit on the summary line, so are rendered underneath
+            if let Answer::Image(image) = answer {
+                print!("{}", image.render());
+            }
+        }
+    }
+    println!("Total time: {}", format_duration(total_time));
+    if num_failed > 0 {
+        return Err(format!("{} part(s) failed.", num_failed));
+    }
+    return Ok(());
+}
+
+/// Formats the answer to fit on a single line. Images are shown by their dimensions only.
+fn format_answer_summary(answer: &Answer) -> String {
+    match answer {
+        Answer::Image(image) => format!("<image {}x{}>", image.get_width(), image.get_height()),
+        _ => answer.to_string(),
+    }
+}
+
+/// Formats the duration in milliseconds with three decimal places, e.g. "12.345 ms".
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_secs_f64() * 1000.0;
+    return format!("{:.3} ms", millis);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Converts the given string slices into owned argument strings.
+    fn to_args(args: &[&str]) -> Vec<String> {
+        return args.iter().map(|a| a.to_string()).collect();
+    }
+
+    #[test]
+    fn test_parse_run_all_options() {
+        let args = to_args(&["run", "--day", "11", "--part", "2", "--input", "in.txt"]);
+        let expected = Command::Run {
+            day: 11,
+            part: Some(2),
+            input: Some(String::from("in.txt")),
+            png: None,
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+    }
+
+    #[test]
+    fn test_parse_run_defaults() {
+        let args = to_args(&["run", "-d", "3"]);
+        let expected = Command::Run {
+            day: 3,
+            part: None,
+            input: None,
+            png: None,
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+    }
+
+    #[test]
+    fn test_parse_bad_args() {
+        assert!(parse_args(&to_args(&[])).is_err());
+        assert!(parse_args(&to_args(&["jump"])).is_err());
+        assert!(parse_args(&to_args(&["run", "--part", "1"])).is_err());
+        assert!(parse_args(&to_args(&["run", "--day", "1", "--part", "3"])).is_err());
+        assert!(parse_args(&to_args(&["run", "--day", "x"])).is_err());
+        assert!(parse_args(&to_args(&["run", "--day"])).is_err());
+        assert!(parse_args(&to_args(&["all", "--day", "1"])).is_err());
+        assert!(parse_args(&to_args(&["all", "--skip", "16"])).is_err());
+        assert!(parse_args(&to_args(&["all", "--skip", "16.3"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let expected = Command::RunAll { skip: vec![] };
+        assert_eq!(Ok(expected), parse_args(&to_args(&["all"])));
+        let args = to_args(&["all", "--skip", "16.2", "-s", "12.1"]);
+        let expected = Command::RunAll {
+            skip: vec![(16, 2), (12, 1)],
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+    }
+
+    #[test]
+    fn test_parse_run_png() {
+        let args = to_args(&["run", "--day", "11", "--part", "2", "--png", "hull.png"]);
+        let expected = Command::Run {
+            day: 11,
+            part: Some(2),
+            input: None,
+            png: Some(String::from("hull.png")),
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+    }
+
+    #[test]
+    fn test_parse_debug() {
+        let expected = Command::Debug {
+            input: get_default_input(17),
+        };
+        assert_eq!(Ok(expected), parse_args(&to_args(&["debug", "--day", "17"])));
+        let expected = Command::Debug {
+            input: String::from("prog.txt"),
+        };
+        assert_eq!(Ok(expected), parse_args(&to_args(&["debug", "-i", "prog.txt"])));
+        assert!(parse_args(&to_args(&["debug"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        let expected = Command::Bench { iterations: 10 };
+        assert_eq!(Ok(expected), parse_args(&to_args(&["bench"])));
+        let expected = Command::Bench { iterations: 50 };
+        assert_eq!(Ok(expected), parse_args(&to_args(&["bench", "--iterations", "50"])));
+        assert!(parse_args(&to_args(&["bench", "--day", "9"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        let args = to_args(&["profile", "--day", "9", "--values", "2"]);
+        let expected = Command::Profile {
+            input: get_default_input(9),
+            values: vec![2],
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+        let args = to_args(&["profile", "-i", "prog.txt", "-v", "1, -2,3"]);
+        let expected = Command::Profile {
+            input: String::from("prog.txt"),
+            values: vec![1, -2, 3],
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+        assert!(parse_args(&to_args(&["profile", "--values", "1"])).is_err());
+        assert!(parse_args(&to_args(&["profile", "--day", "9", "--values", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_console() {
+        let args = to_args(&["console", "-i", "adventure.txt", "--transcript", "log.txt"]);
+        let expected = Command::Console {
+            input: String::from("adventure.txt"),
+            transcript: Some(String::from("log.txt")),
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+        let expected = Command::Console {
+            input: get_default_input(17),
+            transcript: None,
+        };
+        assert_eq!(Ok(expected), parse_args(&to_args(&["console", "--day", "17"])));
+        assert!(parse_args(&to_args(&["console", "--transcript", "log.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_analyze() {
+        let args = to_args(&["analyze", "--day", "17", "--dot", "cfg.dot"]);
+        let expected = Command::Analyze {
+            input: get_default_input(17),
+            dot: Some(String::from("cfg.dot")),
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+        let expected = Command::Analyze {
+            input: String::from("prog.txt"),
+            dot: None,
+        };
+        assert_eq!(Ok(expected), parse_args(&to_args(&["analyze", "-i", "prog.txt"])));
+        assert!(parse_args(&to_args(&["analyze", "--dot", "cfg.dot"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_transpile() {
+        let args = to_args(&["transpile", "--day", "2", "-o", "day_02.rs"]);
+        let expected = Command::Transpile {
+            input: get_default_input(2),
+            output: Some(String::from("day_02.rs")),
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+        assert!(parse_args(&to_args(&["transpile", "--output", "day_02.rs"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_fuzz() {
+        let expected = Command::Fuzz {
+            seed: None,
+            programs: 10000,
+        };
+        assert_eq!(Ok(expected), parse_args(&to_args(&["fuzz"])));
+        let args = to_args(&["fuzz", "--seed", "42", "-n", "500"]);
+        let expected = Command::Fuzz {
+            seed: Some(42),
+            programs: 500,
+        };
+        assert_eq!(Ok(expected), parse_args(&args));
+        assert!(parse_args(&to_args(&["fuzz", "--seed", "-1"])).is_err());
+    }
+
+    #[test]
+    fn test_format_answer_summary() {
+        use crate::solution::Image;
+        assert_eq!("12", format_answer_summary(&Answer::Integer(12)));
+        let image = Answer::Image(Image::new(25, 6));
+        assert_eq!("<image 25x6>", format_answer_summary(&image));
+    }
+
+    #[test]
+    fn test_run_missing_input() {
+        let command = Command::Run {
+            day: 1,
+            part: Some(1),
+            input: Some(String::from("./input/day_01/missing.txt")),
+            png: None,
+        };
+        let error = execute_command(command).unwrap_err();
+        assert!(error.starts_with("Day 01 Part 1 failed - couldn't read"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("1500.000 ms", format_duration(Duration::from_millis(1500)));
+        assert_eq!("0.250 ms", format_duration(Duration::from_micros(250)));
+    }
+}
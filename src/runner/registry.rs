@@ -0,0 +1,123 @@
+use crate::{
+    day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12,
+    day_13, day_14, day_15, day_16, day_17,
+};
+use crate::solution::Solution;
+
+/// Registry entry holding the solution for a single day.
+pub struct DayEntry {
+    pub day: u32,
+    pub solution: Box<dyn Solution>,
+}
+
+/// Returns the registry entries for all days with solutions, in ascending day order.
+pub fn get_registry() -> Vec<DayEntry> {
+    return vec![
+        DayEntry {
+            day: 1,
+            solution: Box::new(day_01::Day01),
+        },
+        DayEntry {
+            day: 2,
+            solution: Box::new(day_02::Day02),
+        },
+        DayEntry {
+            day: 3,
+            solution: Box::new(day_03::Day03),
+        },
+        DayEntry {
+            day: 4,
+            solution: Box::new(day_04::Day04),
+        },
+        DayEntry {
+            day: 5,
+            solution: Box::new(day_05::Day05),
+        },
+        DayEntry {
+            day: 6,
+            solution: Box::new(day_06::Day06),
+        },
+        DayEntry {
+            day: 7,
+            solution: Box::new(day_07::Day07),
+        },
+        DayEntry {
+            day: 8,
+            solution: Box::new(day_08::Day08),
+        },
+        DayEntry {
+            day: 9,
+            solution: Box::new(day_09::Day09),
+        },
+        DayEntry {
+            day: 10,
+            solution: Box::new(day_10::Day10),
+        },
+        DayEntry {
+            day: 11,
+            solution: Box::new(day_11::Day11),
+        },
+        DayEntry {
+            day: 12,
+            solution: Box::new(day_12::Day12),
+        },
+        DayEntry {
+            day: 13,
+            solution: Box::new(day_13::Day13),
+        },
+        DayEntry {
+            day: 14,
+            solution: Box::new(day_14::Day14),
+        },
+        DayEntry {
+            day: 15,
+            solution: Box::new(day_15::Day15),
+        },
+        DayEntry {
+            day: 16,
+            solution: Box::new(day_16::Day16),
+        },
+        DayEntry {
+            day: 17,
+            solution: Box::new(day_17::Day17),
+        },
+    ];
+}
+
+/// Finds the registry entry for the given day.
+pub fn find_day(day: u32) -> Option<DayEntry> {
+    return get_registry().into_iter().find(|entry| entry.day == day);
+}
+
+/// Gets the default input filename for the given day, e.g. "./input/day_07/input.txt".
+pub fn get_default_input(day: u32) -> String {
+    return format!("./input/day_{:02}/input.txt", day);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::Answer;
+
+    #[test]
+    fn test_registry_covers_all_days_in_order() {
+        let days: Vec<u32> = get_registry().iter().map(|entry| entry.day).collect();
+        assert_eq!((1..=17).collect::<Vec<u32>>(), days);
+    }
+
+    #[test]
+    fn test_default_input_path() {
+        assert_eq!("./input/day_07/input.txt", get_default_input(7));
+        assert_eq!("./input/day_11/input.txt", get_default_input(11));
+    }
+
+    #[test]
+    fn test_find_day_runs_part() {
+        let entry = find_day(1).unwrap();
+        let input = String::from("./input/day_01/test/test_04.txt");
+        let answer = entry.solution.solve(1, input.clone()).unwrap().unwrap();
+        assert_eq!(Answer::Integer(33583), answer);
+        assert!(entry.solution.solve(3, input).is_none());
+        assert!(find_day(18).is_none());
+    }
+}
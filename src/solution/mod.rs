@@ -0,0 +1,217 @@
+//! # solution - common interface for daily solutions
+//!
+//! This module contains the Solution trait implemented by every day, along with the Answer type
+//! used to represent the result of any puzzle part.
+
+use super::utils::error::AocError;
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+// Pixel renders
+const RENDER_LIT: char = '#';
+const RENDER_UNLIT: char = ' ';
+
+/// Common interface implemented by the solution for each day. Both parts take the input filename
+/// and return the answer in a common form, or an error if the input couldn't be loaded.
+pub trait Solution {
+    /// Calculates the answer for Part 1 using the given input file.
+    fn part_1(&self, filename: String) -> Result<Answer, AocError>;
+
+    /// Calculates the answer for Part 2 using the given input file.
+    fn part_2(&self, filename: String) -> Result<Answer, AocError>;
+
+    /// Calculates the answer for the given part (1 or 2). Returns None if the part is not valid.
+    fn solve(&self, part: u32, filename: String) -> Option<Result<Answer, AocError>> {
+        match part {
+            1 => Some(self.part_1(filename)),
+            2 => Some(self.part_2(filename)),
+            _ => None,
+        }
+    }
+}
+
+/// Represents the answer to a single puzzle part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Integer(i64),
+    BigInteger(u128),
+    Text(String),
+    Image(Image),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{}", value),
+            Answer::BigInteger(value) => write!(f, "{}", value),
+            Answer::Text(value) => write!(f, "{}", value),
+            Answer::Image(image) => write!(f, "\n{}", image.render()),
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(value: i32) -> Self {
+        return Answer::Integer(i64::from(value));
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(value: u32) -> Self {
+        return Answer::Integer(i64::from(value));
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        return Answer::Integer(value);
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        if value > i64::MAX as u64 {
+            return Answer::BigInteger(u128::from(value));
+        }
+        return Answer::Integer(value as i64);
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        return Answer::from(value as u64);
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(value: u128) -> Self {
+        return Answer::BigInteger(value);
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        return Answer::Text(value);
+    }
+}
+
+impl From<Image> for Answer {
+    fn from(image: Image) -> Self {
+        return Answer::Image(image);
+    }
+}
+
+/// Monochrome image produced by puzzles whose answer is read visually, e.g. Day 08 Part 2 and
+/// Day 11 Part 2. Pixels are stored in row-major order and are either lit (true) or unlit (false).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Image {
+    /// Creates a new image with all pixels unlit.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.height;
+    }
+
+    /// Checks if the pixel at the given location is lit. Locations outside of the image are unlit.
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        return self.pixels[y * self.width + x];
+    }
+
+    /// Sets the state of the pixel at the given location. Panics if the location is outside of the
+    /// image.
+    pub fn set_pixel(&mut self, x: usize, y: usize, lit: bool) {
+        if x >= self.width || y >= self.height {
+            panic!("Pixel ({}, {}) is outside of image.", x, y);
+        }
+        self.pixels[y * self.width + x] = lit;
+    }
+
+    /// Renders the image as lines of text, with lit pixels shown as '#'.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.is_lit(x, y) {
+                    output.push(RENDER_LIT);
+                } else {
+                    output.push(RENDER_UNLIT);
+                }
+            }
+            output.push('\n');
+        }
+        return output;
+    }
+
+    /// Writes the image to a PNG file at the given path, with lit pixels in white and unlit pixels
+    /// in black.
+    pub fn write_png(&self, path: &Path) -> Result<(), png::EncodingError> {
+        let mut image_data: Vec<u8> = vec![];
+        for lit in self.pixels.iter() {
+            if *lit {
+                image_data.extend_from_slice(&[255, 255, 255, 255]);
+            } else {
+                image_data.extend_from_slice(&[0, 0, 0, 255]);
+            }
+        }
+        let file = File::create(path)?;
+        let w = BufWriter::new(file);
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&image_data)?;
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_from_integers() {
+        assert_eq!(Answer::Integer(-5), Answer::from(-5i32));
+        assert_eq!(Answer::Integer(7), Answer::from(7usize));
+        assert_eq!(Answer::Integer(9), Answer::from(9u64));
+        assert_eq!(Answer::BigInteger(u64::MAX as u128), Answer::from(u64::MAX));
+        assert_eq!(Answer::BigInteger(12), Answer::from(12u128));
+    }
+
+    #[test]
+    fn test_answer_display() {
+        assert_eq!("42", Answer::Integer(42).to_string());
+        assert_eq!("27229269", Answer::Text(String::from("27229269")).to_string());
+    }
+
+    #[test]
+    fn test_image_render() {
+        let mut image = Image::new(3, 2);
+        image.set_pixel(0, 0, true);
+        image.set_pixel(2, 1, true);
+        assert!(image.is_lit(0, 0));
+        assert!(!image.is_lit(1, 0));
+        assert!(!image.is_lit(5, 5));
+        assert_eq!("#  \n  #\n", image.render());
+    }
+}
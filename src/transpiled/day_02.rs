@@ -0,0 +1,529 @@
+//! Intcode program transpiled to Rust by the `transpile` command, from:
+//! ./input/day_02/input.txt
+//!
+//! Do not edit - transpile the program again instead.
+
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::utils::intcode::{IntcodeMachine, MachineFault, RunState};
+
+/// Memory image the program was transpiled from.
+const IMAGE: [i64; 145] = [
+    1, 12, 2, 3, 1, 1, 2, 3, 1, 3, 4, 3, 1, 5, 0, 3,
+    2, 10, 1, 19, 1, 6, 19, 23, 2, 23, 6, 27, 1, 5, 27, 31,
+    1, 31, 9, 35, 2, 10, 35, 39, 1, 5, 39, 43, 2, 43, 10, 47,
+    1, 47, 6, 51, 2, 51, 6, 55, 2, 55, 13, 59, 2, 6, 59, 63,
+    1, 63, 5, 67, 1, 6, 67, 71, 2, 71, 9, 75, 1, 6, 75, 79,
+    2, 13, 79, 83, 1, 9, 83, 87, 1, 87, 13, 91, 2, 91, 10, 95,
+    1, 6, 95, 99, 1, 99, 13, 103, 1, 13, 103, 107, 2, 107, 10, 111,
+    1, 9, 111, 115, 1, 115, 10, 119, 1, 5, 119, 123, 1, 6, 123, 127,
+    1, 10, 127, 131, 1, 2, 131, 135, 1, 135, 10, 0, 99, 2, 14, 0,
+    0,
+];
+/// Addresses of the cells compiled into the code, which must hold the same values as the image.
+const FIXED_CELLS: [usize; 36] = [
+    0, 4, 8, 12, 16, 20, 24, 28, 32, 36, 40, 44, 48, 52, 56, 60,
+    64, 68, 72, 76, 80, 84, 88, 92, 96, 100, 104, 108, 112, 116, 120, 124,
+    128, 132, 136, 140,
+];
+/// Index (plus one) of the block compiled from each cell of the image, or 0 for other cells.
+const CELL_BLOCKS: [u32; 145] = [
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+    0,
+];
+/// Start and end addresses of each compiled block.
+const BLOCKS: [(usize, usize); 1] = [
+    (0, 141),
+];
+/// Highest address the program can access.
+const MAX_ADDRESS: i64 = 4294967295;
+/// Highest address compiled code writes to.
+const MAX_COMPILED_ADDRESS: usize = 1048576;
+
+/// Intcode program compiled to Rust, which falls back to the interpreter once it can't run the
+/// compiled code.
+pub struct TranspiledProgram {
+    memory: Vec<i64>,
+    pc: usize,
+    relative_base: i64,
+    input: VecDeque<i64>,
+    halted: bool,
+    awaiting_input: bool,
+    dirty: Vec<bool>,
+    machine: Option<IntcodeMachine>,
+    fallbacks: u64,
+}
+
+impl TranspiledProgram {
+    /// Creates the program with the memory image it was transpiled from.
+    pub fn new() -> Self {
+        return Self::with_memory(IMAGE.to_vec()).unwrap();
+    }
+
+    /// Creates the program with the given memory, e.g. the image with some values patched.
+    /// Returns None if a cell compiled into the code doesn't hold the value it has in the image.
+    pub fn with_memory(mut memory: Vec<i64>) -> Option<Self> {
+        for &address in FIXED_CELLS.iter() {
+            if *memory.get(address).unwrap_or(&0) != IMAGE[address] {
+                return None;
+            }
+        }
+        if memory.len() < IMAGE.len() {
+            memory.resize(IMAGE.len(), 0);
+        }
+        return Some(Self {
+            memory,
+            pc: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            halted: false,
+            awaiting_input: false,
+            dirty: vec![false; BLOCKS.len()],
+            machine: None,
+            fallbacks: 0,
+        });
+    }
+
+    /// Adds the value to the input queue of the program.
+    pub fn add_input(&mut self, value: i64) {
+        match &mut self.machine {
+            Some(machine) => machine.add_input(value),
+            None => {
+                self.input.push_back(value);
+                self.awaiting_input = false;
+            }
+        }
+    }
+
+    /// Runs the program until it halts, needs input or outputs a value, and returns the reason it
+    /// stopped - the same as IntcodeMachine::run().
+    pub fn run(&mut self) -> Result<RunState, MachineFault> {
+        loop {
+            if let Some(machine) = &mut self.machine {
+                if let Some(state) = machine.step()? {
+                    return Ok(state);
+                }
+                self.try_resume_compiled();
+                continue;
+            }
+            if self.halted {
+                return Ok(RunState::Halted);
+            }
+            match self.run_block() {
+                Ok(Some(state)) => return Ok(state),
+                Ok(None) => (),
+                Err(pc) => self.fall_back(pc),
+            }
+        }
+    }
+
+    /// Checks if the program has halted.
+    pub fn has_halted(&self) -> bool {
+        match &self.machine {
+            Some(machine) => return machine.has_halted(),
+            None => return self.halted,
+        }
+    }
+
+    /// Checks if the program is waiting for input.
+    pub fn is_awaiting_input(&self) -> bool {
+        match &self.machine {
+            Some(machine) => return machine.is_awaiting_input(),
+            None => return self.awaiting_input,
+        }
+    }
+
+    /// Checks if the program is currently being run by the interpreter.
+    pub fn is_interpreted(&self) -> bool {
+        return self.machine.is_some();
+    }
+
+    /// Returns the number of times the program has fallen back to the interpreter.
+    pub fn get_fallback_count(&self) -> u64 {
+        return self.fallbacks;
+    }
+
+    /// Returns the value held at the given address of memory.
+    pub fn peek_memory(&self, address: usize) -> i64 {
+        match &self.machine {
+            Some(machine) => return machine.peek_memory(address),
+            None => return *self.memory.get(address).unwrap_or(&0),
+        }
+    }
+
+    /// Returns the current value of the program counter.
+    pub fn get_program_counter(&self) -> usize {
+        match &self.machine {
+            Some(machine) => return machine.get_program_counter(),
+            None => return self.pc,
+        }
+    }
+
+    /// Returns the current value of the relative base.
+    pub fn get_relative_base(&self) -> i64 {
+        match &self.machine {
+            Some(machine) => return machine.get_relative_base(),
+            None => return self.relative_base,
+        }
+    }
+
+    /// Converts the program into an IntcodeMachine in the same state.
+    pub fn into_machine(mut self) -> IntcodeMachine {
+        if let Some(machine) = self.machine.take() {
+            return machine;
+        }
+        return IntcodeMachine::from_transpiled_state(
+            self.take_memory(),
+            self.pc,
+            self.relative_base,
+            mem::take(&mut self.input),
+            self.halted,
+            self.awaiting_input,
+        );
+    }
+
+    /// Hands the program over to the interpreter, which continues running it from the given
+    /// address.
+    fn fall_back(&mut self, pc: usize) {
+        self.fallbacks += 1;
+        self.machine = Some(IntcodeMachine::from_transpiled_state(
+            self.take_memory(),
+            pc,
+            self.relative_base,
+            mem::take(&mut self.input),
+            false,
+            false,
+        ));
+    }
+
+    /// Switches back from the interpreter to compiled code if the interpreter has reached the
+    /// start of a compiled block that hasn't been changed since the program was transpiled.
+    fn try_resume_compiled(&mut self) {
+        let machine = self.machine.as_ref().unwrap();
+        let pc = machine.get_program_counter();
+        let (start, end) = match BLOCKS.binary_search_by_key(&pc, |(start, _)| *start) {
+            Ok(index) => BLOCKS[index],
+            Err(_) => return,
+        };
+        let changed = (start..end).any(|a| CELL_BLOCKS[a] != 0 && machine.peek_memory(a) != IMAGE[a]);
+        if changed || machine.get_memory_len() > MAX_COMPILED_ADDRESS + 1 {
+            return;
+        }
+        let machine = self.machine.take().unwrap();
+        self.memory = machine.get_memory_dump();
+        self.pc = pc;
+        self.relative_base = machine.get_relative_base();
+        self.input = machine.get_input_vec();
+        for dirty in self.dirty.iter_mut() {
+            *dirty = false;
+        }
+        for &address in FIXED_CELLS.iter() {
+            if self.memory[address] != IMAGE[address] {
+                self.dirty[CELL_BLOCKS[address] as usize - 1] = true;
+            }
+        }
+    }
+
+    /// Takes the memory of the program, without any zeroes past the end of the image.
+    fn take_memory(&mut self) -> Vec<i64> {
+        let mut memory = mem::take(&mut self.memory);
+        while memory.len() > IMAGE.len() && memory.last() == Some(&0) {
+            memory.pop();
+        }
+        return memory;
+    }
+
+    // Helpers used by the blocks return the address of the instruction being run as the error,
+    // so the interpreter can run the instruction instead (and fault if it is invalid)
+
+    /// Returns the value held at the address.
+    fn read(&self, address: i64, pc: usize) -> Result<i64, usize> {
+        if address < 0 || address > MAX_ADDRESS {
+            return Err(pc);
+        }
+        return Ok(*self.memory.get(address as usize).unwrap_or(&0));
+    }
+
+    /// Returns the address relative to the relative base.
+    fn relative(&self, offset: i64, pc: usize) -> Result<i64, usize> {
+        return self.relative_base.checked_add(offset).ok_or(pc);
+    }
+
+    /// Adds the values.
+    fn add(&self, a: i64, b: i64, pc: usize) -> Result<i64, usize> {
+        return a.checked_add(b).ok_or(pc);
+    }
+
+    /// Multiplies the values.
+    fn multiply(&self, a: i64, b: i64, pc: usize) -> Result<i64, usize> {
+        return a.checked_mul(b).ok_or(pc);
+    }
+
+    /// Checks the address can be written to by compiled code.
+    fn write_address(&self, address: i64, pc: usize) -> Result<usize, usize> {
+        if address < 0 || address as u64 > MAX_COMPILED_ADDRESS as u64 {
+            return Err(pc);
+        }
+        return Ok(address as usize);
+    }
+
+    /// Checks the address can be jumped to.
+    fn jump_address(&self, address: i64, pc: usize) -> Result<usize, usize> {
+        if address < 0 || address > MAX_ADDRESS {
+            return Err(pc);
+        }
+        return Ok(address as usize);
+    }
+
+    /// Writes the value to the address. Returns true if it changed a cell compiled into the code,
+    /// in which case the block compiled from the cell can no longer be run.
+    fn write(&mut self, address: usize, value: i64) -> bool {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        let old_value = mem::replace(&mut self.memory[address], value);
+        if address >= IMAGE.len() || old_value == value || CELL_BLOCKS[address] == 0 {
+            return false;
+        }
+        self.dirty[CELL_BLOCKS[address] as usize - 1] = true;
+        return true;
+    }
+
+    /// Runs the compiled block starting at the program counter, if there is one.
+    fn run_block(&mut self) -> Result<Option<RunState>, usize> {
+        match self.pc {
+            0 => return self.block_0(),
+            pc => return Err(pc),
+        }
+    }
+
+    /// Runs the block at address 0.
+    fn block_0(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[0] {
+            return Err(0);
+        }
+        // 0: add 12, 2, 3
+        let value = self.add(self.read(self.memory[1], 0)?, self.read(self.memory[2], 0)?, 0)?;
+        let address = self.write_address(self.memory[3], 0)?;
+        if self.write(address, value) && (4..141).contains(&address) {
+            return Err(4);
+        }
+        // 4: add 1, 2, 3
+        let value = self.add(self.read(self.memory[5], 4)?, self.read(self.memory[6], 4)?, 4)?;
+        let address = self.write_address(self.memory[7], 4)?;
+        if self.write(address, value) && (8..141).contains(&address) {
+            return Err(8);
+        }
+        // 8: add 3, 4, 3
+        let value = self.add(self.read(self.memory[9], 8)?, self.read(self.memory[10], 8)?, 8)?;
+        let address = self.write_address(self.memory[11], 8)?;
+        if self.write(address, value) && (12..141).contains(&address) {
+            return Err(12);
+        }
+        // 12: add 5, 0, 3
+        let value = self.add(self.read(self.memory[13], 12)?, self.read(self.memory[14], 12)?, 12)?;
+        let address = self.write_address(self.memory[15], 12)?;
+        if self.write(address, value) && (16..141).contains(&address) {
+            return Err(16);
+        }
+        // 16: mul 10, 1, 19
+        let value = self.multiply(self.read(self.memory[17], 16)?, self.read(self.memory[18], 16)?, 16)?;
+        let address = self.write_address(self.memory[19], 16)?;
+        if self.write(address, value) && (20..141).contains(&address) {
+            return Err(20);
+        }
+        // 20: add 6, 19, 23
+        let value = self.add(self.read(self.memory[21], 20)?, self.read(self.memory[22], 20)?, 20)?;
+        let address = self.write_address(self.memory[23], 20)?;
+        if self.write(address, value) && (24..141).contains(&address) {
+            return Err(24);
+        }
+        // 24: mul 23, 6, 27
+        let value = self.multiply(self.read(self.memory[25], 24)?, self.read(self.memory[26], 24)?, 24)?;
+        let address = self.write_address(self.memory[27], 24)?;
+        if self.write(address, value) && (28..141).contains(&address) {
+            return Err(28);
+        }
+        // 28: add 5, 27, 31
+        let value = self.add(self.read(self.memory[29], 28)?, self.read(self.memory[30], 28)?, 28)?;
+        let address = self.write_address(self.memory[31], 28)?;
+        if self.write(address, value) && (32..141).contains(&address) {
+            return Err(32);
+        }
+        // 32: add 31, 9, 35
+        let value = self.add(self.read(self.memory[33], 32)?, self.read(self.memory[34], 32)?, 32)?;
+        let address = self.write_address(self.memory[35], 32)?;
+        if self.write(address, value) && (36..141).contains(&address) {
+            return Err(36);
+        }
+        // 36: mul 10, 35, 39
+        let value = self.multiply(self.read(self.memory[37], 36)?, self.read(self.memory[38], 36)?, 36)?;
+        let address = self.write_address(self.memory[39], 36)?;
+        if self.write(address, value) && (40..141).contains(&address) {
+            return Err(40);
+        }
+        // 40: add 5, 39, 43
+        let value = self.add(self.read(self.memory[41], 40)?, self.read(self.memory[42], 40)?, 40)?;
+        let address = self.write_address(self.memory[43], 40)?;
+        if self.write(address, value) && (44..141).contains(&address) {
+            return Err(44);
+        }
+        // 44: mul 43, 10, 47
+        let value = self.multiply(self.read(self.memory[45], 44)?, self.read(self.memory[46], 44)?, 44)?;
+        let address = self.write_address(self.memory[47], 44)?;
+        if self.write(address, value) && (48..141).contains(&address) {
+            return Err(48);
+        }
+        // 48: add 47, 6, 51
+        let value = self.add(self.read(self.memory[49], 48)?, self.read(self.memory[50], 48)?, 48)?;
+        let address = self.write_address(self.memory[51], 48)?;
+        if self.write(address, value) && (52..141).contains(&address) {
+            return Err(52);
+        }
+        // 52: mul 51, 6, 55
+        let value = self.multiply(self.read(self.memory[53], 52)?, self.read(self.memory[54], 52)?, 52)?;
+        let address = self.write_address(self.memory[55], 52)?;
+        if self.write(address, value) && (56..141).contains(&address) {
+            return Err(56);
+        }
+        // 56: mul 55, 13, 59
+        let value = self.multiply(self.read(self.memory[57], 56)?, self.read(self.memory[58], 56)?, 56)?;
+        let address = self.write_address(self.memory[59], 56)?;
+        if self.write(address, value) && (60..141).contains(&address) {
+            return Err(60);
+        }
+        // 60: mul 6, 59, 63
+        let value = self.multiply(self.read(self.memory[61], 60)?, self.read(self.memory[62], 60)?, 60)?;
+        let address = self.write_address(self.memory[63], 60)?;
+        if self.write(address, value) && (64..141).contains(&address) {
+            return Err(64);
+        }
+        // 64: add 63, 5, 67
+        let value = self.add(self.read(self.memory[65], 64)?, self.read(self.memory[66], 64)?, 64)?;
+        let address = self.write_address(self.memory[67], 64)?;
+        if self.write(address, value) && (68..141).contains(&address) {
+            return Err(68);
+        }
+        // 68: add 6, 67, 71
+        let value = self.add(self.read(self.memory[69], 68)?, self.read(self.memory[70], 68)?, 68)?;
+        let address = self.write_address(self.memory[71], 68)?;
+        if self.write(address, value) && (72..141).contains(&address) {
+            return Err(72);
+        }
+        // 72: mul 71, 9, 75
+        let value = self.multiply(self.read(self.memory[73], 72)?, self.read(self.memory[74], 72)?, 72)?;
+        let address = self.write_address(self.memory[75], 72)?;
+        if self.write(address, value) && (76..141).contains(&address) {
+            return Err(76);
+        }
+        // 76: add 6, 75, 79
+        let value = self.add(self.read(self.memory[77], 76)?, self.read(self.memory[78], 76)?, 76)?;
+        let address = self.write_address(self.memory[79], 76)?;
+        if self.write(address, value) && (80..141).contains(&address) {
+            return Err(80);
+        }
+        // 80: mul 13, 79, 83
+        let value = self.multiply(self.read(self.memory[81], 80)?, self.read(self.memory[82], 80)?, 80)?;
+        let address = self.write_address(self.memory[83], 80)?;
+        if self.write(address, value) && (84..141).contains(&address) {
+            return Err(84);
+        }
+        // 84: add 9, 83, 87
+        let value = self.add(self.read(self.memory[85], 84)?, self.read(self.memory[86], 84)?, 84)?;
+        let address = self.write_address(self.memory[87], 84)?;
+        if self.write(address, value) && (88..141).contains(&address) {
+            return Err(88);
+        }
+        // 88: add 87, 13, 91
+        let value = self.add(self.read(self.memory[89], 88)?, self.read(self.memory[90], 88)?, 88)?;
+        let address = self.write_address(self.memory[91], 88)?;
+        if self.write(address, value) && (92..141).contains(&address) {
+            return Err(92);
+        }
+        // 92: mul 91, 10, 95
+        let value = self.multiply(self.read(self.memory[93], 92)?, self.read(self.memory[94], 92)?, 92)?;
+        let address = self.write_address(self.memory[95], 92)?;
+        if self.write(address, value) && (96..141).contains(&address) {
+            return Err(96);
+        }
+        // 96: add 6, 95, 99
+        let value = self.add(self.read(self.memory[97], 96)?, self.read(self.memory[98], 96)?, 96)?;
+        let address = self.write_address(self.memory[99], 96)?;
+        if self.write(address, value) && (100..141).contains(&address) {
+            return Err(100);
+        }
+        // 100: add 99, 13, 103
+        let value = self.add(self.read(self.memory[101], 100)?, self.read(self.memory[102], 100)?, 100)?;
+        let address = self.write_address(self.memory[103], 100)?;
+        if self.write(address, value) && (104..141).contains(&address) {
+            return Err(104);
+        }
+        // 104: add 13, 103, 107
+        let value = self.add(self.read(self.memory[105], 104)?, self.read(self.memory[106], 104)?, 104)?;
+        let address = self.write_address(self.memory[107], 104)?;
+        if self.write(address, value) && (108..141).contains(&address) {
+            return Err(108);
+        }
+        // 108: mul 107, 10, 111
+        let value = self.multiply(self.read(self.memory[109], 108)?, self.read(self.memory[110], 108)?, 108)?;
+        let address = self.write_address(self.memory[111], 108)?;
+        if self.write(address, value) && (112..141).contains(&address) {
+            return Err(112);
+        }
+        // 112: add 9, 111, 115
+        let value = self.add(self.read(self.memory[113], 112)?, self.read(self.memory[114], 112)?, 112)?;
+        let address = self.write_address(self.memory[115], 112)?;
+        if self.write(address, value) && (116..141).contains(&address) {
+            return Err(116);
+        }
+        // 116: add 115, 10, 119
+        let value = self.add(self.read(self.memory[117], 116)?, self.read(self.memory[118], 116)?, 116)?;
+        let address = self.write_address(self.memory[119], 116)?;
+        if self.write(address, value) && (120..141).contains(&address) {
+            return Err(120);
+        }
+        // 120: add 5, 119, 123
+        let value = self.add(self.read(self.memory[121], 120)?, self.read(self.memory[122], 120)?, 120)?;
+        let address = self.write_address(self.memory[123], 120)?;
+        if self.write(address, value) && (124..141).contains(&address) {
+            return Err(124);
+        }
+        // 124: add 6, 123, 127
+        let value = self.add(self.read(self.memory[125], 124)?, self.read(self.memory[126], 124)?, 124)?;
+        let address = self.write_address(self.memory[127], 124)?;
+        if self.write(address, value) && (128..141).contains(&address) {
+            return Err(128);
+        }
+        // 128: add 10, 127, 131
+        let value = self.add(self.read(self.memory[129], 128)?, self.read(self.memory[130], 128)?, 128)?;
+        let address = self.write_address(self.memory[131], 128)?;
+        if self.write(address, value) && (132..141).contains(&address) {
+            return Err(132);
+        }
+        // 132: add 2, 131, 135
+        let value = self.add(self.read(self.memory[133], 132)?, self.read(self.memory[134], 132)?, 132)?;
+        let address = self.write_address(self.memory[135], 132)?;
+        if self.write(address, value) && (136..141).contains(&address) {
+            return Err(136);
+        }
+        // 136: add 135, 10, 0
+        let value = self.add(self.read(self.memory[137], 136)?, self.read(self.memory[138], 136)?, 136)?;
+        let address = self.write_address(self.memory[139], 136)?;
+        if self.write(address, value) && (140..141).contains(&address) {
+            return Err(140);
+        }
+        // 140: hlt
+        self.pc = 140;
+        self.halted = true;
+        return Ok(Some(RunState::Halted));
+    }
+}
@@ -0,0 +1,1658 @@
+//! Intcode program transpiled to Rust by the `transpile` command, from:
+//! ./input/day_09/input.txt
+//!
+//! Do not edit - transpile the program again instead.
+
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::utils::intcode::{IntcodeMachine, MachineFault, RunState};
+
+/// Memory image the program was transpiled from.
+const IMAGE: [i64; 973] = [
+    1102, 34463338, 34463338, 63, 1007, 63, 34463338, 63, 1005, 63, 53, 1102, 1, 3, 1000, 109,
+    988, 209, 12, 9, 1000, 209, 6, 209, 3, 203, 0, 1008, 1000, 1, 63, 1005,
+    63, 65, 1008, 1000, 2, 63, 1005, 63, 904, 1008, 1000, 0, 63, 1005, 63, 58,
+    4, 25, 104, 0, 99, 4, 0, 104, 0, 99, 4, 17, 104, 0, 99, 0,
+    0, 1101, 0, 0, 1020, 1101, 34, 0, 1004, 1101, 0, 26, 1008, 1102, 1, 37,
+    1011, 1101, 39, 0, 1018, 1102, 587, 1, 1022, 1101, 1, 0, 1021, 1102, 22, 1,
+    1012, 1101, 0, 33, 1014, 1101, 24, 0, 1016, 1101, 0, 752, 1029, 1101, 36, 0,
+    1002, 1101, 35, 0, 1006, 1101, 32, 0, 1009, 1102, 38, 1, 1003, 1102, 584, 1,
+    1023, 1101, 0, 20, 1001, 1102, 892, 1, 1025, 1102, 29, 1, 1000, 1101, 411, 0,
+    1026, 1102, 1, 901, 1024, 1101, 0, 761, 1028, 1101, 23, 0, 1017, 1102, 30, 1,
+    1013, 1101, 0, 27, 1015, 1102, 28, 1, 1005, 1101, 408, 0, 1027, 1101, 25, 0,
+    1007, 1102, 31, 1, 1019, 1101, 0, 21, 1010, 109, 5, 1207, -2, 39, 63, 1005,
+    63, 199, 4, 187, 1105, 1, 203, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    12, 21102, 40, 1, -1, 1008, 1016, 40, 63, 1005, 63, 229, 4, 209, 1001, 64,
+    1, 64, 1106, 0, 229, 1002, 64, 2, 64, 109, -5, 1207, -5, 24, 63, 1005,
+    63, 249, 1001, 64, 1, 64, 1106, 0, 251, 4, 235, 1002, 64, 2, 64, 109,
+    -14, 2102, 1, 6, 63, 1008, 63, 32, 63, 1005, 63, 271, 1106, 0, 277, 4,
+    257, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 2, 1202, 1, 1, 63, 1008,
+    63, 20, 63, 1005, 63, 303, 4, 283, 1001, 64, 1, 64, 1106, 0, 303, 1002,
+    64, 2, 64, 109, 7, 2108, 34, 2, 63, 1005, 63, 319, 1106, 0, 325, 4,
+    309, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 6, 2101, 0, -6, 63, 1008,
+    63, 24, 63, 1005, 63, 349, 1001, 64, 1, 64, 1105, 1, 351, 4, 331, 1002,
+    64, 2, 64, 109, 4, 21107, 41, 42, 0, 1005, 1017, 369, 4, 357, 1105, 1,
+    373, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 5, 21101, 42, 0, -5, 1008,
+    1017, 41, 63, 1005, 63, 397, 1001, 64, 1, 64, 1106, 0, 399, 4, 379, 1002,
+    64, 2, 64, 109, 9, 2106, 0, -4, 1106, 0, 417, 4, 405, 1001, 64, 1,
+    64, 1002, 64, 2, 64, 109, -20, 21108, 43, 43, 0, 1005, 1011, 435, 4, 423,
+    1105, 1, 439, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -15, 2102, 1, 8,
+    63, 1008, 63, 34, 63, 1005, 63, 465, 4, 445, 1001, 64, 1, 64, 1105, 1,
+    465, 1002, 64, 2, 64, 109, 3, 1201, 6, 0, 63, 1008, 63, 28, 63, 1005,
+    63, 491, 4, 471, 1001, 64, 1, 64, 1106, 0, 491, 1002, 64, 2, 64, 109,
+    18, 21108, 44, 46, 0, 1005, 1017, 511, 1001, 64, 1, 64, 1106, 0, 513, 4,
+    497, 1002, 64, 2, 64, 109, 12, 1205, -8, 527, 4, 519, 1105, 1, 531, 1001,
+    64, 1, 64, 1002, 64, 2, 64, 109, -17, 1208, -3, 32, 63, 1005, 63, 553,
+    4, 537, 1001, 64, 1, 64, 1105, 1, 553, 1002, 64, 2, 64, 109, -13, 1208,
+    10, 31, 63, 1005, 63, 573, 1001, 64, 1, 64, 1105, 1, 575, 4, 559, 1002,
+    64, 2, 64, 109, 17, 2105, 1, 7, 1105, 1, 593, 4, 581, 1001, 64, 1,
+    64, 1002, 64, 2, 64, 109, -8, 2107, 19, -7, 63, 1005, 63, 615, 4, 599,
+    1001, 64, 1, 64, 1105, 1, 615, 1002, 64, 2, 64, 109, 4, 1206, 8, 629,
+    4, 621, 1106, 0, 633, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -2, 2101,
+    0, -6, 63, 1008, 63, 34, 63, 1005, 63, 655, 4, 639, 1105, 1, 659, 1001,
+    64, 1, 64, 1002, 64, 2, 64, 109, 10, 1205, 0, 671, 1105, 1, 677, 4,
+    665, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -21, 2107, 26, 8, 63, 1005,
+    63, 693, 1106, 0, 699, 4, 683, 1001, 64, 1, 64, 1002, 64, 2, 64, 109,
+    19, 1201, -9, 0, 63, 1008, 63, 30, 63, 1005, 63, 719, 1105, 1, 725, 4,
+    705, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 9, 1206, -6, 741, 1001, 64,
+    1, 64, 1106, 0, 743, 4, 731, 1002, 64, 2, 64, 109, -5, 2106, 0, 6,
+    4, 749, 1001, 64, 1, 64, 1105, 1, 761, 1002, 64, 2, 64, 109, -14, 1202,
+    -1, 1, 63, 1008, 63, 27, 63, 1005, 63, 781, 1105, 1, 787, 4, 767, 1001,
+    64, 1, 64, 1002, 64, 2, 64, 109, 1, 21107, 45, 44, 5, 1005, 1014, 807,
+    1001, 64, 1, 64, 1105, 1, 809, 4, 793, 1002, 64, 2, 64, 109, 8, 21101,
+    46, 0, 0, 1008, 1017, 46, 63, 1005, 63, 835, 4, 815, 1001, 64, 1, 64,
+    1106, 0, 835, 1002, 64, 2, 64, 109, -26, 2108, 20, 10, 63, 1005, 63, 857,
+    4, 841, 1001, 64, 1, 64, 1106, 0, 857, 1002, 64, 2, 64, 109, 24, 21102,
+    47, 1, -5, 1008, 1010, 46, 63, 1005, 63, 881, 1001, 64, 1, 64, 1106, 0,
+    883, 4, 863, 1002, 64, 2, 64, 109, 6, 2105, 1, 3, 4, 889, 1001, 64,
+    1, 64, 1105, 1, 901, 4, 64, 99, 21102, 27, 1, 1, 21101, 915, 0, 0,
+    1105, 1, 922, 21201, 1, 29830, 1, 204, 1, 99, 109, 3, 1207, -2, 3, 63,
+    1005, 63, 964, 21201, -2, -1, 1, 21101, 0, 942, 0, 1105, 1, 922, 21202, 1,
+    1, -1, 21201, -2, -3, 1, 21102, 1, 957, 0, 1105, 1, 922, 22201, 1, -1,
+    -2, 1105, 1, 968, 21201, -2, 0, -2, 109, -3, 2106, 0, 0,
+];
+/// Addresses of the cells compiled into the code, which must hold the same values as the image.
+const FIXED_CELLS: [usize; 204] = [
+    0, 4, 8, 9, 10, 11, 15, 17, 19, 21, 23, 25, 27, 31, 32, 33,
+    34, 38, 39, 40, 41, 45, 46, 47, 48, 50, 52, 53, 55, 57, 58, 60,
+    62, 65, 69, 73, 77, 81, 85, 89, 93, 97, 101, 105, 109, 113, 117, 121,
+    125, 129, 133, 137, 141, 145, 149, 153, 157, 161, 165, 169, 173, 177, 181, 185,
+    187, 191, 192, 193, 194, 196, 197, 198, 199, 203, 207, 209, 213, 217, 218, 219,
+    220, 222, 226, 227, 228, 229, 233, 235, 239, 240, 241, 242, 246, 247, 248, 249,
+    251, 255, 257, 261, 265, 266, 267, 268, 269, 270, 271, 273, 277, 281, 283, 287,
+    291, 292, 293, 294, 296, 300, 301, 302, 303, 307, 309, 313, 314, 315, 316, 317,
+    318, 319, 321, 325, 329, 331, 335, 339, 340, 341, 342, 346, 347, 348, 349, 351,
+    355, 357, 361, 362, 363, 364, 366, 367, 368, 369, 373, 377, 379, 383, 387, 388,
+    389, 390, 394, 395, 396, 397, 399, 403, 405, 406, 407, 904, 908, 912, 913, 914,
+    915, 919, 921, 922, 924, 928, 929, 930, 931, 935, 939, 940, 941, 942, 946, 950,
+    954, 955, 956, 957, 961, 962, 963, 964, 968, 970, 971, 972,
+];
+/// Index (plus one) of the block compiled from each cell of the image, or 0 for other cells.
+const CELL_BLOCKS: [u32; 973] = [
+    1, 0, 0, 0, 1, 0, 0, 0, 1, 1, 1, 2, 0, 0, 0, 2,
+    0, 2, 0, 2, 0, 2, 0, 2, 0, 3, 0, 3, 0, 0, 0, 3,
+    3, 3, 4, 0, 0, 0, 4, 4, 4, 5, 0, 0, 0, 5, 5, 5,
+    6, 0, 7, 0, 8, 9, 0, 10, 0, 11, 12, 0, 13, 0, 14, 0,
+    0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0,
+    0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0,
+    0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0,
+    0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0,
+    0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0,
+    0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0,
+    0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 0,
+    0, 15, 0, 0, 0, 15, 0, 0, 0, 15, 0, 15, 0, 0, 0, 15,
+    15, 15, 16, 0, 17, 17, 17, 18, 0, 0, 0, 19, 0, 0, 0, 19,
+    0, 19, 0, 0, 0, 19, 0, 0, 0, 19, 19, 19, 20, 0, 21, 0,
+    0, 0, 21, 21, 21, 22, 0, 0, 0, 22, 0, 22, 0, 0, 0, 22,
+    22, 22, 23, 0, 0, 0, 23, 23, 23, 24, 0, 25, 0, 0, 0, 25,
+    0, 25, 0, 0, 0, 25, 0, 0, 0, 25, 25, 25, 26, 26, 26, 27,
+    0, 28, 0, 0, 0, 29, 0, 0, 0, 29, 0, 29, 0, 0, 0, 29,
+    0, 0, 0, 29, 29, 29, 30, 0, 31, 0, 0, 0, 31, 31, 31, 32,
+    0, 0, 0, 32, 0, 32, 0, 0, 0, 32, 32, 32, 33, 33, 33, 34,
+    0, 35, 0, 0, 0, 36, 0, 0, 0, 36, 0, 36, 0, 0, 0, 36,
+    0, 0, 0, 36, 36, 36, 37, 0, 0, 0, 37, 37, 37, 38, 0, 39,
+    0, 0, 0, 39, 0, 39, 0, 0, 0, 39, 39, 39, 40, 0, 41, 41,
+    41, 42, 0, 0, 0, 43, 0, 0, 0, 43, 0, 43, 0, 0, 0, 43,
+    0, 0, 0, 43, 43, 43, 44, 0, 0, 0, 44, 44, 44, 45, 0, 46,
+    0, 0, 0, 46, 0, 46, 46, 46, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 47, 0, 0, 0, 47, 0, 0, 0,
+    47, 47, 47, 48, 0, 0, 0, 48, 0, 49, 50, 0, 50, 0, 0, 0,
+    50, 50, 50, 51, 0, 0, 0, 51, 0, 0, 0, 51, 51, 51, 52, 0,
+    0, 0, 52, 0, 0, 0, 52, 0, 0, 0, 52, 52, 52, 53, 0, 0,
+    0, 53, 53, 53, 54, 0, 0, 0, 55, 0, 55, 55, 55,
+];
+/// Start and end addresses of each compiled block.
+const BLOCKS: [(usize, usize); 55] = [
+    (0, 11),
+    (11, 25),
+    (25, 34),
+    (34, 41),
+    (41, 48),
+    (48, 50),
+    (50, 52),
+    (52, 53),
+    (53, 55),
+    (55, 57),
+    (57, 58),
+    (58, 60),
+    (60, 62),
+    (62, 63),
+    (65, 194),
+    (194, 196),
+    (196, 199),
+    (199, 203),
+    (203, 220),
+    (220, 222),
+    (222, 229),
+    (229, 242),
+    (242, 249),
+    (249, 251),
+    (251, 268),
+    (268, 271),
+    (271, 273),
+    (273, 277),
+    (277, 294),
+    (294, 296),
+    (296, 303),
+    (303, 316),
+    (316, 319),
+    (319, 321),
+    (321, 325),
+    (325, 342),
+    (342, 349),
+    (349, 351),
+    (351, 364),
+    (364, 366),
+    (366, 369),
+    (369, 373),
+    (373, 390),
+    (390, 397),
+    (397, 399),
+    (399, 408),
+    (904, 915),
+    (915, 921),
+    (921, 922),
+    (922, 931),
+    (931, 942),
+    (942, 957),
+    (957, 964),
+    (964, 968),
+    (968, 973),
+];
+/// Highest address the program can access.
+const MAX_ADDRESS: i64 = 4294967295;
+/// Highest address compiled code writes to.
+const MAX_COMPILED_ADDRESS: usize = 1048576;
+
+/// Intcode program compiled to Rust, which falls back to the interpreter once it can't run the
+/// compiled code.
+pub struct TranspiledProgram {
+    memory: Vec<i64>,
+    pc: usize,
+    relative_base: i64,
+    input: VecDeque<i64>,
+    halted: bool,
+    awaiting_input: bool,
+    dirty: Vec<bool>,
+    machine: Option<IntcodeMachine>,
+    fallbacks: u64,
+}
+
+impl TranspiledProgram {
+    /// Creates the program with the memory image it was transpiled from.
+    pub fn new() -> Self {
+        return Self::with_memory(IMAGE.to_vec()).unwrap();
+    }
+
+    /// Creates the program with the given memory, e.g. the image with some values patched.
+    /// Returns None if a cell compiled into the code doesn't hold the value it has in the image.
+    pub fn with_memory(mut memory: Vec<i64>) -> Option<Self> {
+        for &address in FIXED_CELLS.iter() {
+            if *memory.get(address).unwrap_or(&0) != IMAGE[address] {
+                return None;
+            }
+        }
+        if memory.len() < IMAGE.len() {
+            memory.resize(IMAGE.len(), 0);
+        }
+        return Some(Self {
+            memory,
+            pc: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            halted: false,
+            awaiting_input: false,
+            dirty: vec![false; BLOCKS.len()],
+            machine: None,
+            fallbacks: 0,
+        });
+    }
+
+    /// Adds the value to the input queue of the program.
+    pub fn add_input(&mut self, value: i64) {
+        match &mut self.machine {
+            Some(machine) => machine.add_input(value),
+            None => {
+                self.input.push_back(value);
+                self.awaiting_input = false;
+            }
+        }
+    }
+
+    /// Runs the program until it halts, needs input or outputs a value, and returns the reason it
+    /// stopped - the same as IntcodeMachine::run().
+    pub fn run(&mut self) -> Result<RunState, MachineFault> {
+        loop {
+            if let Some(machine) = &mut self.machine {
+                if let Some(state) = machine.step()? {
+                    return Ok(state);
+                }
+                self.try_resume_compiled();
+                continue;
+            }
+            if self.halted {
+                return Ok(RunState::Halted);
+            }
+            match self.run_block() {
+                Ok(Some(state)) => return Ok(state),
+                Ok(None) => (),
+                Err(pc) => self.fall_back(pc),
+            }
+        }
+    }
+
+    /// Checks if the program has halted.
+    pub fn has_halted(&self) -> bool {
+        match &self.machine {
+            Some(machine) => return machine.has_halted(),
+            None => return self.halted,
+        }
+    }
+
+    /// Checks if the program is waiting for input.
+    pub fn is_awaiting_input(&self) -> bool {
+        match &self.machine {
+            Some(machine) => return machine.is_awaiting_input(),
+            None => return self.awaiting_input,
+        }
+    }
+
+    /// Checks if the program is currently being run by the interpreter.
+    pub fn is_interpreted(&self) -> bool {
+        return self.machine.is_some();
+    }
+
+    /// Returns the number of times the program has fallen back to the interpreter.
+    pub fn get_fallback_count(&self) -> u64 {
+        return self.fallbacks;
+    }
+
+    /// Returns the value held at the given address of memory.
+    pub fn peek_memory(&self, address: usize) -> i64 {
+        match &self.machine {
+            Some(machine) => return machine.peek_memory(address),
+            None => return *self.memory.get(address).unwrap_or(&0),
+        }
+    }
+
+    /// Returns the current value of the program counter.
+    pub fn get_program_counter(&self) -> usize {
+        match &self.machine {
+            Some(machine) => return machine.get_program_counter(),
+            None => return self.pc,
+        }
+    }
+
+    /// Returns the current value of the relative base.
+    pub fn get_relative_base(&self) -> i64 {
+        match &self.machine {
+            Some(machine) => return machine.get_relative_base(),
+            None => return self.relative_base,
+        }
+    }
+
+    /// Converts the program into an IntcodeMachine in the same state.
+    pub fn into_machine(mut self) -> IntcodeMachine {
+        if let Some(machine) = self.machine.take() {
+            return machine;
+        }
+        return IntcodeMachine::from_transpiled_state(
+            self.take_memory(),
+            self.pc,
+            self.relative_base,
+            mem::take(&mut self.input),
+            self.halted,
+            self.awaiting_input,
+        );
+    }
+
+    /// Hands the program over to the interpreter, which continues running it from the given
+    /// address.
+    fn fall_back(&mut self, pc: usize) {
+        self.fallbacks += 1;
+        self.machine = Some(IntcodeMachine::from_transpiled_state(
+            self.take_memory(),
+            pc,
+            self.relative_base,
+            mem::take(&mut self.input),
+            false,
+            false,
+        ));
+    }
+
+    /// Switches back from the interpreter to compiled code if the interpreter has reached the
+    /// start of a compiled block that hasn't been changed since the program was transpiled.
+    fn try_resume_compiled(&mut self) {
+        let machine = self.machine.as_ref().unwrap();
+        let pc = machine.get_program_counter();
+        let (start, end) = match BLOCKS.binary_search_by_key(&pc, |(start, _)| *start) {
+            Ok(index) => BLOCKS[index],
+            Err(_) => return,
+        };
+        let changed = (start..end).any(|a| CELL_BLOCKS[a] != 0 && machine.peek_memory(a) != IMAGE[a]);
+        if changed || machine.get_memory_len() > MAX_COMPILED_ADDRESS + 1 {
+            return;
+        }
+        let machine = self.machine.take().unwrap();
+        self.memory = machine.get_memory_dump();
+        self.pc = pc;
+        self.relative_base = machine.get_relative_base();
+        self.input = machine.get_input_vec();
+        for dirty in self.dirty.iter_mut() {
+            *dirty = false;
+        }
+        for &address in FIXED_CELLS.iter() {
+            if self.memory[address] != IMAGE[address] {
+                self.dirty[CELL_BLOCKS[address] as usize - 1] = true;
+            }
+        }
+    }
+
+    /// Takes the memory of the program, without any zeroes past the end of the image.
+    fn take_memory(&mut self) -> Vec<i64> {
+        let mut memory = mem::take(&mut self.memory);
+        while memory.len() > IMAGE.len() && memory.last() == Some(&0) {
+            memory.pop();
+        }
+        return memory;
+    }
+
+    // Helpers used by the blocks return the address of the instruction being run as the error,
+    // so the interpreter can run the instruction instead (and fault if it is invalid)
+
+    /// Returns the value held at the address.
+    fn read(&self, address: i64, pc: usize) -> Result<i64, usize> {
+        if address < 0 || address > MAX_ADDRESS {
+            return Err(pc);
+        }
+        return Ok(*self.memory.get(address as usize).unwrap_or(&0));
+    }
+
+    /// Returns the address relative to the relative base.
+    fn relative(&self, offset: i64, pc: usize) -> Result<i64, usize> {
+        return self.relative_base.checked_add(offset).ok_or(pc);
+    }
+
+    /// Adds the values.
+    fn add(&self, a: i64, b: i64, pc: usize) -> Result<i64, usize> {
+        return a.checked_add(b).ok_or(pc);
+    }
+
+    /// Multiplies the values.
+    fn multiply(&self, a: i64, b: i64, pc: usize) -> Result<i64, usize> {
+        return a.checked_mul(b).ok_or(pc);
+    }
+
+    /// Checks the address can be written to by compiled code.
+    fn write_address(&self, address: i64, pc: usize) -> Result<usize, usize> {
+        if address < 0 || address as u64 > MAX_COMPILED_ADDRESS as u64 {
+            return Err(pc);
+        }
+        return Ok(address as usize);
+    }
+
+    /// Checks the address can be jumped to.
+    fn jump_address(&self, address: i64, pc: usize) -> Result<usize, usize> {
+        if address < 0 || address > MAX_ADDRESS {
+            return Err(pc);
+        }
+        return Ok(address as usize);
+    }
+
+    /// Writes the value to the address. Returns true if it changed a cell compiled into the code,
+    /// in which case the block compiled from the cell can no longer be run.
+    fn write(&mut self, address: usize, value: i64) -> bool {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        let old_value = mem::replace(&mut self.memory[address], value);
+        if address >= IMAGE.len() || old_value == value || CELL_BLOCKS[address] == 0 {
+            return false;
+        }
+        self.dirty[CELL_BLOCKS[address] as usize - 1] = true;
+        return true;
+    }
+
+    /// Runs the compiled block starting at the program counter, if there is one.
+    fn run_block(&mut self) -> Result<Option<RunState>, usize> {
+        match self.pc {
+            0 => return self.block_0(),
+            11 => return self.block_11(),
+            25 => return self.block_25(),
+            34 => return self.block_34(),
+            41 => return self.block_41(),
+            48 => return self.block_48(),
+            50 => return self.block_50(),
+            52 => return self.block_52(),
+            53 => return self.block_53(),
+            55 => return self.block_55(),
+            57 => return self.block_57(),
+            58 => return self.block_58(),
+            60 => return self.block_60(),
+            62 => return self.block_62(),
+            65 => return self.block_65(),
+            194 => return self.block_194(),
+            196 => return self.block_196(),
+            199 => return self.block_199(),
+            203 => return self.block_203(),
+            220 => return self.block_220(),
+            222 => return self.block_222(),
+            229 => return self.block_229(),
+            242 => return self.block_242(),
+            249 => return self.block_249(),
+            251 => return self.block_251(),
+            268 => return self.block_268(),
+            271 => return self.block_271(),
+            273 => return self.block_273(),
+            277 => return self.block_277(),
+            294 => return self.block_294(),
+            296 => return self.block_296(),
+            303 => return self.block_303(),
+            316 => return self.block_316(),
+            319 => return self.block_319(),
+            321 => return self.block_321(),
+            325 => return self.block_325(),
+            342 => return self.block_342(),
+            349 => return self.block_349(),
+            351 => return self.block_351(),
+            364 => return self.block_364(),
+            366 => return self.block_366(),
+            369 => return self.block_369(),
+            373 => return self.block_373(),
+            390 => return self.block_390(),
+            397 => return self.block_397(),
+            399 => return self.block_399(),
+            904 => return self.block_904(),
+            915 => return self.block_915(),
+            921 => return self.block_921(),
+            922 => return self.block_922(),
+            931 => return self.block_931(),
+            942 => return self.block_942(),
+            957 => return self.block_957(),
+            964 => return self.block_964(),
+            968 => return self.block_968(),
+            pc => return Err(pc),
+        }
+    }
+
+    /// Runs the block at address 0.
+    fn block_0(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[0] {
+            return Err(0);
+        }
+        // 0: mul #34463338, #34463338, 63
+        let value = self.multiply(self.memory[1], self.memory[2], 0)?;
+        let address = self.write_address(self.memory[3], 0)?;
+        if self.write(address, value) && (4..11).contains(&address) {
+            return Err(4);
+        }
+        // 4: lt 63, #34463338, 63
+        let value = (self.read(self.memory[5], 4)? < self.memory[6]) as i64;
+        let address = self.write_address(self.memory[7], 4)?;
+        if self.write(address, value) && (8..11).contains(&address) {
+            return Err(8);
+        }
+        // 8: jt 63, #53
+        if self.read(63, 8)? != 0 {
+            self.pc = 53;
+            return Ok(None);
+        }
+        self.pc = 11;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 11.
+    fn block_11(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[1] {
+            return Err(11);
+        }
+        // 11: mul #1, #3, 1000
+        let value = self.multiply(self.memory[12], self.memory[13], 11)?;
+        let address = self.write_address(self.memory[14], 11)?;
+        if self.write(address, value) && (15..25).contains(&address) {
+            return Err(15);
+        }
+        // 15: arb #988
+        self.relative_base = self.relative(self.memory[16], 15)?;
+        // 17: arb rb+12
+        self.relative_base = self.relative(self.read(self.relative(self.memory[18], 17)?, 17)?, 17)?;
+        // 19: arb 1000
+        self.relative_base = self.relative(self.read(self.memory[20], 19)?, 19)?;
+        // 21: arb rb+6
+        self.relative_base = self.relative(self.read(self.relative(self.memory[22], 21)?, 21)?, 21)?;
+        // 23: arb rb+3
+        self.relative_base = self.relative(self.read(self.relative(self.memory[24], 23)?, 23)?, 23)?;
+        self.pc = 25;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 25.
+    fn block_25(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[2] {
+            return Err(25);
+        }
+        // 25: in rb+0
+        let address = self.write_address(self.relative(self.memory[26], 25)?, 25)?;
+        let value = match self.input.pop_front() {
+            Some(value) => value,
+            None => {
+                self.awaiting_input = true;
+                return Ok(Some(RunState::NeedsInput));
+            }
+        };
+        if self.write(address, value) && (27..34).contains(&address) {
+            return Err(27);
+        }
+        // 27: eq 1000, #1, 63
+        let value = (self.read(self.memory[28], 27)? == self.memory[29]) as i64;
+        let address = self.write_address(self.memory[30], 27)?;
+        if self.write(address, value) && (31..34).contains(&address) {
+            return Err(31);
+        }
+        // 31: jt 63, #65
+        if self.read(63, 31)? != 0 {
+            self.pc = 65;
+            return Ok(None);
+        }
+        self.pc = 34;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 34.
+    fn block_34(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[3] {
+            return Err(34);
+        }
+        // 34: eq 1000, #2, 63
+        let value = (self.read(self.memory[35], 34)? == self.memory[36]) as i64;
+        let address = self.write_address(self.memory[37], 34)?;
+        if self.write(address, value) && (38..41).contains(&address) {
+            return Err(38);
+        }
+        // 38: jt 63, #904
+        if self.read(63, 38)? != 0 {
+            self.pc = 904;
+            return Ok(None);
+        }
+        self.pc = 41;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 41.
+    fn block_41(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[4] {
+            return Err(41);
+        }
+        // 41: eq 1000, #0, 63
+        let value = (self.read(self.memory[42], 41)? == self.memory[43]) as i64;
+        let address = self.write_address(self.memory[44], 41)?;
+        if self.write(address, value) && (45..48).contains(&address) {
+            return Err(45);
+        }
+        // 45: jt 63, #58
+        if self.read(63, 45)? != 0 {
+            self.pc = 58;
+            return Ok(None);
+        }
+        self.pc = 48;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 48.
+    fn block_48(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[5] {
+            return Err(48);
+        }
+        // 48: out 25
+        let value = self.read(self.memory[49], 48)?;
+        self.pc = 50;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 50.
+    fn block_50(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[6] {
+            return Err(50);
+        }
+        // 50: out #0
+        let value = self.memory[51];
+        self.pc = 52;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 52.
+    fn block_52(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[7] {
+            return Err(52);
+        }
+        // 52: hlt
+        self.pc = 52;
+        self.halted = true;
+        return Ok(Some(RunState::Halted));
+    }
+
+    /// Runs the block at address 53.
+    fn block_53(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[8] {
+            return Err(53);
+        }
+        // 53: out 0
+        let value = self.read(self.memory[54], 53)?;
+        self.pc = 55;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 55.
+    fn block_55(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[9] {
+            return Err(55);
+        }
+        // 55: out #0
+        let value = self.memory[56];
+        self.pc = 57;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 57.
+    fn block_57(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[10] {
+            return Err(57);
+        }
+        // 57: hlt
+        self.pc = 57;
+        self.halted = true;
+        return Ok(Some(RunState::Halted));
+    }
+
+    /// Runs the block at address 58.
+    fn block_58(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[11] {
+            return Err(58);
+        }
+        // 58: out 17
+        let value = self.read(self.memory[59], 58)?;
+        self.pc = 60;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 60.
+    fn block_60(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[12] {
+            return Err(60);
+        }
+        // 60: out #0
+        let value = self.memory[61];
+        self.pc = 62;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 62.
+    fn block_62(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[13] {
+            return Err(62);
+        }
+        // 62: hlt
+        self.pc = 62;
+        self.halted = true;
+        return Ok(Some(RunState::Halted));
+    }
+
+    /// Runs the block at address 65.
+    fn block_65(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[14] {
+            return Err(65);
+        }
+        // 65: add #0, #0, 1020
+        let value = self.add(self.memory[66], self.memory[67], 65)?;
+        let address = self.write_address(self.memory[68], 65)?;
+        if self.write(address, value) && (69..194).contains(&address) {
+            return Err(69);
+        }
+        // 69: add #34, #0, 1004
+        let value = self.add(self.memory[70], self.memory[71], 69)?;
+        let address = self.write_address(self.memory[72], 69)?;
+        if self.write(address, value) && (73..194).contains(&address) {
+            return Err(73);
+        }
+        // 73: add #0, #26, 1008
+        let value = self.add(self.memory[74], self.memory[75], 73)?;
+        let address = self.write_address(self.memory[76], 73)?;
+        if self.write(address, value) && (77..194).contains(&address) {
+            return Err(77);
+        }
+        // 77: mul #1, #37, 1011
+        let value = self.multiply(self.memory[78], self.memory[79], 77)?;
+        let address = self.write_address(self.memory[80], 77)?;
+        if self.write(address, value) && (81..194).contains(&address) {
+            return Err(81);
+        }
+        // 81: add #39, #0, 1018
+        let value = self.add(self.memory[82], self.memory[83], 81)?;
+        let address = self.write_address(self.memory[84], 81)?;
+        if self.write(address, value) && (85..194).contains(&address) {
+            return Err(85);
+        }
+        // 85: mul #587, #1, 1022
+        let value = self.multiply(self.memory[86], self.memory[87], 85)?;
+        let address = self.write_address(self.memory[88], 85)?;
+        if self.write(address, value) && (89..194).contains(&address) {
+            return Err(89);
+        }
+        // 89: add #1, #0, 1021
+        let value = self.add(self.memory[90], self.memory[91], 89)?;
+        let address = self.write_address(self.memory[92], 89)?;
+        if self.write(address, value) && (93..194).contains(&address) {
+            return Err(93);
+        }
+        // 93: mul #22, #1, 1012
+        let value = self.multiply(self.memory[94], self.memory[95], 93)?;
+        let address = self.write_address(self.memory[96], 93)?;
+        if self.write(address, value) && (97..194).contains(&address) {
+            return Err(97);
+        }
+        // 97: add #0, #33, 1014
+        let value = self.add(self.memory[98], self.memory[99], 97)?;
+        let address = self.write_address(self.memory[100], 97)?;
+        if self.write(address, value) && (101..194).contains(&address) {
+            return Err(101);
+        }
+        // 101: add #24, #0, 1016
+        let value = self.add(self.memory[102], self.memory[103], 101)?;
+        let address = self.write_address(self.memory[104], 101)?;
+        if self.write(address, value) && (105..194).contains(&address) {
+            return Err(105);
+        }
+        // 105: add #0, #752, 1029
+        let value = self.add(self.memory[106], self.memory[107], 105)?;
+        let address = self.write_address(self.memory[108], 105)?;
+        if self.write(address, value) && (109..194).contains(&address) {
+            return Err(109);
+        }
+        // 109: add #36, #0, 1002
+        let value = self.add(self.memory[110], self.memory[111], 109)?;
+        let address = self.write_address(self.memory[112], 109)?;
+        if self.write(address, value) && (113..194).contains(&address) {
+            return Err(113);
+        }
+        // 113: add #35, #0, 1006
+        let value = self.add(self.memory[114], self.memory[115], 113)?;
+        let address = self.write_address(self.memory[116], 113)?;
+        if self.write(address, value) && (117..194).contains(&address) {
+            return Err(117);
+        }
+        // 117: add #32, #0, 1009
+        let value = self.add(self.memory[118], self.memory[119], 117)?;
+        let address = self.write_address(self.memory[120], 117)?;
+        if self.write(address, value) && (121..194).contains(&address) {
+            return Err(121);
+        }
+        // 121: mul #38, #1, 1003
+        let value = self.multiply(self.memory[122], self.memory[123], 121)?;
+        let address = self.write_address(self.memory[124], 121)?;
+        if self.write(address, value) && (125..194).contains(&address) {
+            return Err(125);
+        }
+        // 125: mul #584, #1, 1023
+        let value = self.multiply(self.memory[126], self.memory[127], 125)?;
+        let address = self.write_address(self.memory[128], 125)?;
+        if self.write(address, value) && (129..194).contains(&address) {
+            return Err(129);
+        }
+        // 129: add #0, #20, 1001
+        let value = self.add(self.memory[130], self.memory[131], 129)?;
+        let address = self.write_address(self.memory[132], 129)?;
+        if self.write(address, value) && (133..194).contains(&address) {
+            return Err(133);
+        }
+        // 133: mul #892, #1, 1025
+        let value = self.multiply(self.memory[134], self.memory[135], 133)?;
+        let address = self.write_address(self.memory[136], 133)?;
+        if self.write(address, value) && (137..194).contains(&address) {
+            return Err(137);
+        }
+        // 137: mul #29, #1, 1000
+        let value = self.multiply(self.memory[138], self.memory[139], 137)?;
+        let address = self.write_address(self.memory[140], 137)?;
+        if self.write(address, value) && (141..194).contains(&address) {
+            return Err(141);
+        }
+        // 141: add #411, #0, 1026
+        let value = self.add(self.memory[142], self.memory[143], 141)?;
+        let address = self.write_address(self.memory[144], 141)?;
+        if self.write(address, value) && (145..194).contains(&address) {
+            return Err(145);
+        }
+        // 145: mul #1, #901, 1024
+        let value = self.multiply(self.memory[146], self.memory[147], 145)?;
+        let address = self.write_address(self.memory[148], 145)?;
+        if self.write(address, value) && (149..194).contains(&address) {
+            return Err(149);
+        }
+        // 149: add #0, #761, 1028
+        let value = self.add(self.memory[150], self.memory[151], 149)?;
+        let address = self.write_address(self.memory[152], 149)?;
+        if self.write(address, value) && (153..194).contains(&address) {
+            return Err(153);
+        }
+        // 153: add #23, #0, 1017
+        let value = self.add(self.memory[154], self.memory[155], 153)?;
+        let address = self.write_address(self.memory[156], 153)?;
+        if self.write(address, value) && (157..194).contains(&address) {
+            return Err(157);
+        }
+        // 157: mul #30, #1, 1013
+        let value = self.multiply(self.memory[158], self.memory[159], 157)?;
+        let address = self.write_address(self.memory[160], 157)?;
+        if self.write(address, value) && (161..194).contains(&address) {
+            return Err(161);
+        }
+        // 161: add #0, #27, 1015
+        let value = self.add(self.memory[162], self.memory[163], 161)?;
+        let address = self.write_address(self.memory[164], 161)?;
+        if self.write(address, value) && (165..194).contains(&address) {
+            return Err(165);
+        }
+        // 165: mul #28, #1, 1005
+        let value = self.multiply(self.memory[166], self.memory[167], 165)?;
+        let address = self.write_address(self.memory[168], 165)?;
+        if self.write(address, value) && (169..194).contains(&address) {
+            return Err(169);
+        }
+        // 169: add #408, #0, 1027
+        let value = self.add(self.memory[170], self.memory[171], 169)?;
+        let address = self.write_address(self.memory[172], 169)?;
+        if self.write(address, value) && (173..194).contains(&address) {
+            return Err(173);
+        }
+        // 173: add #25, #0, 1007
+        let value = self.add(self.memory[174], self.memory[175], 173)?;
+        let address = self.write_address(self.memory[176], 173)?;
+        if self.write(address, value) && (177..194).contains(&address) {
+            return Err(177);
+        }
+        // 177: mul #31, #1, 1019
+        let value = self.multiply(self.memory[178], self.memory[179], 177)?;
+        let address = self.write_address(self.memory[180], 177)?;
+        if self.write(address, value) && (181..194).contains(&address) {
+            return Err(181);
+        }
+        // 181: add #0, #21, 1010
+        let value = self.add(self.memory[182], self.memory[183], 181)?;
+        let address = self.write_address(self.memory[184], 181)?;
+        if self.write(address, value) && (185..194).contains(&address) {
+            return Err(185);
+        }
+        // 185: arb #5
+        self.relative_base = self.relative(self.memory[186], 185)?;
+        // 187: lt rb-2, #39, 63
+        let value = (self.read(self.relative(self.memory[188], 187)?, 187)? < self.memory[189]) as i64;
+        let address = self.write_address(self.memory[190], 187)?;
+        if self.write(address, value) && (191..194).contains(&address) {
+            return Err(191);
+        }
+        // 191: jt 63, #199
+        if self.read(63, 191)? != 0 {
+            self.pc = 199;
+            return Ok(None);
+        }
+        self.pc = 194;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 194.
+    fn block_194(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[15] {
+            return Err(194);
+        }
+        // 194: out 187
+        let value = self.read(self.memory[195], 194)?;
+        self.pc = 196;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 196.
+    fn block_196(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[16] {
+            return Err(196);
+        }
+        // 196: jt #1, #203
+        self.pc = 203;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 199.
+    fn block_199(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[17] {
+            return Err(199);
+        }
+        // 199: add 64, #1, 64
+        let value = self.add(self.read(self.memory[200], 199)?, self.memory[201], 199)?;
+        let address = self.write_address(self.memory[202], 199)?;
+        self.write(address, value);
+        self.pc = 203;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 203.
+    fn block_203(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[18] {
+            return Err(203);
+        }
+        // 203: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[204], 203)?, self.memory[205], 203)?;
+        let address = self.write_address(self.memory[206], 203)?;
+        if self.write(address, value) && (207..220).contains(&address) {
+            return Err(207);
+        }
+        // 207: arb #12
+        self.relative_base = self.relative(self.memory[208], 207)?;
+        // 209: mul #40, #1, rb-1
+        let value = self.multiply(self.memory[210], self.memory[211], 209)?;
+        let address = self.write_address(self.relative(self.memory[212], 209)?, 209)?;
+        if self.write(address, value) && (213..220).contains(&address) {
+            return Err(213);
+        }
+        // 213: eq 1016, #40, 63
+        let value = (self.read(self.memory[214], 213)? == self.memory[215]) as i64;
+        let address = self.write_address(self.memory[216], 213)?;
+        if self.write(address, value) && (217..220).contains(&address) {
+            return Err(217);
+        }
+        // 217: jt 63, #229
+        if self.read(63, 217)? != 0 {
+            self.pc = 229;
+            return Ok(None);
+        }
+        self.pc = 220;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 220.
+    fn block_220(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[19] {
+            return Err(220);
+        }
+        // 220: out 209
+        let value = self.read(self.memory[221], 220)?;
+        self.pc = 222;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 222.
+    fn block_222(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[20] {
+            return Err(222);
+        }
+        // 222: add 64, #1, 64
+        let value = self.add(self.read(self.memory[223], 222)?, self.memory[224], 222)?;
+        let address = self.write_address(self.memory[225], 222)?;
+        if self.write(address, value) && (226..229).contains(&address) {
+            return Err(226);
+        }
+        // 226: jf #0, #229
+        self.pc = 229;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 229.
+    fn block_229(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[21] {
+            return Err(229);
+        }
+        // 229: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[230], 229)?, self.memory[231], 229)?;
+        let address = self.write_address(self.memory[232], 229)?;
+        if self.write(address, value) && (233..242).contains(&address) {
+            return Err(233);
+        }
+        // 233: arb #-5
+        self.relative_base = self.relative(self.memory[234], 233)?;
+        // 235: lt rb-5, #24, 63
+        let value = (self.read(self.relative(self.memory[236], 235)?, 235)? < self.memory[237]) as i64;
+        let address = self.write_address(self.memory[238], 235)?;
+        if self.write(address, value) && (239..242).contains(&address) {
+            return Err(239);
+        }
+        // 239: jt 63, #249
+        if self.read(63, 239)? != 0 {
+            self.pc = 249;
+            return Ok(None);
+        }
+        self.pc = 242;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 242.
+    fn block_242(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[22] {
+            return Err(242);
+        }
+        // 242: add 64, #1, 64
+        let value = self.add(self.read(self.memory[243], 242)?, self.memory[244], 242)?;
+        let address = self.write_address(self.memory[245], 242)?;
+        if self.write(address, value) && (246..249).contains(&address) {
+            return Err(246);
+        }
+        // 246: jf #0, #251
+        self.pc = 251;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 249.
+    fn block_249(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[23] {
+            return Err(249);
+        }
+        // 249: out 235
+        let value = self.read(self.memory[250], 249)?;
+        self.pc = 251;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 251.
+    fn block_251(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[24] {
+            return Err(251);
+        }
+        // 251: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[252], 251)?, self.memory[253], 251)?;
+        let address = self.write_address(self.memory[254], 251)?;
+        if self.write(address, value) && (255..268).contains(&address) {
+            return Err(255);
+        }
+        // 255: arb #-14
+        self.relative_base = self.relative(self.memory[256], 255)?;
+        // 257: mul #1, rb+6, 63
+        let value = self.multiply(self.memory[258], self.read(self.relative(self.memory[259], 257)?, 257)?, 257)?;
+        let address = self.write_address(self.memory[260], 257)?;
+        if self.write(address, value) && (261..268).contains(&address) {
+            return Err(261);
+        }
+        // 261: eq 63, #32, 63
+        let value = (self.read(self.memory[262], 261)? == self.memory[263]) as i64;
+        let address = self.write_address(self.memory[264], 261)?;
+        if self.write(address, value) && (265..268).contains(&address) {
+            return Err(265);
+        }
+        // 265: jt 63, #271
+        if self.read(63, 265)? != 0 {
+            self.pc = 271;
+            return Ok(None);
+        }
+        self.pc = 268;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 268.
+    fn block_268(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[25] {
+            return Err(268);
+        }
+        // 268: jf #0, #277
+        self.pc = 277;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 271.
+    fn block_271(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[26] {
+            return Err(271);
+        }
+        // 271: out 257
+        let value = self.read(self.memory[272], 271)?;
+        self.pc = 273;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 273.
+    fn block_273(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[27] {
+            return Err(273);
+        }
+        // 273: add 64, #1, 64
+        let value = self.add(self.read(self.memory[274], 273)?, self.memory[275], 273)?;
+        let address = self.write_address(self.memory[276], 273)?;
+        self.write(address, value);
+        self.pc = 277;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 277.
+    fn block_277(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[28] {
+            return Err(277);
+        }
+        // 277: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[278], 277)?, self.memory[279], 277)?;
+        let address = self.write_address(self.memory[280], 277)?;
+        if self.write(address, value) && (281..294).contains(&address) {
+            return Err(281);
+        }
+        // 281: arb #2
+        self.relative_base = self.relative(self.memory[282], 281)?;
+        // 283: mul rb+1, #1, 63
+        let value = self.multiply(self.read(self.relative(self.memory[284], 283)?, 283)?, self.memory[285], 283)?;
+        let address = self.write_address(self.memory[286], 283)?;
+        if self.write(address, value) && (287..294).contains(&address) {
+            return Err(287);
+        }
+        // 287: eq 63, #20, 63
+        let value = (self.read(self.memory[288], 287)? == self.memory[289]) as i64;
+        let address = self.write_address(self.memory[290], 287)?;
+        if self.write(address, value) && (291..294).contains(&address) {
+            return Err(291);
+        }
+        // 291: jt 63, #303
+        if self.read(63, 291)? != 0 {
+            self.pc = 303;
+            return Ok(None);
+        }
+        self.pc = 294;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 294.
+    fn block_294(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[29] {
+            return Err(294);
+        }
+        // 294: out 283
+        let value = self.read(self.memory[295], 294)?;
+        self.pc = 296;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 296.
+    fn block_296(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[30] {
+            return Err(296);
+        }
+        // 296: add 64, #1, 64
+        let value = self.add(self.read(self.memory[297], 296)?, self.memory[298], 296)?;
+        let address = self.write_address(self.memory[299], 296)?;
+        if self.write(address, value) && (300..303).contains(&address) {
+            return Err(300);
+        }
+        // 300: jf #0, #303
+        self.pc = 303;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 303.
+    fn block_303(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[31] {
+            return Err(303);
+        }
+        // 303: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[304], 303)?, self.memory[305], 303)?;
+        let address = self.write_address(self.memory[306], 303)?;
+        if self.write(address, value) && (307..316).contains(&address) {
+            return Err(307);
+        }
+        // 307: arb #7
+        self.relative_base = self.relative(self.memory[308], 307)?;
+        // 309: eq #34, rb+2, 63
+        let value = (self.memory[310] == self.read(self.relative(self.memory[311], 309)?, 309)?) as i64;
+        let address = self.write_address(self.memory[312], 309)?;
+        if self.write(address, value) && (313..316).contains(&address) {
+            return Err(313);
+        }
+        // 313: jt 63, #319
+        if self.read(63, 313)? != 0 {
+            self.pc = 319;
+            return Ok(None);
+        }
+        self.pc = 316;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 316.
+    fn block_316(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[32] {
+            return Err(316);
+        }
+        // 316: jf #0, #325
+        self.pc = 325;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 319.
+    fn block_319(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[33] {
+            return Err(319);
+        }
+        // 319: out 309
+        let value = self.read(self.memory[320], 319)?;
+        self.pc = 321;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 321.
+    fn block_321(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[34] {
+            return Err(321);
+        }
+        // 321: add 64, #1, 64
+        let value = self.add(self.read(self.memory[322], 321)?, self.memory[323], 321)?;
+        let address = self.write_address(self.memory[324], 321)?;
+        self.write(address, value);
+        self.pc = 325;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 325.
+    fn block_325(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[35] {
+            return Err(325);
+        }
+        // 325: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[326], 325)?, self.memory[327], 325)?;
+        let address = self.write_address(self.memory[328], 325)?;
+        if self.write(address, value) && (329..342).contains(&address) {
+            return Err(329);
+        }
+        // 329: arb #6
+        self.relative_base = self.relative(self.memory[330], 329)?;
+        // 331: add #0, rb-6, 63
+        let value = self.add(self.memory[332], self.read(self.relative(self.memory[333], 331)?, 331)?, 331)?;
+        let address = self.write_address(self.memory[334], 331)?;
+        if self.write(address, value) && (335..342).contains(&address) {
+            return Err(335);
+        }
+        // 335: eq 63, #24, 63
+        let value = (self.read(self.memory[336], 335)? == self.memory[337]) as i64;
+        let address = self.write_address(self.memory[338], 335)?;
+        if self.write(address, value) && (339..342).contains(&address) {
+            return Err(339);
+        }
+        // 339: jt 63, #349
+        if self.read(63, 339)? != 0 {
+            self.pc = 349;
+            return Ok(None);
+        }
+        self.pc = 342;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 342.
+    fn block_342(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[36] {
+            return Err(342);
+        }
+        // 342: add 64, #1, 64
+        let value = self.add(self.read(self.memory[343], 342)?, self.memory[344], 342)?;
+        let address = self.write_address(self.memory[345], 342)?;
+        if self.write(address, value) && (346..349).contains(&address) {
+            return Err(346);
+        }
+        // 346: jt #1, #351
+        self.pc = 351;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 349.
+    fn block_349(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[37] {
+            return Err(349);
+        }
+        // 349: out 331
+        let value = self.read(self.memory[350], 349)?;
+        self.pc = 351;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 351.
+    fn block_351(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[38] {
+            return Err(351);
+        }
+        // 351: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[352], 351)?, self.memory[353], 351)?;
+        let address = self.write_address(self.memory[354], 351)?;
+        if self.write(address, value) && (355..364).contains(&address) {
+            return Err(355);
+        }
+        // 355: arb #4
+        self.relative_base = self.relative(self.memory[356], 355)?;
+        // 357: lt #41, #42, rb+0
+        let value = (self.memory[358] < self.memory[359]) as i64;
+        let address = self.write_address(self.relative(self.memory[360], 357)?, 357)?;
+        if self.write(address, value) && (361..364).contains(&address) {
+            return Err(361);
+        }
+        // 361: jt 1017, #369
+        if self.read(1017, 361)? != 0 {
+            self.pc = 369;
+            return Ok(None);
+        }
+        self.pc = 364;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 364.
+    fn block_364(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[39] {
+            return Err(364);
+        }
+        // 364: out 357
+        let value = self.read(self.memory[365], 364)?;
+        self.pc = 366;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 366.
+    fn block_366(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[40] {
+            return Err(366);
+        }
+        // 366: jt #1, #373
+        self.pc = 373;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 369.
+    fn block_369(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[41] {
+            return Err(369);
+        }
+        // 369: add 64, #1, 64
+        let value = self.add(self.read(self.memory[370], 369)?, self.memory[371], 369)?;
+        let address = self.write_address(self.memory[372], 369)?;
+        self.write(address, value);
+        self.pc = 373;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 373.
+    fn block_373(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[42] {
+            return Err(373);
+        }
+        // 373: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[374], 373)?, self.memory[375], 373)?;
+        let address = self.write_address(self.memory[376], 373)?;
+        if self.write(address, value) && (377..390).contains(&address) {
+            return Err(377);
+        }
+        // 377: arb #5
+        self.relative_base = self.relative(self.memory[378], 377)?;
+        // 379: add #42, #0, rb-5
+        let value = self.add(self.memory[380], self.memory[381], 379)?;
+        let address = self.write_address(self.relative(self.memory[382], 379)?, 379)?;
+        if self.write(address, value) && (383..390).contains(&address) {
+            return Err(383);
+        }
+        // 383: eq 1017, #41, 63
+        let value = (self.read(self.memory[384], 383)? == self.memory[385]) as i64;
+        let address = self.write_address(self.memory[386], 383)?;
+        if self.write(address, value) && (387..390).contains(&address) {
+            return Err(387);
+        }
+        // 387: jt 63, #397
+        if self.read(63, 387)? != 0 {
+            self.pc = 397;
+            return Ok(None);
+        }
+        self.pc = 390;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 390.
+    fn block_390(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[43] {
+            return Err(390);
+        }
+        // 390: add 64, #1, 64
+        let value = self.add(self.read(self.memory[391], 390)?, self.memory[392], 390)?;
+        let address = self.write_address(self.memory[393], 390)?;
+        if self.write(address, value) && (394..397).contains(&address) {
+            return Err(394);
+        }
+        // 394: jf #0, #399
+        self.pc = 399;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 397.
+    fn block_397(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[44] {
+            return Err(397);
+        }
+        // 397: out 379
+        let value = self.read(self.memory[398], 397)?;
+        self.pc = 399;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 399.
+    fn block_399(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[45] {
+            return Err(399);
+        }
+        // 399: mul 64, #2, 64
+        let value = self.multiply(self.read(self.memory[400], 399)?, self.memory[401], 399)?;
+        let address = self.write_address(self.memory[402], 399)?;
+        if self.write(address, value) && (403..408).contains(&address) {
+            return Err(403);
+        }
+        // 403: arb #9
+        self.relative_base = self.relative(self.memory[404], 403)?;
+        // 405: jf #0, rb-4
+        self.pc = self.jump_address(self.read(self.relative(-4, 405)?, 405)?, 405)?;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 904.
+    fn block_904(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[46] {
+            return Err(904);
+        }
+        // 904: mul #27, #1, rb+1
+        let value = self.multiply(self.memory[905], self.memory[906], 904)?;
+        let address = self.write_address(self.relative(self.memory[907], 904)?, 904)?;
+        if self.write(address, value) && (908..915).contains(&address) {
+            return Err(908);
+        }
+        // 908: add #915, #0, rb+0
+        let value = self.add(self.memory[909], self.memory[910], 908)?;
+        let address = self.write_address(self.relative(self.memory[911], 908)?, 908)?;
+        if self.write(address, value) && (912..915).contains(&address) {
+            return Err(912);
+        }
+        // 912: jt #1, #922
+        self.pc = 922;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 915.
+    fn block_915(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[47] {
+            return Err(915);
+        }
+        // 915: add rb+1, #29830, rb+1
+        let value = self.add(self.read(self.relative(self.memory[916], 915)?, 915)?, self.memory[917], 915)?;
+        let address = self.write_address(self.relative(self.memory[918], 915)?, 915)?;
+        if self.write(address, value) && (919..921).contains(&address) {
+            return Err(919);
+        }
+        // 919: out rb+1
+        let value = self.read(self.relative(self.memory[920], 919)?, 919)?;
+        self.pc = 921;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 921.
+    fn block_921(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[48] {
+            return Err(921);
+        }
+        // 921: hlt
+        self.pc = 921;
+        self.halted = true;
+        return Ok(Some(RunState::Halted));
+    }
+
+    /// Runs the block at address 922.
+    fn block_922(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[49] {
+            return Err(922);
+        }
+        // 922: arb #3
+        self.relative_base = self.relative(self.memory[923], 922)?;
+        // 924: lt rb-2, #3, 63
+        let value = (self.read(self.relative(self.memory[925], 924)?, 924)? < self.memory[926]) as i64;
+        let address = self.write_address(self.memory[927], 924)?;
+        if self.write(address, value) && (928..931).contains(&address) {
+            return Err(928);
+        }
+        // 928: jt 63, #964
+        if self.read(63, 928)? != 0 {
+            self.pc = 964;
+            return Ok(None);
+        }
+        self.pc = 931;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 931.
+    fn block_931(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[50] {
+            return Err(931);
+        }
+        // 931: add rb-2, #-1, rb+1
+        let value = self.add(self.read(self.relative(self.memory[932], 931)?, 931)?, self.memory[933], 931)?;
+        let address = self.write_address(self.relative(self.memory[934], 931)?, 931)?;
+        if self.write(address, value) && (935..942).contains(&address) {
+            return Err(935);
+        }
+        // 935: add #0, #942, rb+0
+        let value = self.add(self.memory[936], self.memory[937], 935)?;
+        let address = self.write_address(self.relative(self.memory[938], 935)?, 935)?;
+        if self.write(address, value) && (939..942).contains(&address) {
+            return Err(939);
+        }
+        // 939: jt #1, #922
+        self.pc = 922;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 942.
+    fn block_942(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[51] {
+            return Err(942);
+        }
+        // 942: mul rb+1, #1, rb-1
+        let value = self.multiply(self.read(self.relative(self.memory[943], 942)?, 942)?, self.memory[944], 942)?;
+        let address = self.write_address(self.relative(self.memory[945], 942)?, 942)?;
+        if self.write(address, value) && (946..957).contains(&address) {
+            return Err(946);
+        }
+        // 946: add rb-2, #-3, rb+1
+        let value = self.add(self.read(self.relative(self.memory[947], 946)?, 946)?, self.memory[948], 946)?;
+        let address = self.write_address(self.relative(self.memory[949], 946)?, 946)?;
+        if self.write(address, value) && (950..957).contains(&address) {
+            return Err(950);
+        }
+        // 950: mul #1, #957, rb+0
+        let value = self.multiply(self.memory[951], self.memory[952], 950)?;
+        let address = self.write_address(self.relative(self.memory[953], 950)?, 950)?;
+        if self.write(address, value) && (954..957).contains(&address) {
+            return Err(954);
+        }
+        // 954: jt #1, #922
+        self.pc = 922;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 957.
+    fn block_957(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[52] {
+            return Err(957);
+        }
+        // 957: add rb+1, rb-1, rb-2
+        let value = self.add(self.read(self.relative(self.memory[958], 957)?, 957)?, self.read(self.relative(self.memory[959], 957)?, 957)?, 957)?;
+        let address = self.write_address(self.relative(self.memory[960], 957)?, 957)?;
+        if self.write(address, value) && (961..964).contains(&address) {
+            return Err(961);
+        }
+        // 961: jt #1, #968
+        self.pc = 968;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 964.
+    fn block_964(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[53] {
+            return Err(964);
+        }
+        // 964: add rb-2, #0, rb-2
+        let value = self.add(self.read(self.relative(self.memory[965], 964)?, 964)?, self.memory[966], 964)?;
+        let address = self.write_address(self.relative(self.memory[967], 964)?, 964)?;
+        self.write(address, value);
+        self.pc = 968;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 968.
+    fn block_968(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[54] {
+            return Err(968);
+        }
+        // 968: arb #-3
+        self.relative_base = self.relative(self.memory[969], 968)?;
+        // 970: jf #0, rb+0
+        self.pc = self.jump_address(self.read(self.relative(0, 970)?, 970)?, 970)?;
+        return Ok(None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Intcode programs transpiled to Rust by the `transpile` command, so they run as compiled code.
+//! The tests below check each module is up to date with the program it was transpiled from, and
+//! rewrite the modules instead when run with UPDATE_TRANSPILED set.
+
+#[cfg(test)]
+#[rustfmt::skip]
+#[allow(dead_code, clippy::all)]
+mod day_02;
+#[rustfmt::skip]
+#[allow(dead_code, clippy::all)]
+pub mod day_09;
+#[cfg(test)]
+#[rustfmt::skip]
+#[allow(dead_code, clippy::all)]
+mod self_modifying;
+
+#[cfg(test)]
+mod tests {
+    use super::super::utils::intcode::{assemble, transpile, IntcodeMachine, RunState};
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Runs the program (a machine or a transpiled program) until it halts, faults or needs more
+    /// input than given, returning the values output, any fault and the program.
+    macro_rules! run_with_input {
+        ($program:expr, $input:expr) => {{
+            let mut program = $program;
+            let mut input = $input.to_vec().into_iter();
+            let mut output = vec![];
+            let fault = loop {
+                match program.run() {
+                    Ok(RunState::Output(value)) => output.push(value),
+                    Ok(RunState::NeedsInput) => match input.next() {
+                        Some(value) => program.add_input(value),
+                        None => break None,
+                    },
+                    Ok(_) => break None,
+                    Err(fault) => break Some(fault),
+                }
+            };
+            (output, fault, program)
+        }};
+    }
+
+    /// Program that modifies its own code, depending on the value it reads - 0 leaves the code
+    /// alone, 1 changes the halt following the instruction doing the write into an output, and
+    /// any other value changes an output in a later block into a halt.
+    const SELF_MODIFYING_PROGRAM: &str = "
+                in value
+                eq value, #1, flag
+                jt flag, #ahead
+                jf value, #skip
+                add #99, #0, patch
+        skip:   out #1
+        patch:  out #2
+                out #3
+                hlt
+        ahead:  add #104, #0, later
+        later:  data 99, 42
+                hlt
+        value:  data 0
+        flag:   data 0
+        ";
+
+    /// Checks the machines are in the same state, ignoring any zeroes at the end of memory.
+    fn assert_same_state(expected: &IntcodeMachine, actual: &IntcodeMachine) {
+        let trimmed_memory = |machine: &IntcodeMachine| {
+            let mut memory = machine.get_memory_dump();
+            while memory.last() == Some(&0) {
+                memory.pop();
+            }
+            return memory;
+        };
+        assert_eq!(trimmed_memory(expected), trimmed_memory(actual));
+        assert_eq!(expected.get_program_counter(), actual.get_program_counter());
+        assert_eq!(expected.get_relative_base(), actual.get_relative_base());
+        assert_eq!(expected.has_halted(), actual.has_halted());
+        assert_eq!(expected.is_awaiting_input(), actual.is_awaiting_input());
+    }
+
+    /// Checks the transpiled module matches the program transpiled again, or rewrites the module
+    /// if UPDATE_TRANSPILED is set.
+    fn check_module(filename: &str, module: &str, memory: &[i64], source: &str) {
+        let expected = transpile(memory, source);
+        if std::env::var_os("UPDATE_TRANSPILED").is_some() {
+            std::fs::write(format!("./src/transpiled/{}", filename), expected).unwrap();
+            return;
+        }
+        assert!(
+            expected == module,
+            "src/transpiled/{} is out of date - run the tests with UPDATE_TRANSPILED=1 set",
+            filename
+        );
+    }
+
+    #[test]
+    fn test_modules_up_to_date() {
+        for (day, filename, module) in [
+            (2, "day_02.rs", include_str!("day_02.rs")),
+            (9, "day_09.rs", include_str!("day_09.rs")),
+        ] {
+            let input = format!("./input/day_{:02}/input.txt", day);
+            let memory = IntcodeMachine::extract_intcode_memory_from_filename(input.clone());
+            check_module(filename, module, &memory, &input);
+        }
+        check_module(
+            "self_modifying.rs",
+            include_str!("self_modifying.rs"),
+            &assemble(SELF_MODIFYING_PROGRAM).unwrap(),
+            "the self-modifying test program in src/transpiled/mod.rs",
+        );
+    }
+
+    #[test]
+    fn test_day_02_matches_machine() {
+        let image = IntcodeMachine::extract_intcode_memory_from_filename(String::from(
+            "./input/day_02/input.txt",
+        ));
+        for (noun, verb) in iproduct!((0..100).step_by(9), (0..100).step_by(9)) {
+            let mut memory = image.clone();
+            memory[1] = noun;
+            memory[2] = verb;
+            let machine = IntcodeMachine::new(memory.clone(), VecDeque::new());
+            let program = day_02::TranspiledProgram::with_memory(memory).unwrap();
+            let (expected_output, expected_fault, machine) =
+                run_with_input!(machine, Vec::<i64>::new());
+            let (output, fault, program) = run_with_input!(program, Vec::<i64>::new());
+            assert_eq!(expected_output, output);
+            assert_eq!(expected_fault, fault);
+            // Writes to the noun and verb (and other parameters) don't affect the compiled code
+            assert_eq!(0, program.get_fallback_count());
+            assert_same_state(&machine, &program.into_machine());
+        }
+        // Patching an opcode means the image can't be run as compiled code
+        let mut memory = image;
+        memory[0] = 2;
+        assert!(day_02::TranspiledProgram::with_memory(memory).is_none());
+    }
+
+    #[test]
+    fn test_day_09_matches_machine() {
+        let image = IntcodeMachine::extract_intcode_memory_from_filename(String::from(
+            "./input/day_09/input.txt",
+        ));
+        for input in [vec![1], vec![2], vec![]] {
+            let machine = IntcodeMachine::new(image.clone(), VecDeque::new());
+            let program = day_09::TranspiledProgram::new();
+            let (expected_output, expected_fault, machine) = run_with_input!(machine, input);
+            let (output, fault, program) = run_with_input!(program, input);
+            assert_eq!(expected_output, output);
+            assert_eq!(expected_fault, fault);
+            assert_same_state(&machine, &program.into_machine());
+        }
+        // Part 1 runs self-tests reached through computed jumps, which are interpreted, while part
+        // 2 only runs code found by the analysis
+        let (_, _, program) = run_with_input!(day_09::TranspiledProgram::new(), [1]);
+        assert!(program.get_fallback_count() > 0);
+        let (_, _, program) = run_with_input!(day_09::TranspiledProgram::new(), [2]);
+        assert_eq!(0, program.get_fallback_count());
+    }
+
+    #[test]
+    fn test_self_modifying_falls_back() {
+        let image = assemble(SELF_MODIFYING_PROGRAM).unwrap();
+        for (value, interpreted) in [(0, false), (1, true), (2, true)] {
+            let machine = IntcodeMachine::new(image.clone(), VecDeque::new());
+            let program = self_modifying::TranspiledProgram::new();
+            let (expected_output, expected_fault, machine) = run_with_input!(machine, [value]);
+            let (output, fault, program) = run_with_input!(program, [value]);
+            assert_eq!(expected_output, output);
+            assert_eq!(expected_fault, fault);
+            assert_eq!(interpreted, program.is_interpreted());
+            assert_same_state(&machine, &program.into_machine());
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! Intcode program transpiled to Rust by the `transpile` command, from:
+//! the self-modifying test program in src/transpiled/mod.rs
+//!
+//! Do not edit - transpile the program again instead.
+
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::utils::intcode::{IntcodeMachine, MachineFault, RunState};
+
+/// Memory image the program was transpiled from.
+const IMAGE: [i64; 32] = [
+    3, 30, 1008, 30, 1, 31, 1005, 31, 23, 1006, 30, 16, 1101, 99, 0, 18,
+    104, 1, 104, 2, 104, 3, 99, 1101, 104, 0, 27, 99, 42, 99, 0, 0,
+];
+/// Addresses of the cells compiled into the code, which must hold the same values as the image.
+const FIXED_CELLS: [usize; 15] = [
+    0, 2, 6, 7, 8, 9, 10, 11, 12, 16, 18, 20, 22, 23, 27,
+];
+/// Index (plus one) of the block compiled from each cell of the image, or 0 for other cells.
+const CELL_BLOCKS: [u32; 32] = [
+    1, 0, 1, 0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 0, 0, 0,
+    4, 0, 5, 0, 6, 0, 7, 8, 0, 0, 0, 8, 0, 0, 0, 0,
+];
+/// Start and end addresses of each compiled block.
+const BLOCKS: [(usize, usize); 8] = [
+    (0, 9),
+    (9, 12),
+    (12, 16),
+    (16, 18),
+    (18, 20),
+    (20, 22),
+    (22, 23),
+    (23, 28),
+];
+/// Highest address the program can access.
+const MAX_ADDRESS: i64 = 4294967295;
+/// Highest address compiled code writes to.
+const MAX_COMPILED_ADDRESS: usize = 1048576;
+
+/// Intcode program compiled to Rust, which falls back to the interpreter once it can't run the
+/// compiled code.
+pub struct TranspiledProgram {
+    memory: Vec<i64>,
+    pc: usize,
+    relative_base: i64,
+    input: VecDeque<i64>,
+    halted: bool,
+    awaiting_input: bool,
+    dirty: Vec<bool>,
+    machine: Option<IntcodeMachine>,
+    fallbacks: u64,
+}
+
+impl TranspiledProgram {
+    /// Creates the program with the memory image it was transpiled from.
+    pub fn new() -> Self {
+        return Self::with_memory(IMAGE.to_vec()).unwrap();
+    }
+
+    /// Creates the program with the given memory, e.g. the image with some values patched.
+    /// Returns None if a cell compiled into the code doesn't hold the value it has in the image.
+    pub fn with_memory(mut memory: Vec<i64>) -> Option<Self> {
+        for &address in FIXED_CELLS.iter() {
+            if *memory.get(address).unwrap_or(&0) != IMAGE[address] {
+                return None;
+            }
+        }
+        if memory.len() < IMAGE.len() {
+            memory.resize(IMAGE.len(), 0);
+        }
+        return Some(Self {
+            memory,
+            pc: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            halted: false,
+            awaiting_input: false,
+            dirty: vec![false; BLOCKS.len()],
+            machine: None,
+            fallbacks: 0,
+        });
+    }
+
+    /// Adds the value to the input queue of the program.
+    pub fn add_input(&mut self, value: i64) {
+        match &mut self.machine {
+            Some(machine) => machine.add_input(value),
+            None => {
+                self.input.push_back(value);
+                self.awaiting_input = false;
+            }
+        }
+    }
+
+    /// Runs the program until it halts, needs input or outputs a value, and returns the reason it
+    /// stopped - the same as IntcodeMachine::run().
+    pub fn run(&mut self) -> Result<RunState, MachineFault> {
+        loop {
+            if let Some(machine) = &mut self.machine {
+                if let Some(state) = machine.step()? {
+                    return Ok(state);
+                }
+                self.try_resume_compiled();
+                continue;
+            }
+            if self.halted {
+                return Ok(RunState::Halted);
+            }
+            match self.run_block() {
+                Ok(Some(state)) => return Ok(state),
+                Ok(None) => (),
+                Err(pc) => self.fall_back(pc),
+            }
+        }
+    }
+
+    /// Checks if the program has halted.
+    pub fn has_halted(&self) -> bool {
+        match &self.machine {
+            Some(machine) => return machine.has_halted(),
+            None => return self.halted,
+        }
+    }
+
+    /// Checks if the program is waiting for input.
+    pub fn is_awaiting_input(&self) -> bool {
+        match &self.machine {
+            Some(machine) => return machine.is_awaiting_input(),
+            None => return self.awaiting_input,
+        }
+    }
+
+    /// Checks if the program is currently being run by the interpreter.
+    pub fn is_interpreted(&self) -> bool {
+        return self.machine.is_some();
+    }
+
+    /// Returns the number of times the program has fallen back to the interpreter.
+    pub fn get_fallback_count(&self) -> u64 {
+        return self.fallbacks;
+    }
+
+    /// Returns the value held at the given address of memory.
+    pub fn peek_memory(&self, address: usize) -> i64 {
+        match &self.machine {
+            Some(machine) => return machine.peek_memory(address),
+            None => return *self.memory.get(address).unwrap_or(&0),
+        }
+    }
+
+    /// Returns the current value of the program counter.
+    pub fn get_program_counter(&self) -> usize {
+        match &self.machine {
+            Some(machine) => return machine.get_program_counter(),
+            None => return self.pc,
+        }
+    }
+
+    /// Returns the current value of the relative base.
+    pub fn get_relative_base(&self) -> i64 {
+        match &self.machine {
+            Some(machine) => return machine.get_relative_base(),
+            None => return self.relative_base,
+        }
+    }
+
+    /// Converts the program into an IntcodeMachine in the same state.
+    pub fn into_machine(mut self) -> IntcodeMachine {
+        if let Some(machine) = self.machine.take() {
+            return machine;
+        }
+        return IntcodeMachine::from_transpiled_state(
+            self.take_memory(),
+            self.pc,
+            self.relative_base,
+            mem::take(&mut self.input),
+            self.halted,
+            self.awaiting_input,
+        );
+    }
+
+    /// Hands the program over to the interpreter, which continues running it from the given
+    /// address.
+    fn fall_back(&mut self, pc: usize) {
+        self.fallbacks += 1;
+        self.machine = Some(IntcodeMachine::from_transpiled_state(
+            self.take_memory(),
+            pc,
+            self.relative_base,
+            mem::take(&mut self.input),
+            false,
+            false,
+        ));
+    }
+
+    /// Switches back from the interpreter to compiled code if the interpreter has reached the
+    /// start of a compiled block that hasn't been changed since the program was transpiled.
+    fn try_resume_compiled(&mut self) {
+        let machine = self.machine.as_ref().unwrap();
+        let pc = machine.get_program_counter();
+        let (start, end) = match BLOCKS.binary_search_by_key(&pc, |(start, _)| *start) {
+            Ok(index) => BLOCKS[index],
+            Err(_) => return,
+        };
+        let changed = (start..end).any(|a| CELL_BLOCKS[a] != 0 && machine.peek_memory(a) != IMAGE[a]);
+        if changed || machine.get_memory_len() > MAX_COMPILED_ADDRESS + 1 {
+            return;
+        }
+        let machine = self.machine.take().unwrap();
+        self.memory = machine.get_memory_dump();
+        self.pc = pc;
+        self.relative_base = machine.get_relative_base();
+        self.input = machine.get_input_vec();
+        for dirty in self.dirty.iter_mut() {
+            *dirty = false;
+        }
+        for &address in FIXED_CELLS.iter() {
+            if self.memory[address] != IMAGE[address] {
+                self.dirty[CELL_BLOCKS[address] as usize - 1] = true;
+            }
+        }
+    }
+
+    /// Takes the memory of the program, without any zeroes past the end of the image.
+    fn take_memory(&mut self) -> Vec<i64> {
+        let mut memory = mem::take(&mut self.memory);
+        while memory.len() > IMAGE.len() && memory.last() == Some(&0) {
+            memory.pop();
+        }
+        return memory;
+    }
+
+    // Helpers used by the blocks return the address of the instruction being run as the error,
+    // so the interpreter can run the instruction instead (and fault if it is invalid)
+
+    /// Returns the value held at the address.
+    fn read(&self, address: i64, pc: usize) -> Result<i64, usize> {
+        if address < 0 || address > MAX_ADDRESS {
+            return Err(pc);
+        }
+        return Ok(*self.memory.get(address as usize).unwrap_or(&0));
+    }
+
+    /// Returns the address relative to the relative base.
+    fn relative(&self, offset: i64, pc: usize) -> Result<i64, usize> {
+        return self.relative_base.checked_add(offset).ok_or(pc);
+    }
+
+    /// Adds the values.
+    fn add(&self, a: i64, b: i64, pc: usize) -> Result<i64, usize> {
+        return a.checked_add(b).ok_or(pc);
+    }
+
+    /// Multiplies the values.
+    fn multiply(&self, a: i64, b: i64, pc: usize) -> Result<i64, usize> {
+        return a.checked_mul(b).ok_or(pc);
+    }
+
+    /// Checks the address can be written to by compiled code.
+    fn write_address(&self, address: i64, pc: usize) -> Result<usize, usize> {
+        if address < 0 || address as u64 > MAX_COMPILED_ADDRESS as u64 {
+            return Err(pc);
+        }
+        return Ok(address as usize);
+    }
+
+    /// Checks the address can be jumped to.
+    fn jump_address(&self, address: i64, pc: usize) -> Result<usize, usize> {
+        if address < 0 || address > MAX_ADDRESS {
+            return Err(pc);
+        }
+        return Ok(address as usize);
+    }
+
+    /// Writes the value to the address. Returns true if it changed a cell compiled into the code,
+    /// in which case the block compiled from the cell can no longer be run.
+    fn write(&mut self, address: usize, value: i64) -> bool {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        let old_value = mem::replace(&mut self.memory[address], value);
+        if address >= IMAGE.len() || old_value == value || CELL_BLOCKS[address] == 0 {
+            return false;
+        }
+        self.dirty[CELL_BLOCKS[address] as usize - 1] = true;
+        return true;
+    }
+
+    /// Runs the compiled block starting at the program counter, if there is one.
+    fn run_block(&mut self) -> Result<Option<RunState>, usize> {
+        match self.pc {
+            0 => return self.block_0(),
+            9 => return self.block_9(),
+            12 => return self.block_12(),
+            16 => return self.block_16(),
+            18 => return self.block_18(),
+            20 => return self.block_20(),
+            22 => return self.block_22(),
+            23 => return self.block_23(),
+            pc => return Err(pc),
+        }
+    }
+
+    /// Runs the block at address 0.
+    fn block_0(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[0] {
+            return Err(0);
+        }
+        // 0: in 30
+        let address = self.write_address(self.memory[1], 0)?;
+        let value = match self.input.pop_front() {
+            Some(value) => value,
+            None => {
+                self.awaiting_input = true;
+                return Ok(Some(RunState::NeedsInput));
+            }
+        };
+        if self.write(address, value) && (2..9).contains(&address) {
+            return Err(2);
+        }
+        // 2: eq 30, #1, 31
+        let value = (self.read(self.memory[3], 2)? == self.memory[4]) as i64;
+        let address = self.write_address(self.memory[5], 2)?;
+        if self.write(address, value) && (6..9).contains(&address) {
+            return Err(6);
+        }
+        // 6: jt 31, #23
+        if self.read(31, 6)? != 0 {
+            self.pc = 23;
+            return Ok(None);
+        }
+        self.pc = 9;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 9.
+    fn block_9(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[1] {
+            return Err(9);
+        }
+        // 9: jf 30, #16
+        if self.read(30, 9)? == 0 {
+            self.pc = 16;
+            return Ok(None);
+        }
+        self.pc = 12;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 12.
+    fn block_12(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[2] {
+            return Err(12);
+        }
+        // 12: add #99, #0, 18
+        let value = self.add(self.memory[13], self.memory[14], 12)?;
+        let address = self.write_address(self.memory[15], 12)?;
+        self.write(address, value);
+        self.pc = 16;
+        return Ok(None);
+    }
+
+    /// Runs the block at address 16.
+    fn block_16(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[3] {
+            return Err(16);
+        }
+        // 16: out #1
+        let value = self.memory[17];
+        self.pc = 18;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 18.
+    fn block_18(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[4] {
+            return Err(18);
+        }
+        // 18: out #2
+        let value = self.memory[19];
+        self.pc = 20;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 20.
+    fn block_20(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[5] {
+            return Err(20);
+        }
+        // 20: out #3
+        let value = self.memory[21];
+        self.pc = 22;
+        return Ok(Some(RunState::Output(value)));
+    }
+
+    /// Runs the block at address 22.
+    fn block_22(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[6] {
+            return Err(22);
+        }
+        // 22: hlt
+        self.pc = 22;
+        self.halted = true;
+        return Ok(Some(RunState::Halted));
+    }
+
+    /// Runs the block at address 23.
+    fn block_23(&mut self) -> Result<Option<RunState>, usize> {
+        if self.dirty[7] {
+            return Err(23);
+        }
+        // 23: add #104, #0, 27
+        let value = self.add(self.memory[24], self.memory[25], 23)?;
+        let address = self.write_address(self.memory[26], 23)?;
+        if self.write(address, value) && (27..28).contains(&address) {
+            return Err(27);
+        }
+        // 27: hlt
+        self.pc = 27;
+        self.halted = true;
+        return Ok(Some(RunState::Halted));
+    }
+}
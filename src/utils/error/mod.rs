@@ -0,0 +1,97 @@
+//! # error - crate-wide error type
+//!
+//! This module contains the AocError type returned by the fallible (non-panicking) variants of the
+//! input loading and Intcode APIs.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use super::intcode::MachineFault;
+
+/// Errors that can occur while loading puzzle input or running an Intcode program.
+#[derive(Debug)]
+pub enum AocError {
+    /// Input file couldn't be opened or read.
+    Io { filename: String, source: io::Error },
+    /// Output file couldn't be created or written to.
+    Write { filename: String, source: io::Error },
+    /// Input contents couldn't be parsed. Line and column numbers start at 1.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Intcode program is not valid, e.g. it is empty.
+    InvalidProgram(String),
+    /// Intcode machine encountered a fault while executing a program.
+    MachineFault(MachineFault),
+}
+
+impl AocError {
+    /// Creates a new parse error at the given line and column.
+    pub fn parse_error(line: usize, column: usize, message: String) -> Self {
+        return AocError::Parse {
+            line,
+            column,
+            message,
+        };
+    }
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::Io { filename, source } => {
+                write!(f, "couldn't read {}. ({})", filename, source)
+            }
+            AocError::Write { filename, source } => {
+                write!(f, "couldn't write {}. ({})", filename, source)
+            }
+            AocError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "parse error at line {}, column {}: {}", line, column, message),
+            AocError::InvalidProgram(message) => write!(f, "invalid Intcode program: {}", message),
+            AocError::MachineFault(fault) => write!(f, "Intcode machine fault: {}", fault),
+        }
+    }
+}
+
+impl Error for AocError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AocError::Io { source, .. } => Some(source),
+            AocError::Write { source, .. } => Some(source),
+            AocError::MachineFault(fault) => Some(fault),
+            _ => None,
+        }
+    }
+}
+
+impl From<MachineFault> for AocError {
+    fn from(fault: MachineFault) -> Self {
+        return AocError::MachineFault(fault);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_parse_error() {
+        let error = AocError::parse_error(3, 14, String::from("bad value"));
+        assert_eq!("parse error at line 3, column 14: bad value", error.to_string());
+    }
+
+    #[test]
+    fn test_io_error_source() {
+        let error = AocError::Io {
+            filename: String::from("missing.txt"),
+            source: io::Error::new(io::ErrorKind::NotFound, "not found"),
+        };
+        assert!(error.source().is_some());
+        assert!(error.to_string().starts_with("couldn't read missing.txt."));
+    }
+}
@@ -1,14 +1,36 @@
 use std::fs::File;
 use std::path::Path;
+use super::error::AocError;
+
+#[cfg(test)]
+mod temp_file;
+#[cfg(test)]
+pub use self::temp_file::TempFile;
 
 /// Opens up the given file in read-only mode. Panics if an error occurs.
 pub fn open_file(filename: String) -> File {
+    match try_open_file(filename) {
+        Err(e) => panic!("ERROR - {}", e),
+        Ok(file) => file,
+    }
+}
+
+/// Opens up the given file in read-only mode, returning an error if the file can't be opened.
+pub fn try_open_file(filename: String) -> Result<File, AocError> {
     // Open up the file (read-only)
     let filepath = Path::new(&filename);
-    let file = match File::open(&filepath) {
-        Err(e) => panic!("ERROR - couldn't open {}. ({})", filepath.display(),
-            e.to_string()),
-        Ok(file) => file,
-    };
-    return file;
+    return File::open(filepath).map_err(|e| AocError::Io {
+        filename: filepath.display().to_string(),
+        source: e,
+    });
+}
+
+/// Creates the given file for writing, truncating it if it already exists. Returns an error if the
+/// file can't be created.
+pub fn try_create_file(filename: String) -> Result<File, AocError> {
+    let filepath = Path::new(&filename);
+    return File::create(filepath).map_err(|e| AocError::Write {
+        filename: filepath.display().to_string(),
+        source: e,
+    });
 }
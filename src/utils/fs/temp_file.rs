@@ -0,0 +1,32 @@
+use std::env;
+use std::fs;
+use std::process;
+
+/// Path of a file in the system temporary directory, for tests that write and read back files.
+/// The file is removed (if it was created) when the TempFile is dropped, even if the test fails.
+pub struct TempFile {
+    filename: String,
+}
+
+impl TempFile {
+    /// Creates the path of a temporary file with the given name, which must be unique among the
+    /// tests. The file itself isn't created.
+    pub fn new(name: &str) -> Self {
+        let filename = env::temp_dir()
+            .join(format!("aoc2019_{}_{}.txt", name, process::id()))
+            .display()
+            .to_string();
+        return Self { filename };
+    }
+
+    /// Returns the path of the file.
+    pub fn get_filename(&self) -> String {
+        return self.filename.clone();
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.filename);
+    }
+}
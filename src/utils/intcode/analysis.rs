@@ -0,0 +1,526 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fmt::Write;
+
+use super::disassembler::get_pushed_return_address;
+use super::fault::{FaultKind, MachineFault};
+use super::instruction::{Instruction, Opcode, ParamMode, Parameter};
+
+/// Maximum number of memory cells used by a single instruction.
+const MAX_INSTRUCTION_LENGTH: usize = 4;
+
+/// How control passes from one basic block to another.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Execution continues into the block starting straight after the block, either because the
+    /// block ends before the start of another block or because a conditional jump isn't taken.
+    Fallthrough,
+    /// Jump with an immediate-mode target is taken.
+    Jump,
+    /// Subroutine called from the block returns to the return address it pushed onto the stack.
+    Return,
+}
+
+/// Edge of a control-flow graph, from the end of one basic block to the start of another (or to an
+/// invalid instruction).
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// Sequence of instructions always executed one after another, from the first to the last. Only
+/// the first instruction is jumped to, and only the last instruction jumps.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// Address of the first instruction of the block.
+    pub start: usize,
+    /// Instructions of the block with their addresses, in execution order.
+    pub instructions: Vec<(usize, Instruction)>,
+    /// Edges to the blocks that can be executed next.
+    pub successors: Vec<Edge>,
+    /// Block ends with a jump to a target computed at run time (e.g. a subroutine return), so the
+    /// block may also be followed by blocks not found by the analysis.
+    pub computed_jump: bool,
+}
+
+impl BasicBlock {
+    /// Returns the address straight after the last instruction of the block.
+    pub fn get_end(&self) -> usize {
+        let (address, instruction) = self.instructions.last().unwrap();
+        return address + instruction.get_length();
+    }
+}
+
+/// Reachable address that doesn't hold a valid instruction, so executing it faults the machine.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct InvalidInstruction {
+    pub address: usize,
+    /// Value at the address (0 for addresses past the end of the memory image).
+    pub value: i64,
+    /// Kind of fault the machine encounters - UnknownOpcode or BadParameterMode.
+    pub kind: FaultKind,
+}
+
+/// Instruction that writes into a reachable instruction (or its parameters), i.e. self-modifying
+/// code. Only position-mode writes are found, as relative-mode addresses depend on the relative
+/// base at run time.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct CodeWrite {
+    /// Address of the instruction doing the write.
+    pub address: usize,
+    /// Address written to.
+    pub target: usize,
+    /// Address of the instruction written into.
+    pub instruction: usize,
+}
+
+/// Input or output instruction found by the analysis.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct IoSite {
+    pub address: usize,
+    /// Opcode::Input or Opcode::Output.
+    pub opcode: Opcode,
+    /// Parameter written to (for input) or read from (for output).
+    pub param: Parameter,
+}
+
+/// Control-flow graph of an Intcode program, built by static analysis of its memory image.
+///
+/// Code is found the same way as the disassembler finds it - by following execution from the
+/// entry points through each instruction to the next, to the immediate-mode targets of jumps and
+/// to return addresses pushed onto the stack before subroutine calls. Jumps to computed targets
+/// can't be followed, so code only reachable through them is missing from the graph unless given
+/// as an extra entry point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    entry_points: Vec<usize>,
+    blocks: BTreeMap<usize, BasicBlock>,
+    invalid_instructions: Vec<InvalidInstruction>,
+    code_writes: Vec<CodeWrite>,
+    io_sites: Vec<IoSite>,
+}
+
+/// Analyzes the given memory image, with execution starting from address 0.
+pub fn analyze(memory: &[i64]) -> ControlFlowGraph {
+    return analyze_from(memory, &[0]);
+}
+
+/// Analyzes the given memory image, with execution starting from each of the entry points.
+pub fn analyze_from(memory: &[i64], entry_points: &[usize]) -> ControlFlowGraph {
+    // Decode every reachable instruction, noting the addresses that start basic blocks
+    let mut decoded = BTreeMap::<usize, Instruction>::new();
+    let mut invalid = BTreeMap::<usize, InvalidInstruction>::new();
+    let mut leaders: BTreeSet<usize> = entry_points.iter().cloned().collect();
+    let mut to_visit: Vec<usize> = entry_points.iter().rev().cloned().collect();
+    while let Some(address) = to_visit.pop() {
+        if decoded.contains_key(&address) || invalid.contains_key(&address) {
+            continue;
+        }
+        let instruction = match Instruction::try_decode(memory, address) {
+            Ok(instruction) => instruction,
+            Err(kind) => {
+                let value = *memory.get(address).unwrap_or(&0);
+                invalid.insert(
+                    address,
+                    InvalidInstruction {
+                        address,
+                        value,
+                        kind,
+                    },
+                );
+                leaders.insert(address);
+                continue;
+            }
+        };
+        let next = address + instruction.get_length();
+        if let Some(return_address) = get_pushed_return_address(&instruction) {
+            leaders.insert(return_address);
+            to_visit.push(return_address);
+        }
+        if instruction.is_jump() || instruction.opcode == Opcode::Halt {
+            leaders.insert(next);
+        }
+        if let Some(target) = get_jump_target(&instruction) {
+            leaders.insert(target);
+            to_visit.push(target);
+        }
+        if instruction.opcode != Opcode::Halt && !instruction.is_unconditional_jump() {
+            to_visit.push(next);
+        }
+        decoded.insert(address, instruction);
+    }
+    // Split the instructions into basic blocks, each starting at a leader
+    let mut blocks = BTreeMap::<usize, BasicBlock>::new();
+    for &start in leaders.iter() {
+        if !decoded.contains_key(&start) {
+            continue;
+        }
+        let mut block = BasicBlock {
+            start,
+            instructions: vec![],
+            successors: vec![],
+            computed_jump: false,
+        };
+        let mut address = start;
+        while let Some(instruction) = decoded.get(&address) {
+            block.instructions.push((address, instruction.clone()));
+            let next = address + instruction.get_length();
+            // Calls return to the block starting at the return address they push
+            if let Some(return_address) = get_pushed_return_address(instruction) {
+                block.successors.push(Edge {
+                    from: start,
+                    to: return_address,
+                    kind: EdgeKind::Return,
+                });
+            }
+            if instruction.is_jump() {
+                if let Some(target) = get_jump_target(instruction) {
+                    block.successors.push(Edge {
+                        from: start,
+                        to: target,
+                        kind: EdgeKind::Jump,
+                    });
+                } else {
+                    block.computed_jump = true;
+                }
+            }
+            if instruction.opcode == Opcode::Halt || instruction.is_unconditional_jump() {
+                break;
+            }
+            if instruction.is_jump() || leaders.contains(&next) {
+                block.successors.push(Edge {
+                    from: start,
+                    to: next,
+                    kind: EdgeKind::Fallthrough,
+                });
+                break;
+            }
+            address = next;
+        }
+        blocks.insert(start, block);
+    }
+    let mut code_writes = vec![];
+    let mut io_sites = vec![];
+    for (&address, instruction) in decoded.iter() {
+        if let Some(target) = get_position_write_target(instruction) {
+            let first = target.saturating_sub(MAX_INSTRUCTION_LENGTH - 1);
+            let written = decoded
+                .range(first..=target)
+                .find(|(a, i)| target < *a + i.get_length());
+            if let Some((&written, _)) = written {
+                code_writes.push(CodeWrite {
+                    address,
+                    target,
+                    instruction: written,
+                });
+            }
+        }
+        if instruction.opcode == Opcode::Input || instruction.opcode == Opcode::Output {
+            io_sites.push(IoSite {
+                address,
+                opcode: instruction.opcode,
+                param: instruction.params[0],
+            });
+        }
+    }
+    return ControlFlowGraph {
+        entry_points: entry_points.to_vec(),
+        blocks,
+        invalid_instructions: invalid.into_values().collect(),
+        code_writes,
+        io_sites,
+    };
+}
+
+impl ControlFlowGraph {
+    /// Returns the basic blocks of the program in address order.
+    pub fn get_blocks(&self) -> Vec<&BasicBlock> {
+        return self.blocks.values().collect();
+    }
+
+    /// Returns the basic block starting at the given address, if there is one.
+    pub fn get_block(&self, start: usize) -> Option<&BasicBlock> {
+        return self.blocks.get(&start);
+    }
+
+    /// Returns every edge of the graph, in order of the blocks they leave.
+    pub fn get_edges(&self) -> Vec<Edge> {
+        return self
+            .blocks
+            .values()
+            .flat_map(|block| block.successors.iter().cloned())
+            .collect();
+    }
+
+    /// Returns the reachable addresses that don't hold valid instructions, in address order.
+    pub fn get_invalid_instructions(&self) -> &[InvalidInstruction] {
+        return &self.invalid_instructions;
+    }
+
+    /// Returns the instructions that write into code, in address order.
+    pub fn get_code_writes(&self) -> &[CodeWrite] {
+        return &self.code_writes;
+    }
+
+    /// Returns the input and output instructions, in address order.
+    pub fn get_io_sites(&self) -> &[IoSite] {
+        return &self.io_sites;
+    }
+
+    /// Returns the graph in Graphviz DOT format. Each basic block is a node listing its
+    /// instructions, and invalid instructions are shown as red nodes. Jump edges are labelled,
+    /// return edges are dashed, and blocks ending with a computed jump have a dotted edge to a
+    /// shared "computed jump" node.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph intcode {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for &entry_point in self.entry_points.iter() {
+            writeln!(dot, "    entry_{} [shape=point];", entry_point).unwrap();
+            writeln!(dot, "    entry_{} -> n{};", entry_point, entry_point).unwrap();
+        }
+        let mut any_computed = false;
+        for block in self.blocks.values() {
+            let mut label = String::new();
+            for (address, instruction) in block.instructions.iter() {
+                write!(label, "{}: {}\\l", address, instruction).unwrap();
+            }
+            writeln!(dot, "    n{} [label=\"{}\"];", block.start, label).unwrap();
+            for edge in block.successors.iter() {
+                let attributes = match edge.kind {
+                    EdgeKind::Fallthrough => "",
+                    EdgeKind::Jump => " [label=\"jump\"]",
+                    EdgeKind::Return => " [style=dashed, label=\"return\"]",
+                };
+                writeln!(dot, "    n{} -> n{}{};", edge.from, edge.to, attributes).unwrap();
+            }
+            if block.computed_jump {
+                any_computed = true;
+                writeln!(dot, "    n{} -> computed [style=dotted];", block.start).unwrap();
+            }
+        }
+        for invalid in self.invalid_instructions.iter() {
+            writeln!(
+                dot,
+                "    n{} [label=\"{}: {}\", color=red];",
+                invalid.address,
+                invalid.address,
+                describe_fault(invalid)
+            )
+            .unwrap();
+        }
+        if any_computed {
+            dot.push_str("    computed [label=\"computed jump\", shape=ellipse, style=dotted];\n");
+        }
+        dot.push_str("}\n");
+        return dot;
+    }
+}
+
+impl fmt::Display for ControlFlowGraph {
+    /// Displays a summary of the analysis - the size of the graph, then any invalid instructions,
+    /// writes into code and I/O sites found.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let num_computed = self.blocks.values().filter(|b| b.computed_jump).count();
+        writeln!(f, "basic blocks:         {}", self.blocks.len())?;
+        writeln!(f, "edges:                {}", self.get_edges().len())?;
+        writeln!(f, "computed jumps:       {}", num_computed)?;
+        writeln!(f, "\ninvalid instructions:")?;
+        for invalid in self.invalid_instructions.iter() {
+            writeln!(f, "  {:>6} {}", invalid.address, describe_fault(invalid))?;
+        }
+        writeln!(f, "\nwrites into code:")?;
+        for write in self.code_writes.iter() {
+            writeln!(
+                f,
+                "  {:>6} writes {} (instruction at {})",
+                write.address, write.target, write.instruction
+            )?;
+        }
+        writeln!(f, "\ninput/output:")?;
+        for site in self.io_sites.iter() {
+            let mnemonic = site.opcode.get_mnemonic();
+            writeln!(f, "  {:>6} {} {}", site.address, mnemonic, site.param)?;
+        }
+        return Ok(());
+    }
+}
+
+/// Gets the target of the instruction if it is a jump with an immediate-mode target that is a
+/// valid address.
+fn get_jump_target(instruction: &Instruction) -> Option<usize> {
+    let target = instruction.get_static_jump_target()?;
+    if target < 0 {
+        return None;
+    }
+    return Some(target as usize);
+}
+
+/// Gets the address written to by the instruction if it writes to memory with a position-mode
+/// parameter.
+fn get_position_write_target(instruction: &Instruction) -> Option<usize> {
+    if !instruction.opcode.writes_to_memory() {
+        return None;
+    }
+    let param = instruction.params.last()?;
+    if param.mode != ParamMode::Position || param.value < 0 {
+        return None;
+    }
+    return Some(param.value as usize);
+}
+
+/// Describes the fault the machine encounters when executing the invalid instruction, using the
+/// same wording as MachineFault.
+fn describe_fault(invalid: &InvalidInstruction) -> String {
+    let fault = MachineFault {
+        kind: invalid.kind,
+        opcode: invalid.value % 100,
+        pc: invalid.address,
+        relative_base: 0,
+        address: None,
+    };
+    let text = fault.to_string();
+    return text[..text.find(" [").unwrap_or(text.len())].to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assemble;
+    use super::*;
+
+    #[test]
+    fn test_basic_blocks() {
+        let memory = assemble(
+            "
+                    in value
+            loop:   jf value, #end
+                    add value, #-1, value
+                    out value
+                    jt #1, #loop
+            end:    hlt
+            value:  data 0
+            ",
+        )
+        .unwrap();
+        let graph = analyze(&memory);
+        let starts: Vec<usize> = graph.get_blocks().iter().map(|b| b.start).collect();
+        assert_eq!(vec![0, 2, 5, 14], starts);
+        let edges = vec![
+            Edge {
+                from: 0,
+                to: 2,
+                kind: EdgeKind::Fallthrough,
+            },
+            Edge {
+                from: 2,
+                to: 14,
+                kind: EdgeKind::Jump,
+            },
+            Edge {
+                from: 2,
+                to: 5,
+                kind: EdgeKind::Fallthrough,
+            },
+            Edge {
+                from: 5,
+                to: 2,
+                kind: EdgeKind::Jump,
+            },
+        ];
+        assert_eq!(edges, graph.get_edges());
+        assert_eq!(3, graph.get_block(5).unwrap().instructions.len());
+        assert_eq!(14, graph.get_block(5).unwrap().get_end());
+        let sites: Vec<(usize, Opcode)> = graph
+            .get_io_sites()
+            .iter()
+            .map(|s| (s.address, s.opcode))
+            .collect();
+        assert_eq!(vec![(0, Opcode::Input), (9, Opcode::Output)], sites);
+        assert!(graph.get_invalid_instructions().is_empty());
+        assert!(graph.get_code_writes().is_empty());
+    }
+
+    #[test]
+    fn test_calls_and_computed_jumps() {
+        // Push return address 9, call subroutine at 11 which returns via the stack
+        let memory = vec![109, 20, 21101, 9, 0, 0, 1105, 1, 11, 99, 0, 2106, 0, 0];
+        let graph = analyze(&memory);
+        let call = graph.get_block(0).unwrap();
+        let return_edge = Edge {
+            from: 0,
+            to: 9,
+            kind: EdgeKind::Return,
+        };
+        assert!(call.successors.contains(&return_edge));
+        assert!(graph.get_block(9).is_some());
+        assert!(graph.get_block(11).unwrap().computed_jump);
+        assert!(graph.get_block(11).unwrap().successors.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_instructions_and_code_writes() {
+        // Overwrite the halt at address 9 with the value read, then maybe jump to an unknown opcode
+        let memory = vec![3, 9, 1008, 9, 0, 11, 1005, 10, 12, 99, 0, 0, 42];
+        let graph = analyze(&memory);
+        let expected = vec![CodeWrite {
+            address: 0,
+            target: 9,
+            instruction: 9,
+        }];
+        assert_eq!(expected, graph.get_code_writes());
+        let expected = vec![InvalidInstruction {
+            address: 12,
+            value: 42,
+            kind: FaultKind::UnknownOpcode,
+        }];
+        assert_eq!(expected, graph.get_invalid_instructions());
+        // Write to address 11 is a write to data, as nothing jumps there
+        assert_eq!(1, graph.get_code_writes().len());
+        let summary = graph.to_string();
+        assert!(summary.contains("\n      12 unknown opcode 42\n"));
+        assert!(summary.contains("\n       0 writes 9 (instruction at 9)\n"));
+    }
+
+    #[test]
+    fn test_dot_export() {
+        let memory = vec![1105, 0, 6, 2106, 0, 0, 99];
+        let dot = analyze(&memory).to_dot();
+        let expected = concat!(
+            "digraph intcode {\n",
+            "    node [shape=box, fontname=\"monospace\"];\n",
+            "    entry_0 [shape=point];\n",
+            "    entry_0 -> n0;\n",
+            "    n0 [label=\"0: jt #0, #6\\l\"];\n",
+            "    n0 -> n6 [label=\"jump\"];\n",
+            "    n0 -> n3;\n",
+            "    n3 [label=\"3: jf #0, rb+0\\l\"];\n",
+            "    n3 -> computed [style=dotted];\n",
+            "    n6 [label=\"6: hlt\\l\"];\n",
+            "    computed [label=\"computed jump\", shape=ellipse, style=dotted];\n",
+            "}\n",
+        );
+        assert_eq!(expected, dot);
+    }
+
+    #[test]
+    fn test_analyze_day_17_program() {
+        let memory = super::super::IntcodeMachine::extract_intcode_memory_from_filename(
+            String::from("./input/day_17/input.txt"),
+        );
+        let graph = analyze(&memory);
+        assert!(graph.get_invalid_instructions().is_empty());
+        assert!(graph
+            .get_io_sites()
+            .iter()
+            .any(|s| s.opcode == Opcode::Input));
+        assert!(graph
+            .get_io_sites()
+            .iter()
+            .any(|s| s.opcode == Opcode::Output));
+        // Without invalid instructions, every edge leads to a block
+        for edge in graph.get_edges() {
+            assert!(graph.get_block(edge.to).is_some());
+        }
+    }
+}
@@ -0,0 +1,55 @@
+/// How an Intcode machine handles arithmetic results that don't fit in an i64 - the results of add
+/// and multiply instructions, relative base adjustments and relative mode addresses.
+///
+/// Memory cells hold i64 values, so wider results can't be stored, and an overflow either faults
+/// or wraps. There is no exact (i128 or bignum) mode, as every result is stored in a cell or the
+/// relative base straight away, so would fault exactly where checked mode does. The behaviour is
+/// the same whichever profile the crate is built with.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum ArithmeticMode {
+    /// Overflow faults the machine with FaultKind::ArithmeticOverflow, leaving the instruction
+    /// unexecuted. This is the default.
+    Checked,
+    /// Results wrap around in two's complement, as plain i64 arithmetic does in release builds.
+    Wrapping,
+}
+
+impl Default for ArithmeticMode {
+    fn default() -> Self {
+        return ArithmeticMode::Checked;
+    }
+}
+
+impl ArithmeticMode {
+    /// Adds the values, returning None if the result overflows in checked mode.
+    pub fn add(self, a: i64, b: i64) -> Option<i64> {
+        match self {
+            ArithmeticMode::Checked => return a.checked_add(b),
+            ArithmeticMode::Wrapping => return Some(a.wrapping_add(b)),
+        }
+    }
+
+    /// Multiplies the values, returning None if the result overflows in checked mode.
+    pub fn multiply(self, a: i64, b: i64) -> Option<i64> {
+        match self {
+            ArithmeticMode::Checked => return a.checked_mul(b),
+            ArithmeticMode::Wrapping => return Some(a.wrapping_mul(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic_modes() {
+        let big = 1 << 62;
+        assert_eq!(Some(-2), ArithmeticMode::Checked.add(-7, 5));
+        assert_eq!(None, ArithmeticMode::Checked.add(big, big));
+        assert_eq!(Some(i64::MIN), ArithmeticMode::Wrapping.add(big, big));
+        assert_eq!(None, ArithmeticMode::Checked.multiply(big, -4));
+        assert_eq!(Some(0), ArithmeticMode::Wrapping.multiply(big, -4));
+        assert_eq!(ArithmeticMode::Checked, ArithmeticMode::default());
+    }
+}
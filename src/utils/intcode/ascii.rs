@@ -0,0 +1,165 @@
+use std::collections::VecDeque;
+
+use super::fault::MachineFault;
+use super::run_state::RunState;
+use super::IntcodeMachine;
+
+/// Adapter for Intcode programs that communicate in ASCII text, such as the day 17 vacuum robot.
+///
+/// Text is sent to the program one character code per input value. Output values that are ASCII
+/// character codes (0 to 127) are collected as text, and any other values (e.g. a final numeric
+/// answer) are kept separately and retrieved with read_value().
+pub struct AsciiAdapter {
+    machine: IntcodeMachine,
+    text: String,
+    values: VecDeque<i64>,
+}
+
+impl AsciiAdapter {
+    /// Creates a new adapter around the given machine.
+    pub fn new(machine: IntcodeMachine) -> Self {
+        Self {
+            machine,
+            text: String::new(),
+            values: VecDeque::new(),
+        }
+    }
+
+    /// Returns the machine running the program.
+    pub fn get_machine(&self) -> &IntcodeMachine {
+        return &self.machine;
+    }
+
+    /// Returns the machine running the program, e.g. to take a snapshot of it.
+    pub fn get_machine_mut(&mut self) -> &mut IntcodeMachine {
+        return &mut self.machine;
+    }
+
+    /// Consumes the adapter, returning the machine. Any output not yet read is discarded.
+    pub fn into_machine(self) -> IntcodeMachine {
+        return self.machine;
+    }
+
+    /// Sends the text to the program, without adding a newline.
+    pub fn send_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.machine.add_input(c as i64);
+        }
+    }
+
+    /// Sends the line of text to the program, followed by a newline.
+    pub fn send_line(&mut self, line: &str) {
+        self.send_text(line);
+        self.machine.add_input('\n' as i64);
+    }
+
+    /// Runs the program until it outputs a complete line of text, returning the line without its
+    /// newline. Returns None if the program halts or needs input first - any partial line output
+    /// is kept, and is returned by later reads.
+    pub fn read_line(&mut self) -> Result<Option<String>, MachineFault> {
+        loop {
+            if let Some(index) = self.text.find('\n') {
+                let line = self.text[..index].to_string();
+                self.text.drain(..=index);
+                return Ok(Some(line));
+            }
+            if let RunState::Output(_) = self.run_once()? {
+                continue;
+            }
+            return Ok(None);
+        }
+    }
+
+    /// Runs the program until it halts or needs input, returning all of the text output that
+    /// hasn't been read yet. The prompt (if any) shown by the program is at the end of the text.
+    pub fn read_until_prompt(&mut self) -> Result<String, MachineFault> {
+        while let RunState::Output(_) = self.run_once()? {}
+        return Ok(self.take_text());
+    }
+
+    /// Returns the text output by the program so far that hasn't been read yet, without running
+    /// the program.
+    pub fn take_text(&mut self) -> String {
+        return std::mem::take(&mut self.text);
+    }
+
+    /// Returns the earliest non-ASCII value output by the program that hasn't been read yet,
+    /// without running the program.
+    pub fn read_value(&mut self) -> Option<i64> {
+        return self.values.pop_front();
+    }
+
+    /// Returns all of the non-ASCII values output by the program that haven't been read yet,
+    /// without running the program.
+    pub fn take_values(&mut self) -> Vec<i64> {
+        return self.values.drain(..).collect();
+    }
+
+    /// Runs the machine until it stops, sorting any value output into text or non-ASCII values.
+    fn run_once(&mut self) -> Result<RunState, MachineFault> {
+        let state = self.machine.run()?;
+        if let RunState::Output(value) = state {
+            if (0..=127).contains(&value) {
+                self.text.push(value as u8 as char);
+            } else {
+                self.values.push_back(value);
+            }
+        }
+        return Ok(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assemble;
+    use super::*;
+
+    /// Program showing a prompt and echoing the line read, then outputting 1000 and halting.
+    fn get_echo_program() -> Vec<i64> {
+        return assemble(
+            "
+                   out #62          ; '>'
+            loop:  in char
+                   out char
+                   eq char, #10, flag
+                   jf flag, #loop
+                   out #1000
+                   hlt
+            char:  data 0
+            flag:  data 0
+            ",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_ascii_adapter() {
+        let mut adapter =
+            AsciiAdapter::new(IntcodeMachine::new(get_echo_program(), VecDeque::new()));
+        assert_eq!(Ok(String::from(">")), adapter.read_until_prompt());
+        assert!(adapter.get_machine().is_awaiting_input());
+        adapter.send_line("hello");
+        assert_eq!(Ok(Some(String::from("hello"))), adapter.read_line());
+        // Non-ASCII value is only output once the program runs on
+        assert_eq!(None, adapter.read_value());
+        assert_eq!(Ok(String::new()), adapter.read_until_prompt());
+        assert!(adapter.get_machine().has_halted());
+        assert_eq!(Some(1000), adapter.read_value());
+        assert!(adapter.take_values().is_empty());
+    }
+
+    #[test]
+    fn test_read_line_partial() {
+        let mut adapter =
+            AsciiAdapter::new(IntcodeMachine::new(get_echo_program(), VecDeque::new()));
+        // Prompt doesn't end in a newline, so isn't a complete line
+        assert_eq!(Ok(None), adapter.read_line());
+        adapter.send_text("ab");
+        assert_eq!(Ok(None), adapter.read_line());
+        adapter.send_line("c");
+        assert_eq!(Ok(Some(String::from(">abc"))), adapter.read_line());
+        assert_eq!(Ok(None), adapter.read_line());
+        assert_eq!(vec![1000], adapter.take_values());
+        assert_eq!(String::new(), adapter.take_text());
+    }
+}
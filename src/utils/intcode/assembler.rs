@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+
+use super::super::error::AocError;
+use super::instruction::{Opcode, ParamMode};
+
+/// Value of a word in an assembled program, which may refer to a label defined elsewhere.
+enum Value {
+    Number(i64),
+    Label { name: String, offset: i64 },
+}
+
+/// Single word of an assembled program, along with where it came from in the source.
+struct Word {
+    value: Value,
+    line: usize,
+    column: usize,
+}
+
+/// Assembles the given source into an Intcode program that can be loaded into an IntcodeMachine.
+///
+/// Each line holds an optional label definition ("name:") followed by an instruction or data
+/// directive, and anything after a ';' is a comment. Instructions are a mnemonic (add, mul, in,
+/// out, jt, jf, lt, eq, arb, hlt) followed by comma-separated operands, which are written as
+/// "123" for position mode, "#123" for immediate mode and "rb+123" or "rb-123" for relative mode.
+/// Position and immediate operands can also be a label, optionally with an offset ("loop",
+/// "#end+2"). The "data" directive emits its comma-separated values directly, which can be
+/// numbers, labels or double-quoted strings (emitted as one ASCII value per character).
+pub fn assemble(source: &str) -> Result<Vec<i64>, AocError> {
+    let mut words = Vec::<Word>::new();
+    let mut labels = HashMap::<String, usize>::new();
+    for (line_index, raw_line) in source.lines().enumerate() {
+        let line = line_index + 1;
+        let text = strip_comment(raw_line);
+        let mut start = skip_whitespace(text, 0);
+        // Check for a label definition at the start of the line
+        if let Some(colon) = text.find(':') {
+            let name = text[start..colon].trim();
+            if !text[..colon].contains('"') {
+                if !is_label(name) {
+                    let message = format!("bad label name \"{}\"", name);
+                    return Err(AocError::parse_error(line, start + 1, message));
+                }
+                if labels.insert(String::from(name), words.len()).is_some() {
+                    let message = format!("label \"{}\" defined more than once", name);
+                    return Err(AocError::parse_error(line, start + 1, message));
+                }
+                start = skip_whitespace(text, colon + 1);
+            }
+        }
+        if start >= text.len() {
+            continue;
+        }
+        let mnemonic_end = match text[start..].find(char::is_whitespace) {
+            Some(i) => start + i,
+            None => text.len(),
+        };
+        let mnemonic = &text[start..mnemonic_end];
+        let operands = split_operands(text, mnemonic_end, line)?;
+        if mnemonic == "data" {
+            if operands.is_empty() {
+                let message = String::from("data directive needs at least one value");
+                return Err(AocError::parse_error(line, start + 1, message));
+            }
+            for (column, operand) in operands {
+                words.extend(parse_data_value(operand, line, column)?);
+            }
+            continue;
+        }
+        let opcode = match Opcode::from_mnemonic(mnemonic) {
+            Some(o) => o,
+            None => {
+                let message = format!("unknown mnemonic \"{}\"", mnemonic);
+                return Err(AocError::parse_error(line, start + 1, message));
+            }
+        };
+        if operands.len() != opcode.get_param_count() {
+            let message = format!(
+                "{} takes {} operand(s) but {} were given",
+                mnemonic,
+                opcode.get_param_count(),
+                operands.len()
+            );
+            return Err(AocError::parse_error(line, start + 1, message));
+        }
+        // Opcode word is followed by one word per operand, with modes encoded in the opcode word
+        let mut arg = opcode.get_code();
+        let mut multiplier = 100;
+        let mut operand_words = Vec::<Word>::new();
+        for (i, (column, operand)) in operands.into_iter().enumerate() {
+            let (mode, value) = parse_operand(operand, line, column)?;
+            let is_write = opcode.writes_to_memory() && i == opcode.get_param_count() - 1;
+            if mode == ParamMode::Immediate && is_write {
+                let message = String::from("can't write to an immediate-mode operand");
+                return Err(AocError::parse_error(line, column, message));
+            }
+            arg += mode.get_code() * multiplier;
+            multiplier *= 10;
+            operand_words.push(Word {
+                value,
+                line,
+                column,
+            });
+        }
+        words.push(Word {
+            value: Value::Number(arg),
+            line,
+            column: start + 1,
+        });
+        words.extend(operand_words);
+    }
+    // Second pass replaces labels with the addresses they were defined at
+    let mut program = Vec::<i64>::with_capacity(words.len());
+    for word in words {
+        match word.value {
+            Value::Number(n) => program.push(n),
+            Value::Label { name, offset } => match labels.get(&name) {
+                Some(address) => program.push(*address as i64 + offset),
+                None => {
+                    let message = format!("undefined label \"{}\"", name);
+                    return Err(AocError::parse_error(word.line, word.column, message));
+                }
+            },
+        }
+    }
+    return Ok(program);
+}
+
+/// Returns the line with any comment removed. Semicolons inside strings don't start a comment.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ';' {
+            return &line[..i];
+        }
+    }
+    return line;
+}
+
+/// Returns the index of the first non-whitespace character in the text at or after the index.
+fn skip_whitespace(text: &str, index: usize) -> usize {
+    match text[index..].find(|c: char| !c.is_whitespace()) {
+        Some(i) => return index + i,
+        None => return text.len(),
+    }
+}
+
+/// Splits the text after the given index into comma-separated operands, returning each trimmed
+/// operand with the column it starts at. Commas inside strings don't separate operands.
+fn split_operands(text: &str, index: usize, line: usize) -> Result<Vec<(usize, &str)>, AocError> {
+    let mut operands = Vec::<(usize, &str)>::new();
+    if text[index..].trim().is_empty() {
+        return Ok(operands);
+    }
+    let mut start = index;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text[index..].char_indices().map(|(i, c)| (i + index, c)) {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            operands.push(get_operand(text, start, i, line)?);
+            start = i + 1;
+        }
+    }
+    operands.push(get_operand(text, start, text.len(), line)?);
+    return Ok(operands);
+}
+
+/// Gets the trimmed operand between the given indices and the column it starts at.
+fn get_operand(
+    text: &str,
+    start: usize,
+    end: usize,
+    line: usize,
+) -> Result<(usize, &str), AocError> {
+    let operand = text[start..end].trim();
+    let column = skip_whitespace(text, start) + 1;
+    if operand.is_empty() {
+        return Err(AocError::parse_error(
+            line,
+            column,
+            String::from("missing operand"),
+        ));
+    }
+    return Ok((column, operand));
+}
+
+/// Parses an instruction operand into its parameter mode and value.
+fn parse_operand(
+    operand: &str,
+    line: usize,
+    column: usize,
+) -> Result<(ParamMode, Value), AocError> {
+    if let Some(immediate) = operand.strip_prefix('#') {
+        let value = parse_value(immediate, line, column + 1)?;
+        return Ok((ParamMode::Immediate, value));
+    }
+    if operand == "rb" {
+        return Ok((ParamMode::Relative, Value::Number(0)));
+    }
+    if operand.starts_with("rb+") || operand.starts_with("rb-") {
+        // Keep the sign when parsing the offset
+        let offset = operand[2..].trim_start_matches('+');
+        match offset.parse::<i64>() {
+            Ok(n) => return Ok((ParamMode::Relative, Value::Number(n))),
+            Err(_) => {
+                let message = format!("bad relative offset \"{}\"", &operand[2..]);
+                return Err(AocError::parse_error(line, column + 2, message));
+            }
+        }
+    }
+    let value = parse_value(operand, line, column)?;
+    return Ok((ParamMode::Position, value));
+}
+
+/// Parses a number, or a label with an optional offset (e.g. "loop+2").
+fn parse_value(text: &str, line: usize, column: usize) -> Result<Value, AocError> {
+    if let Ok(n) = text.parse::<i64>() {
+        return Ok(Value::Number(n));
+    }
+    let (name, offset) = match text.find(&['+', '-'][..]) {
+        Some(i) => {
+            let offset = match text[i..].trim_start_matches('+').parse::<i64>() {
+                Ok(n) => n,
+                Err(_) => {
+                    let message = format!("bad label offset \"{}\"", &text[i..]);
+                    return Err(AocError::parse_error(line, column + i, message));
+                }
+            };
+            (&text[..i], offset)
+        }
+        None => (text, 0),
+    };
+    if !is_label(name) {
+        let message = format!("bad value \"{}\"", text);
+        return Err(AocError::parse_error(line, column, message));
+    }
+    return Ok(Value::Label {
+        name: String::from(name),
+        offset,
+    });
+}
+
+/// Parses a value given to the data directive into the words it emits.
+fn parse_data_value(text: &str, line: usize, column: usize) -> Result<Vec<Word>, AocError> {
+    if !text.starts_with('"') {
+        let value = parse_value(text, line, column)?;
+        return Ok(vec![Word {
+            value,
+            line,
+            column,
+        }]);
+    }
+    if text.len() < 2 || !text.ends_with('"') {
+        let message = String::from("unterminated string");
+        return Err(AocError::parse_error(line, column, message));
+    }
+    let mut words = Vec::<Word>::new();
+    let mut chars = text[1..text.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            match chars.next() {
+                Some('n') => '\n',
+                Some('\\') => '\\',
+                Some('"') => '"',
+                _ => {
+                    let message = String::from("bad escape sequence in string");
+                    return Err(AocError::parse_error(line, column, message));
+                }
+            }
+        } else {
+            c
+        };
+        if !c.is_ascii() {
+            let message = format!("non-ASCII character '{}' in string", c);
+            return Err(AocError::parse_error(line, column, message));
+        }
+        words.push(Word {
+            value: Value::Number(c as i64),
+            line,
+            column,
+        });
+    }
+    return Ok(words);
+}
+
+/// Checks if the text is a valid label name: letters, digits and underscores, not starting with a
+/// digit, and not the relative base register name "rb".
+fn is_label(text: &str) -> bool {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    return text != "rb" && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::disassembler::disassemble;
+    use super::*;
+
+    #[test]
+    fn test_assemble_instructions() {
+        let program = assemble(
+            "
+            ; Add two inputs and output the result
+            in a
+            in b
+            add a, b, rb-1
+            out rb-1
+            hlt
+            a: data 0
+            b: data 0
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![3, 11, 3, 12, 20001, 11, 12, -1, 204, -1, 99, 0, 0],
+            program
+        );
+    }
+
+    #[test]
+    fn test_assemble_labels_and_immediates() {
+        let program = assemble(
+            "
+            start: jt #1, #end
+                   data 5, start, end+1
+            end:   hlt
+            ",
+        )
+        .unwrap();
+        assert_eq!(vec![1105, 1, 6, 5, 0, 7, 99], program);
+    }
+
+    #[test]
+    fn test_assemble_strings() {
+        let program = assemble("msg: data \"Hi; there\\n\", 0 ; trailing comment").unwrap();
+        assert_eq!(
+            vec![72, 105, 59, 32, 116, 104, 101, 114, 101, 10, 0],
+            program
+        );
+    }
+
+    #[test]
+    fn test_assemble_round_trip() {
+        // Day 09 example program that outputs a copy of itself
+        let memory = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let source: String = disassemble(&memory)
+            .to_string()
+            .lines()
+            .map(|line| format!("{}\n", &line[33..]))
+            .collect();
+        assert_eq!(memory, assemble(&source).unwrap());
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let check = |source: &str, line: usize, column: usize| match assemble(source) {
+            Err(AocError::Parse {
+                line: l, column: c, ..
+            }) => {
+                assert_eq!((line, column), (l, c), "{}", source);
+            }
+            _ => panic!("Expected parse error for: {}", source),
+        };
+        check("nop 1", 1, 1);
+        check("hlt\nadd 1, 2", 2, 1);
+        check("add 1, 2, #3", 1, 11);
+        check("out missing", 1, 5);
+        check("x: hlt\nx: hlt", 2, 1);
+        check("out rb+x", 1, 7);
+        check("data 1,, 2", 1, 8);
+        check("data \"abc", 1, 6);
+    }
+}
@@ -0,0 +1,229 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufWriter, Write};
+
+use super::super::error::AocError;
+use super::super::fs;
+use super::ascii::AsciiAdapter;
+use super::IntcodeMachine;
+
+/// Help text displayed by the "/help" command of the console.
+const CONSOLE_HELP: &str = "Console commands (any other line is sent to the program):
+    /save <path>             Save a snapshot of the machine to a file
+    /load <path>             Replace the machine with one loaded from a snapshot file
+    /log <path>              Start logging the transcript to a file (replacing any current log)
+    /nolog                   Stop logging the transcript
+    /help                    Show this help
+    /quit                    Exit the console";
+
+/// Interactive console for Intcode programs that communicate in ASCII text. Lines read from the
+/// input are sent to the program and its text output is written to the output as it is produced.
+/// Lines starting with '/' are console commands instead, used to save and load snapshots of the
+/// machine and to log a transcript of the session.
+pub struct Console {
+    adapter: AsciiAdapter,
+    transcript: Option<(String, BufWriter<File>)>,
+}
+
+impl Console {
+    /// Creates a new console running the program held by the given machine.
+    pub fn new(machine: IntcodeMachine) -> Self {
+        Self {
+            adapter: AsciiAdapter::new(machine),
+            transcript: None,
+        }
+    }
+
+    /// Returns the machine running the program.
+    pub fn get_machine(&self) -> &IntcodeMachine {
+        return self.adapter.get_machine();
+    }
+
+    /// Starts logging everything output by the program and every line sent to it to the given
+    /// file, which is created (or truncated). Any transcript already being logged is ended.
+    pub fn start_transcript(&mut self, filename: String) -> Result<(), AocError> {
+        self.end_transcript()?;
+        let file = fs::try_create_file(filename.clone())?;
+        self.transcript = Some((filename, BufWriter::new(file)));
+        return Ok(());
+    }
+
+    /// Ends the transcript being logged, if any, making sure all of it has been written.
+    pub fn end_transcript(&mut self) -> Result<(), AocError> {
+        if let Some((filename, mut transcript)) = self.transcript.take() {
+            transcript.flush().map_err(|e| AocError::Write {
+                filename,
+                source: e,
+            })?;
+        }
+        return Ok(());
+    }
+
+    /// Runs an interactive session, reading lines from the input and writing program output and
+    /// command results to the output until the "/quit" command is given, the input ends or the
+    /// program halts or faults.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, output: &mut W) -> io::Result<()> {
+        let mut lines = input.lines();
+        loop {
+            if !self.show_program_output(output)? {
+                break;
+            }
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => break,
+            };
+            if line.starts_with('/') {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                if words[0] == "/quit" {
+                    break;
+                }
+                match self.execute_command(&words) {
+                    Ok(message) => writeln!(output, "{}", message)?,
+                    Err(message) => writeln!(output, "ERROR - {}", message)?,
+                }
+                continue;
+            }
+            self.adapter.send_line(&line);
+            if let Some((_, transcript)) = &mut self.transcript {
+                writeln!(transcript, "{}", line)?;
+            }
+        }
+        if let Some((_, transcript)) = &mut self.transcript {
+            transcript.flush()?;
+        }
+        return Ok(());
+    }
+
+    /// Runs the program until it needs input, writing its output (and logging it to the
+    /// transcript). Non-ASCII values are shown on their own lines. Returns false if the program
+    /// halted or faulted, so no more input can be given to it.
+    fn show_program_output<W: Write>(&mut self, output: &mut W) -> io::Result<bool> {
+        let result = self.adapter.read_until_prompt();
+        let mut text = match &result {
+            Ok(text) => text.clone(),
+            Err(_) => self.adapter.take_text(),
+        };
+        for value in self.adapter.take_values() {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&format!("[value {}]\n", value));
+        }
+        match &result {
+            Err(fault) => text.push_str(&format!("[fault: {}]\n", fault)),
+            Ok(_) if self.adapter.get_machine().has_halted() => text.push_str("[halted]\n"),
+            Ok(_) => (),
+        }
+        write!(output, "{}", text)?;
+        output.flush()?;
+        if let Some((_, transcript)) = &mut self.transcript {
+            write!(transcript, "{}", text)?;
+        }
+        return Ok(result.is_ok() && !self.adapter.get_machine().has_halted());
+    }
+
+    /// Executes a single console command, returning the message to display or an error message.
+    fn execute_command(&mut self, words: &[&str]) -> Result<String, String> {
+        let path = words.get(1).map(|p| p.to_string());
+        match (words[0], path) {
+            ("/save", Some(path)) => {
+                self.adapter
+                    .get_machine()
+                    .save_snapshot(path.clone())
+                    .map_err(|e| e.to_string())?;
+                return Ok(format!("Saved snapshot to {}", path));
+            }
+            ("/load", Some(path)) => {
+                let machine =
+                    IntcodeMachine::load_snapshot(path.clone()).map_err(|e| e.to_string())?;
+                self.adapter = AsciiAdapter::new(machine);
+                return Ok(format!("Loaded snapshot from {}", path));
+            }
+            ("/log", Some(path)) => {
+                self.start_transcript(path.clone())
+                    .map_err(|e| e.to_string())?;
+                return Ok(format!("Logging transcript to {}", path));
+            }
+            ("/nolog", _) => {
+                self.end_transcript().map_err(|e| e.to_string())?;
+                return Ok(String::from("Stopped logging transcript"));
+            }
+            ("/help", _) => return Ok(String::from(CONSOLE_HELP)),
+            ("/save", None) | ("/load", None) | ("/log", None) => {
+                return Err(format!("Missing path for command: {}", words[0]));
+            }
+            (other, _) => return Err(format!("Unknown command: {} (try \"/help\")", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::fs::TempFile;
+    use super::super::assemble;
+    use super::*;
+    use std::collections::VecDeque;
+    use std::io::Cursor;
+
+    /// Program that shows a prompt, echoes back each line read and halts on an empty line.
+    fn get_echo_program() -> Vec<i64> {
+        return assemble(
+            "
+            prompt: out #62          ; '>'
+                    out #32
+                    in char
+                    eq char, #10, flag
+                    jt flag, #end
+            loop:   out char
+                    in char
+                    eq char, #10, flag
+                    jf flag, #loop
+                    out #10
+                    jt #1, #prompt
+            end:    out #1000
+                    hlt
+            char:   data 0
+            flag:   data 0
+            ",
+        )
+        .unwrap();
+    }
+
+    /// Runs a console session with the given input, returning the output.
+    fn run_session(console: &mut Console, input: &str) -> String {
+        let mut output = Vec::<u8>::new();
+        console.run(Cursor::new(input), &mut output).unwrap();
+        return String::from_utf8(output).unwrap();
+    }
+
+    #[test]
+    fn test_console_session() {
+        let machine = IntcodeMachine::new(get_echo_program(), VecDeque::new());
+        let mut console = Console::new(machine);
+        let output = run_session(&mut console, "hi\n/bad\n\n");
+        let expected =
+            "> hi\n> ERROR - Unknown command: /bad (try \"/help\")\n[value 1000]\n[halted]\n";
+        assert_eq!(expected, output);
+        assert!(console.get_machine().has_halted());
+    }
+
+    #[test]
+    fn test_console_snapshot_and_transcript() {
+        let snapshot_file = TempFile::new("console_snapshot");
+        let transcript_file = TempFile::new("console_transcript");
+        let snapshot = snapshot_file.get_filename();
+        let transcript = transcript_file.get_filename();
+        let machine = IntcodeMachine::new(get_echo_program(), VecDeque::new());
+        let mut console = Console::new(machine);
+        let input = format!("/log {}\none\n/save {}\ntwo\n/quit\n", transcript, snapshot);
+        run_session(&mut console, &input);
+        // Continue from the saved snapshot in a new console, running a program waiting for input
+        let machine = IntcodeMachine::new(vec![3, 0, 99], VecDeque::new());
+        let mut console = Console::new(machine);
+        let output = run_session(&mut console, &format!("/load {}\nthree\n/quit\n", snapshot));
+        let transcript_text = std::fs::read_to_string(&transcript).unwrap();
+        assert!(output.ends_with(&format!("Loaded snapshot from {}\nthree\n> ", snapshot)));
+        // Lines sent are logged along with the output, which echoes them back
+        assert_eq!("one\none\n> two\ntwo\n> ", transcript_text);
+    }
+}
@@ -0,0 +1,68 @@
+use super::memory::IntcodeMemory;
+
+/// Detects an Intcode machine returning to exactly the same state (program counter, relative base
+/// and memory) without any input or output in between, which means it will loop forever.
+///
+/// Uses Brent's algorithm, so only one earlier state is kept at a time - it is replaced after a
+/// power of two instructions, and a cycle is found within a few times its length of starting. The
+/// memory kept shares its pages with the machine, copying them only once the machine writes to
+/// them, so checking a state only compares the contents of the pages written since it was saved.
+#[derive(Clone, Default)]
+pub struct CycleDetector {
+    saved: Option<(usize, i64, IntcodeMemory)>,
+    power: u64,
+    steps: u64,
+}
+
+impl CycleDetector {
+    /// Creates a new detector, with no earlier states recorded.
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Forgets the earlier states recorded, e.g. because input or output has occurred.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Records the state of the machine after it has executed an instruction. Returns true if the
+    /// machine was in the same state earlier.
+    pub fn check(&mut self, pc: usize, relative_base: i64, memory: &IntcodeMemory) -> bool {
+        if let Some((saved_pc, saved_relative_base, saved_memory)) = &self.saved {
+            // Compare the cheap parts of the state first
+            if *saved_pc == pc && *saved_relative_base == relative_base && saved_memory == memory {
+                return true;
+            }
+        }
+        self.steps += 1;
+        if self.steps >= self.power {
+            self.saved = Some((pc, relative_base, memory.clone()));
+            self.power = (self.power * 2).max(1);
+            self.steps = 0;
+        }
+        return false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::memory::MemoryLimits;
+    use super::*;
+
+    #[test]
+    fn test_cycle_detector() {
+        let mut memory = IntcodeMemory::new(vec![0], MemoryLimits::default());
+        let mut detector = CycleDetector::new();
+        // Counting up never repeats
+        for value in 0..100 {
+            memory.write(0, value).unwrap();
+            assert!(!detector.check(0, 0, &memory));
+        }
+        // Cycling between three states is found once the next earlier state (after 128 steps) is
+        // saved
+        let found = (0..40).any(|i| detector.check(i % 3, 0, &memory));
+        assert!(found);
+        detector.reset();
+        assert!(!detector.check(0, 0, &memory));
+    }
+}
@@ -0,0 +1,554 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::io;
+use std::io::{BufRead, Write};
+
+use super::fault::MachineFault;
+use super::instruction::Instruction;
+use super::run_state::RunState;
+use super::IntcodeMachine;
+
+/// Maximum number of values shown for each queue in the register view.
+const MAX_QUEUE_VALUES_SHOWN: usize = 10;
+
+/// Maximum number of memory cells or instructions shown by a single "x" or "list" command.
+const MAX_COUNT_SHOWN: usize = 1000;
+
+/// Help text displayed by the "help" command of the debugger REPL.
+const REPL_HELP: &str = "Commands:
+    s, step [n]              Execute the next n instructions (default 1)
+    c, continue              Run until a breakpoint, watchpoint, halt, fault or input is needed
+    b, break <addr>          Set a breakpoint on an address
+    d, delete <addr>         Remove the breakpoint on an address
+    w, watch <addr>          Stop whenever the value at an address changes
+    u, unwatch <addr>        Remove the watchpoint on an address
+    r, regs                  Show pc, relative base and the input/output queues
+    x <addr> [n]             Examine n memory cells starting at an address (default 1)
+    set <addr> <value>       Patch the memory cell at an address
+    l, list [addr] [n]       Disassemble n instructions starting at an address (default pc, 5)
+    i, input <value>...      Add values to the input queue
+    o, output                Show and clear the output queue
+    q, quit                  Exit the debugger";
+
+/// Reason the debugger stopped executing the program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DebugEvent {
+    /// A single instruction was executed without anything else of note occurring.
+    Stepped,
+    /// An instruction output the given value, which has been left in the output queue.
+    Output(i64),
+    /// Execution reached an address with a breakpoint set on it.
+    Breakpoint(usize),
+    /// An instruction changed the value of a watched memory cell.
+    Watchpoint {
+        address: usize,
+        old_value: i64,
+        new_value: i64,
+    },
+    /// Machine has reached an input instruction and the input queue is empty.
+    NeedsInput,
+    /// Machine has executed a HALT instruction.
+    Halted,
+    /// Machine has encountered a fault and can't execute any further.
+    Fault(MachineFault),
+    /// Machine has executed its instruction budget.
+    BudgetExhausted,
+    /// Machine has returned to an earlier state without any input or output in between.
+    CycleDetected,
+}
+
+/// Debugger that controls the execution of an IntcodeMachine, stopping it at breakpoints and when
+/// watched memory cells change.
+pub struct Debugger {
+    machine: IntcodeMachine,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<usize>,
+}
+
+impl Debugger {
+    /// Creates a new debugger controlling the given machine.
+    pub fn new(machine: IntcodeMachine) -> Self {
+        Self {
+            machine,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+        }
+    }
+
+    /// Returns a reference to the machine being debugged.
+    pub fn get_machine(&self) -> &IntcodeMachine {
+        return &self.machine;
+    }
+
+    /// Returns a mutable reference to the machine being debugged, e.g. to add input or patch memory.
+    pub fn get_machine_mut(&mut self) -> &mut IntcodeMachine {
+        return &mut self.machine;
+    }
+
+    /// Consumes the debugger and returns the machine being debugged.
+    pub fn into_machine(self) -> IntcodeMachine {
+        return self.machine;
+    }
+
+    /// Sets a breakpoint on the given address. Returns false if one was already set.
+    pub fn add_breakpoint(&mut self, address: usize) -> bool {
+        return self.breakpoints.insert(address);
+    }
+
+    /// Removes the breakpoint on the given address. Returns false if none was set.
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        return self.breakpoints.remove(&address);
+    }
+
+    /// Sets a watchpoint on the given memory address. Returns false if one was already set.
+    pub fn add_watchpoint(&mut self, address: usize) -> bool {
+        return self.watchpoints.insert(address);
+    }
+
+    /// Removes the watchpoint on the given memory address. Returns false if none was set.
+    pub fn remove_watchpoint(&mut self, address: usize) -> bool {
+        return self.watchpoints.remove(&address);
+    }
+
+    /// Executes a single instruction and returns what happened.
+    pub fn step(&mut self) -> DebugEvent {
+        let watched_values: Vec<(usize, i64)> = self
+            .watchpoints
+            .iter()
+            .map(|address| (*address, self.machine.peek_memory(*address)))
+            .collect();
+        let state = match self.machine.step_and_keep_output() {
+            Ok(s) => s,
+            Err(fault) => return DebugEvent::Fault(fault),
+        };
+        match state {
+            Some(RunState::Halted) => return DebugEvent::Halted,
+            Some(RunState::NeedsInput) => return DebugEvent::NeedsInput,
+            Some(RunState::Output(value)) => return DebugEvent::Output(value),
+            Some(RunState::BudgetExhausted) => return DebugEvent::BudgetExhausted,
+            Some(RunState::CycleDetected) => return DebugEvent::CycleDetected,
+            None => (),
+        }
+        for (address, old_value) in watched_values {
+            let new_value = self.machine.peek_memory(address);
+            if new_value != old_value {
+                return DebugEvent::Watchpoint {
+                    address,
+                    old_value,
+                    new_value,
+                };
+            }
+        }
+        return DebugEvent::Stepped;
+    }
+
+    /// Runs the program until it reaches a breakpoint, changes a watched memory cell, halts, needs
+    /// input or faults. The instruction at the current program counter is always executed, so
+    /// execution can be continued from a breakpoint.
+    pub fn continue_execution(&mut self) -> DebugEvent {
+        loop {
+            match self.step() {
+                DebugEvent::Stepped | DebugEvent::Output(_) => (),
+                event => return event,
+            }
+            let pc = self.machine.get_program_counter();
+            if self.breakpoints.contains(&pc) {
+                return DebugEvent::Breakpoint(pc);
+            }
+        }
+    }
+
+    /// Returns a description of the machine registers: program counter, relative base and the
+    /// input and output queues.
+    pub fn get_register_view(&self) -> String {
+        return format!(
+            "pc: {}  rb: {}  input: {}  output: {}",
+            self.machine.get_program_counter(),
+            self.machine.get_relative_base(),
+            format_queue(&self.machine.get_input_vec()),
+            format_queue(&self.machine.get_output_vec())
+        );
+    }
+
+    /// Returns the disassembly of up to count instructions starting at the given address, stopping
+    /// early at any value that isn't a valid instruction.
+    pub fn get_listing(&self, address: usize, count: usize) -> Vec<String> {
+        let mut lines = Vec::<String>::new();
+        let mut address = address;
+        for _ in 0..count {
+            let values: Vec<i64> = (0..4)
+                .filter_map(|offset| address.checked_add(offset))
+                .map(|a| self.machine.peek_memory(a))
+                .collect();
+            let marker = if address == self.machine.get_program_counter() {
+                "=>"
+            } else if self.breakpoints.contains(&address) {
+                " *"
+            } else {
+                "  "
+            };
+            match Instruction::decode(&values, 0) {
+                Some(instruction) => {
+                    lines.push(format!("{} {:>6}: {}", marker, address, instruction));
+                    address = match address.checked_add(instruction.get_length()) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                }
+                None => {
+                    lines.push(format!("{} {:>6}: data {}", marker, address, values[0]));
+                    break;
+                }
+            }
+        }
+        return lines;
+    }
+
+    /// Checks that the count of addresses starting at the given address all lie within the memory
+    /// limits of the machine, returning an error for the REPL if they don't.
+    fn check_address_range(&self, address: usize, count: usize) -> Result<(), String> {
+        let max_address = self.machine.get_memory_limits().max_address;
+        match address.checked_add(count) {
+            Some(end) if end <= max_address.saturating_add(1) => return Ok(()),
+            _ => return Err(format!("Bad address: {}", address)),
+        }
+    }
+
+    /// Runs an interactive debugging session, reading commands from the input and writing results
+    /// to the output until the "quit" command is given or the input ends.
+    pub fn run_repl<R: BufRead, W: Write>(&mut self, input: R, output: &mut W) -> io::Result<()> {
+        writeln!(
+            output,
+            "{}",
+            self.get_listing(self.machine.get_program_counter(), 1)[0]
+        )?;
+        write!(output, "(icdb) ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.is_empty() {
+                write!(output, "(icdb) ")?;
+                output.flush()?;
+                continue;
+            }
+            if words[0] == "q" || words[0] == "quit" {
+                return Ok(());
+            }
+            match self.execute_repl_command(&words) {
+                Ok(lines) => {
+                    for l in lines {
+                        writeln!(output, "{}", l)?;
+                    }
+                }
+                Err(message) => writeln!(output, "ERROR - {}", message)?,
+            }
+            write!(output, "(icdb) ")?;
+            output.flush()?;
+        }
+        return Ok(());
+    }
+
+    /// Executes a single REPL command, returning the lines to display or an error message.
+    fn execute_repl_command(&mut self, words: &[&str]) -> Result<Vec<String>, String> {
+        let args = &words[1..];
+        match words[0] {
+            "s" | "step" => {
+                let count = parse_optional_arg(args, 0, 1)?;
+                let mut lines = Vec::<String>::new();
+                for _ in 0..count {
+                    let event = self.step();
+                    if let Some(description) = describe_event(&event) {
+                        lines.push(description);
+                    }
+                    match event {
+                        DebugEvent::Stepped | DebugEvent::Output(_) => (),
+                        _ => break,
+                    }
+                }
+                lines.extend(self.get_listing(self.machine.get_program_counter(), 1));
+                return Ok(lines);
+            }
+            "c" | "continue" => {
+                let event = self.continue_execution();
+                let mut lines: Vec<String> = describe_event(&event).into_iter().collect();
+                lines.extend(self.get_listing(self.machine.get_program_counter(), 1));
+                return Ok(lines);
+            }
+            "b" | "break" => {
+                let address = parse_required_arg(args, 0)?;
+                self.add_breakpoint(address);
+                return Ok(vec![format!("Breakpoint set at {}", address)]);
+            }
+            "d" | "delete" => {
+                let address = parse_required_arg(args, 0)?;
+                if !self.remove_breakpoint(address) {
+                    return Err(format!("No breakpoint at {}", address));
+                }
+                return Ok(vec![format!("Breakpoint removed from {}", address)]);
+            }
+            "w" | "watch" => {
+                let address = parse_required_arg(args, 0)?;
+                self.add_watchpoint(address);
+                return Ok(vec![format!("Watching {}", address)]);
+            }
+            "u" | "unwatch" => {
+                let address = parse_required_arg(args, 0)?;
+                if !self.remove_watchpoint(address) {
+                    return Err(format!("No watchpoint at {}", address));
+                }
+                return Ok(vec![format!("Stopped watching {}", address)]);
+            }
+            "r" | "regs" => {
+                let mut lines = vec![self.get_register_view()];
+                if let Some(fault) = self.machine.get_fault() {
+                    lines.push(format!("fault: {}", fault));
+                }
+                return Ok(lines);
+            }
+            "x" => {
+                let address = parse_required_arg(args, 0)?;
+                let count = parse_count_arg(args, 1, 1)?;
+                self.check_address_range(address, count)?;
+                let values: Vec<String> = (address..address + count)
+                    .map(|a| self.machine.peek_memory(a).to_string())
+                    .collect();
+                return Ok(vec![format!("{:>6}: {}", address, values.join(", "))]);
+            }
+            "set" => {
+                let address = parse_required_arg(args, 0)?;
+                let value = parse_value_arg(args, 1)?;
+                if let Err(kind) = self.machine.poke_memory(address, value) {
+                    return Err(format!("Can't write to {} ({:?})", address, kind));
+                }
+                return Ok(vec![format!("{:>6}: {}", address, value)]);
+            }
+            "l" | "list" => {
+                let pc = self.machine.get_program_counter();
+                let address = parse_optional_arg(args, 0, pc)?;
+                let count = parse_count_arg(args, 1, 5)?;
+                self.check_address_range(address, 1)?;
+                return Ok(self.get_listing(address, count));
+            }
+            "i" | "input" => {
+                if args.is_empty() {
+                    return Err(String::from("No input values given."));
+                }
+                for i in 0..args.len() {
+                    let value = parse_value_arg(args, i)?;
+                    self.machine.add_input(value);
+                }
+                return Ok(vec![self.get_register_view()]);
+            }
+            "o" | "output" => {
+                let output = Vec::from(self.machine.get_output_vec());
+                self.machine.clear_output();
+                return Ok(vec![format!("{:?}", output)]);
+            }
+            "h" | "help" => return Ok(vec![String::from(REPL_HELP)]),
+            other => return Err(format!("Unknown command: {} (try \"help\")", other)),
+        }
+    }
+}
+
+/// Formats the queue for the register view. Long queues are shortened to their last few values.
+fn format_queue(queue: &VecDeque<i64>) -> String {
+    if queue.len() <= MAX_QUEUE_VALUES_SHOWN {
+        return format!("{:?}", Vec::from(queue.clone()));
+    }
+    let last: Vec<String> = queue
+        .iter()
+        .skip(queue.len() - MAX_QUEUE_VALUES_SHOWN)
+        .map(|v| v.to_string())
+        .collect();
+    return format!("[..., {}] ({} values)", last.join(", "), queue.len());
+}
+
+/// Describes the debug event for display in the REPL. Returns None for plain steps.
+fn describe_event(event: &DebugEvent) -> Option<String> {
+    match event {
+        DebugEvent::Stepped => return None,
+        DebugEvent::Output(value) => return Some(format!("Output: {}", value)),
+        DebugEvent::Breakpoint(address) => return Some(format!("Breakpoint at {}", address)),
+        DebugEvent::Watchpoint {
+            address,
+            old_value,
+            new_value,
+        } => {
+            return Some(format!(
+                "Watchpoint at {}: {} -> {}",
+                address, old_value, new_value
+            ));
+        }
+        DebugEvent::NeedsInput => return Some(String::from("Waiting for input")),
+        DebugEvent::Halted => return Some(String::from("Halted")),
+        DebugEvent::Fault(fault) => return Some(format!("Fault: {}", fault)),
+        DebugEvent::BudgetExhausted => return Some(String::from("Instruction budget exhausted")),
+        DebugEvent::CycleDetected => return Some(String::from("Cycle detected")),
+    }
+}
+
+/// Parses the address or count argument at the given index.
+fn parse_required_arg(args: &[&str], index: usize) -> Result<usize, String> {
+    match args.get(index) {
+        Some(arg) => {
+            return arg
+                .parse::<usize>()
+                .map_err(|_| format!("Bad number: {}", arg))
+        }
+        None => return Err(String::from("Missing argument.")),
+    }
+}
+
+/// Parses the address or count argument at the given index, using the default if it is missing.
+fn parse_optional_arg(args: &[&str], index: usize, default: usize) -> Result<usize, String> {
+    if index >= args.len() {
+        return Ok(default);
+    }
+    return parse_required_arg(args, index);
+}
+
+/// Parses the count of cells or instructions to show at the given index, using the default if it
+/// is missing.
+fn parse_count_arg(args: &[&str], index: usize, default: usize) -> Result<usize, String> {
+    let count = parse_optional_arg(args, index, default)?;
+    if count > MAX_COUNT_SHOWN {
+        return Err(format!("Count above {}: {}", MAX_COUNT_SHOWN, count));
+    }
+    return Ok(count);
+}
+
+/// Parses the (possibly negative) value argument at the given index.
+fn parse_value_arg(args: &[&str], index: usize) -> Result<i64, String> {
+    match args.get(index) {
+        Some(arg) => {
+            return arg
+                .parse::<i64>()
+                .map_err(|_| format!("Bad value: {}", arg))
+        }
+        None => return Err(String::from("Missing argument.")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assembler::assemble;
+    use super::*;
+
+    /// Creates a debugger for a program that counts down from 3, outputting each value.
+    fn create_countdown_debugger() -> Debugger {
+        let program = assemble(
+            "
+            loop:  add count, #-1, count
+                   out count
+                   jt count, #loop
+                   hlt
+            count: data 3
+            ",
+        )
+        .unwrap();
+        return Debugger::new(IntcodeMachine::new(program, VecDeque::new()));
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = create_countdown_debugger();
+        debugger.add_breakpoint(6);
+        assert_eq!(DebugEvent::Breakpoint(6), debugger.continue_execution());
+        assert_eq!(
+            VecDeque::from(vec![2]),
+            debugger.get_machine().get_output_vec()
+        );
+        // Continuing from a breakpoint executes the instruction at it
+        assert_eq!(DebugEvent::Breakpoint(6), debugger.continue_execution());
+        assert!(debugger.remove_breakpoint(6));
+        assert_eq!(DebugEvent::Halted, debugger.continue_execution());
+        assert_eq!(
+            VecDeque::from(vec![2, 1, 0]),
+            debugger.get_machine().get_output_vec()
+        );
+    }
+
+    #[test]
+    fn test_watchpoints_and_step() {
+        let mut debugger = create_countdown_debugger();
+        debugger.add_watchpoint(10);
+        let expected = DebugEvent::Watchpoint {
+            address: 10,
+            old_value: 3,
+            new_value: 2,
+        };
+        assert_eq!(expected, debugger.continue_execution());
+        assert_eq!(DebugEvent::Output(2), debugger.step());
+        assert_eq!(DebugEvent::Stepped, debugger.step());
+        assert_eq!(
+            "pc: 0  rb: 0  input: []  output: [2]",
+            debugger.get_register_view()
+        );
+    }
+
+    #[test]
+    fn test_format_queue() {
+        assert_eq!("[1, 2]", format_queue(&VecDeque::from(vec![1, 2])));
+        let queue: VecDeque<i64> = (0..12).collect();
+        assert_eq!(
+            "[..., 2, 3, 4, 5, 6, 7, 8, 9, 10, 11] (12 values)",
+            format_queue(&queue)
+        );
+    }
+
+    #[test]
+    fn test_fault_event() {
+        let machine = IntcodeMachine::new(vec![42], VecDeque::new());
+        let mut debugger = Debugger::new(machine);
+        match debugger.continue_execution() {
+            DebugEvent::Fault(fault) => assert_eq!(0, fault.pc),
+            event => panic!("Expected fault, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_repl_session() {
+        let mut debugger = create_countdown_debugger();
+        let commands = "b 6\nc\nx 10\nset 10 1\nregs\nl 0 2\nc\nc\nq\nc\n";
+        let mut output = Vec::<u8>::new();
+        debugger.run_repl(commands.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = concat!(
+            "=>      0: add 10, #-1, 10\n",
+            "(icdb) Breakpoint set at 6\n",
+            "(icdb) Breakpoint at 6\n",
+            "=>      6: jt 10, #0\n",
+            "(icdb)     10: 2\n",
+            "(icdb)     10: 1\n",
+            "(icdb) pc: 6  rb: 0  input: []  output: [2]\n",
+            "(icdb)         0: add 10, #-1, 10\n",
+            "        4: out 10\n",
+            "(icdb) Breakpoint at 6\n",
+            "=>      6: jt 10, #0\n",
+            "(icdb) Halted\n",
+            "=>      9: hlt\n",
+            "(icdb) ",
+        );
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn test_bad_addresses() {
+        let mut debugger = create_countdown_debugger();
+        let max = usize::MAX.to_string();
+        for command in [vec!["x", &max, "2"], vec!["l", &max]].iter() {
+            let expected = Err(format!("Bad address: {}", command[1]));
+            assert_eq!(expected, debugger.execute_repl_command(command));
+        }
+        // Counts are capped rather than showing billions of values
+        for command in [vec!["x", "0", &max], vec!["l", "0", "1001"]].iter() {
+            let expected = Err(format!("Count above 1000: {}", command[2]));
+            assert_eq!(expected, debugger.execute_repl_command(command));
+        }
+        let lines = debugger.execute_repl_command(&["x", "0", "1000"]).unwrap();
+        assert_eq!(1, lines.len());
+        // Listing stops at the last address rather than overflowing
+        let lines = debugger.get_listing(usize::MAX, 3);
+        assert_eq!(vec![format!("   {}: data 0", usize::MAX)], lines);
+    }
+}
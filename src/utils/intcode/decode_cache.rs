@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use super::instruction::{Opcode, ParamMode};
+use super::memory::IntcodeMemory;
+
+/// Instructions at addresses from this one upwards are decoded every time they are executed, to
+/// stop programs that jump around far memory from growing the cache without bound.
+const MAX_CACHED_ADDRESS: usize = 1 << 16;
+
+/// Instruction decoded into the form used by the execution core of the machine. Only instructions
+/// that decode without a fault are represented - anything else is left to the uncached core, so
+/// that it reports the fault.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    pub opcode: Opcode,
+    pub modes: [ParamMode; 3],
+    pub params: [i64; 3],
+}
+
+impl DecodedInstruction {
+    /// Decodes the instruction held in memory at the given address, using the same rules as the
+    /// uncached core (so digits beyond the modes of the parameters used are ignored). Returns None
+    /// if the opcode is unknown, a mode used by the instruction is bad, an address written to is
+    /// given in immediate mode, or the instruction extends past the highest address allowed.
+    pub fn decode(memory: &IntcodeMemory, address: usize) -> Option<Self> {
+        let arg = memory.read(address);
+        let opcode = Opcode::from_code(arg % 100)?;
+        let num_params = opcode.get_param_count();
+        if address + num_params > memory.get_limits().max_address {
+            return None;
+        }
+        let mut decoded = Self {
+            opcode,
+            modes: [ParamMode::Position; 3],
+            params: [0; 3],
+        };
+        let mut divisor = 100;
+        for i in 0..num_params {
+            decoded.modes[i] = ParamMode::from_code((arg / divisor) % 10)?;
+            decoded.params[i] = memory.read(address + 1 + i);
+            divisor *= 10;
+        }
+        if opcode.writes_to_memory() && decoded.modes[num_params - 1] == ParamMode::Immediate {
+            return None;
+        }
+        return Some(decoded);
+    }
+}
+
+/// Cache of the decoded instructions of a program, indexed by address. Entries are invalidated
+/// when memory they were decoded from is written to, so self-modifying programs are executed
+/// correctly.
+///
+/// Decoding only depends on the memory at the decoded address, so any address can be decoded
+/// ahead of being executed. Entries are shared copy-on-write between clones of the cache, so
+/// forks of a machine don't have to decode the program again.
+#[derive(Clone)]
+pub struct DecodeCache {
+    enabled: bool,
+    entries: Arc<Vec<Option<DecodedInstruction>>>,
+}
+
+impl DecodeCache {
+    /// Creates a new cache holding the decoded instruction at every address of the program
+    /// currently loaded in memory (or as much of it as is below the highest cached address).
+    pub fn new_filled(memory: &IntcodeMemory) -> Self {
+        let len = memory.len().min(MAX_CACHED_ADDRESS);
+        let entries = (0..len)
+            .map(|address| DecodedInstruction::decode(memory, address))
+            .collect();
+        Self {
+            enabled: true,
+            entries: Arc::new(entries),
+        }
+    }
+
+    /// Checks if instructions should be executed through the cache.
+    pub fn is_enabled(&self) -> bool {
+        return self.enabled;
+    }
+
+    /// Enables or disables the cache. Disabling the cache also clears it, and it is refilled as
+    /// instructions are executed once enabled again.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.entries = Arc::new(vec![]);
+        }
+        self.enabled = enabled;
+    }
+
+    /// Returns the decoded instruction at the given address, decoding and caching it if needed.
+    /// Returns None if the instruction can't be decoded (see DecodedInstruction::decode()).
+    pub fn get(&mut self, memory: &IntcodeMemory, address: usize) -> Option<DecodedInstruction> {
+        if let Some(Some(decoded)) = self.entries.get(address) {
+            return Some(*decoded);
+        }
+        let decoded = DecodedInstruction::decode(memory, address)?;
+        if address < MAX_CACHED_ADDRESS {
+            let entries = Arc::make_mut(&mut self.entries);
+            if address >= entries.len() {
+                entries.resize(address + 1, None);
+            }
+            entries[address] = Some(decoded);
+        }
+        return Some(decoded);
+    }
+
+    /// Invalidates any cached instruction that includes the given address. Instructions are at most
+    /// four values long, so only those starting up to three addresses before it are affected.
+    pub fn invalidate(&mut self, address: usize) {
+        let end = (address + 1).min(self.entries.len());
+        let start = address.saturating_sub(3).min(end);
+        // Check before writing, to avoid copying entries shared with a fork unless needed
+        if self.entries[start..end].iter().any(|entry| entry.is_some()) {
+            let entries = Arc::make_mut(&mut self.entries);
+            for entry in entries[start..end].iter_mut() {
+                *entry = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::memory::MemoryLimits;
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let memory =
+            IntcodeMemory::new(vec![21101, 5, -6, 7, 99, 1103, 3], MemoryLimits::default());
+        let decoded = DecodedInstruction::decode(&memory, 0).unwrap();
+        assert_eq!(Opcode::Add, decoded.opcode);
+        let modes = [
+            ParamMode::Immediate,
+            ParamMode::Immediate,
+            ParamMode::Relative,
+        ];
+        assert_eq!(modes, decoded.modes);
+        assert_eq!([5, -6, 7], decoded.params);
+        assert_eq!(
+            Opcode::Halt,
+            DecodedInstruction::decode(&memory, 4).unwrap().opcode
+        );
+        // Immediate mode for an address written to is left to the uncached core
+        assert_eq!(None, DecodedInstruction::decode(&memory, 5));
+        // Unknown opcode
+        assert_eq!(None, DecodedInstruction::decode(&memory, 2));
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let mut memory = IntcodeMemory::new(vec![1, 0, 0, 0, 99], MemoryLimits::default());
+        let mut cache = DecodeCache::new_filled(&memory);
+        assert_eq!(Opcode::Add, cache.get(&memory, 0).unwrap().opcode);
+        // Rewrite the add instruction as a multiply, which is only seen once invalidated
+        memory.write(0, 2).unwrap();
+        assert_eq!(Opcode::Add, cache.get(&memory, 0).unwrap().opcode);
+        cache.invalidate(3);
+        assert_eq!(Opcode::Multiply, cache.get(&memory, 0).unwrap().opcode);
+        // Writes well past the cached instructions don't affect them
+        cache.invalidate(1000);
+        assert_eq!(Opcode::Multiply, cache.get(&memory, 0).unwrap().opcode);
+    }
+
+    #[test]
+    fn test_shared_entries() {
+        let memory = IntcodeMemory::new(vec![1, 0, 0, 0, 99], MemoryLimits::default());
+        let cache = DecodeCache::new_filled(&memory);
+        assert_eq!(5, cache.entries.len());
+        let mut fork = cache.clone();
+        assert!(Arc::ptr_eq(&cache.entries, &fork.entries));
+        // Writing to an address with no instructions decoded around it leaves entries shared
+        fork.invalidate(4000);
+        assert!(Arc::ptr_eq(&cache.entries, &fork.entries));
+        fork.invalidate(2);
+        assert!(!Arc::ptr_eq(&cache.entries, &fork.entries));
+        assert!(cache.entries[0].is_some());
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Stdin, Stdout, Write};
+use std::sync::mpsc::{Receiver, Sender};
+
+use super::super::error::AocError;
+use super::super::fs;
+use super::run_state::RunState;
+use super::IntcodeMachine;
+
+/// Source of the values read by the input instructions of an Intcode machine.
+pub trait InputDevice {
+    /// Returns the next input value, or None if no value is available. The machine stops with
+    /// RunState::NeedsInput when no value is available, and asks the device again when resumed.
+    fn read_input(&mut self) -> Option<i64>;
+}
+
+/// Destination of the values written by the output instructions of an Intcode machine.
+pub trait OutputDevice {
+    /// Accepts the next value output by the machine.
+    fn write_output(&mut self, value: i64);
+
+    /// Completes any output not yet written by the device, returning the first error that
+    /// occurred while writing. Devices that can't fail don't need to implement this.
+    fn finish(&mut self) -> Result<(), AocError> {
+        return Ok(());
+    }
+}
+
+/// Queues are the default devices of a machine - input values are read from the front and output
+/// values added to the back.
+impl InputDevice for VecDeque<i64> {
+    fn read_input(&mut self) -> Option<i64> {
+        return self.pop_front();
+    }
+}
+
+impl OutputDevice for VecDeque<i64> {
+    fn write_output(&mut self, value: i64) {
+        self.push_back(value);
+    }
+}
+
+/// Input device that calls a closure for each value.
+pub struct FnInput<F>(pub F);
+
+impl<F: FnMut() -> Option<i64>> InputDevice for FnInput<F> {
+    fn read_input(&mut self) -> Option<i64> {
+        return (self.0)();
+    }
+}
+
+/// Output device that calls a closure with each value.
+pub struct FnOutput<F>(pub F);
+
+impl<F: FnMut(i64)> OutputDevice for FnOutput<F> {
+    fn write_output(&mut self, value: i64) {
+        (self.0)(value);
+    }
+}
+
+/// Channel receivers don't block - a machine waiting for a value that hasn't been sent yet stops
+/// with RunState::NeedsInput.
+impl InputDevice for Receiver<i64> {
+    fn read_input(&mut self) -> Option<i64> {
+        return self.try_recv().ok();
+    }
+}
+
+/// Values sent after the receiver has been dropped are discarded.
+impl OutputDevice for Sender<i64> {
+    fn write_output(&mut self, value: i64) {
+        let _ = self.send(value);
+    }
+}
+
+/// Input device reading comma- or whitespace-separated values from a reader, such as a file or
+/// stdin, a line at a time.
+///
+/// Once the reader is exhausted, or a read or parse error occurs, no more values are returned. The
+/// error is kept and can be retrieved with get_error().
+pub struct ReaderInput<R> {
+    reader: R,
+    name: String,
+    line: usize,
+    values: VecDeque<i64>,
+    error: Option<AocError>,
+}
+
+impl<R: BufRead> ReaderInput<R> {
+    /// Creates an input device reading from the given reader. The name is used in errors.
+    pub fn new(reader: R, name: String) -> Self {
+        Self {
+            reader,
+            name,
+            line: 0,
+            values: VecDeque::new(),
+            error: None,
+        }
+    }
+
+    /// Returns the error that stopped the device reading values, if one has occurred.
+    pub fn get_error(&self) -> Option<&AocError> {
+        return self.error.as_ref();
+    }
+
+    /// Reads lines until at least one value has been parsed, the reader is exhausted or an error
+    /// occurs.
+    fn read_values(&mut self) {
+        let mut text = String::new();
+        while self.values.is_empty() && self.error.is_none() {
+            text.clear();
+            match self.reader.read_line(&mut text) {
+                Ok(0) => return,
+                Ok(_) => self.line += 1,
+                Err(e) => {
+                    self.error = Some(AocError::Io {
+                        filename: self.name.clone(),
+                        source: e,
+                    });
+                    return;
+                }
+            }
+            for token in text.split(|c: char| c == ',' || c.is_whitespace()) {
+                if token.is_empty() {
+                    continue;
+                }
+                match token.parse::<i64>() {
+                    Ok(value) => self.values.push_back(value),
+                    Err(e) => {
+                        let column = token.as_ptr() as usize - text.as_ptr() as usize + 1;
+                        let message = format!("bad input value \"{}\" ({})", token, e);
+                        self.error = Some(AocError::parse_error(self.line, column, message));
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ReaderInput<BufReader<File>> {
+    /// Creates an input device reading values from the given file.
+    pub fn open_file(filename: String) -> Result<Self, AocError> {
+        let file = fs::try_open_file(filename.clone())?;
+        return Ok(ReaderInput::new(BufReader::new(file), filename));
+    }
+}
+
+impl ReaderInput<BufReader<Stdin>> {
+    /// Creates an input device reading values from stdin.
+    pub fn stdin() -> Self {
+        return ReaderInput::new(BufReader::new(io::stdin()), String::from("<stdin>"));
+    }
+}
+
+impl<R: BufRead> InputDevice for ReaderInput<R> {
+    fn read_input(&mut self) -> Option<i64> {
+        if self.values.is_empty() {
+            self.read_values();
+        }
+        return self.values.pop_front();
+    }
+}
+
+/// Output device writing each value on its own line to a writer, such as a file or stdout.
+pub struct WriterOutput<W: Write> {
+    writer: W,
+    name: String,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> WriterOutput<W> {
+    /// Creates an output device writing to the given writer. The name is used in errors.
+    pub fn new(writer: W, name: String) -> Self {
+        Self {
+            writer,
+            name,
+            error: None,
+        }
+    }
+
+    /// Writes the text as a line of its own, e.g. a header before the values. Only the first
+    /// error is kept, and is reported by finish().
+    pub fn write_line(&mut self, line: &str) {
+        if self.error.is_none() {
+            self.error = writeln!(self.writer, "{}", line).err();
+        }
+    }
+}
+
+impl WriterOutput<BufWriter<File>> {
+    /// Creates an output device writing values to the given file, which is created (or
+    /// truncated). Call finish() once the machine is done to make sure all values are written.
+    pub fn create_file(filename: String) -> Result<Self, AocError> {
+        let file = fs::try_create_file(filename.clone())?;
+        return Ok(WriterOutput::new(BufWriter::new(file), filename));
+    }
+}
+
+impl WriterOutput<Stdout> {
+    /// Creates an output device writing values to stdout.
+    pub fn stdout() -> Self {
+        return WriterOutput::new(io::stdout(), String::from("<stdout>"));
+    }
+}
+
+impl<W: Write> OutputDevice for WriterOutput<W> {
+    fn write_output(&mut self, value: i64) {
+        self.write_line(&value.to_string());
+    }
+
+    fn finish(&mut self) -> Result<(), AocError> {
+        let result = match self.error.take() {
+            Some(e) => Err(e),
+            None => self.writer.flush(),
+        };
+        return result.map_err(|e| AocError::Write {
+            filename: self.name.clone(),
+            source: e,
+        });
+    }
+}
+
+/// Input device connected to another machine, which is run whenever a value is needed until it
+/// outputs one. No value is returned while the other machine needs input, has halted or has
+/// faulted.
+pub struct MachineInput {
+    machine: IntcodeMachine,
+}
+
+impl MachineInput {
+    /// Creates an input device taking its values from the output of the given machine.
+    pub fn new(machine: IntcodeMachine) -> Self {
+        Self { machine }
+    }
+
+    /// Returns the machine that values are taken from.
+    pub fn get_machine(&self) -> &IntcodeMachine {
+        return &self.machine;
+    }
+
+    /// Returns the machine that values are taken from, e.g. to give it more input.
+    pub fn get_machine_mut(&mut self) -> &mut IntcodeMachine {
+        return &mut self.machine;
+    }
+}
+
+impl InputDevice for MachineInput {
+    fn read_input(&mut self) -> Option<i64> {
+        match self.machine.run() {
+            Ok(RunState::Output(value)) => return Some(value),
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_queue_devices() {
+        let mut queue = VecDeque::from(vec![1, 2]);
+        assert_eq!(Some(1), queue.read_input());
+        queue.write_output(3);
+        assert_eq!(VecDeque::from(vec![2, 3]), queue);
+    }
+
+    #[test]
+    fn test_channel_devices() {
+        let (mut sender, mut receiver) = mpsc::channel();
+        assert_eq!(None, receiver.read_input());
+        sender.write_output(7);
+        assert_eq!(Some(7), receiver.read_input());
+        drop(receiver);
+        // Sending to a dropped receiver is ignored
+        sender.write_output(8);
+    }
+
+    #[test]
+    fn test_reader_input() {
+        let text = "1, 2\n\n-3 4,5\n";
+        let mut device = ReaderInput::new(Cursor::new(text), String::from("test"));
+        let values: Vec<i64> = (0..6).filter_map(|_| device.read_input()).collect();
+        assert_eq!(vec![1, 2, -3, 4, 5], values);
+        assert!(device.get_error().is_none());
+        let mut device = ReaderInput::new(Cursor::new("1\n2 x3\n"), String::from("test"));
+        assert_eq!(Some(1), device.read_input());
+        // Values before the bad one on the line are still read
+        assert_eq!(Some(2), device.read_input());
+        assert_eq!(None, device.read_input());
+        match device.get_error() {
+            Some(AocError::Parse { line, column, .. }) => assert_eq!((2, 3), (*line, *column)),
+            _ => panic!("Expected parse error."),
+        }
+    }
+
+    #[test]
+    fn test_writer_output() {
+        let mut device = WriterOutput::new(Vec::new(), String::from("test"));
+        device.write_line("# values");
+        device.write_output(12);
+        device.write_output(-1);
+        device.finish().unwrap();
+        assert_eq!(b"# values\n12\n-1\n".to_vec(), device.writer);
+    }
+
+    #[test]
+    fn test_machine_input() {
+        // Outputs 3, 2, 1 then halts
+        let program = vec![101, -1, 10, 10, 4, 10, 1005, 10, 0, 99, 4];
+        let mut device = MachineInput::new(IntcodeMachine::new(program, VecDeque::new()));
+        let values: Vec<i64> = (0..5).filter_map(|_| device.read_input()).collect();
+        assert_eq!(vec![3, 2, 1, 0], values);
+        assert!(device.get_machine().has_halted());
+    }
+}
@@ -0,0 +1,263 @@
+use std::fmt;
+
+use super::instruction::{Instruction, Opcode, ParamMode};
+
+/// Maximum number of values displayed on a single data line of a listing.
+const DATA_VALUES_PER_LINE: usize = 8;
+
+/// A single line of a disassembled listing, either a decoded instruction or a run of data values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListingEntry {
+    Instruction {
+        address: usize,
+        values: Vec<i64>,
+        instruction: Instruction,
+    },
+    Data {
+        address: usize,
+        values: Vec<i64>,
+    },
+}
+
+impl fmt::Display for ListingEntry {
+    /// Entries are displayed as the address, the raw memory values and then the decoded
+    /// instruction. Data entries made up of printable ASCII are also displayed as a string.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ListingEntry::Instruction {
+                address,
+                values,
+                instruction,
+            } => {
+                let raw = join_values(values, ",");
+                write!(f, "{:>6}: {:<24} {}", address, raw, instruction)
+            }
+            ListingEntry::Data { address, values } => {
+                let raw = join_values(values, ",");
+                write!(
+                    f,
+                    "{:>6}: {:<24} data {}",
+                    address,
+                    raw,
+                    join_values(values, ", ")
+                )?;
+                if let Some(text) = get_ascii_text(values) {
+                    write!(f, " ; {:?}", text)?;
+                }
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Disassembled listing of an Intcode memory image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Listing {
+    entries: Vec<ListingEntry>,
+}
+
+impl Listing {
+    /// Returns the entries of the listing in address order.
+    pub fn get_entries(&self) -> &[ListingEntry] {
+        return &self.entries;
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for entry in self.entries.iter() {
+            writeln!(f, "{}", entry)?;
+        }
+        return Ok(());
+    }
+}
+
+/// Disassembles the given memory image, with execution starting from address 0.
+pub fn disassemble(memory: &[i64]) -> Listing {
+    return disassemble_from(memory, &[0]);
+}
+
+/// Disassembles the given memory image, with execution starting from each of the entry points.
+///
+/// Code is found by following execution from the entry points: through each instruction to the
+/// next (except after a halt or an unconditional jump), and to the target of any jump with an
+/// immediate-mode target. Return addresses pushed with the usual "add #addr, #0, rb+0" (or "mul
+/// #addr, #1, rb+0") idiom are followed as well, so code after a call is found. Jumps to computed
+/// targets can't be followed, so any code only reachable through them is listed as data unless
+/// given as an extra entry point. Everything not found to be code is listed as data.
+pub fn disassemble_from(memory: &[i64], entry_points: &[usize]) -> Listing {
+    let mut decoded: Vec<Option<Instruction>> = vec![None; memory.len()];
+    let mut covered = vec![false; memory.len()];
+    let mut to_visit: Vec<usize> = entry_points.iter().rev().cloned().collect();
+    while let Some(address) = to_visit.pop() {
+        if address >= memory.len() || covered[address] {
+            continue;
+        }
+        let instruction = match Instruction::decode(memory, address) {
+            Some(i) => i,
+            None => continue,
+        };
+        // Don't decode instructions that would overlap with one already found
+        let end = address + instruction.get_length();
+        if covered[address..end].iter().any(|c| *c) {
+            continue;
+        }
+        for c in covered[address..end].iter_mut() {
+            *c = true;
+        }
+        if let Some(target) = instruction.get_static_jump_target() {
+            if target >= 0 {
+                to_visit.push(target as usize);
+            }
+        }
+        if let Some(return_address) = get_pushed_return_address(&instruction) {
+            to_visit.push(return_address);
+        }
+        if instruction.opcode != Opcode::Halt && !instruction.is_unconditional_jump() {
+            to_visit.push(end);
+        }
+        decoded[address] = Some(instruction);
+    }
+    // Walk through memory in order to build the listing, grouping anything not decoded into data
+    let mut entries = Vec::<ListingEntry>::new();
+    let mut address = 0;
+    while address < memory.len() {
+        if let Some(instruction) = decoded[address].take() {
+            let length = instruction.get_length();
+            entries.push(ListingEntry::Instruction {
+                address,
+                values: memory[address..address + length].to_vec(),
+                instruction,
+            });
+            address += length;
+            continue;
+        }
+        let start = address;
+        while address < memory.len() && !covered[address] && address - start < DATA_VALUES_PER_LINE
+        {
+            address += 1;
+        }
+        entries.push(ListingEntry::Data {
+            address: start,
+            values: memory[start..address].to_vec(),
+        });
+    }
+    return Listing { entries };
+}
+
+/// Checks if the instruction pushes a return address onto the stack using the idiom generated by
+/// the compiler used for the puzzle programs, e.g. "add #58, #0, rb+0", and returns the address.
+pub fn get_pushed_return_address(instruction: &Instruction) -> Option<usize> {
+    let identity = match instruction.opcode {
+        Opcode::Add => 0,
+        Opcode::Multiply => 1,
+        _ => return None,
+    };
+    let params = &instruction.params;
+    if params[0].mode != ParamMode::Immediate || params[1].mode != ParamMode::Immediate {
+        return None;
+    }
+    if params[2].mode != ParamMode::Relative || params[2].value != 0 {
+        return None;
+    }
+    let value = if params[1].value == identity {
+        params[0].value
+    } else if params[0].value == identity {
+        params[1].value
+    } else {
+        return None;
+    };
+    if value < 0 {
+        return None;
+    }
+    return Some(value as usize);
+}
+
+/// Joins the values into a single string using the given separator.
+fn join_values(values: &[i64], separator: &str) -> String {
+    return values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(separator);
+}
+
+/// Gets the values as a string if they are all printable ASCII characters or newlines.
+fn get_ascii_text(values: &[i64]) -> Option<String> {
+    let mut text = String::new();
+    for value in values.iter() {
+        if *value == 10 || (*value >= 32 && *value <= 126) {
+            text.push(*value as u8 as char);
+        } else {
+            return None;
+        }
+    }
+    return Some(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble_code_and_data() {
+        // Output the value at address 7 then halt, followed by data
+        let memory = vec![4, 7, 1101, 1, 2, 8, 99, 72, 105];
+        let listing = disassemble(&memory);
+        let expected = concat!(
+            "     0: 4,7                      out 7\n",
+            "     2: 1101,1,2,8               add #1, #2, 8\n",
+            "     6: 99                       hlt\n",
+            "     7: 72,105                   data 72, 105 ; \"Hi\"\n",
+        );
+        assert_eq!(expected, listing.to_string());
+    }
+
+    #[test]
+    fn test_disassemble_follows_jumps() {
+        // Jump over data, then jump back to the halt at address 3
+        let memory = vec![1105, 1, 6, 99, 42, 43, 1106, 0, 3];
+        let listing = disassemble(&memory);
+        let addresses: Vec<(usize, bool)> = listing
+            .get_entries()
+            .iter()
+            .map(|entry| match entry {
+                ListingEntry::Instruction { address, .. } => (*address, true),
+                ListingEntry::Data { address, .. } => (*address, false),
+            })
+            .collect();
+        assert_eq!(vec![(0, true), (3, true), (4, false), (6, true)], addresses);
+    }
+
+    #[test]
+    fn test_disassemble_follows_return_address() {
+        // Push return address 9, call subroutine at 11 which returns via the stack
+        let memory = vec![109, 20, 21101, 9, 0, 0, 1105, 1, 11, 99, 0, 2106, 0, 0];
+        let listing = disassemble(&memory);
+        let text = listing.to_string();
+        assert!(text.contains("     9: 99                       hlt\n"));
+        assert!(text.contains("    10: 0                        data 0\n"));
+        assert!(text.contains("    11: 2106,0,0                 jf #0, rb+0\n"));
+    }
+
+    #[test]
+    fn test_disassemble_day_17_program() {
+        let memory = super::super::IntcodeMachine::extract_intcode_memory_from_filename(
+            String::from("./input/day_17/input.txt"),
+        );
+        let listing = disassemble(&memory);
+        let text = listing.to_string();
+        assert!(text.starts_with("     0: 1,330,331,332            add 330, 331, 332\n"));
+        assert!(text.contains("add #58, #0, rb+0"));
+        // Listing covers every value of the memory image
+        let total: usize = listing
+            .get_entries()
+            .iter()
+            .map(|entry| match entry {
+                ListingEntry::Instruction { instruction, .. } => instruction.get_length(),
+                ListingEntry::Data { values, .. } => values.len(),
+            })
+            .sum();
+        assert_eq!(memory.len(), total);
+    }
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+
+/// Represents the reason an Intcode machine stopped with a fault.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum FaultKind {
+    /// Instruction opcode is not one of the recognised Intcode opcodes.
+    UnknownOpcode,
+    /// Parameter mode is not valid for the parameter it was given to. Contains the bad mode.
+    BadParameterMode(i64),
+    /// Instruction tried to access (or jump to) a negative address.
+    NegativeAddress,
+    /// Instruction tried to access an address beyond the maximum address of machine memory.
+    AddressOutOfRange,
+    /// Instruction tried to write to a new page of memory when the page limit had been reached.
+    MemoryLimitExceeded,
+    /// Result of an arithmetic operation didn't fit in an i64 (in checked arithmetic mode).
+    ArithmeticOverflow,
+}
+
+/// Details of a fault encountered while executing an Intcode program. The program counter and
+/// relative base are those of the faulting instruction, which has not been executed.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct MachineFault {
+    pub kind: FaultKind,
+    pub opcode: i64,
+    pub pc: usize,
+    pub relative_base: i64,
+    pub address: Option<i64>,
+}
+
+impl fmt::Display for MachineFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            FaultKind::UnknownOpcode => write!(f, "unknown opcode {}", self.opcode)?,
+            FaultKind::BadParameterMode(mode) => {
+                write!(f, "bad parameter mode {} for opcode {}", mode, self.opcode)?
+            }
+            FaultKind::NegativeAddress => write!(f, "negative address for opcode {}", self.opcode)?,
+            FaultKind::AddressOutOfRange => {
+                write!(f, "address out of range for opcode {}", self.opcode)?
+            }
+            FaultKind::MemoryLimitExceeded => {
+                write!(f, "memory limit exceeded for opcode {}", self.opcode)?
+            }
+            FaultKind::ArithmeticOverflow => {
+                write!(f, "arithmetic overflow for opcode {}", self.opcode)?
+            }
+        }
+        write!(
+            f,
+            " [pc: {}, relative base: {}",
+            self.pc, self.relative_base
+        )?;
+        if let Some(address) = self.address {
+            write!(f, ", address: {}", address)?;
+        }
+        return write!(f, "]");
+    }
+}
+
+impl Error for MachineFault {}
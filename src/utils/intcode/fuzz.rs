@@ -0,0 +1,750 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::mem;
+
+use super::fault::{FaultKind, MachineFault};
+use super::memory::{MemoryLimits, PAGE_SIZE};
+use super::run_state::RunState;
+use super::IntcodeMachine;
+
+/// Maximum number of instructions each generated program is run for.
+pub const FUZZ_INSTRUCTION_BUDGET: u64 = 2000;
+
+/// Number of instructions the forked backend executes between forks of the machine.
+const FORK_INTERVAL: u64 = 7;
+/// Number of cells in the data area following the code of a generated program.
+const DATA_LEN: usize = 32;
+/// Maximum number of instructions in the code of a generated program.
+const MAX_CODE_INSTRUCTIONS: u64 = 40;
+/// Maximum number of differing memory cells listed when reporting a mismatch.
+const MAX_REPORTED_CELLS: usize = 8;
+
+/// Small, fast pseudo-random number generator (xorshift64*) used to generate fuzz programs. The
+/// same seed always gives the same sequence of numbers, so any program can be generated again.
+#[derive(Clone, Debug)]
+pub struct FuzzRng {
+    state: u64,
+}
+
+impl FuzzRng {
+    /// Creates a generator from the given seed. Seeds are scrambled first, so that similar seeds
+    /// give unrelated sequences.
+    pub fn new(seed: u64) -> Self {
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        state ^= state >> 31;
+        // The state must never be zero, as xorshift would then only ever produce zero
+        return Self { state: state | 1 };
+    }
+
+    /// Returns the next number in the sequence.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        return self.state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+    }
+
+    /// Returns a number from 0 up to (but excluding) the given bound, which must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        return self.next_u64() % bound;
+    }
+
+    /// Returns a number in the given inclusive range.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        return low + self.below((high - low) as u64 + 1) as i64;
+    }
+
+    /// Returns true with the given percentage chance.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        return self.below(100) < percent;
+    }
+}
+
+/// Randomly generated Intcode program, along with the input values it is given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzProgram {
+    pub seed: u64,
+    pub memory: Vec<i64>,
+    pub input: Vec<i64>,
+}
+
+impl FuzzProgram {
+    /// Generates a well-formed program from the given seed.
+    ///
+    /// The program is a run of instructions with valid opcodes and parameter modes, ending with a
+    /// halt, followed by an area of data. Parameters mostly refer to the data area (directly, or
+    /// relative to a relative base that wanders around it) and jumps go to instruction starts. A
+    /// few parameters refer to code, beyond the end of the program or to distant pages, so some
+    /// programs modify their own code, grow their memory or fault. Some values are large enough
+    /// to overflow arithmetic. Programs that loop are stopped by the instruction budget.
+    pub fn generate(seed: u64) -> Self {
+        let mut rng = FuzzRng::new(seed);
+        let code_instructions = 1 + rng.below(MAX_CODE_INSTRUCTIONS);
+        // Lay out the opcodes first, so jumps can target any instruction start
+        let mut opcodes = vec![];
+        let mut starts = vec![];
+        let mut code_len = 0;
+        for _ in 0..code_instructions {
+            let opcode = match rng.below(20) {
+                0 => 99,
+                n => 1 + (n as i64 % 9),
+            };
+            starts.push(code_len as i64);
+            code_len += get_instruction_length(opcode);
+            opcodes.push(opcode);
+        }
+        starts.push(code_len as i64);
+        code_len += 1;
+        let mut memory = vec![];
+        for opcode in opcodes {
+            let mut modes = vec![];
+            let mut params = vec![];
+            let param_count = get_instruction_length(opcode) - 1;
+            for index in 0..param_count {
+                let (mode, value) = match opcode {
+                    5 | 6 if index == 1 => generate_jump_target(&mut rng, &starts, code_len),
+                    1 | 2 | 7 | 8 if index == 2 => generate_write_param(&mut rng, code_len),
+                    3 => generate_write_param(&mut rng, code_len),
+                    _ => generate_read_param(&mut rng, code_len),
+                };
+                modes.push(mode);
+                params.push(value);
+            }
+            let code = modes.iter().rev().fold(0, |code, mode| code * 10 + mode) * 100 + opcode;
+            memory.push(code);
+            memory.extend(params);
+        }
+        memory.push(99);
+        for _ in 0..DATA_LEN {
+            memory.push(generate_value(&mut rng));
+        }
+        let input = (0..rng.below(4))
+            .map(|_| generate_value(&mut rng))
+            .collect();
+        return Self {
+            seed,
+            memory,
+            input,
+        };
+    }
+}
+
+/// Returns the number of cells taken by an instruction with the given (valid) opcode.
+fn get_instruction_length(opcode: i64) -> usize {
+    match opcode {
+        1 | 2 | 7 | 8 => return 4,
+        5 | 6 => return 3,
+        3 | 4 | 9 => return 2,
+        _ => return 1,
+    }
+}
+
+/// Generates a value for a program to work on - mostly small, but sometimes large enough for
+/// arithmetic on it to overflow.
+fn generate_value(rng: &mut FuzzRng) -> i64 {
+    match rng.below(10) {
+        0 => return rng.range(i64::MIN / 2, i64::MAX / 2) * 2 + rng.range(0, 1),
+        1 => return rng.range(-1 << 31, 1 << 31),
+        _ => return rng.range(-10, 10),
+    }
+}
+
+/// Generates an address for a parameter in position mode, or an offset for one in relative mode
+/// (where the relative base stays near zero). Most are in the data area.
+fn generate_address(rng: &mut FuzzRng, code_len: usize) -> i64 {
+    let data_start = code_len as i64;
+    match rng.below(20) {
+        0 => return rng.range(0, data_start - 1),
+        1 => return rng.range(1, 4) * PAGE_SIZE as i64 + rng.range(0, 7),
+        2 => return data_start + DATA_LEN as i64 + rng.range(0, 16),
+        _ => return data_start + rng.range(0, DATA_LEN as i64 - 1),
+    }
+}
+
+/// Generates the mode and value of a parameter that is read from.
+fn generate_read_param(rng: &mut FuzzRng, code_len: usize) -> (i64, i64) {
+    match rng.below(3) {
+        0 => return (0, generate_address(rng, code_len)),
+        1 => return (1, generate_value(rng)),
+        _ => return (2, generate_address(rng, code_len) - rng.range(0, 4)),
+    }
+}
+
+/// Generates the mode and value of a parameter that is written to.
+fn generate_write_param(rng: &mut FuzzRng, code_len: usize) -> (i64, i64) {
+    match rng.below(2) {
+        0 => return (0, generate_address(rng, code_len)),
+        _ => return (2, generate_address(rng, code_len) - rng.range(0, 4)),
+    }
+}
+
+/// Generates the mode and value of the target of a jump. Most are an instruction start given in
+/// immediate mode, the rest are taken from the data area.
+fn generate_jump_target(rng: &mut FuzzRng, starts: &[i64], code_len: usize) -> (i64, i64) {
+    if rng.chance(90) {
+        return (1, starts[rng.below(starts.len() as u64) as usize]);
+    }
+    return (0, code_len as i64 + rng.range(0, DATA_LEN as i64 - 1));
+}
+
+/// Ways of running an Intcode machine that are checked against the reference interpreter.
+///
+/// Transpiled programs aren't included, as they are compiled with the crate - the modules in
+/// src/transpiled are checked against the machine by their own tests instead.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum FuzzBackend {
+    /// Default machine, executing through the decoded instruction cache.
+    Cached,
+    /// Machine with the decode cache disabled, executing through the original core.
+    Uncached,
+    /// Machine that is stepped one instruction at a time and repeatedly replaced by a fork of
+    /// itself, with the previous machine kept alive so that memory pages stay shared.
+    Forked,
+}
+
+impl FuzzBackend {
+    /// Returns all of the backends.
+    pub fn all() -> [FuzzBackend; 3] {
+        return [
+            FuzzBackend::Cached,
+            FuzzBackend::Uncached,
+            FuzzBackend::Forked,
+        ];
+    }
+}
+
+impl fmt::Display for FuzzBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FuzzBackend::Cached => return write!(f, "cached"),
+            FuzzBackend::Uncached => return write!(f, "uncached"),
+            FuzzBackend::Forked => return write!(f, "forked"),
+        }
+    }
+}
+
+/// State a program was left in after being run, which must be the same whichever way it is run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzOutcome {
+    /// Non-zero memory cells, by address.
+    pub memory: BTreeMap<usize, i64>,
+    pub output: Vec<i64>,
+    pub pc: usize,
+    pub relative_base: i64,
+    pub halted: bool,
+    pub awaiting_input: bool,
+    pub fault: Option<MachineFault>,
+    /// Number of instructions executed.
+    pub instructions: u64,
+}
+
+impl FuzzOutcome {
+    /// Returns a description of each way the other outcome differs from this one.
+    pub fn get_differences(&self, other: &FuzzOutcome) -> Vec<String> {
+        let mut differences = vec![];
+        let cells: Vec<String> = self
+            .memory
+            .keys()
+            .chain(other.memory.keys())
+            .filter(|address| self.memory.get(address) != other.memory.get(address))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .take(MAX_REPORTED_CELLS)
+            .map(|address| {
+                let value = |memory: &BTreeMap<usize, i64>| *memory.get(address).unwrap_or(&0);
+                format!(
+                    "[{}] {} != {}",
+                    address,
+                    value(&self.memory),
+                    value(&other.memory)
+                )
+            })
+            .collect();
+        if !cells.is_empty() {
+            differences.push(format!("memory: {}", cells.join(", ")));
+        }
+        if self.output != other.output {
+            differences.push(format!("output: {:?} != {:?}", self.output, other.output));
+        }
+        if self.pc != other.pc {
+            differences.push(format!("pc: {} != {}", self.pc, other.pc));
+        }
+        if self.relative_base != other.relative_base {
+            let (expected, actual) = (self.relative_base, other.relative_base);
+            differences.push(format!("relative base: {} != {}", expected, actual));
+        }
+        if self.halted != other.halted {
+            differences.push(format!("halted: {} != {}", self.halted, other.halted));
+        }
+        if self.awaiting_input != other.awaiting_input {
+            let (expected, actual) = (self.awaiting_input, other.awaiting_input);
+            differences.push(format!("awaiting input: {} != {}", expected, actual));
+        }
+        if self.fault != other.fault {
+            let describe = |fault: &Option<MachineFault>| match fault {
+                Some(fault) => fault.to_string(),
+                None => String::from("none"),
+            };
+            let (expected, actual) = (describe(&self.fault), describe(&other.fault));
+            differences.push(format!("fault: {} != {}", expected, actual));
+        }
+        if self.instructions != other.instructions {
+            let (expected, actual) = (self.instructions, other.instructions);
+            differences.push(format!("instructions: {} != {}", expected, actual));
+        }
+        return differences;
+    }
+}
+
+/// Program that finished in a different state on a backend than on the reference interpreter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzMismatch {
+    pub program: FuzzProgram,
+    pub backend: FuzzBackend,
+    pub expected: FuzzOutcome,
+    pub actual: FuzzOutcome,
+}
+
+impl fmt::Display for FuzzMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "program {} differs from the reference on the {} backend (expected != actual):",
+            self.program.seed, self.backend
+        )?;
+        for difference in self.expected.get_differences(&self.actual) {
+            writeln!(f, "  {}", difference)?;
+        }
+        let memory: Vec<String> = self.program.memory.iter().map(|v| v.to_string()).collect();
+        writeln!(f, "program: {}", memory.join(","))?;
+        return writeln!(f, "input: {:?}", self.program.input);
+    }
+}
+
+/// Totals for the programs checked by a fuzzing run, showing how they finished.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FuzzReport {
+    pub programs: u64,
+    pub instructions: u64,
+    pub halted: u64,
+    pub awaiting_input: u64,
+    pub faulted: u64,
+    pub budget_exhausted: u64,
+}
+
+impl FuzzReport {
+    /// Adds the outcome of a program to the totals.
+    fn record(&mut self, outcome: &FuzzOutcome) {
+        self.programs += 1;
+        self.instructions += outcome.instructions;
+        if outcome.fault.is_some() {
+            self.faulted += 1;
+        } else if outcome.halted {
+            self.halted += 1;
+        } else if outcome.awaiting_input {
+            self.awaiting_input += 1;
+        } else {
+            self.budget_exhausted += 1;
+        }
+    }
+}
+
+impl fmt::Display for FuzzReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "programs checked:     {}", self.programs)?;
+        writeln!(f, "instructions:         {}", self.instructions)?;
+        writeln!(f, "halted:               {}", self.halted)?;
+        writeln!(f, "awaiting input:       {}", self.awaiting_input)?;
+        writeln!(f, "faulted:              {}", self.faulted)?;
+        return writeln!(f, "budget exhausted:     {}", self.budget_exhausted);
+    }
+}
+
+/// Generates the given number of programs from the seed, and checks that each finishes in the
+/// same state on every backend as on the reference interpreter. Returns the first mismatch found.
+pub fn fuzz(seed: u64, programs: u64) -> Result<FuzzReport, Box<FuzzMismatch>> {
+    let mut rng = FuzzRng::new(seed);
+    let mut report = FuzzReport::default();
+    for _ in 0..programs {
+        let program = FuzzProgram::generate(rng.next_u64());
+        report.record(&check_program(&program)?);
+    }
+    return Ok(report);
+}
+
+/// Runs the program on the reference interpreter and every backend, returning the reference
+/// outcome if all of them agree.
+pub fn check_program(program: &FuzzProgram) -> Result<FuzzOutcome, Box<FuzzMismatch>> {
+    let expected = run_reference(program);
+    for backend in FuzzBackend::all().iter() {
+        let actual = run_backend(program, *backend);
+        if actual != expected {
+            return Err(Box::new(FuzzMismatch {
+                program: program.clone(),
+                backend: *backend,
+                expected,
+                actual,
+            }));
+        }
+    }
+    return Ok(expected);
+}
+
+/// Runs the program on the reference interpreter until it halts, needs more input, faults or
+/// has executed FUZZ_INSTRUCTION_BUDGET instructions.
+pub fn run_reference(program: &FuzzProgram) -> FuzzOutcome {
+    let mut machine = ReferenceMachine::new(program);
+    let mut output = vec![];
+    let mut instructions = 0;
+    let mut fault = None;
+    while instructions < FUZZ_INSTRUCTION_BUDGET {
+        match machine.step() {
+            Ok(Some(RunState::Output(value))) => output.push(value),
+            Ok(Some(RunState::NeedsInput)) => break,
+            Ok(Some(_)) => {
+                instructions += 1;
+                break;
+            }
+            Ok(None) => (),
+            Err(error) => {
+                fault = Some(error);
+                break;
+            }
+        }
+        instructions += 1;
+    }
+    return FuzzOutcome {
+        memory: machine
+            .memory
+            .into_iter()
+            .filter(|(_, v)| *v != 0)
+            .collect(),
+        output,
+        pc: machine.pc,
+        relative_base: machine.relative_base,
+        halted: machine.halted,
+        awaiting_input: machine.awaiting_input,
+        fault,
+        instructions,
+    };
+}
+
+/// Runs the program on a machine using the given backend, with the same limits as run_reference.
+pub fn run_backend(program: &FuzzProgram, backend: FuzzBackend) -> FuzzOutcome {
+    let input = VecDeque::from(program.input.clone());
+    let mut machine = IntcodeMachine::new(program.memory.clone(), input);
+    machine.set_instruction_budget(Some(FUZZ_INSTRUCTION_BUDGET));
+    if backend == FuzzBackend::Uncached {
+        machine.set_decode_cache_enabled(false);
+    }
+    let mut output = vec![];
+    let mut parent = None;
+    let mut steps_until_fork = 0;
+    loop {
+        let result = match backend {
+            FuzzBackend::Forked => {
+                if steps_until_fork == 0 {
+                    let fork = machine.fork();
+                    parent = Some(mem::replace(&mut machine, fork));
+                    steps_until_fork = FORK_INTERVAL;
+                }
+                steps_until_fork -= 1;
+                machine.step()
+            }
+            _ => machine.run().map(Some),
+        };
+        match result {
+            Ok(Some(RunState::Output(value))) => output.push(value),
+            Ok(Some(_)) | Err(_) => break,
+            Ok(None) => (),
+        }
+    }
+    // The last parent shares pages with the machine until now
+    drop(parent);
+    let mut memory = BTreeMap::new();
+    for (index, page) in machine.memory.get_pages() {
+        for (offset, value) in page.iter().enumerate() {
+            if *value != 0 {
+                memory.insert(index * PAGE_SIZE + offset, *value);
+            }
+        }
+    }
+    return FuzzOutcome {
+        memory,
+        output,
+        pc: machine.prog_c,
+        relative_base: machine.relative_base,
+        halted: machine.halted,
+        awaiting_input: machine.awaiting_input,
+        fault: machine.fault.clone(),
+        instructions: FUZZ_INSTRUCTION_BUDGET - machine.get_instruction_budget().unwrap(),
+    };
+}
+
+/// Minimal Intcode interpreter, written as directly from the puzzle descriptions as possible to
+/// serve as the reference that the machine is checked against. It uses checked arithmetic (the
+/// machine default) and has sparse memory with no page limit. Faults are reported the same way as
+/// the machine, with parameters read (and write addresses checked) in order before the result is
+/// calculated.
+struct ReferenceMachine {
+    memory: BTreeMap<usize, i64>,
+    input: VecDeque<i64>,
+    pc: usize,
+    relative_base: i64,
+    halted: bool,
+    awaiting_input: bool,
+    max_address: usize,
+}
+
+impl ReferenceMachine {
+    /// Creates a machine loaded with the program and its input.
+    fn new(program: &FuzzProgram) -> Self {
+        return Self {
+            memory: program.memory.iter().cloned().enumerate().collect(),
+            input: program.input.iter().cloned().collect(),
+            pc: 0,
+            relative_base: 0,
+            halted: false,
+            awaiting_input: false,
+            max_address: MemoryLimits::default().max_address,
+        };
+    }
+
+    /// Executes the instruction at the program counter.
+    fn step(&mut self) -> Result<Option<RunState>, MachineFault> {
+        if self.halted {
+            return Ok(Some(RunState::Halted));
+        }
+        let instruction = self.read(self.pc as i64)?;
+        let opcode = instruction % 100;
+        let modes = [
+            (instruction / 100) % 10,
+            (instruction / 1000) % 10,
+            (instruction / 10000) % 10,
+        ];
+        match opcode {
+            99 => {
+                self.halted = true;
+                return Ok(Some(RunState::Halted));
+            }
+            1 | 2 | 7 | 8 => {
+                let a = self.read_param(0, modes[0])?;
+                let b = self.read_param(1, modes[1])?;
+                let address = self.write_param(2, modes[2])?;
+                let value = match opcode {
+                    1 => a
+                        .checked_add(b)
+                        .ok_or_else(|| self.fault(FaultKind::ArithmeticOverflow, None))?,
+                    2 => a
+                        .checked_mul(b)
+                        .ok_or_else(|| self.fault(FaultKind::ArithmeticOverflow, None))?,
+                    7 => (a < b) as i64,
+                    _ => (a == b) as i64,
+                };
+                self.memory.insert(address, value);
+                self.pc += 4;
+            }
+            3 => {
+                let address = self.write_param(0, modes[0])?;
+                match self.input.pop_front() {
+                    Some(value) => self.memory.insert(address, value),
+                    None => {
+                        self.awaiting_input = true;
+                        return Ok(Some(RunState::NeedsInput));
+                    }
+                };
+                self.pc += 2;
+            }
+            4 => {
+                let value = self.read_param(0, modes[0])?;
+                self.pc += 2;
+                return Ok(Some(RunState::Output(value)));
+            }
+            5 | 6 => {
+                let condition = self.read_param(0, modes[0])?;
+                let target = self.read_param(1, modes[1])?;
+                if (condition != 0) == (opcode == 5) {
+                    self.pc = self.check_address(target)?;
+                } else {
+                    self.pc += 3;
+                }
+            }
+            9 => {
+                let delta = self.read_param(0, modes[0])?;
+                self.relative_base = self.relative_address(delta)?;
+                self.pc += 2;
+            }
+            _ => return Err(self.fault(FaultKind::UnknownOpcode, None)),
+        }
+        return Ok(None);
+    }
+
+    /// Returns the value of the instruction parameter with the given index.
+    fn read_param(&self, index: usize, mode: i64) -> Result<i64, MachineFault> {
+        let param = self.read((self.pc + 1 + index) as i64)?;
+        match mode {
+            0 => return self.read(param),
+            1 => return Ok(param),
+            2 => return self.read(self.relative_address(param)?),
+            _ => return Err(self.fault(FaultKind::BadParameterMode(mode), None)),
+        }
+    }
+
+    /// Returns the (checked) address given by the instruction parameter with the given index.
+    fn write_param(&self, index: usize, mode: i64) -> Result<usize, MachineFault> {
+        let param = self.read((self.pc + 1 + index) as i64)?;
+        match mode {
+            0 => return self.check_address(param),
+            2 => return self.check_address(self.relative_address(param)?),
+            _ => return Err(self.fault(FaultKind::BadParameterMode(mode), None)),
+        }
+    }
+
+    /// Adds the value to the relative base.
+    fn relative_address(&self, value: i64) -> Result<i64, MachineFault> {
+        return self
+            .relative_base
+            .checked_add(value)
+            .ok_or_else(|| self.fault(FaultKind::ArithmeticOverflow, None));
+    }
+
+    /// Returns the value at the given address.
+    fn read(&self, address: i64) -> Result<i64, MachineFault> {
+        let address = self.check_address(address)?;
+        return Ok(*self.memory.get(&address).unwrap_or(&0));
+    }
+
+    /// Checks the address is within memory, returning it as an index.
+    fn check_address(&self, address: i64) -> Result<usize, MachineFault> {
+        if address < 0 {
+            return Err(self.fault(FaultKind::NegativeAddress, Some(address)));
+        }
+        if address as u64 > self.max_address as u64 {
+            return Err(self.fault(FaultKind::AddressOutOfRange, Some(address)));
+        }
+        return Ok(address as usize);
+    }
+
+    /// Creates a fault of the given kind for the instruction at the program counter.
+    fn fault(&self, kind: FaultKind, address: Option<i64>) -> MachineFault {
+        return MachineFault {
+            kind,
+            opcode: self.memory.get(&self.pc).unwrap_or(&0) % 100,
+            pc: self.pc,
+            relative_base: self.relative_base,
+            address,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::analyze;
+    use super::*;
+
+    #[test]
+    fn test_fuzz_rng() {
+        let mut a = FuzzRng::new(7);
+        let mut b = FuzzRng::new(7);
+        let mut c = FuzzRng::new(8);
+        let sequence: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        assert_eq!(
+            sequence,
+            (0..10).map(|_| b.next_u64()).collect::<Vec<u64>>()
+        );
+        assert_ne!(
+            sequence,
+            (0..10).map(|_| c.next_u64()).collect::<Vec<u64>>()
+        );
+        assert!(FuzzRng::new(0).next_u64() != 0);
+        for _ in 0..1000 {
+            let value = a.range(-3, 3);
+            assert!((-3..=3).contains(&value));
+            assert!(a.below(5) < 5);
+        }
+    }
+
+    #[test]
+    fn test_generated_programs_well_formed() {
+        for seed in 0..200 {
+            let program = FuzzProgram::generate(seed);
+            assert_eq!(program, FuzzProgram::generate(seed));
+            let graph = analyze(&program.memory);
+            assert!(graph.get_invalid_instructions().is_empty(), "seed {}", seed);
+            assert!(program.input.len() < 4);
+        }
+        assert_ne!(FuzzProgram::generate(1), FuzzProgram::generate(2));
+    }
+
+    #[test]
+    fn test_reference_machine() {
+        // Outputs 999, 1000 or 1001 for input below, equal to or above 8
+        let memory = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        for (input, expected) in [(7, 999), (8, 1000), (9, 1001)].iter() {
+            let program = FuzzProgram {
+                seed: 0,
+                memory: memory.clone(),
+                input: vec![*input],
+            };
+            let outcome = check_program(&program).unwrap();
+            assert_eq!(vec![*expected], outcome.output);
+            assert!(outcome.halted);
+        }
+        // Relative mode write past the end, then a read of a negative address
+        let program = FuzzProgram {
+            seed: 0,
+            memory: vec![109, 10, 21101, 2, 3, 5, 4, 15, 4, -1, 99],
+            input: vec![],
+        };
+        let outcome = check_program(&program).unwrap();
+        assert_eq!(vec![5], outcome.output);
+        assert_eq!(Some(&5), outcome.memory.get(&15));
+        let fault = outcome.fault.unwrap();
+        assert_eq!(FaultKind::NegativeAddress, fault.kind);
+        assert_eq!((8, Some(-1)), (fault.pc, fault.address));
+    }
+
+    #[test]
+    fn test_fuzz_backends_agree() {
+        let report = match fuzz(2019, 1000) {
+            Ok(report) => report,
+            Err(mismatch) => panic!("{}", mismatch),
+        };
+        assert_eq!(1000, report.programs);
+        // The generator should produce programs that finish in each of the possible ways
+        assert!(report.halted > 0);
+        assert!(report.awaiting_input > 0);
+        assert!(report.faulted > 0);
+        assert!(report.budget_exhausted > 0);
+    }
+
+    #[test]
+    fn test_outcome_differences() {
+        let program = FuzzProgram::generate(5);
+        let expected = run_reference(&program);
+        let mut actual = expected.clone();
+        assert!(expected.get_differences(&actual).is_empty());
+        actual.memory.insert(1 << 20, 3);
+        actual.output.push(4);
+        actual.relative_base += 1;
+        let differences = expected.get_differences(&actual);
+        assert_eq!(3, differences.len());
+        assert_eq!(format!("memory: [{}] 0 != 3", 1 << 20), differences[0]);
+        assert!(differences[2].starts_with("relative base: "));
+        let mismatch = FuzzMismatch {
+            program,
+            backend: FuzzBackend::Forked,
+            expected,
+            actual,
+        };
+        let report = mismatch.to_string();
+        assert!(report.starts_with("program 5 differs from the reference on the forked backend"));
+        assert!(report.contains("\n  output: "));
+    }
+}
@@ -0,0 +1,327 @@
+use std::fmt;
+
+use super::fault::FaultKind;
+
+/// Operations that can be performed by an Intcode instruction.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Opcode {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustRelativeBase,
+    Halt,
+}
+
+impl Opcode {
+    /// Gets the Opcode represented by the given numeric code, if it is valid.
+    pub fn from_code(code: i64) -> Option<Opcode> {
+        match code {
+            1 => Some(Opcode::Add),
+            2 => Some(Opcode::Multiply),
+            3 => Some(Opcode::Input),
+            4 => Some(Opcode::Output),
+            5 => Some(Opcode::JumpIfTrue),
+            6 => Some(Opcode::JumpIfFalse),
+            7 => Some(Opcode::LessThan),
+            8 => Some(Opcode::Equals),
+            9 => Some(Opcode::AdjustRelativeBase),
+            99 => Some(Opcode::Halt),
+            _ => None,
+        }
+    }
+
+    /// Gets the numeric code of the opcode.
+    pub fn get_code(&self) -> i64 {
+        match self {
+            Opcode::Add => 1,
+            Opcode::Multiply => 2,
+            Opcode::Input => 3,
+            Opcode::Output => 4,
+            Opcode::JumpIfTrue => 5,
+            Opcode::JumpIfFalse => 6,
+            Opcode::LessThan => 7,
+            Opcode::Equals => 8,
+            Opcode::AdjustRelativeBase => 9,
+            Opcode::Halt => 99,
+        }
+    }
+
+    /// Gets the opcode represented by the given mnemonic, if it is valid.
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+        match mnemonic {
+            "add" => Some(Opcode::Add),
+            "mul" => Some(Opcode::Multiply),
+            "in" => Some(Opcode::Input),
+            "out" => Some(Opcode::Output),
+            "jt" => Some(Opcode::JumpIfTrue),
+            "jf" => Some(Opcode::JumpIfFalse),
+            "lt" => Some(Opcode::LessThan),
+            "eq" => Some(Opcode::Equals),
+            "arb" => Some(Opcode::AdjustRelativeBase),
+            "hlt" => Some(Opcode::Halt),
+            _ => None,
+        }
+    }
+
+    /// Gets the mnemonic used for the opcode in listings.
+    pub fn get_mnemonic(&self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Multiply => "mul",
+            Opcode::Input => "in",
+            Opcode::Output => "out",
+            Opcode::JumpIfTrue => "jt",
+            Opcode::JumpIfFalse => "jf",
+            Opcode::LessThan => "lt",
+            Opcode::Equals => "eq",
+            Opcode::AdjustRelativeBase => "arb",
+            Opcode::Halt => "hlt",
+        }
+    }
+
+    /// Gets the number of parameters taken by instructions with this opcode.
+    pub fn get_param_count(&self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => 3,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => 2,
+            Opcode::Input | Opcode::Output | Opcode::AdjustRelativeBase => 1,
+            Opcode::Halt => 0,
+        }
+    }
+
+    /// Checks if the last parameter of instructions with this opcode is an address written to.
+    pub fn writes_to_memory(&self) -> bool {
+        return matches!(
+            self,
+            Opcode::Add | Opcode::Multiply | Opcode::Input | Opcode::LessThan | Opcode::Equals
+        );
+    }
+}
+
+/// Modes that can be used by an instruction parameter.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum ParamMode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl ParamMode {
+    /// Gets the ParamMode represented by the given numeric mode, if it is valid.
+    pub fn from_code(code: i64) -> Option<ParamMode> {
+        match code {
+            0 => Some(ParamMode::Position),
+            1 => Some(ParamMode::Immediate),
+            2 => Some(ParamMode::Relative),
+            _ => None,
+        }
+    }
+
+    /// Gets the numeric code of the parameter mode.
+    pub fn get_code(&self) -> i64 {
+        match self {
+            ParamMode::Position => 0,
+            ParamMode::Immediate => 1,
+            ParamMode::Relative => 2,
+        }
+    }
+}
+
+/// A single parameter of an Intcode instruction.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Parameter {
+    pub mode: ParamMode,
+    pub value: i64,
+}
+
+impl fmt::Display for Parameter {
+    /// Parameters are displayed as "123" (position), "#123" (immediate) or "rb+123" (relative).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            ParamMode::Position => write!(f, "{}", self.value),
+            ParamMode::Immediate => write!(f, "#{}", self.value),
+            ParamMode::Relative => {
+                if self.value < 0 {
+                    write!(f, "rb{}", self.value)
+                } else {
+                    write!(f, "rb+{}", self.value)
+                }
+            }
+        }
+    }
+}
+
+/// A decoded Intcode instruction.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub params: Vec<Parameter>,
+}
+
+impl Instruction {
+    /// Decodes the instruction starting at the given address of the memory image. Returns None if
+    /// the value at the address isn't a valid instruction, if it uses immediate mode for a
+    /// parameter that is written to, or if its parameters run past the end of memory.
+    pub fn decode(memory: &[i64], address: usize) -> Option<Instruction> {
+        let instruction = Instruction::try_decode(memory, address).ok()?;
+        if address + instruction.get_length() > memory.len() {
+            return None;
+        }
+        return Some(instruction);
+    }
+
+    /// Decodes the instruction starting at the given address of the memory image the same way an
+    /// Intcode machine would, returning the kind of fault the machine would encounter if it isn't
+    /// valid - UnknownOpcode, or BadParameterMode for an unknown mode or immediate mode used for a
+    /// parameter that is written to. Addresses past the end of the image read as 0, as they do in
+    /// machine memory.
+    pub fn try_decode(memory: &[i64], address: usize) -> Result<Instruction, FaultKind> {
+        let read = |address: usize| *memory.get(address).unwrap_or(&0);
+        let arg = read(address);
+        let opcode = Opcode::from_code(arg % 100).ok_or(FaultKind::UnknownOpcode)?;
+        let mut params = Vec::<Parameter>::new();
+        let mut modes = arg / 100;
+        for i in 0..opcode.get_param_count() {
+            let is_write = opcode.writes_to_memory() && i == opcode.get_param_count() - 1;
+            let mode = match ParamMode::from_code(modes % 10) {
+                Some(ParamMode::Immediate) if is_write => None,
+                mode => mode,
+            };
+            let mode = mode.ok_or(FaultKind::BadParameterMode(modes % 10))?;
+            params.push(Parameter {
+                mode,
+                value: read(address + 1 + i),
+            });
+            modes /= 10;
+        }
+        return Ok(Instruction { opcode, params });
+    }
+
+    /// Gets the number of memory cells used by the instruction, including its parameters.
+    pub fn get_length(&self) -> usize {
+        return 1 + self.params.len();
+    }
+
+    /// Encodes the instruction back into the memory values it was decoded from.
+    pub fn encode(&self) -> Vec<i64> {
+        let mut arg = self.opcode.get_code();
+        let mut multiplier = 100;
+        for param in self.params.iter() {
+            arg += param.mode.get_code() * multiplier;
+            multiplier *= 10;
+        }
+        let mut values = vec![arg];
+        values.extend(self.params.iter().map(|param| param.value));
+        return values;
+    }
+
+    /// Checks if the instruction is a jump, whether or not it is taken.
+    pub fn is_jump(&self) -> bool {
+        return self.opcode == Opcode::JumpIfTrue || self.opcode == Opcode::JumpIfFalse;
+    }
+
+    /// Gets the jump target of the instruction if it is a jump with an immediate-mode target.
+    pub fn get_static_jump_target(&self) -> Option<i64> {
+        match self.opcode {
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                if self.params[1].mode == ParamMode::Immediate {
+                    return Some(self.params[1].value);
+                }
+                return None;
+            }
+            _ => return None,
+        }
+    }
+
+    /// Checks if the instruction is a jump that is always taken, i.e. its condition is an
+    /// immediate value that always passes.
+    pub fn is_unconditional_jump(&self) -> bool {
+        if self.params.is_empty() || self.params[0].mode != ParamMode::Immediate {
+            return false;
+        }
+        match self.opcode {
+            Opcode::JumpIfTrue => return self.params[0].value != 0,
+            Opcode::JumpIfFalse => return self.params[0].value == 0,
+            _ => return false,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    /// Instructions are displayed as the mnemonic followed by the comma-separated parameters.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.opcode.get_mnemonic())?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i == 0 {
+                write!(f, " {}", param)?;
+            } else {
+                write!(f, ", {}", param)?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_instruction() {
+        let instruction = Instruction::decode(&[21101, 58, 0, -3], 0).unwrap();
+        assert_eq!(Opcode::Add, instruction.opcode);
+        assert_eq!(ParamMode::Immediate, instruction.params[0].mode);
+        assert_eq!(ParamMode::Relative, instruction.params[2].mode);
+        assert_eq!("add #58, #0, rb-3", instruction.to_string());
+        assert_eq!(vec![21101, 58, 0, -3], instruction.encode());
+        assert_eq!(4, instruction.get_length());
+    }
+
+    #[test]
+    fn test_decode_invalid_instruction() {
+        // Unknown opcode, bad mode, immediate-mode write and parameters past end of memory
+        assert_eq!(None, Instruction::decode(&[42], 0));
+        assert_eq!(None, Instruction::decode(&[304, 1], 0));
+        assert_eq!(None, Instruction::decode(&[11101, 1, 1, 0], 0));
+        assert_eq!(None, Instruction::decode(&[103, 0], 0));
+        assert_eq!(None, Instruction::decode(&[1, 0, 0], 0));
+    }
+
+    #[test]
+    fn test_try_decode_instruction() {
+        assert_eq!(
+            Err(FaultKind::UnknownOpcode),
+            Instruction::try_decode(&[42], 0)
+        );
+        assert_eq!(
+            Err(FaultKind::UnknownOpcode),
+            Instruction::try_decode(&[-1], 0)
+        );
+        let bad_mode = Instruction::try_decode(&[304, 1], 0);
+        assert_eq!(Err(FaultKind::BadParameterMode(3)), bad_mode);
+        let immediate_write = Instruction::try_decode(&[11101, 1, 1, 0], 0);
+        assert_eq!(Err(FaultKind::BadParameterMode(1)), immediate_write);
+        // Parameters past the end of memory read as 0
+        let instruction = Instruction::try_decode(&[1, 0, 0], 0).unwrap();
+        assert_eq!("add 0, 0, 0", instruction.to_string());
+    }
+
+    #[test]
+    fn test_jumps() {
+        let call = Instruction::decode(&[1105, 1, 786], 0).unwrap();
+        assert!(call.is_unconditional_jump());
+        assert_eq!(Some(786), call.get_static_jump_target());
+        let ret = Instruction::decode(&[2106, 0, 0], 0).unwrap();
+        assert!(ret.is_unconditional_jump());
+        assert_eq!(None, ret.get_static_jump_target());
+        let branch = Instruction::decode(&[1006, 570, 36], 0).unwrap();
+        assert!(!branch.is_unconditional_jump());
+        assert!(call.is_jump() && ret.is_jump() && branch.is_jump());
+        assert!(!Instruction::decode(&[1101, 1, 2, 0], 0).unwrap().is_jump());
+    }
+}
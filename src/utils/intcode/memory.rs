@@ -0,0 +1,442 @@
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Index;
+use std::sync::Arc;
+
+use itertools::{EitherOrBoth, Itertools};
+
+use super::fault::FaultKind;
+
+/// Number of cells held in each page of Intcode memory.
+pub const PAGE_SIZE: usize = 1024;
+
+/// Pages with an index below this are held in a directly indexed table, so the program and the
+/// memory just beyond it can be accessed without a tree lookup.
+const NUM_NEAR_PAGES: usize = 64;
+
+/// Limits on how far the memory of an Intcode machine is allowed to grow.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct MemoryLimits {
+    /// Highest address that the machine may read from or write to.
+    pub max_address: usize,
+    /// Maximum number of pages that may be allocated, including those holding the program.
+    pub max_pages: usize,
+}
+
+impl Default for MemoryLimits {
+    /// Default limits allow addresses up to 2^32 - 1 and 64Mi cells (512 MiB) of allocated memory.
+    fn default() -> Self {
+        Self {
+            max_address: (1 << 32) - 1,
+            max_pages: (1 << 26) / PAGE_SIZE,
+        }
+    }
+}
+
+/// Sparse, paged memory of an Intcode machine. Pages are only allocated when a non-zero value is
+/// written to them, so reads from addresses that have never been written to return 0 without
+/// using any memory.
+///
+/// Pages are copy-on-write: cloning the memory shares all of its pages with the clone, and a page
+/// is only copied when one of the memories sharing it writes to it.
+///
+/// Memories are compared and hashed by their length, limits and contents, so a page that has been
+/// allocated but holds only zeros is the same as a page that was never allocated.
+#[derive(Clone, Debug)]
+pub struct IntcodeMemory {
+    near_pages: Vec<Option<Arc<Vec<i64>>>>,
+    far_pages: BTreeMap<usize, Arc<Vec<i64>>>,
+    program_len: usize,
+    len: usize,
+    limits: MemoryLimits,
+}
+
+impl IntcodeMemory {
+    /// Creates new memory containing the given program. The program is always loaded in full, so
+    /// limits only apply to memory the program uses beyond its own length.
+    pub fn new(program: Vec<i64>, limits: MemoryLimits) -> Self {
+        let mut memory = IntcodeMemory::new_empty(program.len(), limits);
+        for (page_index, chunk) in program.chunks(PAGE_SIZE).enumerate() {
+            let mut page = chunk.to_vec();
+            page.resize(PAGE_SIZE, 0);
+            memory.insert_page(page_index, Arc::new(page));
+        }
+        return memory;
+    }
+
+    /// Creates new memory with the given length, but no pages allocated.
+    fn new_empty(len: usize, limits: MemoryLimits) -> Self {
+        Self {
+            near_pages: vec![None; NUM_NEAR_PAGES],
+            far_pages: BTreeMap::new(),
+            program_len: len,
+            len,
+            limits,
+        }
+    }
+
+    /// Returns the value held at the given address.
+    pub fn read(&self, address: usize) -> i64 {
+        match self.get_page(address / PAGE_SIZE) {
+            Some(page) => return page[address % PAGE_SIZE],
+            None => return 0,
+        }
+    }
+
+    /// Writes the value to the given address, allocating a new page if needed. Returns the kind of
+    /// fault if the write would take the memory beyond its limits.
+    pub fn write(&mut self, address: usize, value: i64) -> Result<(), FaultKind> {
+        if address > self.limits.max_address {
+            return Err(FaultKind::AddressOutOfRange);
+        }
+        let page_index = address / PAGE_SIZE;
+        if self.get_page(page_index).is_none() {
+            // Unwritten cells already read as 0, so there is no need to allocate a page for them
+            if value == 0 {
+                return Ok(());
+            }
+            if self.get_page_count() >= self.limits.max_pages {
+                return Err(FaultKind::MemoryLimitExceeded);
+            }
+            self.insert_page(page_index, Arc::new(vec![0; PAGE_SIZE]));
+        }
+        // Copies the page first if it is shared with another memory
+        let page = if page_index < NUM_NEAR_PAGES {
+            self.near_pages[page_index].as_mut()
+        } else {
+            self.far_pages.get_mut(&page_index)
+        };
+        Arc::make_mut(page.unwrap())[address % PAGE_SIZE] = value;
+        if value != 0 && address >= self.len {
+            self.len = address + 1;
+        } else if value == 0 && address + 1 == self.len && self.len > self.program_len {
+            self.len = self.get_content_len();
+        }
+        return Ok(());
+    }
+
+    /// Returns the number of cells from address 0 up to the highest address that has been loaded
+    /// or currently holds a non-zero value.
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    /// Checks if no program has been loaded and nothing has been written to memory.
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    /// Returns the length of the program that was loaded, which memory never shrinks below.
+    pub fn get_program_len(&self) -> usize {
+        return self.program_len;
+    }
+
+    /// Returns the number of pages currently allocated.
+    pub fn get_page_count(&self) -> usize {
+        return self.iter_pages().count();
+    }
+
+    /// Returns the number of allocated pages that are shared with another memory, i.e. that
+    /// haven't been written to since the memory was cloned.
+    pub fn get_shared_page_count(&self) -> usize {
+        return self
+            .iter_pages()
+            .filter(|(_, page)| Arc::strong_count(page) > 1)
+            .count();
+    }
+
+    /// Returns the limits on how far the memory is allowed to grow.
+    pub fn get_limits(&self) -> MemoryLimits {
+        return self.limits;
+    }
+
+    /// Returns a copy of the memory contents from address 0 up to (but excluding) len().
+    pub fn to_vec(&self) -> Vec<i64> {
+        return (0..self.len).map(|address| self.read(address)).collect();
+    }
+
+    /// Returns the allocated pages in order of page index, along with their index.
+    pub fn get_pages(&self) -> Vec<(usize, &[i64])> {
+        return self
+            .iter_pages()
+            .map(|(index, page)| (index, page.as_slice()))
+            .collect();
+    }
+
+    /// Recreates memory from pages previously returned by get_pages(), along with the length of
+    /// the program that was loaded and the length of memory at that time. Returns None if they
+    /// couldn't have come from memory with the given limits, i.e. a page is the wrong size,
+    /// repeated or beyond the highest address, there are more pages than allowed, or the length
+    /// doesn't match the contents.
+    pub fn from_pages(
+        pages: Vec<(usize, Vec<i64>)>,
+        program_len: usize,
+        len: usize,
+        limits: MemoryLimits,
+    ) -> Option<Self> {
+        // Only the program itself may exceed the limits
+        let program_pages = program_len.saturating_add(PAGE_SIZE - 1) / PAGE_SIZE;
+        if pages.len() > limits.max_pages.max(program_pages) {
+            return None;
+        }
+        if len > program_len && len - 1 > limits.max_address {
+            return None;
+        }
+        let mut memory = IntcodeMemory::new_empty(program_len, limits);
+        memory.len = len;
+        for (index, page) in pages {
+            // Page indexes too large to have an address are also beyond the highest address
+            let start = index.checked_mul(PAGE_SIZE);
+            let in_limits =
+                start.filter(|start| *start < program_len || *start <= limits.max_address);
+            if page.len() != PAGE_SIZE || in_limits.is_none() {
+                return None;
+            }
+            if memory.get_page(index).is_some() {
+                return None;
+            }
+            memory.insert_page(index, Arc::new(page));
+        }
+        if len < program_len || memory.get_content_len() != len {
+            return None;
+        }
+        return Some(memory);
+    }
+
+    /// Returns the length of memory worked out from its contents, which is never shorter than the
+    /// program that was loaded.
+    fn get_content_len(&self) -> usize {
+        // Highest non-zero value is in the last page holding one, so stop at that page
+        let written_len = self
+            .iter_pages()
+            .rev()
+            .find_map(|(index, page)| {
+                let offset = page.iter().rposition(|value| *value != 0)?;
+                return Some(index * PAGE_SIZE + offset + 1);
+            })
+            .unwrap_or(0);
+        return written_len.max(self.program_len);
+    }
+
+    /// Returns the page with the given index, if it has been allocated.
+    fn get_page(&self, index: usize) -> Option<&Arc<Vec<i64>>> {
+        if index < NUM_NEAR_PAGES {
+            return self.near_pages[index].as_ref();
+        }
+        return self.far_pages.get(&index);
+    }
+
+    /// Stores the page at the given index.
+    fn insert_page(&mut self, index: usize, page: Arc<Vec<i64>>) {
+        if index < NUM_NEAR_PAGES {
+            self.near_pages[index] = Some(page);
+        } else {
+            self.far_pages.insert(index, page);
+        }
+    }
+
+    /// Returns an iterator over the allocated pages and their indices, in order of index.
+    fn iter_pages(&self) -> impl DoubleEndedIterator<Item = (usize, &Arc<Vec<i64>>)> {
+        let near_pages = self
+            .near_pages
+            .iter()
+            .enumerate()
+            .filter_map(|(index, page)| page.as_ref().map(|page| (index, page)));
+        let far_pages = self.far_pages.iter().map(|(index, page)| (*index, page));
+        return near_pages.chain(far_pages);
+    }
+
+    /// Returns an iterator over the allocated pages holding a non-zero value, and their indices.
+    fn iter_non_zero_pages(&self) -> impl Iterator<Item = (usize, &[i64])> {
+        return self
+            .iter_pages()
+            .filter(|(_, page)| page.iter().any(|value| *value != 0))
+            .map(|(index, page)| (index, page.as_slice()));
+    }
+}
+
+impl PartialEq for IntcodeMemory {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len != other.len || self.limits != other.limits {
+            return false;
+        }
+        // Pages still shared with a clone are equal without comparing their contents, so a memory
+        // is compared with an earlier clone of itself in time proportional to the pages written
+        let mut pages = self
+            .iter_pages()
+            .merge_join_by(other.iter_pages(), |(a, _), (b, _)| a.cmp(b));
+        return pages.all(|pages| match pages {
+            EitherOrBoth::Both((_, a), (_, b)) => Arc::ptr_eq(a, b) || a == b,
+            EitherOrBoth::Left((_, page)) | EitherOrBoth::Right((_, page)) => {
+                page.iter().all(|value| *value == 0)
+            }
+        });
+    }
+}
+
+impl Eq for IntcodeMemory {}
+
+impl Hash for IntcodeMemory {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        self.limits.hash(state);
+        for page in self.iter_non_zero_pages() {
+            page.hash(state);
+        }
+    }
+}
+
+impl Index<usize> for IntcodeMemory {
+    type Output = i64;
+
+    fn index(&self, address: usize) -> &i64 {
+        match self.get_page(address / PAGE_SIZE) {
+            Some(page) => return &page[address % PAGE_SIZE],
+            None => return &0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_unwritten_address() {
+        let memory = IntcodeMemory::new(vec![1, 2, 3], MemoryLimits::default());
+        assert_eq!(3, memory.read(2));
+        assert_eq!(0, memory.read(3));
+        assert_eq!(0, memory.read(1 << 30));
+        assert_eq!(1, memory.get_page_count());
+        assert_eq!(vec![1, 2, 3], memory.to_vec());
+    }
+
+    #[test]
+    fn test_write_far_address() {
+        let mut memory = IntcodeMemory::new(vec![99], MemoryLimits::default());
+        memory.write(5_000_000, 7).unwrap();
+        assert_eq!(7, memory.read(5_000_000));
+        assert_eq!(7, memory[5_000_000]);
+        assert_eq!(5_000_001, memory.len());
+        assert_eq!(2, memory.get_page_count());
+        // Writing zero to an unallocated page doesn't allocate it
+        memory.write(9_000_000, 0).unwrap();
+        assert_eq!(2, memory.get_page_count());
+    }
+
+    #[test]
+    fn test_program_larger_than_page() {
+        let program: Vec<i64> = (0..3000).collect();
+        let memory = IntcodeMemory::new(program.clone(), MemoryLimits::default());
+        assert_eq!(3, memory.get_page_count());
+        assert_eq!(program, memory.to_vec());
+    }
+
+    #[test]
+    fn test_memory_limits() {
+        let limits = MemoryLimits {
+            max_address: 4095,
+            max_pages: 2,
+        };
+        let mut memory = IntcodeMemory::new(vec![99], limits);
+        assert_eq!(Err(FaultKind::AddressOutOfRange), memory.write(4096, 1));
+        memory.write(2048, 1).unwrap();
+        assert_eq!(Err(FaultKind::MemoryLimitExceeded), memory.write(3072, 1));
+        // Writes to pages that are already allocated are still allowed
+        memory.write(10, 1).unwrap();
+    }
+
+    #[test]
+    fn test_zero_writes_and_equality() {
+        let mut allocated = IntcodeMemory::new(vec![1, 2, 3], MemoryLimits::default());
+        let mut unallocated = allocated.clone();
+        // Zero writes past the end don't extend memory, whether or not their page is allocated
+        allocated.write(10, 0).unwrap();
+        unallocated.write(5000, 0).unwrap();
+        assert_eq!((3, 3), (allocated.len(), unallocated.len()));
+        assert_eq!(1, unallocated.get_page_count());
+        // Page written to and zeroed again is the same as one never written to
+        let mut zeroed = unallocated.clone();
+        zeroed.write(2, 7).unwrap();
+        zeroed.write(2000, 7).unwrap();
+        zeroed.write(2000, 0).unwrap();
+        unallocated.write(2, 7).unwrap();
+        assert_eq!(2, zeroed.get_page_count());
+        assert_eq!((3, 3), (zeroed.len(), unallocated.len()));
+        assert!(zeroed == unallocated);
+        let hash = |memory: &IntcodeMemory| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            memory.hash(&mut hasher);
+            return hasher.finish();
+        };
+        assert_eq!(hash(&zeroed), hash(&unallocated));
+        assert_eq!(zeroed.to_vec(), unallocated.to_vec());
+        allocated.write(1, 0).unwrap();
+        assert!(allocated != zeroed);
+    }
+
+    #[test]
+    fn test_len_follows_contents() {
+        let mut memory = IntcodeMemory::new(vec![1, 2, 0], MemoryLimits::default());
+        memory.write(3000, 1).unwrap();
+        memory.write(5000, 1).unwrap();
+        assert_eq!(5001, memory.len());
+        // Clearing the highest value shrinks back to the next one, but never below the program
+        memory.write(5000, 0).unwrap();
+        assert_eq!(3001, memory.len());
+        memory.write(3000, 0).unwrap();
+        assert_eq!(3, memory.len());
+        memory.write(1, 0).unwrap();
+        assert_eq!(vec![1, 0, 0], memory.to_vec());
+    }
+
+    #[test]
+    fn test_from_malformed_pages() {
+        let limits = MemoryLimits {
+            max_address: 4095,
+            max_pages: 2,
+        };
+        let page = vec![1; PAGE_SIZE];
+        let memory = IntcodeMemory::from_pages(vec![(1, page.clone())], 3, 2048, limits).unwrap();
+        assert_eq!(1, memory.read(1024));
+        assert_eq!(3, memory.get_program_len());
+        // Zeroed page beyond the length is allowed, as it may have been written to and cleared
+        let pages = vec![(0, page.clone()), (3, vec![0; PAGE_SIZE])];
+        assert!(IntcodeMemory::from_pages(pages, 3, 1024, limits).is_some());
+        // Length that doesn't match the contents, or is shorter than the program
+        assert!(IntcodeMemory::from_pages(vec![(1, page.clone())], 3, 3000, limits).is_none());
+        assert!(IntcodeMemory::from_pages(vec![(1, page.clone())], 3000, 2048, limits).is_none());
+        // Pages too short, repeated, too many, or too far to have an address
+        assert!(IntcodeMemory::from_pages(vec![(0, vec![1; 10])], 3, 1024, limits).is_none());
+        let repeated = vec![(0, page.clone()), (0, page.clone())];
+        assert!(IntcodeMemory::from_pages(repeated, 3, 1024, limits).is_none());
+        let too_many = vec![(0, page.clone()), (1, page.clone()), (2, page.clone())];
+        assert!(IntcodeMemory::from_pages(too_many.clone(), 3, 3072, limits).is_none());
+        assert!(IntcodeMemory::from_pages(too_many, 3072, 3072, limits).is_some());
+        assert!(IntcodeMemory::from_pages(vec![(4, page.clone())], 3, 5120, limits).is_none());
+        let huge = vec![(usize::MAX / 2, page)];
+        assert!(IntcodeMemory::from_pages(huge, usize::MAX, usize::MAX, limits).is_none());
+    }
+
+    #[test]
+    fn test_copy_on_write() {
+        let program: Vec<i64> = (0..3000).collect();
+        let original = IntcodeMemory::new(program.clone(), MemoryLimits::default());
+        let mut copy = original.clone();
+        assert_eq!(3, copy.get_shared_page_count());
+        copy.write(1500, -1).unwrap();
+        copy.write(10_000, 1).unwrap();
+        // Only the written page is copied, and the new page is never shared
+        assert_eq!(2, copy.get_shared_page_count());
+        assert_eq!(2, original.get_shared_page_count());
+        assert_eq!(4, copy.get_page_count());
+        assert_eq!(-1, copy.read(1500));
+        assert_eq!(1500, original.read(1500));
+        assert_eq!(program, original.to_vec());
+        // Copied pages are compared by their contents
+        assert!(copy != original);
+        copy.write(1500, 1500).unwrap();
+        copy.write(10_000, 0).unwrap();
+        assert!(copy == original);
+    }
+}
@@ -1,7 +1,61 @@
+use self::cycle::CycleDetector;
+use self::decode_cache::DecodeCache;
+use super::error::AocError;
+use super::fs;
 use std::collections::VecDeque;
 use std::fs::File;
+use std::hash::{Hash, Hasher};
 use std::io::Read;
-use super::fs;
+
+mod analysis;
+mod arithmetic;
+mod ascii;
+mod assembler;
+mod console;
+mod cycle;
+mod debugger;
+mod decode_cache;
+mod device;
+mod disassembler;
+mod fault;
+mod fuzz;
+mod instruction;
+mod memory;
+mod network;
+mod profiler;
+mod run_state;
+mod snapshot;
+mod threaded;
+mod trace;
+mod transpiler;
+
+pub use self::analysis::{
+    analyze, analyze_from, BasicBlock, CodeWrite, ControlFlowGraph, Edge, EdgeKind,
+    InvalidInstruction, IoSite,
+};
+pub use self::arithmetic::ArithmeticMode;
+pub use self::ascii::AsciiAdapter;
+pub use self::assembler::assemble;
+pub use self::console::Console;
+pub use self::debugger::{DebugEvent, Debugger};
+pub use self::device::{
+    FnInput, FnOutput, InputDevice, MachineInput, OutputDevice, ReaderInput, WriterOutput,
+};
+pub use self::disassembler::{disassemble, disassemble_from, Listing, ListingEntry};
+pub use self::fault::{FaultKind, MachineFault};
+pub use self::fuzz::{
+    check_program, fuzz, run_backend, run_reference, FuzzBackend, FuzzMismatch, FuzzOutcome,
+    FuzzProgram, FuzzReport, FuzzRng, FUZZ_INSTRUCTION_BUDGET,
+};
+pub use self::instruction::{Instruction, Opcode, ParamMode, Parameter};
+pub use self::memory::{IntcodeMemory, MemoryLimits, PAGE_SIZE};
+pub use self::network::{IntcodeNetwork, NetworkState, Packet, Topology};
+pub use self::profiler::{HotLoop, Profile};
+pub use self::run_state::RunState;
+pub use self::snapshot::SNAPSHOT_VERSION;
+pub use self::threaded::{MachineThread, ThreadedNetwork};
+pub use self::trace::{load_trace, MemoryWrite, Replayer, TraceEntry, Tracer};
+pub use self::transpiler::transpile;
 
 // Intcode Opcode constants
 const OPCODE_ADD: i64 = 1;
@@ -18,52 +72,168 @@ const OPCODE_HALT: i64 = 99;
 const PARAM_MODE_POSITION: i64 = 0;
 const PARAM_MODE_IMMEDIATE: i64 = 1;
 const PARAM_MODE_RELATIVE: i64 = 2;
-// Memory config
-const MEMORY_SIZE: usize = 10000;
 
 /// Represents the state of an Intcode Machine.
-#[derive(Clone, Hash, PartialEq, Eq)]
+///
+/// Input values are read from the input queue, and once it is empty from the input device (if one
+/// is attached). Output values are added to the output queue, unless an output device is attached.
+///
+/// Machines are compared and hashed by their execution state only - the arithmetic mode, any
+/// instruction budget or cycle detection, the decode cache, any profile being collected and any
+/// attached devices are ignored.
 pub struct IntcodeMachine {
     prog_c: usize,
-    memory: Vec<i64>,
+    memory: IntcodeMemory,
     input: VecDeque<i64>,
     output: VecDeque<i64>,
     halted: bool,
     awaiting_input: bool,
     relative_base: i64,
+    fault: Option<MachineFault>,
+    arithmetic: ArithmeticMode,
+    instruction_budget: Option<u64>,
+    cycle_detector: Option<Box<CycleDetector>>,
+    decode_cache: DecodeCache,
+    profile: Option<Box<Profile>>,
+    input_device: Option<Box<dyn InputDevice + Send>>,
+    output_device: Option<Box<dyn OutputDevice + Send>>,
+}
+
+/// Devices can't be copied, so clones of a machine have no devices attached.
+impl Clone for IntcodeMachine {
+    fn clone(&self) -> Self {
+        Self {
+            prog_c: self.prog_c,
+            memory: self.memory.clone(),
+            input: self.input.clone(),
+            output: self.output.clone(),
+            halted: self.halted,
+            awaiting_input: self.awaiting_input,
+            relative_base: self.relative_base,
+            fault: self.fault.clone(),
+            arithmetic: self.arithmetic,
+            instruction_budget: self.instruction_budget,
+            cycle_detector: self.cycle_detector.clone(),
+            decode_cache: self.decode_cache.clone(),
+            profile: self.profile.clone(),
+            input_device: None,
+            output_device: None,
+        }
+    }
+}
+
+impl PartialEq for IntcodeMachine {
+    fn eq(&self, other: &Self) -> bool {
+        return self.prog_c == other.prog_c
+            && self.memory == other.memory
+            && self.input == other.input
+            && self.output == other.output
+            && self.halted == other.halted
+            && self.awaiting_input == other.awaiting_input
+            && self.relative_base == other.relative_base
+            && self.fault == other.fault;
+    }
+}
+
+impl Eq for IntcodeMachine {}
+
+impl Hash for IntcodeMachine {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.prog_c.hash(state);
+        self.memory.hash(state);
+        self.input.hash(state);
+        self.output.hash(state);
+        self.halted.hash(state);
+        self.awaiting_input.hash(state);
+        self.relative_base.hash(state);
+        self.fault.hash(state);
+    }
 }
 
 impl IntcodeMachine {
-    /// Creates a new instances of IntcodeMachine.
+    /// Creates a new instances of IntcodeMachine, using the default memory limits.
     pub fn new(initial_memory: Vec<i64>, initial_input: VecDeque<i64>) -> Self {
+        return IntcodeMachine::new_with_memory_limits(
+            initial_memory,
+            initial_input,
+            MemoryLimits::default(),
+        );
+    }
+
+    /// Creates a new instance of IntcodeMachine with the given limits on how far its memory can
+    /// grow while running the program.
+    pub fn new_with_memory_limits(
+        initial_memory: Vec<i64>,
+        initial_input: VecDeque<i64>,
+        limits: MemoryLimits,
+    ) -> Self {
+        let memory = IntcodeMemory::new(initial_memory, limits);
+        let decode_cache = DecodeCache::new_filled(&memory);
         Self {
             prog_c: 0,
-            memory: IntcodeMachine::create_initial_memory_array(initial_memory),
+            memory,
             input: initial_input.clone(),
             output: VecDeque::new(),
             halted: false,
             awaiting_input: false,
             relative_base: 0,
+            fault: None,
+            arithmetic: ArithmeticMode::default(),
+            instruction_budget: None,
+            cycle_detector: None,
+            decode_cache,
+            profile: None,
+            input_device: None,
+            output_device: None,
         }
     }
 
-    /// Returns a copy of the Intcode computer's memory.
+    /// Returns a copy of the Intcode computer's memory, from address 0 up to the highest address
+    /// holding part of the program or a written value.
     pub fn get_memory_dump(&self) -> Vec<i64> {
-        return self.memory.clone();
+        return self.memory.to_vec();
     }
 
-    /// Copies the given initial memory into a vector with maximum memory size of machine, and
-    /// returns the result.
-    fn create_initial_memory_array(initial_memory: Vec<i64>) -> Vec<i64> {
-        let mut memory = Vec::<i64>::with_capacity(MEMORY_SIZE);
-        for i in 0..MEMORY_SIZE {
-            if i < initial_memory.len() {
-                memory.push(initial_memory[i]);
-            } else {
-                memory.push(0);
-            }
-        }
-        return memory;
+    /// Returns the number of cells in the machine memory, from address 0 up to the highest address
+    /// holding part of the program or a written value.
+    pub fn get_memory_len(&self) -> usize {
+        return self.memory.len();
+    }
+
+    /// Returns the value held at the given address of the machine memory.
+    pub fn peek_memory(&self, address: usize) -> i64 {
+        return self.memory.read(address);
+    }
+
+    /// Stores the value at the given address of the machine memory, e.g. to patch a program while
+    /// debugging it. Returns the kind of fault if the address is beyond the memory limits.
+    pub fn poke_memory(&mut self, address: usize, value: i64) -> Result<(), FaultKind> {
+        return self.write_memory(address, value);
+    }
+
+    /// Returns the limits on how far the machine memory is allowed to grow.
+    pub fn get_memory_limits(&self) -> MemoryLimits {
+        return self.memory.get_limits();
+    }
+
+    /// Returns the number of memory pages shared with forks of the machine (or the machine it was
+    /// forked from) that haven't been written to since the fork.
+    pub fn get_shared_page_count(&self) -> usize {
+        return self.memory.get_shared_page_count();
+    }
+
+    /// Sets whether instructions are executed through the decoded instruction cache (enabled by
+    /// default). With the cache disabled, every instruction is decoded from memory as it is
+    /// executed - this is slower, but is kept for comparison in benchmarks and tests.
+    pub fn set_decode_cache_enabled(&mut self, enabled: bool) {
+        self.decode_cache.set_enabled(enabled);
+    }
+
+    /// Creates an independent copy of the machine in its current state. Memory pages are shared
+    /// between the machine and the fork until either of them writes to a page, so forking is cheap
+    /// even for machines with large amounts of memory.
+    pub fn fork(&self) -> Self {
+        return self.clone();
     }
 
     /// Adds the given input value to the input queue of the machine.
@@ -75,6 +245,65 @@ impl IntcodeMachine {
         }
     }
 
+    /// Returns how the machine handles arithmetic results that don't fit in an i64.
+    pub fn get_arithmetic_mode(&self) -> ArithmeticMode {
+        return self.arithmetic;
+    }
+
+    /// Sets how the machine handles arithmetic results that don't fit in an i64 (checked by
+    /// default).
+    pub fn set_arithmetic_mode(&mut self, mode: ArithmeticMode) {
+        self.arithmetic = mode;
+    }
+
+    /// Returns the number of instructions the machine can still execute before it is suspended
+    /// with RunState::BudgetExhausted, or None if there is no budget.
+    pub fn get_instruction_budget(&self) -> Option<u64> {
+        return self.instruction_budget;
+    }
+
+    /// Sets the number of instructions the machine can execute before it is suspended with
+    /// RunState::BudgetExhausted (instructions waiting for input don't count). The machine
+    /// resumes once given a new budget, or None to remove the budget.
+    pub fn set_instruction_budget(&mut self, budget: Option<u64>) {
+        self.instruction_budget = budget;
+    }
+
+    /// Enables or disables cycle detection. While enabled, the machine is suspended with
+    /// RunState::CycleDetected once it returns to exactly the same state (program counter,
+    /// relative base and memory) without any input or output in between, as it will never halt.
+    pub fn set_cycle_detection(&mut self, enabled: bool) {
+        self.cycle_detector = match enabled {
+            true => Some(Box::new(CycleDetector::new())),
+            false => None,
+        };
+    }
+
+    /// Attaches the device that input values are read from once the input queue is empty,
+    /// replacing any device already attached.
+    pub fn set_input_device<D: InputDevice + Send + 'static>(&mut self, device: D) {
+        self.input_device = Some(Box::new(device));
+        // Device may have input available, so let the machine try reading it
+        self.awaiting_input = false;
+    }
+
+    /// Detaches the input device, returning it.
+    pub fn remove_input_device(&mut self) -> Option<Box<dyn InputDevice + Send>> {
+        return self.input_device.take();
+    }
+
+    /// Attaches the device that output values are written to instead of the output queue,
+    /// replacing any device already attached.
+    pub fn set_output_device<D: OutputDevice + Send + 'static>(&mut self, device: D) {
+        self.output_device = Some(Box::new(device));
+    }
+
+    /// Detaches the output device, returning it. Output values are added to the output queue
+    /// again from now on.
+    pub fn remove_output_device(&mut self) -> Option<Box<dyn OutputDevice + Send>> {
+        return self.output_device.take();
+    }
+
     /// Returns the current halt state of the machine.
     pub fn has_halted(&self) -> bool {
         return self.halted;
@@ -94,116 +323,347 @@ impl IntcodeMachine {
     /// execution after the machine executes the first output instruction.
     ///
     /// If the machine has already halted (i.e. encountered a HALT opcode), this function will
-    /// immediately return.
+    /// immediately return. Panics if the machine encounters a fault - use
+    /// try_execute_program_break_on_output() to handle faults instead.
     pub fn execute_program_break_on_output(&mut self, break_on_output: bool) {
-        // If machine has halted or is awaiting input, break progream execution
-        if self.halted || self.awaiting_input {
-            return;
+        if let Err(fault) = self.try_execute_program_break_on_output(break_on_output) {
+            panic!("Intcode machine fault: {}", fault);
         }
+    }
+
+    /// Executes the program contained in machine memory, returning a MachineFault if the program
+    /// does something invalid. The faulting instruction is not executed, so the program counter,
+    /// relative base and memory of the machine are left as they were for inspection. Once a fault
+    /// has occurred, further calls return the same fault.
+    pub fn try_execute_program_break_on_output(
+        &mut self,
+        break_on_output: bool,
+    ) -> Result<(), MachineFault> {
         loop {
-            // Extract program parameters for current run
-            let arg = self.retrieve_from_memory(self.prog_c);
-            let (opcode, mode_1, mode_2, mode_3) =
-                IntcodeMachine::extract_opcode_and_param_modes(arg);
-            // Break here if HALT code is reached, just in case we are at end of program array
-            if opcode == OPCODE_HALT {
-                self.halted = true;
-                break;
+            match self.step_and_keep_output()? {
+                // Only break after executing an output instruction if asked to
+                Some(RunState::Output(_)) if !break_on_output => (),
+                Some(_) => break,
+                None => (),
+            }
+        }
+        return Ok(());
+    }
+
+    /// Runs the program until it halts, needs input or outputs a value, and returns the reason it
+    /// stopped. A value returned in RunState::Output is removed from the output queue, or has
+    /// already been written to the output device if one is attached.
+    ///
+    /// If the machine is awaiting input, it will resume once input has been added with add_input().
+    pub fn run(&mut self) -> Result<RunState, MachineFault> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// Executes a single instruction. Returns the RunState if the instruction halted the machine,
+    /// needed input that wasn't available, or output a value (which is removed from the output
+    /// queue). Returns None if the instruction completed without any of these occurring.
+    pub fn step(&mut self) -> Result<Option<RunState>, MachineFault> {
+        let state = self.step_and_keep_output()?;
+        if let Some(RunState::Output(_)) = state {
+            self.remove_step_output();
+        }
+        return Ok(state);
+    }
+
+    /// Executes instructions until the given predicate returns true for the state of the machine
+    /// (checked after each instruction), or the machine halts, needs input or outputs a value.
+    /// Returns the RunState if the machine stopped for one of the latter reasons, otherwise None.
+    pub fn run_until<F>(&mut self, mut predicate: F) -> Result<Option<RunState>, MachineFault>
+    where
+        F: FnMut(&IntcodeMachine) -> bool,
+    {
+        loop {
+            let state = self.step()?;
+            if state.is_some() {
+                return Ok(state);
+            }
+            if predicate(self) {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Executes a single instruction, leaving any value it outputs in the output queue. Faults are
+    /// recorded against the machine, and a machine that has faulted will not execute any further.
+    pub fn step_and_keep_output(&mut self) -> Result<Option<RunState>, MachineFault> {
+        if let Some(fault) = &self.fault {
+            return Err(fault.clone());
+        }
+        if self.instruction_budget == Some(0) && !self.halted {
+            return Ok(Some(RunState::BudgetExhausted));
+        }
+        let pc = self.prog_c;
+        let was_halted = self.halted;
+        // Read the opcode before executing, in case the instruction overwrites it
+        let opcode = match self.profile {
+            Some(_) => Opcode::from_code(self.memory.read(pc) % 100),
+            None => None,
+        };
+        match self.execute_instruction() {
+            Err(fault) => {
+                self.fault = Some(fault.clone());
+                return Err(fault);
+            }
+            Ok(state) => {
+                // Instructions waiting for input haven't been executed yet
+                if was_halted || state == Some(RunState::NeedsInput) {
+                    return Ok(state);
+                }
+                if let (Some(profile), Some(opcode)) = (&mut self.profile, opcode) {
+                    profile.record(pc, opcode, self.prog_c, state);
+                }
+                if let Some(budget) = &mut self.instruction_budget {
+                    *budget -= 1;
+                }
+                if let (Some(detector), None) = (&mut self.cycle_detector, state) {
+                    if detector.check(self.prog_c, self.relative_base, &self.memory) {
+                        return Ok(Some(RunState::CycleDetected));
+                    }
+                }
+                return Ok(state);
             }
-            // Check the current opcode and perform required operation
-            if opcode == OPCODE_ADD {
-                let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true);
-                let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true);
-                let output_addr = self.retrieve_param_value(self.prog_c + 3, mode_3, false);
-                let output = param_1 + param_2;
-                self.store_in_memory(output, output_addr as usize);
+        }
+    }
+
+    /// Starts collecting an execution profile of the machine, counting the instructions executed
+    /// from now on. Any profile already being collected is kept.
+    pub fn enable_profiling(&mut self) {
+        if self.profile.is_none() {
+            self.profile = Some(Box::new(Profile::new()));
+        }
+    }
+
+    /// Returns the execution profile being collected, if profiling is enabled.
+    pub fn get_profile(&self) -> Option<&Profile> {
+        return self.profile.as_deref();
+    }
+
+    /// Stops profiling the machine, returning the profile that was collected.
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        return self.profile.take().map(|profile| *profile);
+    }
+
+    /// Executes the instruction at the program counter. Returns the RunState if the instruction
+    /// halted the machine, needed input or output a value.
+    ///
+    /// Instructions are taken from the decode cache (if enabled), and those that can't be decoded
+    /// without a fault are left to the uncached core to report the fault.
+    fn execute_instruction(&mut self) -> Result<Option<RunState>, MachineFault> {
+        if self.halted {
+            return Ok(Some(RunState::Halted));
+        }
+        if !self.decode_cache.is_enabled() {
+            return self.execute_instruction_uncached();
+        }
+        let decoded = match self.decode_cache.get(&self.memory, self.prog_c) {
+            Some(decoded) => decoded,
+            None => return self.execute_instruction_uncached(),
+        };
+        let modes = decoded.modes;
+        let params = decoded.params;
+        match decoded.opcode {
+            Opcode::Add => {
+                let a = self.read_operand(modes[0], params[0])?;
+                let b = self.read_operand(modes[1], params[1])?;
+                // Check the write address before the result, so faults match the uncached core
+                let address = self.resolve_write_address(modes[2], params[2])?;
+                let value = self.add_values(a, b)?;
+                self.store_in_memory(value, address)?;
                 self.prog_c += 4;
-            } else if opcode == OPCODE_MULT {
-                let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true);
-                let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true);
-                let output_addr = self.retrieve_param_value(self.prog_c + 3, mode_3, false);
-                let output = param_1 * param_2;
-                self.store_in_memory(output, output_addr as usize);
+            }
+            Opcode::Multiply => {
+                let a = self.read_operand(modes[0], params[0])?;
+                let b = self.read_operand(modes[1], params[1])?;
+                // Check the write address before the result, so faults match the uncached core
+                let address = self.resolve_write_address(modes[2], params[2])?;
+                let value = self.multiply_values(a, b)?;
+                self.store_in_memory(value, address)?;
                 self.prog_c += 4;
-            } else if opcode == OPCODE_INPUT {
-                // If we have no input to read, set awaiting input flag and break execution
-                if self.input.len() == 0 {
-                    self.awaiting_input = true;
-                    break;
-                }
-                let input_value = self.input.pop_front().unwrap();
-                let output_addr = self.retrieve_param_value(self.prog_c + 1, mode_1, false);
-                self.store_in_memory(input_value, output_addr as usize);
+            }
+            Opcode::Input => {
+                let address = self.resolve_write_address(modes[0], params[0])?;
+                let value = match self.read_input() {
+                    Some(value) => value,
+                    None => {
+                        self.awaiting_input = true;
+                        return Ok(Some(RunState::NeedsInput));
+                    }
+                };
+                self.store_in_memory(value, address)?;
                 self.prog_c += 2;
-            } else if opcode == OPCODE_OUTPUT {
-                let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true);
-                self.output.push_back(param_1);
+            }
+            Opcode::Output => {
+                let value = self.read_operand(modes[0], params[0])?;
+                self.write_output(value);
                 self.prog_c += 2;
-                // Check if the machine should break after executing an output instruction
-                if break_on_output {
-                    break;
-                }
-            } else if opcode == OPCODE_JUMP_IF_TRUE {
-                let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true);
-                let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true);
-                if param_1 != 0 {
-                    self.prog_c = param_2 as usize;
-                } else {
-                    self.prog_c += 3;
-                }
-            } else if opcode == OPCODE_JUMP_IF_FALSE {
-                let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true);
-                let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true);
-                if param_1 == 0 {
-                    self.prog_c = param_2 as usize;
+                return Ok(Some(RunState::Output(value)));
+            }
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                let condition = self.read_operand(modes[0], params[0])?;
+                let target = self.read_operand(modes[1], params[1])?;
+                if (condition != 0) == (decoded.opcode == Opcode::JumpIfTrue) {
+                    self.prog_c = self.check_address(target)?;
                 } else {
                     self.prog_c += 3;
                 }
-            } else if opcode == OPCODE_LESS_THAN {
-                let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true);
-                let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true);
-                let output_addr = self.retrieve_param_value(self.prog_c + 3, mode_3, false);
-                if param_1 < param_2 {
-                    self.store_in_memory(1, output_addr as usize);
-                } else {
-                    self.store_in_memory(0, output_addr as usize);
-                }
+            }
+            Opcode::LessThan => {
+                let value = self.read_operand(modes[0], params[0])?
+                    < self.read_operand(modes[1], params[1])?;
+                self.write_operand(modes[2], params[2], value as i64)?;
                 self.prog_c += 4;
-            } else if opcode == OPCODE_EQUALS {
-                let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true);
-                let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true);
-                let output_addr = self.retrieve_param_value(self.prog_c + 3, mode_3, false);
-                if param_1 == param_2 {
-                    self.store_in_memory(1, output_addr as usize);
-                } else {
-                    self.store_in_memory(0, output_addr as usize);
-                }
+            }
+            Opcode::Equals => {
+                let value = self.read_operand(modes[0], params[0])?
+                    == self.read_operand(modes[1], params[1])?;
+                self.write_operand(modes[2], params[2], value as i64)?;
                 self.prog_c += 4;
-            } else if opcode == OPCODE_ADJUST_REL_BASE {
-                let delta = self.retrieve_param_value(self.prog_c + 1, mode_1, true);
-                self.relative_base += delta;
+            }
+            Opcode::AdjustRelativeBase => {
+                let delta = self.read_operand(modes[0], params[0])?;
+                self.relative_base = self.add_values(self.relative_base, delta)?;
                 self.prog_c += 2;
+            }
+            Opcode::Halt => {
+                self.halted = true;
+                return Ok(Some(RunState::Halted));
+            }
+        }
+        return Ok(None);
+    }
+
+    /// Executes the instruction at the program counter, decoding it directly from memory. This was
+    /// the original execution core of the machine, and is used for instructions that fault while
+    /// being decoded.
+    fn execute_instruction_uncached(&mut self) -> Result<Option<RunState>, MachineFault> {
+        // Extract program parameters for current instruction
+        let arg = self.retrieve_from_memory(self.prog_c as i64)?;
+        let (opcode, mode_1, mode_2, mode_3) = IntcodeMachine::extract_opcode_and_param_modes(arg);
+        if opcode == OPCODE_HALT {
+            self.halted = true;
+            return Ok(Some(RunState::Halted));
+        }
+        // Check the current opcode and perform required operation
+        if opcode == OPCODE_ADD {
+            let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true)?;
+            let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true)?;
+            let output_addr = self.retrieve_param_value(self.prog_c + 3, mode_3, false)?;
+            let output = self.add_values(param_1, param_2)?;
+            self.store_in_memory(output, output_addr)?;
+            self.prog_c += 4;
+        } else if opcode == OPCODE_MULT {
+            let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true)?;
+            let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true)?;
+            let output_addr = self.retrieve_param_value(self.prog_c + 3, mode_3, false)?;
+            let output = self.multiply_values(param_1, param_2)?;
+            self.store_in_memory(output, output_addr)?;
+            self.prog_c += 4;
+        } else if opcode == OPCODE_INPUT {
+            // Check output address before reading input, so a fault doesn't lose the value
+            let output_addr = self.retrieve_param_value(self.prog_c + 1, mode_1, false)?;
+            // If we have no input to read, set awaiting input flag and break execution
+            let input_value = match self.read_input() {
+                Some(value) => value,
+                None => {
+                    self.awaiting_input = true;
+                    return Ok(Some(RunState::NeedsInput));
+                }
+            };
+            self.store_in_memory(input_value, output_addr)?;
+            self.prog_c += 2;
+        } else if opcode == OPCODE_OUTPUT {
+            let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true)?;
+            self.write_output(param_1);
+            self.prog_c += 2;
+            return Ok(Some(RunState::Output(param_1)));
+        } else if opcode == OPCODE_JUMP_IF_TRUE {
+            let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true)?;
+            let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true)?;
+            if param_1 != 0 {
+                self.prog_c = self.check_address(param_2)?;
+            } else {
+                self.prog_c += 3;
+            }
+        } else if opcode == OPCODE_JUMP_IF_FALSE {
+            let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true)?;
+            let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true)?;
+            if param_1 == 0 {
+                self.prog_c = self.check_address(param_2)?;
+            } else {
+                self.prog_c += 3;
+            }
+        } else if opcode == OPCODE_LESS_THAN {
+            let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true)?;
+            let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true)?;
+            let output_addr = self.retrieve_param_value(self.prog_c + 3, mode_3, false)?;
+            if param_1 < param_2 {
+                self.store_in_memory(1, output_addr)?;
+            } else {
+                self.store_in_memory(0, output_addr)?;
+            }
+            self.prog_c += 4;
+        } else if opcode == OPCODE_EQUALS {
+            let param_1 = self.retrieve_param_value(self.prog_c + 1, mode_1, true)?;
+            let param_2 = self.retrieve_param_value(self.prog_c + 2, mode_2, true)?;
+            let output_addr = self.retrieve_param_value(self.prog_c + 3, mode_3, false)?;
+            if param_1 == param_2 {
+                self.store_in_memory(1, output_addr)?;
             } else {
-                // Shouldn't get here
-                panic!(
-                    "Opcode not recognised [pc: {}, opcode {}]",
-                    self.prog_c, opcode
-                );
+                self.store_in_memory(0, output_addr)?;
             }
+            self.prog_c += 4;
+        } else if opcode == OPCODE_ADJUST_REL_BASE {
+            let delta = self.retrieve_param_value(self.prog_c + 1, mode_1, true)?;
+            self.relative_base = self.add_values(self.relative_base, delta)?;
+            self.prog_c += 2;
+        } else {
+            return Err(self.new_fault(FaultKind::UnknownOpcode, None));
         }
+        return Ok(None);
     }
 
-    /// Executes the program contained within the machine.
+    /// Executes the program contained within the machine. Panics if the machine encounters a
+    /// fault.
     pub fn execute_program(&mut self) {
         self.execute_program_break_on_output(false);
     }
 
+    /// Executes the program contained within the machine, returning a MachineFault if the
+    /// program does something invalid.
+    pub fn try_execute_program(&mut self) -> Result<(), MachineFault> {
+        return self.try_execute_program_break_on_output(false);
+    }
+
+    /// Returns the fault that stopped the machine, if one has occurred.
+    pub fn get_fault(&self) -> Option<&MachineFault> {
+        return self.fault.as_ref();
+    }
+
+    /// Returns the current value of the program counter.
+    pub fn get_program_counter(&self) -> usize {
+        return self.prog_c;
+    }
+
+    /// Returns the current value of the relative base.
+    pub fn get_relative_base(&self) -> i64 {
+        return self.relative_base;
+    }
+
     /// Returns the value held in location 0 of the machine memory.
     pub fn get_location_zero(&self) -> i64 {
         if self.memory.is_empty() {
             panic!("Machine memory is empty!");
         }
-        return self.memory[0];
+        return self.memory.read(0);
     }
 
     /// Returns the first value in the output queue of the machine.
@@ -222,11 +682,42 @@ impl IntcodeMachine {
         return self.output.pop_front().unwrap();
     }
 
+    /// Returns the first value in the output queue of the machine, or an InvalidProgram error if
+    /// the program hasn't output anything.
+    pub fn try_get_output(&self) -> Result<i64, AocError> {
+        let output = self.output.front().copied();
+        return output.ok_or_else(IntcodeMachine::no_output_error);
+    }
+
+    /// Returns the first output value and removes it from the queue, or an InvalidProgram error if
+    /// the program hasn't output anything.
+    pub fn try_get_output_and_remove(&mut self) -> Result<i64, AocError> {
+        let output = self.output.pop_front();
+        return output.ok_or_else(IntcodeMachine::no_output_error);
+    }
+
+    /// Returns the last value in the output queue of the machine, or an InvalidProgram error if
+    /// the program hasn't output anything.
+    pub fn try_get_last_output(&self) -> Result<i64, AocError> {
+        let output = self.output.back().copied();
+        return output.ok_or_else(IntcodeMachine::no_output_error);
+    }
+
+    /// Creates the error returned when a value is needed from an empty output queue.
+    fn no_output_error() -> AocError {
+        return AocError::InvalidProgram(String::from("program produced no output"));
+    }
+
     /// Checks if the output queue of the machine is empty or not.
     pub fn is_output_empty(&self) -> bool {
         return self.output.is_empty();
     }
 
+    /// Returns a copy of the input queue.
+    pub fn get_input_vec(&self) -> VecDeque<i64> {
+        return self.input.clone();
+    }
+
     /// Returns a copy of the output queue.
     pub fn get_output_vec(&self) -> VecDeque<i64> {
         return self.output.clone();
@@ -244,82 +735,276 @@ impl IntcodeMachine {
         return (opcode, mode_1, mode_2, mode_3);
     }
 
-    /// Retrieves the value in the machine memory at the given index. Panics if
-    /// an out-of-bounds access is attempted (bad index).
-    fn retrieve_from_memory(&self, index: usize) -> i64 {
-        if index >= self.memory.len() {
-            panic!("Bad memory index.");
+    /// Creates a fault of the given kind for the instruction at the current program counter.
+    fn new_fault(&self, kind: FaultKind, address: Option<i64>) -> MachineFault {
+        let opcode = self.memory.read(self.prog_c) % 100;
+        return MachineFault {
+            kind,
+            opcode,
+            pc: self.prog_c,
+            relative_base: self.relative_base,
+            address,
+        };
+    }
+
+    /// Adds the values according to the arithmetic mode, faulting on overflow in checked mode.
+    fn add_values(&self, a: i64, b: i64) -> Result<i64, MachineFault> {
+        return self
+            .arithmetic
+            .add(a, b)
+            .ok_or_else(|| self.new_fault(FaultKind::ArithmeticOverflow, None));
+    }
+
+    /// Multiplies the values according to the arithmetic mode, faulting on overflow in checked
+    /// mode.
+    fn multiply_values(&self, a: i64, b: i64) -> Result<i64, MachineFault> {
+        return self
+            .arithmetic
+            .multiply(a, b)
+            .ok_or_else(|| self.new_fault(FaultKind::ArithmeticOverflow, None));
+    }
+
+    /// Checks that the given address is within the machine memory limits, returning it as an
+    /// index.
+    fn check_address(&self, address: i64) -> Result<usize, MachineFault> {
+        if address < 0 {
+            return Err(self.new_fault(FaultKind::NegativeAddress, Some(address)));
+        }
+        if address as u64 > self.memory.get_limits().max_address as u64 {
+            return Err(self.new_fault(FaultKind::AddressOutOfRange, Some(address)));
+        }
+        return Ok(address as usize);
+    }
+
+    /// Retrieves the value in the machine memory at the given address. Returns a fault if an
+    /// out-of-bounds access is attempted (bad address).
+    fn retrieve_from_memory(&self, address: i64) -> Result<i64, MachineFault> {
+        let index = self.check_address(address)?;
+        return Ok(self.memory.read(index));
+    }
+
+    /// Stores the given value at the specified address in the machine memory. Returns a fault if
+    /// an out-of-bounds access is attempted (bad address) or the memory limits are exceeded.
+    fn store_in_memory(&mut self, value: i64, address: i64) -> Result<(), MachineFault> {
+        let index = self.check_address(address)?;
+        if let Err(kind) = self.write_memory(index, value) {
+            return Err(self.new_fault(kind, Some(address)));
+        }
+        return Ok(());
+    }
+
+    /// Reads the next input value from the input queue, or the input device once the queue is
+    /// empty. Returns None if no value is available.
+    fn read_input(&mut self) -> Option<i64> {
+        let value = match self.input.pop_front() {
+            Some(value) => Some(value),
+            None => self.input_device.as_mut()?.read_input(),
+        };
+        // Input can change where the program goes, so earlier states no longer show a cycle
+        if let (Some(detector), Some(_)) = (&mut self.cycle_detector, value) {
+            detector.reset();
+        }
+        return value;
+    }
+
+    /// Writes the output value to the output device, or the output queue if there isn't one.
+    fn write_output(&mut self, value: i64) {
+        // A program outputting values isn't stuck, even if it returns to an earlier state
+        if let Some(detector) = &mut self.cycle_detector {
+            detector.reset();
+        }
+        match &mut self.output_device {
+            Some(device) => device.write_output(value),
+            None => self.output.push_back(value),
+        }
+    }
+
+    /// Removes the value output by the last instruction from the output queue, as it is returned
+    /// in the RunState instead. Values written to an output device are left alone.
+    fn remove_step_output(&mut self) {
+        if self.output_device.is_none() {
+            self.output.pop_back();
+        }
+    }
+
+    /// Writes the value to memory, invalidating any cached instruction decoded from the address.
+    fn write_memory(&mut self, address: usize, value: i64) -> Result<(), FaultKind> {
+        self.memory.write(address, value)?;
+        self.decode_cache.invalidate(address);
+        return Ok(());
+    }
+
+    /// Returns the value of a decoded instruction parameter that is read from.
+    fn read_operand(&self, mode: ParamMode, value: i64) -> Result<i64, MachineFault> {
+        match mode {
+            ParamMode::Immediate => return Ok(value),
+            ParamMode::Position => return self.retrieve_from_memory(value),
+            ParamMode::Relative => {
+                let address = self.add_values(self.relative_base, value)?;
+                return self.retrieve_from_memory(address);
+            }
         }
-        return self.memory[index];
     }
 
-    /// Stores the given value at the specified index in the machine memory.
-    /// Panics if an out-of-bounds access is attempted (bad index).
-    fn store_in_memory(&mut self, value: i64, index: usize) {
-        if index >= self.memory.len() {
-            panic!("Bad memory index.");
+    /// Returns the address given by a decoded instruction parameter that is written to. Immediate
+    /// mode is never decoded for these parameters, so is treated the same as position mode.
+    fn resolve_write_address(&self, mode: ParamMode, value: i64) -> Result<i64, MachineFault> {
+        let address = match mode {
+            ParamMode::Relative => self.add_values(self.relative_base, value)?,
+            _ => value,
+        };
+        self.check_address(address)?;
+        return Ok(address);
+    }
+
+    /// Stores the value at the address given by a decoded instruction parameter.
+    fn write_operand(
+        &mut self,
+        mode: ParamMode,
+        param: i64,
+        value: i64,
+    ) -> Result<(), MachineFault> {
+        let address = self.resolve_write_address(mode, param)?;
+        // Address has already been checked, so only the memory limits can cause a fault
+        if let Err(kind) = self.write_memory(address as usize, value) {
+            return Err(self.new_fault(kind, Some(address)));
         }
-        self.memory[index] = value;
+        return Ok(());
     }
 
     /// Looks up values from the memory of the machine using the provided index and parameter mode.
     /// The third argument indicates whether or not the calculated address value should be used to
-    /// lookup the return value or if the address should itself be returned as the result.
-    fn retrieve_param_value(&self, index: usize, param_mode: i64, do_memory_lookup: bool) -> i64 {
-        if param_mode == PARAM_MODE_POSITION {
-            let address = self.retrieve_from_memory(index);
-            if !do_memory_lookup {
-                return address;
-            } else {
-                return self.retrieve_from_memory(address as usize);
-            }
-        } else if param_mode == PARAM_MODE_IMMEDIATE {
-            let value = self.retrieve_from_memory(index);
-            return value;
+    /// lookup the return value or if the address should itself be returned as the result. Returned
+    /// addresses are checked, so a bad write address faults before the instruction has any effect.
+    fn retrieve_param_value(
+        &self,
+        index: usize,
+        param_mode: i64,
+        do_memory_lookup: bool,
+    ) -> Result<i64, MachineFault> {
+        let address = if param_mode == PARAM_MODE_POSITION {
+            self.retrieve_from_memory(index as i64)?
+        } else if param_mode == PARAM_MODE_IMMEDIATE && do_memory_lookup {
+            return self.retrieve_from_memory(index as i64);
         } else if param_mode == PARAM_MODE_RELATIVE {
-            let address = self.relative_base + self.retrieve_from_memory(index);
-            if !do_memory_lookup {
-                return address;
-            } else {
-                return self.retrieve_from_memory(address as usize);
-            }
+            self.add_values(self.relative_base, self.retrieve_from_memory(index as i64)?)?
         } else {
-            panic!("BAD PARAMETER MODE");
+            // Unknown mode, or immediate mode used for a parameter that is written to
+            return Err(self.new_fault(FaultKind::BadParameterMode(param_mode), None));
+        };
+        if !do_memory_lookup {
+            self.check_address(address)?;
+            return Ok(address);
+        } else {
+            return self.retrieve_from_memory(address);
         }
     }
 
-    /// Extracts the intcode memory from the file specified by the given filename.
+    /// Extracts the intcode memory from the file specified by the given filename. Panics if the
+    /// file can't be read or doesn't contain a valid Intcode program.
     pub fn extract_intcode_memory_from_filename(filename: String) -> Vec<i64> {
-        let mut file = fs::open_file(filename);
-        return IntcodeMachine::extract_intcode_memory_from_file(&mut file);
+        match IntcodeMachine::try_extract_intcode_memory_from_filename(filename) {
+            Err(e) => panic!("ERROR - {}", e),
+            Ok(memory) => memory,
+        }
     }
 
-    /// Extracts the intcode arguments from the given file.
+    /// Extracts the intcode memory from the file specified by the given filename, returning an
+    /// error if the file can't be read or doesn't contain a valid Intcode program.
+    pub fn try_extract_intcode_memory_from_filename(
+        filename: String,
+    ) -> Result<Vec<i64>, AocError> {
+        let mut file = fs::try_open_file(filename)?;
+        return IntcodeMachine::try_extract_intcode_memory_from_file(&mut file);
+    }
+
+    /// Extracts the intcode arguments from the given file. Panics if the file can't be read or
+    /// doesn't contain a valid Intcode program.
     ///
     /// File is read to string before arguments are split and converted to i64.
     pub fn extract_intcode_memory_from_file(file: &mut File) -> Vec<i64> {
+        match IntcodeMachine::try_extract_intcode_memory_from_file(file) {
+            Err(e) => panic!("ERROR - {}", e),
+            Ok(memory) => memory,
+        }
+    }
+
+    /// Extracts the intcode arguments from the given file, returning an error if the file can't be
+    /// read or doesn't contain a valid Intcode program.
+    pub fn try_extract_intcode_memory_from_file(file: &mut File) -> Result<Vec<i64>, AocError> {
         // Read line from file
         let mut read_buf = String::new();
-        match file.read_to_string(&mut read_buf) {
-            Err(e) => panic!("Error reading file. ({})", e.to_string()),
-            Ok(_) => 0,
-        };
-        // Split string program into arguments
-        read_buf = String::from(read_buf.trim());
-        let str_args: Vec<&str> = read_buf.split(',').collect();
-        // Convert string arguments into integers
+        if let Err(e) = file.read_to_string(&mut read_buf) {
+            return Err(AocError::Io {
+                filename: String::from("<open file>"),
+                source: e,
+            });
+        }
+        return IntcodeMachine::parse_intcode_memory(&read_buf);
+    }
+
+    /// Parses the comma-separated Intcode program held in the given string. Whitespace (including
+    /// line breaks) around values is ignored. Parse errors report the line and column at which the
+    /// bad value starts.
+    pub fn parse_intcode_memory(raw_program: &str) -> Result<Vec<i64>, AocError> {
+        if raw_program.trim().is_empty() {
+            return Err(AocError::InvalidProgram(String::from("program is empty")));
+        }
         let mut int_args = Vec::<i64>::new();
-        for str_arg in str_args {
-            let value = str_arg.parse::<i64>().unwrap();
-            int_args.push(value);
+        let mut line = 1;
+        let mut column = 1;
+        let mut value_start = (line, column);
+        let mut value = String::new();
+        let mut value_ended = false;
+        // Add a trailing separator so the last value is processed within the loop
+        for c in raw_program.chars().chain(std::iter::once(',')) {
+            if c == ',' {
+                if value.is_empty() {
+                    return Err(AocError::parse_error(
+                        line,
+                        column,
+                        String::from("missing value"),
+                    ));
+                }
+                match value.parse::<i64>() {
+                    Ok(v) => int_args.push(v),
+                    Err(e) => {
+                        let message = format!("bad value \"{}\" ({})", value, e);
+                        return Err(AocError::parse_error(value_start.0, value_start.1, message));
+                    }
+                }
+                value.clear();
+                value_ended = false;
+            } else if c.is_whitespace() {
+                // Whitespace can't appear within a value
+                value_ended = !value.is_empty();
+            } else {
+                if value_ended {
+                    let message = String::from("missing separator between values");
+                    return Err(AocError::parse_error(line, column, message));
+                }
+                if value.is_empty() {
+                    value_start = (line, column);
+                }
+                value.push(c);
+            }
+            // Update position after processing character
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
         }
-        return int_args;
+        return Ok(int_args);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_day_09_p1_copy_output() {
@@ -376,6 +1061,20 @@ mod tests {
         assert_eq!(99, machine.get_output());
     }
 
+    #[test]
+    fn test_try_get_output() {
+        let mut machine = IntcodeMachine::new(vec![4, 2, 104, 7, 99], VecDeque::new());
+        let error = machine.try_get_output().unwrap_err();
+        assert!(matches!(error, AocError::InvalidProgram(_)));
+        machine.execute_program();
+        assert_eq!(104, machine.try_get_output().unwrap());
+        assert_eq!(7, machine.try_get_last_output().unwrap());
+        assert_eq!(104, machine.try_get_output_and_remove().unwrap());
+        assert_eq!(7, machine.try_get_output_and_remove().unwrap());
+        assert!(machine.try_get_output_and_remove().is_err());
+        assert!(machine.try_get_last_output().is_err());
+    }
+
     #[test]
     fn test_add() {
         let mut machine = IntcodeMachine::new(vec![1, 2, 2, 0, 99], VecDeque::from(vec![]));
@@ -619,4 +1318,482 @@ mod tests {
         machine.execute_program();
         assert_eq!(456, machine.get_output());
     }
+
+    #[test]
+    fn test_parse_intcode_memory() {
+        let memory = IntcodeMachine::parse_intcode_memory("1,0, 0,3,\n99\n").unwrap();
+        assert_eq!(vec![1, 0, 0, 3, 99], memory);
+    }
+
+    #[test]
+    fn test_parse_intcode_memory_bad_value() {
+        match IntcodeMachine::parse_intcode_memory("1,0,0,3\n,9x9") {
+            Err(AocError::Parse { line, column, .. }) => {
+                assert_eq!(2, line);
+                assert_eq!(2, column);
+            }
+            _ => panic!("Expected parse error."),
+        }
+        match IntcodeMachine::parse_intcode_memory("1,0 0,99") {
+            Err(AocError::Parse { line, column, .. }) => {
+                assert_eq!(1, line);
+                assert_eq!(5, column);
+            }
+            _ => panic!("Expected parse error."),
+        }
+        match IntcodeMachine::parse_intcode_memory("1,,99") {
+            Err(AocError::Parse { line, column, .. }) => {
+                assert_eq!(1, line);
+                assert_eq!(3, column);
+            }
+            _ => panic!("Expected parse error."),
+        }
+    }
+
+    #[test]
+    fn test_parse_intcode_memory_empty() {
+        match IntcodeMachine::parse_intcode_memory(" \n") {
+            Err(AocError::InvalidProgram(_)) => (),
+            _ => panic!("Expected invalid program error."),
+        }
+    }
+
+    #[test]
+    fn test_fault_unknown_opcode() {
+        let mut machine = IntcodeMachine::new(vec![1101, 2, 3, 5, 42, 0], VecDeque::new());
+        let fault = machine.try_execute_program().unwrap_err();
+        assert_eq!(FaultKind::UnknownOpcode, fault.kind);
+        assert_eq!(42, fault.opcode);
+        assert_eq!(4, fault.pc);
+        assert_eq!(None, fault.address);
+        // Machine state is left intact for inspection
+        assert_eq!(4, machine.get_program_counter());
+        assert_eq!(5, machine.get_memory_dump()[5]);
+        assert_eq!(Some(&fault), machine.get_fault());
+        assert!(!machine.has_halted());
+        // Further execution reports the same fault
+        assert_eq!(Err(fault), machine.try_execute_program());
+    }
+
+    #[test]
+    fn test_fault_bad_parameter_mode() {
+        let mut machine = IntcodeMachine::new(vec![109, 7, 301, 0, 0, 0, 99], VecDeque::new());
+        let fault = machine.try_execute_program().unwrap_err();
+        assert_eq!(FaultKind::BadParameterMode(3), fault.kind);
+        assert_eq!(1, fault.opcode);
+        assert_eq!(2, fault.pc);
+        assert_eq!(7, fault.relative_base);
+        // Immediate mode is not valid for a parameter that is written to
+        let mut machine = IntcodeMachine::new(vec![11101, 1, 1, 0, 99], VecDeque::new());
+        let fault = machine.try_execute_program().unwrap_err();
+        assert_eq!(FaultKind::BadParameterMode(1), fault.kind);
+        assert_eq!(11101, machine.get_location_zero());
+    }
+
+    #[test]
+    fn test_fault_negative_address() {
+        let mut machine = IntcodeMachine::new(vec![109, -10, 203, 3, 99], VecDeque::from(vec![5]));
+        let fault = machine.try_execute_program().unwrap_err();
+        assert_eq!(FaultKind::NegativeAddress, fault.kind);
+        assert_eq!(3, fault.opcode);
+        assert_eq!(2, fault.pc);
+        assert_eq!(-10, fault.relative_base);
+        assert_eq!(Some(-7), fault.address);
+        // Input value isn't consumed by the faulting instruction
+        assert_eq!(VecDeque::from(vec![5]), machine.input);
+        // A bad write address is reported before an overflow of the result, with or without the
+        // decode cache
+        for cached in [true, false].iter() {
+            let program = vec![21102, 1 << 62, 4, -1, 99];
+            let mut machine = IntcodeMachine::new(program, VecDeque::new());
+            machine.set_decode_cache_enabled(*cached);
+            let fault = machine.try_execute_program().unwrap_err();
+            assert_eq!(FaultKind::NegativeAddress, fault.kind);
+            assert_eq!(Some(-1), fault.address);
+        }
+    }
+
+    #[test]
+    fn test_fault_address_out_of_range() {
+        let limits = MemoryLimits {
+            max_address: 9999,
+            max_pages: 16,
+        };
+        let mut machine = IntcodeMachine::new_with_memory_limits(
+            vec![1105, 1, 20000, 99],
+            VecDeque::new(),
+            limits,
+        );
+        let fault = machine.try_execute_program().unwrap_err();
+        assert_eq!(FaultKind::AddressOutOfRange, fault.kind);
+        assert_eq!(0, fault.pc);
+        assert_eq!(Some(20000), fault.address);
+        let error = AocError::from(fault);
+        assert_eq!(
+            "Intcode machine fault: address out of range for opcode 5 [pc: 0, relative base: 0, address: 20000]",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fault_panics_without_try() {
+        let mut machine = IntcodeMachine::new(vec![42], VecDeque::new());
+        machine.execute_program();
+    }
+
+    #[test]
+    fn test_write_to_far_address() {
+        // Store input at address 2^31 (via the relative base) then output it
+        let mut machine = IntcodeMachine::new(
+            vec![109, 2147483648, 203, 0, 204, 0, 99],
+            VecDeque::from(vec![77]),
+        );
+        machine.try_execute_program().unwrap();
+        assert_eq!(77, machine.get_output());
+        assert_eq!(77, machine.peek_memory(2147483648));
+    }
+
+    #[test]
+    fn test_program_larger_than_10000_cells() {
+        // Jump over a long run of padding to an output instruction near the end of the program
+        let mut program = vec![1105, 1, 12000];
+        program.resize(12000, 0);
+        program.extend(vec![104, 5, 99]);
+        let mut machine = IntcodeMachine::new(program, VecDeque::new());
+        machine.try_execute_program().unwrap();
+        assert_eq!(5, machine.get_output());
+        assert_eq!(12003, machine.get_memory_dump().len());
+    }
+
+    #[test]
+    fn test_fault_memory_limit_exceeded() {
+        let limits = MemoryLimits {
+            max_address: 1 << 20,
+            max_pages: 1,
+        };
+        let mut machine = IntcodeMachine::new_with_memory_limits(
+            vec![1101, 1, 1, 5000, 99],
+            VecDeque::new(),
+            limits,
+        );
+        let fault = machine.try_execute_program().unwrap_err();
+        assert_eq!(FaultKind::MemoryLimitExceeded, fault.kind);
+        assert_eq!(Some(5000), fault.address);
+    }
+
+    #[test]
+    fn test_run_states() {
+        // Read a value, output it doubled, then halt
+        let program = assemble(
+            "
+                   in value
+                   mul value, #2, value
+                   out value
+                   hlt
+            value: data 0
+            ",
+        )
+        .unwrap();
+        let mut machine = IntcodeMachine::new(program, VecDeque::new());
+        assert_eq!(Ok(RunState::NeedsInput), machine.run());
+        assert_eq!(Ok(RunState::NeedsInput), machine.run());
+        machine.add_input(21);
+        assert_eq!(Ok(RunState::Output(42)), machine.run());
+        assert!(machine.is_output_empty());
+        assert_eq!(Ok(RunState::Halted), machine.run());
+        assert_eq!(Ok(RunState::Halted), machine.run());
+    }
+
+    #[test]
+    fn test_step() {
+        let mut machine = IntcodeMachine::new(vec![1101, 2, 3, 7, 104, 5, 99, 0], VecDeque::new());
+        assert_eq!(Ok(None), machine.step());
+        assert_eq!(4, machine.get_program_counter());
+        assert_eq!(5, machine.peek_memory(7));
+        assert_eq!(Ok(Some(RunState::Output(5))), machine.step());
+        assert_eq!(Ok(Some(RunState::Halted)), machine.step());
+    }
+
+    #[test]
+    fn test_run_until() {
+        // Count down from 3, outputting each value
+        let program = assemble(
+            "
+            loop:  add count, #-1, count
+                   out count            ; address 4
+                   jt count, #loop
+                   hlt
+            count: data 3               ; address 10
+            ",
+        )
+        .unwrap();
+        let mut machine = IntcodeMachine::new(program, VecDeque::new());
+        let state = machine.run_until(|m| m.get_program_counter() == 4);
+        assert_eq!(Ok(None), state);
+        assert_eq!(2, machine.peek_memory(10));
+        assert_eq!(Ok(Some(RunState::Output(2))), machine.run_until(|_| false));
+        assert_eq!(Ok(RunState::Output(1)), machine.run());
+        assert_eq!(Ok(RunState::Output(0)), machine.run());
+        assert_eq!(Ok(RunState::Halted), machine.run());
+    }
+
+    #[test]
+    fn test_fork() {
+        // Program larger than a page, which adds its two inputs and outputs the result
+        let mut program = vec![3, 2000, 3, 2001, 1, 2000, 2001, 2002, 4, 2002, 99];
+        program.resize(2003, 0);
+        let template = IntcodeMachine::new(program, VecDeque::new());
+        let mut first = template.fork();
+        let mut second = template.fork();
+        assert_eq!(2, first.get_shared_page_count());
+        first.add_input(3);
+        first.add_input(4);
+        second.add_input(10);
+        second.add_input(20);
+        assert_eq!(Ok(RunState::Output(7)), first.run());
+        assert_eq!(Ok(RunState::Output(30)), second.run());
+        // Only the page holding the inputs and result was copied by each fork
+        assert_eq!(1, first.get_shared_page_count());
+        assert_eq!(1, template.get_shared_page_count());
+        assert_eq!(0, template.peek_memory(2002));
+    }
+
+    #[test]
+    fn test_self_modifying_program() {
+        // Output instruction increments its own operand until it reaches 4
+        let program = vec![104, 1, 1001, 1, 1, 1, 1007, 1, 4, 14, 1005, 14, 0, 99, 0];
+        for &enabled in [true, false].iter() {
+            let mut machine = IntcodeMachine::new(program.clone(), VecDeque::new());
+            machine.set_decode_cache_enabled(enabled);
+            machine.try_execute_program().unwrap();
+            assert_eq!(VecDeque::from(vec![1, 2, 3]), machine.get_output_vec());
+        }
+    }
+
+    #[test]
+    fn test_decode_cache_matches_uncached() {
+        let programs = vec![
+            // Day 9 quine, which writes beyond its own program
+            vec![
+                109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+            ],
+            // Faults on immediate mode for a write, reached through a jump
+            vec![1101, 2, 3, 7, 1105, 1, 8, 0, 11101, 1, 1, 0],
+            // Faults on an unknown mode, written into the program by its first instruction
+            vec![1101, 300, 4, 4, 0, 5, 99],
+        ];
+        for program in programs {
+            let mut cached = IntcodeMachine::new(program.clone(), VecDeque::new());
+            let mut uncached = IntcodeMachine::new(program, VecDeque::new());
+            uncached.set_decode_cache_enabled(false);
+            assert_eq!(uncached.try_execute_program(), cached.try_execute_program());
+            assert!(cached == uncached);
+        }
+    }
+
+    #[test]
+    fn test_profiling() {
+        let program = assemble(
+            "
+                   in count
+            loop:  add count, #-1, count
+                   out count
+                   jt count, #loop
+                   hlt
+            count: data 0
+            ",
+        )
+        .unwrap();
+        let mut machine = IntcodeMachine::new(program, VecDeque::new());
+        let mut unprofiled = machine.fork();
+        machine.enable_profiling();
+        // Waiting for input doesn't count as executing the input instruction
+        assert_eq!(Ok(RunState::NeedsInput), machine.run());
+        assert_eq!(0, machine.get_profile().unwrap().get_instructions_retired());
+        // Profile isn't part of the machine state
+        unprofiled.run().unwrap();
+        assert!(machine == unprofiled);
+        machine.add_input(3);
+        machine.try_execute_program().unwrap();
+        let profile = machine.take_profile().unwrap();
+        assert_eq!(11, profile.get_instructions_retired());
+        assert_eq!(1, profile.get_inputs_consumed());
+        assert_eq!(3, profile.get_outputs_produced());
+        assert_eq!(3, profile.get_opcode_count(Opcode::Add));
+        assert_eq!(1, profile.get_address_count(11));
+        let expected = HotLoop {
+            start: 2,
+            end: 8,
+            iterations: 2,
+            instructions: 9,
+        };
+        assert_eq!(vec![expected], profile.get_hot_loops(10));
+        assert!(machine.get_profile().is_none());
+    }
+
+    #[test]
+    fn test_arithmetic_modes() {
+        // Multiplies 2^62 by 4, then outputs the result
+        let program = vec![1102, 4611686018427387904, 4, 7, 4, 7, 99, 0];
+        for &use_decode_cache in [false, true].iter() {
+            let mut machine = IntcodeMachine::new(program.clone(), VecDeque::new());
+            machine.set_decode_cache_enabled(use_decode_cache);
+            assert_eq!(ArithmeticMode::Checked, machine.get_arithmetic_mode());
+            let fault = machine.run().unwrap_err();
+            assert_eq!(FaultKind::ArithmeticOverflow, fault.kind);
+            assert_eq!((2, 0), (fault.opcode, fault.pc));
+            let mut machine = IntcodeMachine::new(program.clone(), VecDeque::new());
+            machine.set_decode_cache_enabled(use_decode_cache);
+            machine.set_arithmetic_mode(ArithmeticMode::Wrapping);
+            assert_eq!(Ok(RunState::Output(0)), machine.run());
+        }
+    }
+
+    #[test]
+    fn test_relative_base_overflow() {
+        // Adjusts the relative base to i64::MAX, then reads relative to it
+        let program = vec![109, i64::MAX, 204, 1, 99];
+        let mut machine = IntcodeMachine::new(program.clone(), VecDeque::new());
+        let fault = machine.run().unwrap_err();
+        assert_eq!((FaultKind::ArithmeticOverflow, 2), (fault.kind, fault.pc));
+        // Wrapped address is negative
+        let mut machine = IntcodeMachine::new(program, VecDeque::new());
+        machine.set_arithmetic_mode(ArithmeticMode::Wrapping);
+        assert_eq!(FaultKind::NegativeAddress, machine.run().unwrap_err().kind);
+    }
+
+    #[test]
+    fn test_instruction_budget() {
+        // Adds 1 to the (immediate) condition of the jump back to the start forever
+        let mut machine = IntcodeMachine::new(vec![1001, 5, 1, 5, 1105, 1, 0], VecDeque::new());
+        machine.set_instruction_budget(Some(5));
+        assert_eq!(Ok(RunState::BudgetExhausted), machine.run());
+        assert_eq!(
+            (Some(0), 4),
+            (
+                machine.get_instruction_budget(),
+                machine.get_program_counter()
+            )
+        );
+        // Stays suspended until given a new budget
+        assert_eq!(Ok(RunState::BudgetExhausted), machine.run());
+        assert_eq!(4, machine.peek_memory(5));
+        machine.set_instruction_budget(Some(2));
+        assert_eq!(Ok(RunState::BudgetExhausted), machine.run());
+        assert_eq!(5, machine.peek_memory(5));
+        // Waiting for input doesn't use up the budget
+        let mut machine = IntcodeMachine::new(vec![3, 3, 99], VecDeque::new());
+        machine.set_instruction_budget(Some(2));
+        assert_eq!(Ok(RunState::NeedsInput), machine.run());
+        machine.add_input(7);
+        assert_eq!(Ok(RunState::Halted), machine.run());
+        assert_eq!(Some(0), machine.get_instruction_budget());
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        // Flips the value at address 9 between 0 and 1 forever
+        let mut machine =
+            IntcodeMachine::new(vec![1007, 9, 1, 9, 1105, 1, 0, 99, 0, 0], VecDeque::new());
+        machine.set_cycle_detection(true);
+        assert_eq!(Ok(RunState::CycleDetected), machine.run());
+        // Counting loop outputs values, so is never reported as a cycle
+        let program = assemble(
+            "
+            loop:  add count, #1, count
+                   out count
+                   lt count, #100, flag
+                   jt flag, #loop
+                   hlt
+            count: data 0
+            flag:  data 0
+            ",
+        )
+        .unwrap();
+        let mut machine = IntcodeMachine::new(program, VecDeque::new());
+        machine.set_cycle_detection(true);
+        machine.try_execute_program().unwrap();
+        assert!(machine.has_halted());
+        assert_eq!(100, machine.get_output_vec().len());
+    }
+
+    /// Program reading values until it reads 0, outputting each value doubled.
+    fn get_doubler_program() -> Vec<i64> {
+        return assemble(
+            "
+            loop:  in value
+                   jf value, #end
+                   mul value, #2, value
+                   out value
+                   jt #1, #loop
+            end:   hlt
+            value: data 0
+            ",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_function_devices() {
+        let mut values = vec![0, 5, 4];
+        let outputs = Arc::new(Mutex::new(vec![]));
+        let outputs_written = Arc::clone(&outputs);
+        let mut machine = IntcodeMachine::new(get_doubler_program(), VecDeque::new());
+        // Queued input is read before the device
+        machine.add_input(1);
+        machine.set_input_device(FnInput(move || values.pop()));
+        machine.set_output_device(FnOutput(move |v| outputs_written.lock().unwrap().push(v)));
+        // Output is still reported by run(), as well as being written to the device
+        assert_eq!(Ok(RunState::Output(2)), machine.run());
+        machine.try_execute_program().unwrap();
+        assert!(machine.has_halted());
+        assert_eq!(vec![2, 8, 10], *outputs.lock().unwrap());
+        // Values written to the device don't go in the output queue
+        assert!(machine.get_output_vec().is_empty());
+    }
+
+    #[test]
+    fn test_device_needs_input() {
+        let (sender, receiver) = mpsc::channel();
+        let mut machine = IntcodeMachine::new(get_doubler_program(), VecDeque::new());
+        machine.set_input_device(receiver);
+        assert_eq!(Ok(RunState::NeedsInput), machine.run());
+        sender.send(3).unwrap();
+        assert_eq!(Ok(RunState::Output(6)), machine.run());
+        assert_eq!(Ok(RunState::NeedsInput), machine.run());
+        // Removing the output device sends output back to the queue
+        sender.send(7).unwrap();
+        sender.send(0).unwrap();
+        let (output_sender, output_receiver) = mpsc::channel();
+        machine.set_output_device(output_sender);
+        assert_eq!(Ok(RunState::Output(14)), machine.run());
+        assert!(machine.remove_output_device().is_some());
+        assert_eq!(Ok(RunState::Halted), machine.run());
+        assert_eq!(Ok(14), output_receiver.try_recv());
+        assert!(machine.get_output_vec().is_empty());
+    }
+
+    #[test]
+    fn test_machine_input_chain() {
+        let mut first = IntcodeMachine::new(get_doubler_program(), VecDeque::new());
+        for &value in [1, 2, 3, 0].iter() {
+            first.add_input(value);
+        }
+        let mut second = IntcodeMachine::new(get_doubler_program(), VecDeque::new());
+        second.set_input_device(MachineInput::new(first));
+        let mut outputs = vec![];
+        while let Ok(RunState::Output(value)) = second.run() {
+            outputs.push(value);
+        }
+        assert_eq!(vec![4, 8, 12], outputs);
+        // First machine has halted, so the second waits for input from elsewhere
+        assert!(second.is_awaiting_input());
+        second.add_input(0);
+        assert_eq!(Ok(RunState::Halted), second.run());
+        // Clones of a machine have no devices attached
+        let mut fork = second.fork();
+        assert!(fork.remove_input_device().is_none());
+        assert!(second.remove_input_device().is_some());
+    }
 }
@@ -0,0 +1,370 @@
+use std::collections::VecDeque;
+
+use super::run_state::RunState;
+use super::IntcodeMachine;
+
+/// Default maximum number of instructions each machine executes per turn.
+const DEFAULT_TIME_SLICE: usize = 1000;
+
+/// Number of rounds in a row a packet network must be idle before it is reported as idle, so a
+/// machine that polls for input in the middle of a longer computation isn't mistaken for idle.
+const IDLE_ROUNDS: usize = 2;
+
+/// How the outputs of the machines in a network are routed to the inputs of other machines.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Topology {
+    /// Each machine's output is input to the next machine. Output of the last machine leaves the
+    /// network.
+    Chain,
+    /// Same as a chain, except the output of the last machine is also input to the first machine
+    /// (e.g. the day 7 amplifier feedback loop).
+    Ring,
+    /// Machines send packets of three values - destination address, X and Y (e.g. the day 23
+    /// category six network). Each machine is given its address (index in the network) as its
+    /// first input, and reads -1 whenever it needs input and has no packets waiting. Packets sent
+    /// to addresses outside the network leave it.
+    Packet,
+}
+
+/// Packet sent by a machine in a packet network to an address outside the network.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Packet {
+    pub source: usize,
+    pub destination: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+/// Reason a network stopped running.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum NetworkState {
+    /// Every machine has halted.
+    Halted,
+    /// No machine can make progress, as every machine that hasn't halted is waiting for input
+    /// that no other machine will provide. Only occurs in chain and ring networks.
+    Deadlocked,
+    /// No packets are being sent, and every machine that hasn't halted is waiting for packets.
+    /// Only occurs in packet networks.
+    Idle,
+    /// Machine with the given index faulted. The fault can be retrieved from the machine.
+    Faulted(usize),
+    /// Machine with the given index was suspended, by running out of its instruction budget or
+    /// by being caught in a cycle (see RunState).
+    Suspended(usize),
+}
+
+/// Network of Intcode machines, with outputs routed to inputs according to a topology.
+///
+/// Machines are scheduled round-robin, each running until it has executed the time slice
+/// (maximum number of instructions), needs input it doesn't have, or halts. This continues until
+/// every machine has halted, the network deadlocks or goes idle, or a machine faults.
+pub struct IntcodeNetwork {
+    machines: Vec<IntcodeMachine>,
+    topology: Topology,
+    time_slice: usize,
+    partial_packets: Vec<Vec<i64>>,
+    output: VecDeque<i64>,
+    last_output: Option<i64>,
+    packets: VecDeque<Packet>,
+    idle_rounds: usize,
+}
+
+impl IntcodeNetwork {
+    /// Creates a new network of the given machines, connected with the given topology. In a
+    /// packet network, each machine is given its address as input.
+    pub fn new(mut machines: Vec<IntcodeMachine>, topology: Topology) -> Self {
+        if topology == Topology::Packet {
+            for (address, machine) in machines.iter_mut().enumerate() {
+                machine.add_input(address as i64);
+            }
+        }
+        let num_machines = machines.len();
+        Self {
+            machines,
+            topology,
+            time_slice: DEFAULT_TIME_SLICE,
+            partial_packets: vec![vec![]; num_machines],
+            output: VecDeque::new(),
+            last_output: None,
+            packets: VecDeque::new(),
+            idle_rounds: 0,
+        }
+    }
+
+    /// Sets the maximum number of instructions each machine executes per turn.
+    pub fn set_time_slice(&mut self, time_slice: usize) {
+        self.time_slice = time_slice.max(1);
+    }
+
+    /// Returns the number of machines in the network.
+    pub fn get_num_machines(&self) -> usize {
+        return self.machines.len();
+    }
+
+    /// Returns the machine with the given index.
+    pub fn get_machine(&self, index: usize) -> &IntcodeMachine {
+        return &self.machines[index];
+    }
+
+    /// Returns the machine with the given index, e.g. to patch its memory.
+    pub fn get_machine_mut(&mut self, index: usize) -> &mut IntcodeMachine {
+        return &mut self.machines[index];
+    }
+
+    /// Adds the input value to the input queue of the machine with the given index.
+    pub fn add_input(&mut self, index: usize, value: i64) {
+        self.machines[index].add_input(value);
+    }
+
+    /// Sends a packet into a packet network from outside it, e.g. from the day 23 NAT.
+    pub fn send_packet(&mut self, destination: usize, x: i64, y: i64) {
+        self.add_input(destination, x);
+        self.add_input(destination, y);
+        self.idle_rounds = 0;
+    }
+
+    /// Returns the output values that have left a chain or ring network (i.e. been output by the
+    /// last machine) since the last time they were taken.
+    pub fn take_output(&mut self) -> Vec<i64> {
+        return self.output.drain(..).collect();
+    }
+
+    /// Returns the last value output by the last machine of a chain or ring network, if any. This
+    /// isn't affected by taking the output.
+    pub fn get_last_output(&self) -> Option<i64> {
+        return self.last_output;
+    }
+
+    /// Returns the packets that have been sent to addresses outside a packet network since the
+    /// last time they were taken.
+    pub fn take_packets(&mut self) -> Vec<Packet> {
+        return self.packets.drain(..).collect();
+    }
+
+    /// Runs the network until every machine has halted, the network deadlocks or goes idle, or a
+    /// machine faults or is suspended.
+    pub fn run(&mut self) -> NetworkState {
+        loop {
+            if let Some(state) = self.run_round() {
+                return state;
+            }
+        }
+    }
+
+    /// Gives every machine that hasn't halted a turn, returning the state of the network if it
+    /// can't continue running.
+    pub fn run_round(&mut self) -> Option<NetworkState> {
+        let mut all_halted = true;
+        let mut all_waiting = true;
+        let mut any_sent = false;
+        for index in 0..self.machines.len() {
+            if self.machines[index].has_halted() {
+                continue;
+            }
+            all_halted = false;
+            let turn = match self.run_turn(index) {
+                Ok(turn) => turn,
+                Err(state) => return Some(state),
+            };
+            all_waiting &= turn.waiting;
+            any_sent |= turn.sent;
+        }
+        if all_halted {
+            return Some(NetworkState::Halted);
+        }
+        if self.topology == Topology::Packet {
+            let queues_empty = self.machines.iter().all(|m| m.get_input_vec().is_empty());
+            if all_waiting && !any_sent && queues_empty {
+                self.idle_rounds += 1;
+                if self.idle_rounds >= IDLE_ROUNDS {
+                    self.idle_rounds = 0;
+                    return Some(NetworkState::Idle);
+                }
+            } else {
+                self.idle_rounds = 0;
+            }
+        } else if all_waiting && !any_sent {
+            return Some(NetworkState::Deadlocked);
+        }
+        return None;
+    }
+
+    /// Runs the machine with the given index for a single turn, routing any values it outputs.
+    /// Returns the state of the network if the machine faulted or was suspended.
+    fn run_turn(&mut self, index: usize) -> Result<Turn, NetworkState> {
+        let mut turn = Turn {
+            waiting: false,
+            sent: false,
+        };
+        let mut executed = 0;
+        while executed < self.time_slice {
+            let state = match self.machines[index].step() {
+                Ok(state) => state,
+                Err(_) => return Err(NetworkState::Faulted(index)),
+            };
+            match state {
+                Some(RunState::Output(value)) => {
+                    self.route(index, value);
+                    turn.sent = true;
+                }
+                Some(RunState::NeedsInput) => {
+                    // In a packet network, polling without packets waiting reads -1, but the
+                    // turn ends if the machine polls again without sending anything
+                    if self.topology != Topology::Packet || turn.waiting {
+                        turn.waiting = true;
+                        break;
+                    }
+                    turn.waiting = true;
+                    self.machines[index].add_input(-1);
+                    continue;
+                }
+                Some(RunState::Halted) => break,
+                Some(RunState::BudgetExhausted) | Some(RunState::CycleDetected) => {
+                    return Err(NetworkState::Suspended(index));
+                }
+                None => (),
+            }
+            executed += 1;
+        }
+        if turn.sent {
+            turn.waiting = false;
+        }
+        return Ok(turn);
+    }
+
+    /// Routes the value output by the machine with the given index according to the topology.
+    fn route(&mut self, source: usize, value: i64) {
+        let next = source + 1;
+        match self.topology {
+            Topology::Chain | Topology::Ring if next < self.machines.len() => {
+                self.machines[next].add_input(value);
+            }
+            Topology::Chain | Topology::Ring => {
+                self.output.push_back(value);
+                self.last_output = Some(value);
+                if self.topology == Topology::Ring {
+                    self.machines[0].add_input(value);
+                }
+            }
+            Topology::Packet => {
+                let partial = &mut self.partial_packets[source];
+                partial.push(value);
+                if partial.len() < 3 {
+                    return;
+                }
+                let packet = Packet {
+                    source,
+                    destination: partial[0],
+                    x: partial[1],
+                    y: partial[2],
+                };
+                partial.clear();
+                if packet.destination >= 0 && (packet.destination as usize) < self.machines.len() {
+                    self.send_packet(packet.destination as usize, packet.x, packet.y);
+                } else {
+                    self.packets.push_back(packet);
+                }
+            }
+        }
+    }
+}
+
+/// What a machine did during its turn.
+struct Turn {
+    /// Machine ended its turn waiting for input without having output anything.
+    waiting: bool,
+    /// Machine output at least one value.
+    sent: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assemble;
+    use super::*;
+
+    /// Program reading a value and outputting it doubled, then halting.
+    fn get_doubler_program() -> Vec<i64> {
+        return assemble(
+            "
+                   in value
+                   mul value, #2, value
+                   out value
+                   hlt
+            value: data 0
+            ",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_chain() {
+        let machines = (0..3)
+            .map(|_| IntcodeMachine::new(get_doubler_program(), VecDeque::new()))
+            .collect();
+        let mut network = IntcodeNetwork::new(machines, Topology::Chain);
+        // Nothing can run until the first machine is given input
+        assert_eq!(NetworkState::Deadlocked, network.run());
+        network.add_input(0, 5);
+        assert_eq!(NetworkState::Halted, network.run());
+        assert_eq!(vec![40], network.take_output());
+        assert_eq!(Some(40), network.get_last_output());
+    }
+
+    #[test]
+    fn test_ring_fault() {
+        let mut machines = vec![IntcodeMachine::new(get_doubler_program(), VecDeque::new())];
+        // Second machine faults on an unknown opcode once given input
+        machines.push(IntcodeMachine::new(vec![3, 3, 98, 99], VecDeque::new()));
+        let mut network = IntcodeNetwork::new(machines, Topology::Ring);
+        network.add_input(0, 1);
+        assert_eq!(NetworkState::Faulted(1), network.run());
+        assert!(network.get_machine(1).get_fault().is_some());
+    }
+
+    #[test]
+    fn test_packet_network() {
+        // Machine 0 sends (5, 6) to machine 1, which sends (11, 6) to address 255
+        let program = assemble(
+            "
+                   in addr
+                   jt addr, #recv
+                   out #1
+                   out #5
+                   out #6
+            idle:  in x
+                   jt #1, #idle
+            recv:  in x
+                   eq x, #-1, flag
+                   jt flag, #recv
+                   in y
+                   add x, y, x
+                   out #255
+                   out x
+                   out y
+                   jt #1, #recv
+            addr:  data 0
+            x:     data 0
+            y:     data 0
+            flag:  data 0
+            ",
+        )
+        .unwrap();
+        let machines = (0..2)
+            .map(|_| IntcodeMachine::new(program.clone(), VecDeque::new()))
+            .collect();
+        let mut network = IntcodeNetwork::new(machines, Topology::Packet);
+        network.set_time_slice(5);
+        assert_eq!(NetworkState::Idle, network.run());
+        let expected = Packet {
+            source: 1,
+            destination: 255,
+            x: 11,
+            y: 6,
+        };
+        assert_eq!(vec![expected], network.take_packets());
+        // Packets sent from outside wake the network up again
+        network.send_packet(1, 1, 2);
+        assert_eq!(NetworkState::Idle, network.run());
+        assert_eq!(3, network.take_packets()[0].x);
+    }
+}
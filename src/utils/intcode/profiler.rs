@@ -0,0 +1,208 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use super::instruction::Opcode;
+use super::run_state::RunState;
+
+/// Maximum number of entries shown in each table of the profile report.
+const MAX_REPORT_ENTRIES: usize = 10;
+
+/// Loop found by the profiler, identified by a jump back to an earlier address.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct HotLoop {
+    /// Address jumped back to, i.e. the first address of the loop.
+    pub start: usize,
+    /// Address of the jump instruction at the end of the loop.
+    pub end: usize,
+    /// Number of times the jump back was taken.
+    pub iterations: u64,
+    /// Number of instructions executed at addresses from the start to the end of the loop.
+    pub instructions: u64,
+}
+
+/// Execution profile of an Intcode machine, collected while profiling is enabled on the machine.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    instructions_retired: u64,
+    inputs_consumed: u64,
+    outputs_produced: u64,
+    address_counts: BTreeMap<usize, u64>,
+    opcode_counts: HashMap<Opcode, u64>,
+    backward_jumps: HashMap<(usize, usize), u64>,
+}
+
+impl Profile {
+    /// Creates a new, empty profile.
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Records an instruction that was executed at the given address, and the program counter and
+    /// run state that resulted from it.
+    pub fn record(&mut self, pc: usize, opcode: Opcode, next_pc: usize, state: Option<RunState>) {
+        self.instructions_retired += 1;
+        *self.address_counts.entry(pc).or_insert(0) += 1;
+        *self.opcode_counts.entry(opcode).or_insert(0) += 1;
+        match opcode {
+            Opcode::Input => self.inputs_consumed += 1,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse if next_pc <= pc => {
+                *self.backward_jumps.entry((pc, next_pc)).or_insert(0) += 1;
+            }
+            _ => (),
+        }
+        if let Some(RunState::Output(_)) = state {
+            self.outputs_produced += 1;
+        }
+    }
+
+    /// Returns the total number of instructions executed.
+    pub fn get_instructions_retired(&self) -> u64 {
+        return self.instructions_retired;
+    }
+
+    /// Returns the number of input values read by the program.
+    pub fn get_inputs_consumed(&self) -> u64 {
+        return self.inputs_consumed;
+    }
+
+    /// Returns the number of values output by the program.
+    pub fn get_outputs_produced(&self) -> u64 {
+        return self.outputs_produced;
+    }
+
+    /// Returns the number of times the instruction at the given address was executed.
+    pub fn get_address_count(&self, address: usize) -> u64 {
+        return *self.address_counts.get(&address).unwrap_or(&0);
+    }
+
+    /// Returns the number of instructions executed with the given opcode.
+    pub fn get_opcode_count(&self, opcode: Opcode) -> u64 {
+        return *self.opcode_counts.get(&opcode).unwrap_or(&0);
+    }
+
+    /// Returns the addresses of the most executed instructions with their counts, most executed
+    /// first (then by address).
+    pub fn get_hot_addresses(&self, count: usize) -> Vec<(usize, u64)> {
+        let mut addresses: Vec<(usize, u64)> =
+            self.address_counts.iter().map(|(a, c)| (*a, *c)).collect();
+        addresses.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        addresses.truncate(count);
+        return addresses;
+    }
+
+    /// Returns the loops that executed the most instructions, found from the jumps back to earlier
+    /// addresses that were taken. Nested loops are reported separately, so the instructions of an
+    /// inner loop are also counted in the loops around it.
+    pub fn get_hot_loops(&self, count: usize) -> Vec<HotLoop> {
+        let mut loops: Vec<HotLoop> = self
+            .backward_jumps
+            .iter()
+            .map(|(&(end, start), &iterations)| HotLoop {
+                start,
+                end,
+                iterations,
+                instructions: self.address_counts.range(start..=end).map(|(_, c)| c).sum(),
+            })
+            .collect();
+        loops.sort_by(|a, b| {
+            b.instructions
+                .cmp(&a.instructions)
+                .then(a.start.cmp(&b.start))
+                .then(a.end.cmp(&b.end))
+        });
+        loops.truncate(count);
+        return loops;
+    }
+
+    /// Returns the percentage of all retired instructions that the count represents.
+    fn get_percentage(&self, count: u64) -> f64 {
+        if self.instructions_retired == 0 {
+            return 0.0;
+        }
+        return 100.0 * count as f64 / self.instructions_retired as f64;
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "instructions retired: {}", self.instructions_retired)?;
+        writeln!(f, "inputs consumed:      {}", self.inputs_consumed)?;
+        writeln!(f, "outputs produced:     {}", self.outputs_produced)?;
+        writeln!(f, "\nopcodes:")?;
+        let mut opcodes: Vec<(Opcode, u64)> =
+            self.opcode_counts.iter().map(|(o, c)| (*o, *c)).collect();
+        opcodes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.get_code().cmp(&b.0.get_code())));
+        for (opcode, count) in opcodes {
+            let percentage = self.get_percentage(count);
+            let mnemonic = opcode.get_mnemonic();
+            writeln!(f, "  {:<6} {:>12} {:>6.2}%", mnemonic, count, percentage)?;
+        }
+        writeln!(f, "\nhot addresses:")?;
+        for (address, count) in self.get_hot_addresses(MAX_REPORT_ENTRIES) {
+            let percentage = self.get_percentage(count);
+            writeln!(f, "  {:>6} {:>12} {:>6.2}%", address, count, percentage)?;
+        }
+        writeln!(f, "\nhot loops:")?;
+        for hot_loop in self.get_hot_loops(MAX_REPORT_ENTRIES) {
+            writeln!(
+                f,
+                "  {:>6}..{:<6} iterations: {:>10}  instructions: {:>12} {:>6.2}%",
+                hot_loop.start,
+                hot_loop.end,
+                hot_loop.iterations,
+                hot_loop.instructions,
+                self.get_percentage(hot_loop.instructions)
+            )?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut profile = Profile::new();
+        profile.record(0, Opcode::Input, 2, None);
+        for _ in 0..3 {
+            profile.record(2, Opcode::Add, 6, None);
+            profile.record(6, Opcode::Output, 8, Some(RunState::Output(1)));
+            profile.record(8, Opcode::JumpIfTrue, 2, None);
+        }
+        profile.record(2, Opcode::Add, 6, None);
+        profile.record(6, Opcode::Output, 8, Some(RunState::Output(0)));
+        profile.record(8, Opcode::JumpIfTrue, 11, None);
+        profile.record(11, Opcode::Halt, 11, Some(RunState::Halted));
+        assert_eq!(14, profile.get_instructions_retired());
+        assert_eq!(1, profile.get_inputs_consumed());
+        assert_eq!(4, profile.get_outputs_produced());
+        assert_eq!(4, profile.get_address_count(2));
+        assert_eq!(0, profile.get_address_count(3));
+        assert_eq!(4, profile.get_opcode_count(Opcode::JumpIfTrue));
+        assert_eq!(vec![(2, 4), (6, 4)], profile.get_hot_addresses(2));
+        let expected = HotLoop {
+            start: 2,
+            end: 8,
+            iterations: 3,
+            instructions: 12,
+        };
+        assert_eq!(vec![expected], profile.get_hot_loops(10));
+    }
+
+    #[test]
+    fn test_report() {
+        let mut profile = Profile::new();
+        profile.record(0, Opcode::Add, 4, None);
+        profile.record(4, Opcode::JumpIfFalse, 0, None);
+        profile.record(0, Opcode::Add, 4, None);
+        profile.record(4, Opcode::Halt, 4, Some(RunState::Halted));
+        let report = profile.to_string();
+        assert!(report.starts_with("instructions retired: 4\n"));
+        assert!(report.contains("  add               2  50.00%\n"));
+        assert!(report.contains(
+            "       0..4      iterations:          1  instructions:            4 100.00%\n"
+        ));
+    }
+}
@@ -0,0 +1,15 @@
+/// Reason an Intcode machine stopped running the program.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum RunState {
+    /// Machine has executed a HALT instruction.
+    Halted,
+    /// Machine has reached an input instruction and the input queue is empty.
+    NeedsInput,
+    /// Machine has executed an output instruction, outputting the contained value.
+    Output(i64),
+    /// Machine has executed its instruction budget, and is suspended until given a new budget.
+    BudgetExhausted,
+    /// Machine has returned to an earlier state without any input or output in between (with
+    /// cycle detection enabled), so will loop forever if run on.
+    CycleDetected,
+}
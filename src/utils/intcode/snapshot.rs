@@ -0,0 +1,448 @@
+use std::collections::VecDeque;
+use std::io::Write;
+
+use super::super::error::AocError;
+use super::super::fs;
+use super::super::io;
+use super::arithmetic::ArithmeticMode;
+use super::cycle::CycleDetector;
+use super::decode_cache::DecodeCache;
+use super::fault::{FaultKind, MachineFault};
+use super::memory::{IntcodeMemory, MemoryLimits};
+use super::trace::{join_values, parse_values};
+use super::IntcodeMachine;
+
+/// Version of the snapshot file format written by IntcodeMachine::to_snapshot().
+pub const SNAPSHOT_VERSION: u32 = 3;
+
+/// Prefix of the first line of a snapshot file, which is followed by the format version.
+const SNAPSHOT_HEADER: &str = "# intcode-snapshot v";
+
+/// Keys of the lines that must appear (once each, in this order) after the snapshot header. Any
+/// number of "page" lines follow them.
+const SNAPSHOT_KEYS: [&str; 13] = [
+    "pc",
+    "relative_base",
+    "halted",
+    "awaiting_input",
+    "fault",
+    "arithmetic",
+    "budget",
+    "cycle_detection",
+    "limits",
+    "program_len",
+    "len",
+    "input",
+    "output",
+];
+
+impl IntcodeMachine {
+    /// Returns a snapshot of the full machine state (memory, program counter, relative base,
+    /// input and output queues, halt, input and fault flags, arithmetic mode, instruction budget
+    /// and whether cycle detection is enabled) in a versioned text format that can be loaded with
+    /// from_snapshot(). The states seen by the cycle detector aren't saved, so a restored machine
+    /// starts looking for cycles afresh. Profiles and attached devices aren't saved either.
+    pub fn to_snapshot(&self) -> String {
+        let limits = self.memory.get_limits();
+        let mut lines = vec![
+            format!("{}{}", SNAPSHOT_HEADER, SNAPSHOT_VERSION),
+            format!("pc {}", self.prog_c),
+            format!("relative_base {}", self.relative_base),
+            format!("halted {}", self.halted),
+            format!("awaiting_input {}", self.awaiting_input),
+            format!("fault {}", format_fault(&self.fault)),
+            format!("arithmetic {}", format_arithmetic(self.arithmetic)),
+            format!("budget {}", format_budget(self.instruction_budget)),
+            format!("cycle_detection {}", self.cycle_detector.is_some()),
+            format!("limits {} {}", limits.max_address, limits.max_pages),
+            format!("program_len {}", self.memory.get_program_len()),
+            format!("len {}", self.memory.len()),
+            format!("input {}", join_values(&self.input)),
+            format!("output {}", join_values(&self.output)),
+        ];
+        for (index, page) in self.memory.get_pages() {
+            lines.push(format!("page {} {}", index, join_values(page)));
+        }
+        let mut snapshot = lines.join("\n");
+        snapshot.push('\n');
+        return snapshot;
+    }
+
+    /// Recreates a machine from a snapshot previously returned by to_snapshot().
+    pub fn from_snapshot(snapshot: &str) -> Result<Self, AocError> {
+        let mut lines = snapshot.lines().enumerate().map(|(i, line)| (i + 1, line));
+        // Check the format version before trying to read anything else
+        let version = lines
+            .next()
+            .and_then(|(_, line)| line.strip_prefix(SNAPSHOT_HEADER))
+            .ok_or_else(|| parse_error(1, "expected snapshot header"))?;
+        if version != SNAPSHOT_VERSION.to_string() {
+            let message = format!("unsupported snapshot version {}", version);
+            return Err(AocError::parse_error(1, 1, message));
+        }
+        let mut values = Vec::<(usize, &str)>::new();
+        for key in SNAPSHOT_KEYS.iter() {
+            let (line_num, line) = lines
+                .next()
+                .ok_or_else(|| parse_error(values.len() + 2, &format!("expected {}", key)))?;
+            let value = split_key(line, key)
+                .ok_or_else(|| parse_error(line_num, &format!("expected {}", key)))?;
+            values.push((line_num, value));
+        }
+        let parse_field = |index: usize| {
+            let (line_num, value) = values[index];
+            return value
+                .parse::<i64>()
+                .map_err(|_| parse_error(line_num, &format!("bad {}", SNAPSHOT_KEYS[index])));
+        };
+        let parse_flag = |index: usize| {
+            let (line_num, value) = values[index];
+            return value
+                .parse::<bool>()
+                .map_err(|_| parse_error(line_num, &format!("bad {}", SNAPSHOT_KEYS[index])));
+        };
+        let parse_queue = |index: usize| {
+            let (line_num, value) = values[index];
+            return parse_values(value)
+                .map(VecDeque::from)
+                .ok_or_else(|| parse_error(line_num, &format!("bad {}", SNAPSHOT_KEYS[index])));
+        };
+        let prog_c = parse_usize(parse_field(0)?, values[0].0, "pc")?;
+        let relative_base = parse_field(1)?;
+        let halted = parse_flag(2)?;
+        let awaiting_input = parse_flag(3)?;
+        let fault =
+            parse_fault(values[4].1).ok_or_else(|| parse_error(values[4].0, "bad fault"))?;
+        let arithmetic = parse_arithmetic(values[5].1)
+            .ok_or_else(|| parse_error(values[5].0, "bad arithmetic"))?;
+        let instruction_budget =
+            parse_budget(values[6].1).ok_or_else(|| parse_error(values[6].0, "bad budget"))?;
+        let cycle_detection = parse_flag(7)?;
+        let limits =
+            parse_limits(values[8].1).ok_or_else(|| parse_error(values[8].0, "bad limits"))?;
+        let program_len = parse_usize(parse_field(9)?, values[9].0, "program_len")?;
+        let len = parse_usize(parse_field(10)?, values[10].0, "len")?;
+        let input = parse_queue(11)?;
+        let output = parse_queue(12)?;
+        let mut pages = Vec::<(usize, Vec<i64>)>::new();
+        for (line_num, line) in lines {
+            let page = split_key(line, "page")
+                .and_then(parse_page)
+                .ok_or_else(|| parse_error(line_num, "bad page"))?;
+            pages.push(page);
+        }
+        let memory =
+            IntcodeMemory::from_pages(pages, program_len, len, limits).ok_or_else(|| {
+                AocError::InvalidProgram(String::from("snapshot memory is inconsistent"))
+            })?;
+        let decode_cache = DecodeCache::new_filled(&memory);
+        let cycle_detector = match cycle_detection {
+            true => Some(Box::new(CycleDetector::new())),
+            false => None,
+        };
+        return Ok(Self {
+            prog_c,
+            memory,
+            input,
+            output,
+            halted,
+            awaiting_input,
+            relative_base,
+            fault,
+            arithmetic,
+            instruction_budget,
+            cycle_detector,
+            decode_cache,
+            profile: None,
+            input_device: None,
+            output_device: None,
+        });
+    }
+
+    /// Saves a snapshot of the full machine state to the given file, which is created (or
+    /// truncated).
+    pub fn save_snapshot(&self, filename: String) -> Result<(), AocError> {
+        let mut file = fs::try_create_file(filename.clone())?;
+        return file
+            .write_all(self.to_snapshot().as_bytes())
+            .map_err(|e| AocError::Write {
+                filename,
+                source: e,
+            });
+    }
+
+    /// Loads a machine from a snapshot file written by save_snapshot().
+    pub fn load_snapshot(filename: String) -> Result<Self, AocError> {
+        let snapshot = io::try_read_filename_to_string(filename)?;
+        return IntcodeMachine::from_snapshot(&snapshot);
+    }
+}
+
+/// Creates a parse error for the given snapshot line.
+fn parse_error(line: usize, message: &str) -> AocError {
+    return AocError::parse_error(line, 1, String::from(message));
+}
+
+/// Returns the value of a "key value" line, if the line has the given key.
+fn split_key<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let mut parts = line.splitn(2, ' ');
+    if parts.next() != Some(key) {
+        return None;
+    }
+    return parts.next();
+}
+
+/// Converts the value to a usize, returning a parse error for the given line and key if it is
+/// negative.
+fn parse_usize(value: i64, line: usize, key: &str) -> Result<usize, AocError> {
+    if value < 0 {
+        return Err(parse_error(line, &format!("bad {}", key)));
+    }
+    return Ok(value as usize);
+}
+
+/// Parses the "<max address> <max pages>" value of a limits line.
+fn parse_limits(text: &str) -> Option<MemoryLimits> {
+    let mut parts = text.split(' ');
+    let limits = MemoryLimits {
+        max_address: parts.next()?.parse().ok()?,
+        max_pages: parts.next()?.parse().ok()?,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    return Some(limits);
+}
+
+/// Parses the "<index> <values>" value of a page line.
+fn parse_page(text: &str) -> Option<(usize, Vec<i64>)> {
+    let mut parts = text.splitn(2, ' ');
+    let index = parts.next()?.parse().ok()?;
+    let values = parse_values(parts.next()?)?;
+    return Some((index, values));
+}
+
+/// Formats the instruction budget as its value, or "-" if there is none.
+fn format_budget(budget: Option<u64>) -> String {
+    match budget {
+        Some(budget) => return budget.to_string(),
+        None => return String::from("-"),
+    }
+}
+
+/// Parses an instruction budget written by format_budget(). Returns None if it is malformed.
+fn parse_budget(text: &str) -> Option<Option<u64>> {
+    if text == "-" {
+        return Some(None);
+    }
+    return Some(Some(text.parse().ok()?));
+}
+
+/// Formats the arithmetic mode as the name used in a snapshot.
+fn format_arithmetic(mode: ArithmeticMode) -> &'static str {
+    match mode {
+        ArithmeticMode::Checked => return "checked",
+        ArithmeticMode::Wrapping => return "wrapping",
+    }
+}
+
+/// Parses an arithmetic mode written by format_arithmetic(). Returns None if it is unknown.
+fn parse_arithmetic(text: &str) -> Option<ArithmeticMode> {
+    match text {
+        "checked" => return Some(ArithmeticMode::Checked),
+        "wrapping" => return Some(ArithmeticMode::Wrapping),
+        _ => return None,
+    }
+}
+
+/// Formats the fault as "<kind> <opcode> <pc> <relative base> <address>", or "-" if there is none.
+fn format_fault(fault: &Option<MachineFault>) -> String {
+    let fault = match fault {
+        Some(fault) => fault,
+        None => return String::from("-"),
+    };
+    let kind = match fault.kind {
+        FaultKind::UnknownOpcode => String::from("unknown_opcode"),
+        FaultKind::BadParameterMode(mode) => format!("bad_parameter_mode:{}", mode),
+        FaultKind::NegativeAddress => String::from("negative_address"),
+        FaultKind::AddressOutOfRange => String::from("address_out_of_range"),
+        FaultKind::MemoryLimitExceeded => String::from("memory_limit_exceeded"),
+        FaultKind::ArithmeticOverflow => String::from("arithmetic_overflow"),
+    };
+    let address = match fault.address {
+        Some(address) => address.to_string(),
+        None => String::from("-"),
+    };
+    return format!(
+        "{} {} {} {} {}",
+        kind, fault.opcode, fault.pc, fault.relative_base, address
+    );
+}
+
+/// Parses a fault written by format_fault(). Returns None if it is malformed.
+fn parse_fault(text: &str) -> Option<Option<MachineFault>> {
+    if text == "-" {
+        return Some(None);
+    }
+    let parts: Vec<&str> = text.split(' ').collect();
+    if parts.len() != 5 {
+        return None;
+    }
+    let kind = match parts[0] {
+        "unknown_opcode" => FaultKind::UnknownOpcode,
+        "negative_address" => FaultKind::NegativeAddress,
+        "address_out_of_range" => FaultKind::AddressOutOfRange,
+        "memory_limit_exceeded" => FaultKind::MemoryLimitExceeded,
+        "arithmetic_overflow" => FaultKind::ArithmeticOverflow,
+        other => {
+            FaultKind::BadParameterMode(other.strip_prefix("bad_parameter_mode:")?.parse().ok()?)
+        }
+    };
+    let address = match parts[4] {
+        "-" => None,
+        address => Some(address.parse().ok()?),
+    };
+    return Some(Some(MachineFault {
+        kind,
+        opcode: parts[1].parse().ok()?,
+        pc: parts[2].parse().ok()?,
+        relative_base: parts[3].parse().ok()?,
+        address,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::fs::TempFile;
+    use super::super::memory::PAGE_SIZE;
+    use super::super::RunState;
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        // Outputs its input, then writes to a far address and waits for more input
+        let program = vec![3, 11, 4, 11, 109, 2000, 21101, 5, 6, 0, 3, 0];
+        let mut machine = IntcodeMachine::new(program, VecDeque::from(vec![42]));
+        machine.try_execute_program_break_on_output(true).unwrap();
+        machine.try_execute_program().unwrap();
+        machine.add_input(7);
+        machine.add_input(8);
+        let snapshot = machine.to_snapshot();
+        assert!(snapshot.starts_with("# intcode-snapshot v3\npc 10\n"));
+        let restored = IntcodeMachine::from_snapshot(&snapshot).unwrap();
+        assert!(restored == machine);
+        assert_eq!(VecDeque::from(vec![7, 8]), restored.get_input_vec());
+        assert_eq!(VecDeque::from(vec![42]), restored.get_output_vec());
+        assert_eq!(11, restored.peek_memory(2000));
+    }
+
+    #[test]
+    fn test_snapshot_keeps_program_len() {
+        // Writes 5 then 0 to a far address, so memory shrinks back to the program after the write
+        let program = vec![1101, 5, 0, 2000, 3, 0, 1101, 0, 0, 2000, 99];
+        let mut machine = IntcodeMachine::new(program, VecDeque::new());
+        machine.try_execute_program().unwrap();
+        let mut restored = IntcodeMachine::from_snapshot(&machine.to_snapshot()).unwrap();
+        assert!(restored == machine);
+        machine.add_input(0);
+        restored.add_input(0);
+        machine.try_execute_program().unwrap();
+        restored.try_execute_program().unwrap();
+        assert_eq!(11, machine.get_memory_len());
+        assert_eq!(11, restored.get_memory_len());
+        assert!(restored == machine);
+    }
+
+    #[test]
+    fn test_snapshot_keeps_budget_and_cycle_detection() {
+        // Loops forever without input or output
+        let mut machine = IntcodeMachine::new(vec![1105, 1, 0], VecDeque::new());
+        machine.set_instruction_budget(Some(10));
+        machine.set_cycle_detection(true);
+        let snapshot = machine.to_snapshot();
+        assert!(snapshot.contains("\nbudget 10\ncycle_detection true\n"));
+        let mut restored = IntcodeMachine::from_snapshot(&snapshot).unwrap();
+        assert_eq!(Some(10), restored.get_instruction_budget());
+        assert_eq!(Ok(RunState::CycleDetected), restored.run());
+        machine.set_instruction_budget(None);
+        machine.set_cycle_detection(false);
+        let snapshot = machine.to_snapshot();
+        assert!(snapshot.contains("\nbudget -\ncycle_detection false\n"));
+    }
+
+    #[test]
+    fn test_snapshot_with_fault() {
+        let mut machine = IntcodeMachine::new(vec![1105, 1, -1], VecDeque::new());
+        assert!(machine.try_execute_program().is_err());
+        let restored = IntcodeMachine::from_snapshot(&machine.to_snapshot()).unwrap();
+        assert_eq!(machine.get_fault(), restored.get_fault());
+        assert!(restored == machine);
+    }
+
+    #[test]
+    fn test_snapshot_file_round_trip() {
+        let file = TempFile::new("snapshot_test");
+        let machine = IntcodeMachine::new(vec![3, 0, 99], VecDeque::from(vec![1, 2]));
+        machine.save_snapshot(file.get_filename()).unwrap();
+        let restored = IntcodeMachine::load_snapshot(file.get_filename()).unwrap();
+        assert!(restored == machine);
+    }
+
+    #[test]
+    fn test_snapshot_errors() {
+        let machine = IntcodeMachine::new(vec![99], VecDeque::new());
+        let snapshot = machine.to_snapshot();
+        let newer = snapshot.replace("v3", "v4");
+        match IntcodeMachine::from_snapshot(&newer) {
+            Err(AocError::Parse { line, message, .. }) => {
+                assert_eq!(1, line);
+                assert_eq!("unsupported snapshot version 4", message);
+            }
+            _ => panic!("Expected parse error."),
+        }
+        let bad_flag = snapshot.replace("halted false", "halted maybe");
+        match IntcodeMachine::from_snapshot(&bad_flag) {
+            Err(AocError::Parse { line, .. }) => assert_eq!(4, line),
+            _ => panic!("Expected parse error."),
+        }
+        let missing_key = snapshot.replace("\nlen 1\n", "\n");
+        assert!(IntcodeMachine::from_snapshot(&missing_key).is_err());
+        let bad_page = snapshot.replace("page 0 99,", "page 0 ");
+        assert!(IntcodeMachine::from_snapshot(&bad_page).is_err());
+        // Page index so large its address overflows
+        let values = vec!["1"; PAGE_SIZE].join(",");
+        let huge_page = format!("{}page {} {}\n", snapshot, usize::MAX / 2, values);
+        match IntcodeMachine::from_snapshot(&huge_page) {
+            Err(AocError::InvalidProgram(_)) => (),
+            _ => panic!("Expected invalid program error."),
+        }
+        // Length beyond the highest address the limits allow
+        let beyond_limits = snapshot.replace("limits 4294967295", "limits 0");
+        assert!(IntcodeMachine::from_snapshot(&beyond_limits).is_ok());
+        let beyond_limits = format!("{}page 1 {}\n", beyond_limits, values);
+        let beyond_limits = beyond_limits.replace("\nlen 1\n", "\nlen 2048\n");
+        match IntcodeMachine::from_snapshot(&beyond_limits) {
+            Err(AocError::InvalidProgram(_)) => (),
+            _ => panic!("Expected invalid program error."),
+        }
+        let bad_arithmetic = snapshot.replace("arithmetic checked", "arithmetic saturating");
+        match IntcodeMachine::from_snapshot(&bad_arithmetic) {
+            Err(AocError::Parse { line, .. }) => assert_eq!(7, line),
+            _ => panic!("Expected parse error."),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_keeps_arithmetic_mode() {
+        // Adds 2^62 to itself, which only runs without a fault in wrapping mode
+        let program = vec![1101, 1 << 62, 1 << 62, 0, 99];
+        let mut machine = IntcodeMachine::new(program, VecDeque::new());
+        machine.set_arithmetic_mode(ArithmeticMode::Wrapping);
+        let snapshot = machine.to_snapshot();
+        assert!(snapshot.contains("\narithmetic wrapping\n"));
+        let mut restored = IntcodeMachine::from_snapshot(&snapshot).unwrap();
+        assert_eq!(ArithmeticMode::Wrapping, restored.get_arithmetic_mode());
+        restored.try_execute_program().unwrap();
+        assert_eq!(i64::MIN, restored.get_location_zero());
+    }
+}
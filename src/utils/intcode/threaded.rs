@@ -0,0 +1,412 @@
+use std::panic;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+use super::run_state::RunState;
+use super::IntcodeMachine;
+
+/// Intcode machine running on its own thread, reading input values from a channel and sending
+/// each value it outputs to one or more channels.
+///
+/// The thread blocks while the machine waits for input. It finishes once the machine halts,
+/// faults or is suspended (by its instruction budget or cycle detection), or while it waits for
+/// input after every sender of its input channel has been dropped.
+pub struct MachineThread {
+    handle: JoinHandle<IntcodeMachine>,
+}
+
+impl MachineThread {
+    /// Starts running the machine on a new thread. Output values sent after a receiver has been
+    /// dropped are discarded.
+    pub fn spawn(machine: IntcodeMachine, input: Receiver<i64>, outputs: Vec<Sender<i64>>) -> Self {
+        let handle = thread::spawn(move || {
+            let read_input = move || input.recv().ok();
+            let write_output = move |value| {
+                for output in outputs.iter() {
+                    let _ = output.send(value);
+                }
+            };
+            return run_machine(machine, read_input, write_output);
+        });
+        return Self { handle };
+    }
+
+    /// Waits for the thread to finish, returning the machine in its final state - halted,
+    /// faulted, or awaiting input that will never arrive. A panic on the thread is passed on to
+    /// the caller.
+    pub fn join(self) -> IntcodeMachine {
+        match self.handle.join() {
+            Ok(machine) => return machine,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+/// Runs the machine until it halts, faults or is suspended, or no more input will arrive. Input
+/// values are read with the first closure, which blocks until a value arrives and returns None if
+/// none ever will, and output values are passed to the second.
+fn run_machine<I, O>(
+    mut machine: IntcodeMachine,
+    mut read_input: I,
+    mut write_output: O,
+) -> IntcodeMachine
+where
+    I: FnMut() -> Option<i64>,
+    O: FnMut(i64),
+{
+    loop {
+        match machine.run() {
+            Ok(RunState::Output(value)) => write_output(value),
+            Ok(RunState::NeedsInput) => match read_input() {
+                Some(value) => machine.add_input(value),
+                None => break,
+            },
+            // Suspended machines are returned for the caller to deal with
+            Ok(RunState::BudgetExhausted) | Ok(RunState::CycleDetected) => break,
+            Ok(RunState::Halted) | Err(_) => break,
+        }
+    }
+    return machine;
+}
+
+/// Machines connected in a chain or ring, each running on its own thread (the threaded
+/// counterpart of an IntcodeNetwork with those topologies). Values are sent to the first machine
+/// through the network, and values output by the last machine are received from it.
+///
+/// Once the input of the network has been closed, the network deadlocks if every machine still
+/// running is waiting for input and no values are on their way to any of them (e.g. a ring in
+/// which every machine reads before it outputs). The waiting machines then finish, left awaiting
+/// input, rather than blocking forever.
+pub struct ThreadedNetwork {
+    input: Option<Sender<i64>>,
+    output: Receiver<i64>,
+    threads: Vec<MachineThread>,
+    monitor: Arc<WaitMonitor>,
+}
+
+impl ThreadedNetwork {
+    /// Starts the machines on their own threads, with the output of each machine input to the
+    /// next machine. Output of the last machine leaves the network.
+    pub fn chain(machines: Vec<IntcodeMachine>) -> Self {
+        return Self::spawn(machines, false);
+    }
+
+    /// Starts the machines on their own threads, with the output of each machine input to the
+    /// next machine and the output of the last machine input to the first machine. Output of the
+    /// last machine also leaves the network.
+    pub fn ring(machines: Vec<IntcodeMachine>) -> Self {
+        return Self::spawn(machines, true);
+    }
+
+    /// Starts the machines connected in a chain, or a ring if requested.
+    fn spawn(machines: Vec<IntcodeMachine>, ring: bool) -> Self {
+        let (input, first_receiver) = mpsc::channel();
+        let (output, output_receiver) = mpsc::channel();
+        let monitor = Arc::new(WaitMonitor::new(machines.len()));
+        // Machine at each index reads from the receiver and sends to the machine inputs at that
+        // index, with the last machine also sending to the output of the network
+        let mut receivers = vec![first_receiver];
+        let mut next_inputs = vec![];
+        for _ in 1..machines.len() {
+            let (sender, receiver) = mpsc::channel();
+            receivers.push(receiver);
+            next_inputs.push(vec![sender]);
+        }
+        next_inputs.push(match ring {
+            true => vec![input.clone()],
+            false => vec![],
+        });
+        let num_machines = machines.len();
+        let threads = machines
+            .into_iter()
+            .zip(receivers.into_iter().zip(next_inputs))
+            .enumerate()
+            .map(|(index, (machine, (receiver, next_inputs)))| {
+                let monitor = monitor.clone();
+                let output = match index + 1 == num_machines {
+                    true => Some(output.clone()),
+                    false => None,
+                };
+                let handle = thread::spawn(move || {
+                    // Marks the machine finished once its senders have been dropped, even if it
+                    // panics
+                    let finished = FinishedGuard {
+                        monitor: monitor.clone(),
+                        input: Some(receiver),
+                    };
+                    let read_input = || finished.monitor.recv(finished.input.as_ref().unwrap());
+                    let write_output = move |value| {
+                        for next_input in next_inputs.iter() {
+                            monitor.send(next_input, value);
+                        }
+                        if let Some(output) = &output {
+                            let _ = output.send(value);
+                        }
+                    };
+                    return run_machine(machine, read_input, write_output);
+                });
+                return MachineThread { handle };
+            })
+            .collect();
+        return Self {
+            input: Some(input),
+            output: output_receiver,
+            threads,
+            monitor,
+        };
+    }
+
+    /// Sends the value to the first machine of the network.
+    pub fn send_input(&self, value: i64) {
+        if let Some(input) = &self.input {
+            self.monitor.send(input, value);
+        }
+    }
+
+    /// Waits for the next value output by the last machine of the network. Returns None once
+    /// every machine has finished and all output has been received.
+    pub fn recv_output(&self) -> Option<i64> {
+        return self.output.recv().ok();
+    }
+
+    /// Stops any more values being sent to the first machine, so it finishes once it waits for
+    /// input (unless it is part of a ring, where the last machine can still send it values).
+    pub fn close_input(&mut self) {
+        self.input = None;
+        self.monitor.close_input();
+    }
+
+    /// Closes the input of the network and waits for every machine to finish, returning them in
+    /// their final states along with any output not yet received. Machines left waiting for input
+    /// when the network deadlocks are returned awaiting input.
+    pub fn join(mut self) -> (Vec<IntcodeMachine>, Vec<i64>) {
+        self.close_input();
+        let machines = self.threads.into_iter().map(|t| t.join()).collect();
+        let output = self.output.try_iter().collect();
+        return (machines, output);
+    }
+}
+
+/// Counts shared by the machine threads of a network, used to detect a deadlock.
+struct WaitCounts {
+    /// Number of machine threads that haven't finished.
+    running: usize,
+    /// Number of machine threads waiting for input.
+    waiting: usize,
+    /// Number of values sent to machine inputs that haven't been received yet.
+    pending: usize,
+    /// Whether values can still be sent to the network.
+    input_open: bool,
+    /// Whether the network has deadlocked, so no more input will arrive.
+    deadlocked: bool,
+}
+
+/// Monitors the machine threads of a network, waking waiting machines whenever a value is sent
+/// to them or the network deadlocks. Values are sent and received with the counts locked, so a
+/// value can't arrive between a machine finding its input empty and it starting to wait.
+struct WaitMonitor {
+    counts: Mutex<WaitCounts>,
+    changed: Condvar,
+}
+
+impl WaitMonitor {
+    /// Creates a monitor for a network with the given number of machines.
+    fn new(num_machines: usize) -> Self {
+        let counts = WaitCounts {
+            running: num_machines,
+            waiting: 0,
+            pending: 0,
+            input_open: true,
+            deadlocked: false,
+        };
+        return Self {
+            counts: Mutex::new(counts),
+            changed: Condvar::new(),
+        };
+    }
+
+    /// Sends the value to a machine input, waking the waiting machines. Values sent after the
+    /// receiver has been dropped are discarded.
+    fn send(&self, input: &Sender<i64>, value: i64) {
+        let mut counts = self.counts.lock().unwrap();
+        if input.send(value).is_ok() {
+            counts.pending += 1;
+        }
+        self.changed.notify_all();
+    }
+
+    /// Waits for the next value sent to the machine input. Returns None once every sender has
+    /// been dropped, or the network has deadlocked.
+    fn recv(&self, input: &Receiver<i64>) -> Option<i64> {
+        let mut counts = self.counts.lock().unwrap();
+        loop {
+            match input.try_recv() {
+                Ok(value) => {
+                    counts.pending -= 1;
+                    return Some(value);
+                }
+                Err(TryRecvError::Disconnected) => return None,
+                Err(TryRecvError::Empty) => (),
+            }
+            counts.waiting += 1;
+            self.check_deadlock(&mut counts);
+            if counts.deadlocked {
+                counts.waiting -= 1;
+                return None;
+            }
+            counts = self.changed.wait(counts).unwrap();
+            counts.waiting -= 1;
+        }
+    }
+
+    /// Records that no more values will be sent to the network.
+    fn close_input(&self) {
+        let mut counts = self.counts.lock().unwrap();
+        counts.input_open = false;
+        self.check_deadlock(&mut counts);
+    }
+
+    /// Records that a machine thread has finished, discarding any values left in its input.
+    fn finish(&self, input: Option<Receiver<i64>>) {
+        let mut counts = self.counts.lock().unwrap();
+        if let Some(input) = input {
+            counts.pending -= input.try_iter().count();
+        }
+        counts.running -= 1;
+        self.check_deadlock(&mut counts);
+        // Machines waiting on the finished machine find their input disconnected when woken
+        self.changed.notify_all();
+    }
+
+    /// Marks the network deadlocked, waking the waiting machines, if every running machine is
+    /// waiting for input that can never arrive.
+    fn check_deadlock(&self, counts: &mut WaitCounts) {
+        if !counts.input_open && counts.pending == 0 && counts.waiting == counts.running {
+            counts.deadlocked = true;
+            self.changed.notify_all();
+        }
+    }
+}
+
+/// Marks a machine thread of a network as finished when dropped, which happens after the
+/// machine's senders have been dropped whether it returns or panics.
+struct FinishedGuard {
+    monitor: Arc<WaitMonitor>,
+    input: Option<Receiver<i64>>,
+}
+
+impl Drop for FinishedGuard {
+    fn drop(&mut self) {
+        self.monitor.finish(self.input.take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::assemble;
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Program adding one to each value read and outputting it, halting after reading 0.
+    fn get_increment_program() -> Vec<i64> {
+        return assemble(
+            "
+            loop:  in value
+                   jf value, #end
+                   add value, #1, value
+                   out value
+                   jt #1, #loop
+            end:   hlt
+            value: data 0
+            ",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_machine_thread() {
+        let (input, receiver) = mpsc::channel();
+        let (sender, output) = mpsc::channel();
+        let machine = IntcodeMachine::new(get_increment_program(), VecDeque::new());
+        let thread = MachineThread::spawn(machine, receiver, vec![sender]);
+        input.send(4).unwrap();
+        assert_eq!(Ok(5), output.recv());
+        // Dropping the input channel leaves the machine waiting for input
+        drop(input);
+        let machine = thread.join();
+        assert!(machine.is_awaiting_input());
+        assert!(!machine.has_halted());
+    }
+
+    #[test]
+    fn test_threaded_chain() {
+        let machines = (0..4)
+            .map(|_| IntcodeMachine::new(get_increment_program(), VecDeque::new()))
+            .collect();
+        let network = ThreadedNetwork::chain(machines);
+        network.send_input(10);
+        network.send_input(20);
+        assert_eq!(Some(14), network.recv_output());
+        network.send_input(0);
+        let (machines, output) = network.join();
+        assert_eq!(vec![24], output);
+        // Only the first machine reads the 0, so the others are left waiting for input
+        assert!(machines[0].has_halted());
+        assert!(machines[1..].iter().all(|m| m.is_awaiting_input()));
+    }
+
+    #[test]
+    fn test_threaded_ring() {
+        // Each machine adds one to the value and passes it on until the first machine reads 3
+        let mut machines = vec![IntcodeMachine::new(
+            assemble(
+                "
+                loop:  in value
+                       eq value, #3, flag
+                       jt flag, #end
+                       out value
+                       jt #1, #loop
+                end:   hlt
+                value: data 0
+                flag:  data 0
+                ",
+            )
+            .unwrap(),
+            VecDeque::new(),
+        )];
+        machines.push(IntcodeMachine::new(
+            get_increment_program(),
+            VecDeque::new(),
+        ));
+        let network = ThreadedNetwork::ring(machines);
+        network.send_input(1);
+        let (machines, output) = network.join();
+        assert_eq!(vec![2, 3], output);
+        assert!(machines[0].has_halted());
+        // Second machine is still waiting for the first machine to send it more input
+        assert!(machines[1].is_awaiting_input());
+    }
+
+    #[test]
+    fn test_threaded_ring_deadlock() {
+        // First machine passes its input on and waits for another value, while the second
+        // machine waits for two values, so both end up waiting for each other
+        let machines = vec![
+            IntcodeMachine::new(
+                assemble("in value\nout value\nin value\nhlt\nvalue: data 0").unwrap(),
+                VecDeque::new(),
+            ),
+            IntcodeMachine::new(
+                assemble("in value\nin value\nhlt\nvalue: data 0").unwrap(),
+                VecDeque::new(),
+            ),
+        ];
+        let network = ThreadedNetwork::ring(machines);
+        network.send_input(5);
+        let (machines, output) = network.join();
+        assert!(output.is_empty());
+        assert!(machines.iter().all(|m| m.is_awaiting_input()));
+        assert_eq!(5, machines[1].peek_memory(5));
+    }
+}
@@ -0,0 +1,562 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+
+use super::super::error::AocError;
+use super::super::io;
+use super::device::{OutputDevice, WriterOutput};
+use super::fault::MachineFault;
+use super::instruction::{Instruction, Opcode, ParamMode};
+use super::run_state::RunState;
+use super::IntcodeMachine;
+
+/// First line of a trace file, identifying the format and its version.
+const TRACE_FILE_HEADER: &str = "# intcode-trace v1";
+
+/// Memory write made by a traced instruction.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct MemoryWrite {
+    pub address: usize,
+    pub old_value: i64,
+    pub new_value: i64,
+}
+
+/// Record of a single executed instruction. The program counter and relative base are those from
+/// before the instruction was executed, and the next program counter and relative base those from
+/// after.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub relative_base: i64,
+    pub instruction: Instruction,
+    pub values_read: Vec<i64>,
+    pub write: Option<MemoryWrite>,
+    pub input: Option<i64>,
+    pub output: Option<i64>,
+    pub next_pc: usize,
+    pub next_relative_base: i64,
+}
+
+impl TraceEntry {
+    /// Formats the entry as a single tab-separated line of a trace file.
+    fn to_line(&self) -> String {
+        let write = match self.write {
+            Some(w) => format!("{}:{}>{}", w.address, w.old_value, w.new_value),
+            None => String::from("-"),
+        };
+        let fields = vec![
+            self.pc.to_string(),
+            self.relative_base.to_string(),
+            join_values(&self.instruction.encode()),
+            join_values(&self.values_read),
+            write,
+            format_optional(self.input),
+            format_optional(self.output),
+            self.next_pc.to_string(),
+            self.next_relative_base.to_string(),
+        ];
+        return fields.join("\t");
+    }
+
+    /// Parses a line of a trace file (line number given for error reporting) back into an entry.
+    fn from_line(text: &str, line: usize) -> Result<TraceEntry, AocError> {
+        let bad_line = |message: &str| AocError::parse_error(line, 1, String::from(message));
+        let fields: Vec<&str> = text.split('\t').collect();
+        if fields.len() != 9 {
+            return Err(bad_line("expected 9 tab-separated fields"));
+        }
+        let encoded = parse_values(fields[2]).ok_or_else(|| bad_line("bad instruction"))?;
+        let instruction =
+            Instruction::decode(&encoded, 0).ok_or_else(|| bad_line("bad instruction"))?;
+        let write = if fields[4] == "-" {
+            None
+        } else {
+            let (address, values) =
+                split_pair(fields[4], ':').ok_or_else(|| bad_line("bad write"))?;
+            let (old_value, new_value) =
+                split_pair(values, '>').ok_or_else(|| bad_line("bad write"))?;
+            Some(MemoryWrite {
+                address: address.parse().map_err(|_| bad_line("bad write address"))?,
+                old_value: old_value.parse().map_err(|_| bad_line("bad write value"))?,
+                new_value: new_value.parse().map_err(|_| bad_line("bad write value"))?,
+            })
+        };
+        return Ok(TraceEntry {
+            pc: fields[0].parse().map_err(|_| bad_line("bad pc"))?,
+            relative_base: fields[1]
+                .parse()
+                .map_err(|_| bad_line("bad relative base"))?,
+            instruction,
+            values_read: parse_values(fields[3]).ok_or_else(|| bad_line("bad values read"))?,
+            write,
+            input: parse_optional(fields[5]).ok_or_else(|| bad_line("bad input"))?,
+            output: parse_optional(fields[6]).ok_or_else(|| bad_line("bad output"))?,
+            next_pc: fields[7].parse().map_err(|_| bad_line("bad next pc"))?,
+            next_relative_base: fields[8]
+                .parse()
+                .map_err(|_| bad_line("bad next relative base"))?,
+        });
+    }
+}
+
+/// Where a Tracer sends the entries it records.
+enum TraceSink {
+    /// Keeps the most recent entries in memory, dropping the oldest once capacity is reached.
+    Ring {
+        capacity: usize,
+        entries: VecDeque<TraceEntry>,
+    },
+    /// Writes every entry to a trace file.
+    File(WriterOutput<BufWriter<File>>),
+}
+
+/// Opt-in recorder of the instructions executed by an IntcodeMachine. The machine is run through
+/// the tracer's step() and run() methods, which behave the same as those of the machine.
+pub struct Tracer {
+    sink: TraceSink,
+    num_recorded: u64,
+}
+
+impl Tracer {
+    /// Creates a tracer that keeps the most recent entries (up to the given capacity) in memory.
+    pub fn with_ring_buffer(capacity: usize) -> Self {
+        Self {
+            sink: TraceSink::Ring {
+                capacity,
+                entries: VecDeque::with_capacity(capacity),
+            },
+            num_recorded: 0,
+        }
+    }
+
+    /// Creates a tracer that writes every entry to the given file, which is created (or truncated).
+    pub fn to_file(filename: String) -> Result<Self, AocError> {
+        let mut output = WriterOutput::create_file(filename)?;
+        output.write_line(TRACE_FILE_HEADER);
+        return Ok(Self {
+            sink: TraceSink::File(output),
+            num_recorded: 0,
+        });
+    }
+
+    /// Returns the total number of entries recorded, including any dropped from a ring buffer.
+    pub fn get_num_recorded(&self) -> u64 {
+        return self.num_recorded;
+    }
+
+    /// Returns the entries held by a ring buffer tracer, oldest first. Returns an empty list for a
+    /// tracer writing to a file.
+    pub fn get_entries(&self) -> Vec<TraceEntry> {
+        match &self.sink {
+            TraceSink::Ring { entries, .. } => return entries.iter().cloned().collect(),
+            TraceSink::File(_) => return vec![],
+        }
+    }
+
+    /// Flushes any entries not yet written to the trace file, returning the first write error that
+    /// occurred while tracing.
+    pub fn finish(&mut self) -> Result<(), AocError> {
+        match &mut self.sink {
+            TraceSink::Ring { .. } => return Ok(()),
+            TraceSink::File(output) => return output.finish(),
+        }
+    }
+
+    /// Executes a single instruction of the machine, recording it. Behaves the same as the step()
+    /// method of the machine.
+    pub fn step(&mut self, machine: &mut IntcodeMachine) -> Result<Option<RunState>, MachineFault> {
+        let state = self.step_and_keep_output(machine)?;
+        if let Some(RunState::Output(_)) = state {
+            machine.remove_step_output();
+        }
+        return Ok(state);
+    }
+
+    /// Runs the machine until it halts, needs input or outputs a value, recording each instruction
+    /// executed. Behaves the same as the run() method of the machine.
+    pub fn run(&mut self, machine: &mut IntcodeMachine) -> Result<RunState, MachineFault> {
+        loop {
+            if let Some(state) = self.step(machine)? {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// Executes a single instruction of the machine, leaving any output in its output queue, and
+    /// records it. Instructions that fault or wait for input aren't executed, so aren't recorded.
+    pub fn step_and_keep_output(
+        &mut self,
+        machine: &mut IntcodeMachine,
+    ) -> Result<Option<RunState>, MachineFault> {
+        let pc = machine.prog_c;
+        let relative_base = machine.relative_base;
+        let values: Vec<i64> = (pc..pc + 4).map(|a| machine.peek_memory(a)).collect();
+        let instruction = match Instruction::decode(&values, 0) {
+            Some(i) if !machine.halted => i,
+            // Let the machine report the fault (or halt) for anything that can't be decoded
+            _ => return machine.step_and_keep_output(),
+        };
+        let (values_read, write_address) = resolve_params(machine, &instruction);
+        let old_value = write_address.map(|a| machine.peek_memory(a));
+        let state = machine.step_and_keep_output()?;
+        if state == Some(RunState::NeedsInput) {
+            return Ok(state);
+        }
+        let write = match (write_address, old_value) {
+            (Some(address), Some(old_value)) => Some(MemoryWrite {
+                address,
+                old_value,
+                new_value: machine.peek_memory(address),
+            }),
+            _ => None,
+        };
+        // Input may come from a device, so take the value read from what was written
+        let input = match (instruction.opcode, &write) {
+            (Opcode::Input, Some(write)) => Some(write.new_value),
+            _ => None,
+        };
+        let output = match state {
+            Some(RunState::Output(value)) => Some(value),
+            _ => None,
+        };
+        self.record(TraceEntry {
+            pc,
+            relative_base,
+            instruction,
+            values_read,
+            write,
+            input,
+            output,
+            next_pc: machine.prog_c,
+            next_relative_base: machine.relative_base,
+        });
+        return Ok(state);
+    }
+
+    /// Adds the entry to the ring buffer or trace file.
+    fn record(&mut self, entry: TraceEntry) {
+        self.num_recorded += 1;
+        match &mut self.sink {
+            TraceSink::Ring { capacity, entries } => {
+                if *capacity == 0 {
+                    return;
+                }
+                if entries.len() == *capacity {
+                    entries.pop_front();
+                }
+                entries.push_back(entry);
+            }
+            TraceSink::File(output) => output.write_line(&entry.to_line()),
+        }
+    }
+}
+
+/// Loads the entries from a trace file written by a Tracer.
+pub fn load_trace(filename: String) -> Result<Vec<TraceEntry>, AocError> {
+    let contents = io::try_read_filename_to_string(filename)?;
+    let mut lines = contents.lines();
+    if lines.next() != Some(TRACE_FILE_HEADER) {
+        let message = format!("expected trace file header \"{}\"", TRACE_FILE_HEADER);
+        return Err(AocError::parse_error(1, 1, message));
+    }
+    let mut entries = Vec::<TraceEntry>::new();
+    for (i, line) in lines.enumerate() {
+        entries.push(TraceEntry::from_line(line, i + 2)?);
+    }
+    return Ok(entries);
+}
+
+/// Steps backwards and forwards through a trace, restoring the machine to the state it was in
+/// before (or after) each recorded instruction.
+///
+/// Program counter, relative base, memory and halt state are restored. Input and output queues are
+/// left as they are, as the values consumed and produced are recorded in each entry.
+pub struct Replayer {
+    machine: IntcodeMachine,
+    entries: Vec<TraceEntry>,
+    position: usize,
+}
+
+impl Replayer {
+    /// Creates a replayer for the trace, starting from the given machine, which must be in the
+    /// state it was left in after the last entry was recorded.
+    pub fn new(machine: IntcodeMachine, entries: Vec<TraceEntry>) -> Self {
+        let position = entries.len();
+        Self {
+            machine,
+            entries,
+            position,
+        }
+    }
+
+    /// Returns the machine in its state at the current position in the trace.
+    pub fn get_machine(&self) -> &IntcodeMachine {
+        return &self.machine;
+    }
+
+    /// Returns the number of entries executed to reach the current position in the trace.
+    pub fn get_position(&self) -> usize {
+        return self.position;
+    }
+
+    /// Returns the number of entries in the trace.
+    pub fn get_num_entries(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// Undoes the most recently executed entry, returning it. Returns None at the start of the
+    /// trace.
+    pub fn step_back(&mut self) -> Option<&TraceEntry> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        let entry = &self.entries[self.position];
+        if let Some(write) = entry.write {
+            // Address was written to when the entry was recorded, so is within memory limits
+            self.machine
+                .write_memory(write.address, write.old_value)
+                .unwrap();
+        }
+        self.machine.prog_c = entry.pc;
+        self.machine.relative_base = entry.relative_base;
+        self.machine.halted = false;
+        self.machine.awaiting_input = false;
+        return Some(entry);
+    }
+
+    /// Re-executes the next entry in the trace, returning it. Returns None at the end of the trace.
+    pub fn step_forward(&mut self) -> Option<&TraceEntry> {
+        if self.position == self.entries.len() {
+            return None;
+        }
+        let entry = &self.entries[self.position];
+        self.position += 1;
+        if let Some(write) = entry.write {
+            self.machine
+                .write_memory(write.address, write.new_value)
+                .unwrap();
+        }
+        self.machine.prog_c = entry.next_pc;
+        self.machine.relative_base = entry.next_relative_base;
+        self.machine.halted = entry.instruction.opcode == Opcode::Halt;
+        return Some(entry);
+    }
+
+    /// Moves to the given position in the trace, stepping backwards or forwards as needed.
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.entries.len());
+        while self.position > position {
+            self.step_back();
+        }
+        while self.position < position {
+            self.step_forward();
+        }
+    }
+
+    /// Steps backwards to the most recent entry executed at the given address, returning it. The
+    /// position is left unchanged if there is no such entry.
+    pub fn step_back_to_address(&mut self, address: usize) -> Option<&TraceEntry> {
+        let target = self.entries[..self.position]
+            .iter()
+            .rposition(|entry| entry.pc == address)?;
+        self.seek(target);
+        return Some(&self.entries[target]);
+    }
+
+    /// Steps backwards to the most recent entry that wrote to the given address, returning it. The
+    /// position is left unchanged if there is no such entry.
+    pub fn step_back_to_write(&mut self, address: usize) -> Option<&TraceEntry> {
+        let target = self.entries[..self.position]
+            .iter()
+            .rposition(|entry| entry.write.map(|w| w.address) == Some(address))?;
+        self.seek(target);
+        return Some(&self.entries[target]);
+    }
+}
+
+/// Resolves the values read by the instruction's parameters and the address it writes to, using
+/// the current state of the machine.
+fn resolve_params(
+    machine: &IntcodeMachine,
+    instruction: &Instruction,
+) -> (Vec<i64>, Option<usize>) {
+    let mut values_read = Vec::<i64>::new();
+    let mut write_address = None;
+    let num_params = instruction.params.len();
+    for (i, param) in instruction.params.iter().enumerate() {
+        let address = match param.mode {
+            ParamMode::Position => param.value,
+            // Machine faults on an overflowing address (in checked mode) when stepped
+            ParamMode::Relative => machine.relative_base.wrapping_add(param.value),
+            ParamMode::Immediate => {
+                values_read.push(param.value);
+                continue;
+            }
+        };
+        if instruction.opcode.writes_to_memory() && i == num_params - 1 {
+            if address >= 0 {
+                write_address = Some(address as usize);
+            }
+        } else if address >= 0 {
+            values_read.push(machine.peek_memory(address as usize));
+        }
+    }
+    return (values_read, write_address);
+}
+
+/// Joins the values into a comma-separated string, or "-" if there are none. Also used for the
+/// value lists of snapshots.
+pub fn join_values<'a>(values: impl IntoIterator<Item = &'a i64>) -> String {
+    let values: Vec<String> = values.into_iter().map(|v| v.to_string()).collect();
+    if values.is_empty() {
+        return String::from("-");
+    }
+    return values.join(",");
+}
+
+/// Parses comma-separated values written by join_values().
+pub fn parse_values(text: &str) -> Option<Vec<i64>> {
+    if text == "-" {
+        return Some(vec![]);
+    }
+    return text.split(',').map(|v| v.parse::<i64>().ok()).collect();
+}
+
+/// Formats the optional value, using "-" for None.
+fn format_optional(value: Option<i64>) -> String {
+    match value {
+        Some(v) => return v.to_string(),
+        None => return String::from("-"),
+    }
+}
+
+/// Parses an optional value written by format_optional(). Returns None if it is malformed.
+fn parse_optional(text: &str) -> Option<Option<i64>> {
+    if text == "-" {
+        return Some(None);
+    }
+    return text.parse::<i64>().ok().map(Some);
+}
+
+/// Splits the text into the parts before and after the first instance of the separator.
+fn split_pair(text: &str, separator: char) -> Option<(&str, &str)> {
+    let index = text.find(separator)?;
+    return Some((&text[..index], &text[index + 1..]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::fs::TempFile;
+    use super::super::assembler::assemble;
+    use super::*;
+
+    /// Creates a machine running a program that reads a value, then counts down from it to 0,
+    /// outputting each value.
+    fn create_countdown_machine(start: i64) -> IntcodeMachine {
+        let program = assemble(
+            "
+                   in count
+            loop:  add count, #-1, count
+                   out count
+                   jt count, #loop
+                   hlt
+            count: data 0
+            ",
+        )
+        .unwrap();
+        return IntcodeMachine::new(program, VecDeque::from(vec![start]));
+    }
+
+    #[test]
+    fn test_trace_entries() {
+        let mut machine = create_countdown_machine(2);
+        let mut tracer = Tracer::with_ring_buffer(100);
+        assert_eq!(Ok(RunState::Output(1)), tracer.run(&mut machine));
+        let entries = tracer.get_entries();
+        assert_eq!(3, entries.len());
+        assert_eq!(Some(2), entries[0].input);
+        let expected = TraceEntry {
+            pc: 2,
+            relative_base: 0,
+            instruction: Instruction::decode(&[1001, 12, -1, 12], 0).unwrap(),
+            values_read: vec![2, -1],
+            write: Some(MemoryWrite {
+                address: 12,
+                old_value: 2,
+                new_value: 1,
+            }),
+            input: None,
+            output: None,
+            next_pc: 6,
+            next_relative_base: 0,
+        };
+        assert_eq!(expected, entries[1]);
+        assert_eq!(Some(1), entries[2].output);
+    }
+
+    #[test]
+    fn test_ring_buffer_capacity() {
+        let mut machine = create_countdown_machine(5);
+        let mut tracer = Tracer::with_ring_buffer(4);
+        while tracer.run(&mut machine) != Ok(RunState::Halted) {}
+        // in, then 3 instructions per count, then hlt
+        assert_eq!(17, tracer.get_num_recorded());
+        let entries = tracer.get_entries();
+        assert_eq!(4, entries.len());
+        assert_eq!(Opcode::Halt, entries[3].instruction.opcode);
+    }
+
+    #[test]
+    fn test_replay_backwards_and_forwards() {
+        let initial = create_countdown_machine(3);
+        let mut machine = initial.clone();
+        let mut tracer = Tracer::with_ring_buffer(1000);
+        while tracer.run(&mut machine) != Ok(RunState::Halted) {}
+        let final_memory = machine.get_memory_dump();
+        let mut replayer = Replayer::new(machine, tracer.get_entries());
+        assert!(replayer.get_machine().has_halted());
+        // Step back to the last time the counter was written
+        let entry = replayer.step_back_to_write(12).unwrap();
+        assert_eq!(2, entry.pc);
+        assert_eq!(1, replayer.get_machine().peek_memory(12));
+        // Rewind to the start of the trace
+        replayer.seek(0);
+        assert_eq!(None, replayer.step_back());
+        assert_eq!(
+            initial.get_memory_dump(),
+            replayer.get_machine().get_memory_dump()
+        );
+        assert_eq!(0, replayer.get_machine().get_program_counter());
+        // Replay to the end again
+        replayer.seek(replayer.get_num_entries());
+        assert_eq!(final_memory, replayer.get_machine().get_memory_dump());
+        assert!(replayer.get_machine().has_halted());
+    }
+
+    #[test]
+    fn test_trace_file_round_trip() {
+        let file = TempFile::new("trace_test");
+        let filename = file.get_filename();
+        let mut machine = create_countdown_machine(2);
+        let mut ring_tracer = Tracer::with_ring_buffer(100);
+        while ring_tracer.run(&mut machine) != Ok(RunState::Halted) {}
+        let mut machine = create_countdown_machine(2);
+        let mut file_tracer = Tracer::to_file(filename.clone()).unwrap();
+        while file_tracer.run(&mut machine) != Ok(RunState::Halted) {}
+        file_tracer.finish().unwrap();
+        let loaded = load_trace(filename).unwrap();
+        assert_eq!(ring_tracer.get_entries(), loaded);
+    }
+
+    #[test]
+    fn test_load_trace_errors() {
+        let entry = TraceEntry::from_line("0\t0\t3,7\t-\t7:0>2\t2\t-\t2", 2);
+        match entry {
+            Err(AocError::Parse { line, .. }) => assert_eq!(2, line),
+            _ => panic!("Expected parse error."),
+        }
+        assert!(TraceEntry::from_line("0\t0\t3,7\t-\t7:0>x\t2\t-\t2\t0", 3).is_err());
+        assert!(TraceEntry::from_line("0\t0\t3,7\t-\t7:0>2\t2\t-\t2\t0", 3).is_ok());
+    }
+}
@@ -0,0 +1,709 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+
+use super::analysis::analyze;
+use super::instruction::{Instruction, Opcode, ParamMode, Parameter};
+use super::memory::MemoryLimits;
+use super::IntcodeMachine;
+
+/// Highest address compiled code writes to, so the memory of a transpiled program can grow to at
+/// most 8 MiB before the interpreter takes over.
+const MAX_COMPILED_ADDRESS: usize = 1 << 20;
+
+/// Number of values written on each line of the arrays in the generated source.
+const VALUES_PER_LINE: usize = 16;
+
+/// Basic block of the program compiled into a single function of the generated source.
+struct CompiledBlock {
+    start: usize,
+    end: usize,
+    instructions: Vec<(usize, Instruction)>,
+}
+
+impl IntcodeMachine {
+    /// Creates a machine that continues running a transpiled program from the given state, with
+    /// the default memory limits and arithmetic mode. Used by transpiled programs to fall back to
+    /// the interpreter.
+    pub fn from_transpiled_state(
+        memory: Vec<i64>,
+        pc: usize,
+        relative_base: i64,
+        input: VecDeque<i64>,
+        halted: bool,
+        awaiting_input: bool,
+    ) -> Self {
+        let mut machine = IntcodeMachine::new(memory, input);
+        machine.prog_c = pc;
+        machine.relative_base = relative_base;
+        machine.halted = halted;
+        machine.awaiting_input = awaiting_input;
+        return machine;
+    }
+}
+
+/// Translates the Intcode program in the given memory image into the source of a Rust module.
+/// The source describes where the image came from in the module documentation.
+///
+/// The module defines a TranspiledProgram with the same run() interface as an IntcodeMachine.
+/// Each basic block found by analyzing the image is compiled into a function, and the program
+/// counter is only used to dispatch between blocks. Opcodes and jump parameters are compiled
+/// into the code, while the other parameters are read from memory as the program runs, so writes
+/// to them (e.g. the day 2 noun and verb) don't stop the compiled code being used.
+///
+/// The program falls back to the interpreter when it can't run compiled code - when it jumps to
+/// code the analysis didn't find, a block is changed by self-modifying code, or an instruction
+/// would fault or write beyond the first 2^20 addresses. Compiled code takes over again once the
+/// interpreter reaches the start of a block that hasn't been changed. Arithmetic is checked, as in
+/// the default arithmetic mode of the machine.
+pub fn transpile(memory: &[i64], source: &str) -> String {
+    let blocks = get_compiled_blocks(memory);
+    // Cells compiled into the code, and the block depending on each of them
+    let mut cell_blocks = vec![0; memory.len()];
+    for (index, block) in blocks.iter().enumerate() {
+        for (address, instruction) in block.instructions.iter() {
+            cell_blocks[*address] = index + 1;
+            if instruction.is_jump() {
+                let params = address + 1..address + instruction.get_length();
+                cell_blocks[params]
+                    .iter_mut()
+                    .for_each(|cell| *cell = index + 1);
+            }
+        }
+    }
+    let fixed_cells: Vec<usize> = (0..memory.len()).filter(|a| cell_blocks[*a] != 0).collect();
+    let mut code = String::new();
+    writeln!(
+        code,
+        "//! Intcode program transpiled to Rust by the `transpile` command, from:\n//! {}\n//!\n\
+         //! Do not edit - transpile the program again instead.",
+        source
+    )
+    .unwrap();
+    code.push_str(GENERATED_PRELUDE);
+    write_array(
+        &mut code,
+        "Memory image the program was transpiled from.",
+        "IMAGE",
+        "i64",
+        memory,
+    );
+    write_array(
+        &mut code,
+        "Addresses of the cells compiled into the code, which must hold the same values as the image.",
+        "FIXED_CELLS",
+        "usize",
+        &fixed_cells,
+    );
+    write_array(
+        &mut code,
+        "Index (plus one) of the block compiled from each cell of the image, or 0 for other cells.",
+        "CELL_BLOCKS",
+        "u32",
+        &cell_blocks,
+    );
+    writeln!(code, "/// Start and end addresses of each compiled block.").unwrap();
+    writeln!(code, "const BLOCKS: [(usize, usize); {}] = [", blocks.len()).unwrap();
+    for block in blocks.iter() {
+        writeln!(code, "    ({}, {}),", block.start, block.end).unwrap();
+    }
+    code.push_str("];\n");
+    writeln!(code, "/// Highest address the program can access.").unwrap();
+    let max_address = MemoryLimits::default().max_address;
+    writeln!(code, "const MAX_ADDRESS: i64 = {};", max_address).unwrap();
+    writeln!(code, "/// Highest address compiled code writes to.").unwrap();
+    writeln!(
+        code,
+        "const MAX_COMPILED_ADDRESS: usize = {};",
+        MAX_COMPILED_ADDRESS
+    )
+    .unwrap();
+    code.push_str(GENERATED_PROGRAM);
+    // Dispatch to the block starting at the program counter
+    code.push_str(
+        "\n    /// Runs the compiled block starting at the program counter, if there is one.\n",
+    );
+    code.push_str("    fn run_block(&mut self) -> Result<Option<RunState>, usize> {\n");
+    code.push_str("        match self.pc {\n");
+    for block in blocks.iter() {
+        writeln!(
+            code,
+            "            {} => return self.block_{}(),",
+            block.start, block.start
+        )
+        .unwrap();
+    }
+    code.push_str("            pc => return Err(pc),\n");
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+    for (index, block) in blocks.iter().enumerate() {
+        write_block(&mut code, index, block);
+    }
+    code.push_str("}\n");
+    return code;
+}
+
+/// Gets the blocks of the program to compile, found by analyzing the memory image. Blocks are
+/// split so input instructions start a block and output instructions end one, making each point
+/// the program can stop at the start of a block. Blocks that share cells with other blocks or run
+/// past the end of the image are left to the interpreter.
+fn get_compiled_blocks(memory: &[i64]) -> Vec<CompiledBlock> {
+    let graph = analyze(memory);
+    let mut blocks = Vec::<CompiledBlock>::new();
+    for block in graph.get_blocks() {
+        let mut current: Option<CompiledBlock> = None;
+        for (address, instruction) in block.instructions.iter() {
+            if instruction.opcode == Opcode::Input {
+                blocks.extend(current.take());
+            }
+            let end = address + instruction.get_length();
+            let compiled = current.get_or_insert_with(|| CompiledBlock {
+                start: *address,
+                end,
+                instructions: vec![],
+            });
+            compiled.instructions.push((*address, instruction.clone()));
+            compiled.end = end;
+            if instruction.opcode == Opcode::Output {
+                blocks.extend(current.take());
+            }
+        }
+        blocks.extend(current);
+    }
+    let mut owners = BTreeMap::<usize, usize>::new();
+    let mut excluded = vec![false; blocks.len()];
+    for (index, block) in blocks.iter().enumerate() {
+        if block.end > memory.len() {
+            excluded[index] = true;
+        }
+        for cell in block.start..block.end {
+            if let Some(owner) = owners.insert(cell, index) {
+                excluded[owner] = true;
+                excluded[index] = true;
+            }
+        }
+    }
+    return blocks
+        .into_iter()
+        .zip(excluded)
+        .filter(|(_, excluded)| !excluded)
+        .map(|(block, _)| block)
+        .collect();
+}
+
+/// Writes the function running the compiled block with the given index.
+fn write_block(code: &mut String, index: usize, block: &CompiledBlock) {
+    writeln!(code, "\n    /// Runs the block at address {}.", block.start).unwrap();
+    writeln!(
+        code,
+        "    fn block_{}(&mut self) -> Result<Option<RunState>, usize> {{",
+        block.start
+    )
+    .unwrap();
+    writeln!(code, "        if self.dirty[{}] {{", index).unwrap();
+    writeln!(code, "            return Err({});", block.start).unwrap();
+    code.push_str("        }\n");
+    for (address, instruction) in block.instructions.iter() {
+        writeln!(code, "        // {}: {}", address, instruction).unwrap();
+        write_instruction(code, *address, instruction, block.end);
+    }
+    let last = &block.instructions.last().unwrap().1;
+    if !matches!(last.opcode, Opcode::Halt | Opcode::Output) && !last.is_unconditional_jump() {
+        writeln!(code, "        self.pc = {};", block.end).unwrap();
+        code.push_str("        return Ok(None);\n");
+    }
+    code.push_str("    }\n");
+}
+
+/// Writes the statements executing the instruction at the given address, in a block ending at the
+/// given address.
+fn write_instruction(code: &mut String, address: usize, instruction: &Instruction, end: usize) {
+    let next = address + instruction.get_length();
+    let operand = |index: usize| get_operand(address, instruction, index);
+    match instruction.opcode {
+        Opcode::Add | Opcode::Multiply => {
+            let method = match instruction.opcode {
+                Opcode::Add => "add",
+                _ => "multiply",
+            };
+            writeln!(
+                code,
+                "        let value = self.{}({}, {}, {})?;",
+                method,
+                operand(0),
+                operand(1),
+                address
+            )
+            .unwrap();
+            write_store(code, address, instruction.params[2], next, end);
+        }
+        Opcode::LessThan | Opcode::Equals => {
+            let operator = match instruction.opcode {
+                Opcode::LessThan => "<",
+                _ => "==",
+            };
+            writeln!(
+                code,
+                "        let value = ({} {} {}) as i64;",
+                operand(0),
+                operator,
+                operand(1)
+            )
+            .unwrap();
+            write_store(code, address, instruction.params[2], next, end);
+        }
+        Opcode::Input => {
+            let store_address = get_write_address(address, instruction.params[0], next);
+            writeln!(code, "        let address = {};", store_address).unwrap();
+            code.push_str("        let value = match self.input.pop_front() {\n");
+            code.push_str("            Some(value) => value,\n");
+            code.push_str("            None => {\n");
+            code.push_str("                self.awaiting_input = true;\n");
+            code.push_str("                return Ok(Some(RunState::NeedsInput));\n");
+            code.push_str("            }\n");
+            code.push_str("        };\n");
+            write_write(code, next, end);
+        }
+        Opcode::Output => {
+            writeln!(code, "        let value = {};", operand(0)).unwrap();
+            writeln!(code, "        self.pc = {};", next).unwrap();
+            code.push_str("        return Ok(Some(RunState::Output(value)));\n");
+        }
+        Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+            let target = match instruction.params[1] {
+                Parameter {
+                    mode: ParamMode::Immediate,
+                    value,
+                } if value >= 0 => value.to_string(),
+                _ => format!("self.jump_address({}, {})?", operand(1), address),
+            };
+            if instruction.is_unconditional_jump() {
+                writeln!(code, "        self.pc = {};", target).unwrap();
+                code.push_str("        return Ok(None);\n");
+                return;
+            }
+            let operator = match instruction.opcode {
+                Opcode::JumpIfTrue => "!=",
+                _ => "==",
+            };
+            writeln!(code, "        if {} {} 0 {{", operand(0), operator).unwrap();
+            writeln!(code, "            self.pc = {};", target).unwrap();
+            code.push_str("            return Ok(None);\n");
+            code.push_str("        }\n");
+        }
+        Opcode::AdjustRelativeBase => {
+            writeln!(
+                code,
+                "        self.relative_base = self.relative({}, {})?;",
+                operand(0),
+                address
+            )
+            .unwrap();
+        }
+        Opcode::Halt => {
+            writeln!(code, "        self.pc = {};", address).unwrap();
+            code.push_str("        self.halted = true;\n");
+            code.push_str("        return Ok(Some(RunState::Halted));\n");
+        }
+    }
+}
+
+/// Writes the statements storing the value computed by the instruction at the given address to
+/// the address given by the parameter.
+fn write_store(code: &mut String, address: usize, param: Parameter, next: usize, end: usize) {
+    let store_address = get_write_address(address, param, next);
+    writeln!(code, "        let address = {};", store_address).unwrap();
+    write_write(code, next, end);
+}
+
+/// Writes the statement storing the value to the address, falling back to the interpreter after
+/// the instruction if it changes a later instruction of the same block.
+fn write_write(code: &mut String, next: usize, end: usize) {
+    if next < end {
+        writeln!(
+            code,
+            "        if self.write(address, value) && ({}..{}).contains(&address) {{",
+            next, end
+        )
+        .unwrap();
+        writeln!(code, "            return Err({});", next).unwrap();
+        code.push_str("        }\n");
+    } else {
+        code.push_str("        self.write(address, value);\n");
+    }
+}
+
+/// Gets the expression for the value of the instruction parameter with the given index. Jump
+/// parameters are compiled into the code, and other parameters are read from memory.
+fn get_operand(address: usize, instruction: &Instruction, index: usize) -> String {
+    let param = instruction.params[index];
+    let raw = if instruction.is_jump() {
+        param.value.to_string()
+    } else {
+        format!("self.memory[{}]", address + 1 + index)
+    };
+    match param.mode {
+        ParamMode::Immediate => return raw,
+        ParamMode::Position => return format!("self.read({}, {})?", raw, address),
+        ParamMode::Relative => {
+            return format!(
+                "self.read(self.relative({}, {})?, {})?",
+                raw, address, address
+            );
+        }
+    }
+}
+
+/// Gets the expression for the address written to by the instruction at the given address,
+/// through the parameter at the end of the instruction (which ends at the next address).
+fn get_write_address(address: usize, param: Parameter, next: usize) -> String {
+    let raw = format!("self.memory[{}]", next - 1);
+    match param.mode {
+        ParamMode::Relative => {
+            return format!(
+                "self.write_address(self.relative({}, {})?, {})?",
+                raw, address, address
+            );
+        }
+        _ => return format!("self.write_address({}, {})?", raw, address),
+    }
+}
+
+/// Writes a constant array holding the given values.
+fn write_array<T: ToString>(code: &mut String, doc: &str, name: &str, kind: &str, values: &[T]) {
+    writeln!(code, "/// {}", doc).unwrap();
+    writeln!(code, "const {}: [{}; {}] = [", name, kind, values.len()).unwrap();
+    for chunk in values.chunks(VALUES_PER_LINE) {
+        let line: Vec<String> = chunk.iter().map(|v| v.to_string()).collect();
+        writeln!(code, "    {},", line.join(", ")).unwrap();
+    }
+    code.push_str("];\n");
+}
+
+/// Start of every generated module, before its constants.
+const GENERATED_PRELUDE: &str = "
+use std::collections::VecDeque;
+use std::mem;
+
+use crate::utils::intcode::{IntcodeMachine, MachineFault, RunState};
+
+";
+
+/// Definition of the TranspiledProgram and the methods common to every program, up to (but
+/// excluding) the block functions and the end of its impl block.
+const GENERATED_PROGRAM: &str = "
+/// Intcode program compiled to Rust, which falls back to the interpreter once it can't run the
+/// compiled code.
+pub struct TranspiledProgram {
+    memory: Vec<i64>,
+    pc: usize,
+    relative_base: i64,
+    input: VecDeque<i64>,
+    halted: bool,
+    awaiting_input: bool,
+    dirty: Vec<bool>,
+    machine: Option<IntcodeMachine>,
+    fallbacks: u64,
+}
+
+impl TranspiledProgram {
+    /// Creates the program with the memory image it was transpiled from.
+    pub fn new() -> Self {
+        return Self::with_memory(IMAGE.to_vec()).unwrap();
+    }
+
+    /// Creates the program with the given memory, e.g. the image with some values patched.
+    /// Returns None if a cell compiled into the code doesn't hold the value it has in the image.
+    pub fn with_memory(mut memory: Vec<i64>) -> Option<Self> {
+        for &address in FIXED_CELLS.iter() {
+            if *memory.get(address).unwrap_or(&0) != IMAGE[address] {
+                return None;
+            }
+        }
+        if memory.len() < IMAGE.len() {
+            memory.resize(IMAGE.len(), 0);
+        }
+        return Some(Self {
+            memory,
+            pc: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            halted: false,
+            awaiting_input: false,
+            dirty: vec![false; BLOCKS.len()],
+            machine: None,
+            fallbacks: 0,
+        });
+    }
+
+    /// Adds the value to the input queue of the program.
+    pub fn add_input(&mut self, value: i64) {
+        match &mut self.machine {
+            Some(machine) => machine.add_input(value),
+            None => {
+                self.input.push_back(value);
+                self.awaiting_input = false;
+            }
+        }
+    }
+
+    /// Runs the program until it halts, needs input or outputs a value, and returns the reason it
+    /// stopped - the same as IntcodeMachine::run().
+    pub fn run(&mut self) -> Result<RunState, MachineFault> {
+        loop {
+            if let Some(machine) = &mut self.machine {
+                if let Some(state) = machine.step()? {
+                    return Ok(state);
+                }
+                self.try_resume_compiled();
+                continue;
+            }
+            if self.halted {
+                return Ok(RunState::Halted);
+            }
+            match self.run_block() {
+                Ok(Some(state)) => return Ok(state),
+                Ok(None) => (),
+                Err(pc) => self.fall_back(pc),
+            }
+        }
+    }
+
+    /// Checks if the program has halted.
+    pub fn has_halted(&self) -> bool {
+        match &self.machine {
+            Some(machine) => return machine.has_halted(),
+            None => return self.halted,
+        }
+    }
+
+    /// Checks if the program is waiting for input.
+    pub fn is_awaiting_input(&self) -> bool {
+        match &self.machine {
+            Some(machine) => return machine.is_awaiting_input(),
+            None => return self.awaiting_input,
+        }
+    }
+
+    /// Checks if the program is currently being run by the interpreter.
+    pub fn is_interpreted(&self) -> bool {
+        return self.machine.is_some();
+    }
+
+    /// Returns the number of times the program has fallen back to the interpreter.
+    pub fn get_fallback_count(&self) -> u64 {
+        return self.fallbacks;
+    }
+
+    /// Returns the value held at the given address of memory.
+    pub fn peek_memory(&self, address: usize) -> i64 {
+        match &self.machine {
+            Some(machine) => return machine.peek_memory(address),
+            None => return *self.memory.get(address).unwrap_or(&0),
+        }
+    }
+
+    /// Returns the current value of the program counter.
+    pub fn get_program_counter(&self) -> usize {
+        match &self.machine {
+            Some(machine) => return machine.get_program_counter(),
+            None => return self.pc,
+        }
+    }
+
+    /// Returns the current value of the relative base.
+    pub fn get_relative_base(&self) -> i64 {
+        match &self.machine {
+            Some(machine) => return machine.get_relative_base(),
+            None => return self.relative_base,
+        }
+    }
+
+    /// Converts the program into an IntcodeMachine in the same state.
+    pub fn into_machine(mut self) -> IntcodeMachine {
+        if let Some(machine) = self.machine.take() {
+            return machine;
+        }
+        return IntcodeMachine::from_transpiled_state(
+            self.take_memory(),
+            self.pc,
+            self.relative_base,
+            mem::take(&mut self.input),
+            self.halted,
+            self.awaiting_input,
+        );
+    }
+
+    /// Hands the program over to the interpreter, which continues running it from the given
+    /// address.
+    fn fall_back(&mut self, pc: usize) {
+        self.fallbacks += 1;
+        self.machine = Some(IntcodeMachine::from_transpiled_state(
+            self.take_memory(),
+            pc,
+            self.relative_base,
+            mem::take(&mut self.input),
+            false,
+            false,
+        ));
+    }
+
+    /// Switches back from the interpreter to compiled code if the interpreter has reached the
+    /// start of a compiled block that hasn't been changed since the program was transpiled.
+    fn try_resume_compiled(&mut self) {
+        let machine = self.machine.as_ref().unwrap();
+        let pc = machine.get_program_counter();
+        let (start, end) = match BLOCKS.binary_search_by_key(&pc, |(start, _)| *start) {
+            Ok(index) => BLOCKS[index],
+            Err(_) => return,
+        };
+        let changed = (start..end).any(|a| CELL_BLOCKS[a] != 0 && machine.peek_memory(a) != IMAGE[a]);
+        if changed || machine.get_memory_len() > MAX_COMPILED_ADDRESS + 1 {
+            return;
+        }
+        let machine = self.machine.take().unwrap();
+        self.memory = machine.get_memory_dump();
+        self.pc = pc;
+        self.relative_base = machine.get_relative_base();
+        self.input = machine.get_input_vec();
+        for dirty in self.dirty.iter_mut() {
+            *dirty = false;
+        }
+        for &address in FIXED_CELLS.iter() {
+            if self.memory[address] != IMAGE[address] {
+                self.dirty[CELL_BLOCKS[address] as usize - 1] = true;
+            }
+        }
+    }
+
+    /// Takes the memory of the program, without any zeroes past the end of the image.
+    fn take_memory(&mut self) -> Vec<i64> {
+        let mut memory = mem::take(&mut self.memory);
+        while memory.len() > IMAGE.len() && memory.last() == Some(&0) {
+            memory.pop();
+        }
+        return memory;
+    }
+
+    // Helpers used by the blocks return the address of the instruction being run as the error,
+    // so the interpreter can run the instruction instead (and fault if it is invalid)
+
+    /// Returns the value held at the address.
+    fn read(&self, address: i64, pc: usize) -> Result<i64, usize> {
+        if address < 0 || address > MAX_ADDRESS {
+            return Err(pc);
+        }
+        return Ok(*self.memory.get(address as usize).unwrap_or(&0));
+    }
+
+    /// Returns the address relative to the relative base.
+    fn relative(&self, offset: i64, pc: usize) -> Result<i64, usize> {
+        return self.relative_base.checked_add(offset).ok_or(pc);
+    }
+
+    /// Adds the values.
+    fn add(&self, a: i64, b: i64, pc: usize) -> Result<i64, usize> {
+        return a.checked_add(b).ok_or(pc);
+    }
+
+    /// Multiplies the values.
+    fn multiply(&self, a: i64, b: i64, pc: usize) -> Result<i64, usize> {
+        return a.checked_mul(b).ok_or(pc);
+    }
+
+    /// Checks the address can be written to by compiled code.
+    fn write_address(&self, address: i64, pc: usize) -> Result<usize, usize> {
+        if address < 0 || address as u64 > MAX_COMPILED_ADDRESS as u64 {
+            return Err(pc);
+        }
+        return Ok(address as usize);
+    }
+
+    /// Checks the address can be jumped to.
+    fn jump_address(&self, address: i64, pc: usize) -> Result<usize, usize> {
+        if address < 0 || address > MAX_ADDRESS {
+            return Err(pc);
+        }
+        return Ok(address as usize);
+    }
+
+    /// Writes the value to the address. Returns true if it changed a cell compiled into the code,
+    /// in which case the block compiled from the cell can no longer be run.
+    fn write(&mut self, address: usize, value: i64) -> bool {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        let old_value = mem::replace(&mut self.memory[address], value);
+        if address >= IMAGE.len() || old_value == value || CELL_BLOCKS[address] == 0 {
+            return false;
+        }
+        self.dirty[CELL_BLOCKS[address] as usize - 1] = true;
+        return true;
+    }
+";
+
+#[cfg(test)]
+mod tests {
+    use super::super::assemble;
+    use super::*;
+
+    #[test]
+    fn test_compiled_blocks() {
+        let memory = assemble(
+            "
+                    in value
+            loop:   out value
+                    add value, #-1, value
+                    jt value, #loop
+                    hlt
+            value:  data 0
+            ",
+        )
+        .unwrap();
+        let blocks = get_compiled_blocks(&memory);
+        let starts: Vec<(usize, usize)> = blocks.iter().map(|b| (b.start, b.end)).collect();
+        // Output ends the block at the loop start, so the loop continues in a new block
+        assert_eq!(vec![(0, 2), (2, 4), (4, 11), (11, 12)], starts);
+        let code = transpile(&memory, "a test");
+        assert!(code.starts_with("//! Intcode program transpiled to Rust by the `transpile` command, from:\n//! a test\n"));
+        assert!(code.contains("            4 => return self.block_4(),\n"));
+        // Opcodes and jump parameters are fixed, other parameters are read from memory
+        assert!(code.contains("const FIXED_CELLS: [usize; 7] = [\n    0, 2, 4, 8, 9, 10, 11,\n];"));
+        assert!(code.contains(
+            "        let value = self.add(self.read(self.memory[5], 4)?, self.memory[6], 4)?;\n"
+        ));
+        assert!(code.contains("        if self.read(12, 8)? != 0 {\n            self.pc = 2;\n"));
+    }
+
+    #[test]
+    fn test_write_into_block() {
+        // Write into the opcode of the next instruction of the same block falls back after the
+        // write, while a write into a parameter doesn't
+        let memory = assemble(
+            "
+                    add #1, #1, next+1
+            next:   add #0, #0, 0
+                    hlt
+            ",
+        )
+        .unwrap();
+        let code = transpile(&memory, "a test");
+        assert!(code.contains(
+            "        if self.write(address, value) && (4..9).contains(&address) {\n            \
+             return Err(4);\n"
+        ));
+        assert!(
+            code.contains("const CELL_BLOCKS: [u32; 9] = [\n    1, 0, 0, 0, 1, 0, 0, 0, 1,\n];")
+        );
+    }
+
+    #[test]
+    fn test_from_transpiled_state() {
+        let input = VecDeque::from(vec![5]);
+        let mut machine =
+            IntcodeMachine::from_transpiled_state(vec![3, 0, 99], 2, 7, input, false, false);
+        assert_eq!(2, machine.get_program_counter());
+        assert_eq!(7, machine.get_relative_base());
+        assert_eq!(Ok(super::super::RunState::Halted), machine.run());
+        assert_eq!(3, machine.peek_memory(0));
+    }
+}
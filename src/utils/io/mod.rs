@@ -1,7 +1,8 @@
 use std::fs::File;
 use std::io::Read;
+use super::error::AocError;
 
-/// Reads and returns the contents of the given file.
+/// Reads and returns the contents of the given file. Panics if an error occurs.
 pub fn read_file_to_string(file: &mut File) -> String {
     let mut read_buf = String::from("");
     match file.read_to_string(&mut read_buf) {
@@ -10,3 +11,28 @@ pub fn read_file_to_string(file: &mut File) -> String {
     };
     return read_buf;
 }
+
+/// Reads and returns the contents of the given file, returning an error if it can't be read.
+pub fn try_read_file_to_string(file: &mut File) -> Result<String, AocError> {
+    let mut read_buf = String::from("");
+    if let Err(e) = file.read_to_string(&mut read_buf) {
+        return Err(AocError::Io {
+            filename: String::from("<open file>"),
+            source: e,
+        });
+    }
+    return Ok(read_buf);
+}
+
+/// Opens the file with the given filename and reads its contents to a string.
+pub fn try_read_filename_to_string(filename: String) -> Result<String, AocError> {
+    let mut file = super::fs::try_open_file(filename.clone())?;
+    let mut read_buf = String::from("");
+    if let Err(e) = file.read_to_string(&mut read_buf) {
+        return Err(AocError::Io {
+            filename,
+            source: e,
+        });
+    }
+    return Ok(read_buf);
+}
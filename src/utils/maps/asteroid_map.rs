@@ -1,5 +1,6 @@
 use ::num::integer::*;
 use euclid::*;
+use super::super::error::AocError;
 
 // Expected map characters
 const MAP_CHAR_EMPTY: char = '.';
@@ -31,8 +32,18 @@ struct QuadrantMultiplier {
 
 impl AsteroidMap {
     /// Creates a new AsteroidMap from the given raw data. Raw data is must consist of lines of
-    /// equal length and contain only characters '.' (no asteroid) or '#' (asteroid).
+    /// equal length and contain only characters '.' (no asteroid) or '#' (asteroid). Panics if the
+    /// raw data is poorly formed.
     pub fn new(raw_data: String) -> Self {
+        match AsteroidMap::try_new(raw_data) {
+            Err(e) => panic!("ERROR - {}", e),
+            Ok(map) => map,
+        }
+    }
+
+    /// Creates a new AsteroidMap from the given raw data, returning a parse error if the raw data
+    /// contains an unexpected character or rows of unequal width.
+    pub fn try_new(raw_data: String) -> Result<Self, AocError> {
         let mut asteroid_locations: Vec<Point2D<i64, UnknownUnit>> = vec![];
         let mut map_data: Vec<Vec<char>> = vec![vec![]];
         let mut map_width: i64 = 0;
@@ -40,6 +51,7 @@ impl AsteroidMap {
         // Split raw data into lines
         for line in raw_data.lines() {
             let mut x_loc: i64 = 0;
+            let leading_space = line.len() - line.trim_start().len();
             let line = line.trim();
             map_data.push(vec![]);
             for c in line.chars() {
@@ -47,7 +59,11 @@ impl AsteroidMap {
                 if c == MAP_CHAR_ASTRD {
                     asteroid_locations.push(Point2D::new(x_loc, map_height));
                 } else if c != MAP_CHAR_EMPTY {
-                    panic!("Bad map char: {}", c);
+                    return Err(AocError::parse_error(
+                        map_height as usize + 1,
+                        leading_space + x_loc as usize + 1,
+                        format!("bad map char: {}", c),
+                    ));
                 }
                 map_data[map_height as usize].push(c);
                 x_loc += 1;
@@ -55,22 +71,24 @@ impl AsteroidMap {
             // Set the map width data
             if map_height == 0 {
                 map_width = x_loc;
-            } else {
-                if x_loc > map_width {
-                    panic!("Poorly formed map - unequal row widths.");
-                }
+            } else if x_loc > map_width {
+                return Err(AocError::parse_error(
+                    map_height as usize + 1,
+                    leading_space + map_width as usize + 1,
+                    String::from("poorly formed map - unequal row widths"),
+                ));
             }
             // Go up a row
             map_height += 1;
         }
 
         // Create the instance with the processed data
-        Self {
+        return Ok(Self {
             map_data: map_data,
             asteroid_locations: asteroid_locations,
             map_width: map_width + 1,
             map_height: map_height + 1,
-        }
+        });
     }
 
     /// Checks if the given location contains an asteroid.
@@ -215,3 +233,30 @@ impl AsteroidMap {
         return vaporise_order;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_new_bad_char() {
+        match AsteroidMap::try_new(String::from(".#..\n.#x.\n")) {
+            Err(AocError::Parse { line, column, .. }) => {
+                assert_eq!(2, line);
+                assert_eq!(3, column);
+            }
+            _ => panic!("Expected parse error."),
+        }
+    }
+
+    #[test]
+    fn test_try_new_unequal_rows() {
+        match AsteroidMap::try_new(String::from(".#..\n.#...\n")) {
+            Err(AocError::Parse { line, column, .. }) => {
+                assert_eq!(2, line);
+                assert_eq!(5, column);
+            }
+            _ => panic!("Expected parse error."),
+        }
+    }
+}
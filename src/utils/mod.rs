@@ -1,3 +1,4 @@
+pub mod error;
 pub mod fs;
 pub mod io;
 pub mod intcode;
@@ -4,6 +4,8 @@
 //! AoC 2019 Day 8.
 
 use std::collections::HashMap;
+use super::super::solution::Image;
+use super::error::AocError;
 
 // Pixel constants
 const PIXEL_BLACK: u32 = 0;
@@ -56,6 +58,26 @@ impl SifImage {
         return Ok(self.layer_digit_counts[&layer_index][&digit]);
     }
 
+    /// Loads the image data, as load_image_data() does, but first checks that it only contains
+    /// digits and fills a whole number of layers (of which there is at least one). Returns a parse
+    /// error if it doesn't.
+    pub fn try_load_image_data(&mut self, raw_image_data: String) -> Result<(), AocError> {
+        let mut digits = raw_image_data.chars().enumerate();
+        if let Some((index, c)) = digits.find(|(_, c)| !c.is_ascii_digit()) {
+            let message = format!("bad image digit: {}", c);
+            return Err(AocError::parse_error(1, index + 1, message));
+        }
+        let total_digits = raw_image_data.chars().count();
+        let layer_area = self.get_layer_area() as usize;
+        let num_layers = total_digits / layer_area;
+        if num_layers == 0 || num_layers * layer_area != total_digits {
+            let message = String::from("image data isn't a whole number of layers");
+            return Err(AocError::parse_error(1, total_digits + 1, message));
+        }
+        self.load_image_data(raw_image_data);
+        return Ok(());
+    }
+
     pub fn load_image_data(&mut self, raw_image_data: String) {
         // Get vector of individual characters
         let image_chars: Vec<char> = raw_image_data.chars().collect();
@@ -136,4 +158,16 @@ impl SifImage {
             println!("");
         }
     }
+
+    /// Converts the processed image into a monochrome Image, with white pixels lit.
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(self.image_width as usize, self.image_height as usize);
+        for y_var in 0..self.image_height {
+            for x_var in 0..self.image_width {
+                let pixel_value = self.processed_image[y_var as usize][x_var as usize];
+                image.set_pixel(x_var as usize, y_var as usize, pixel_value == PIXEL_WHITE);
+            }
+        }
+        return image;
+    }
 }